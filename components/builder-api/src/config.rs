@@ -2,7 +2,8 @@
 
 use crate::{bldr_core::{self,
                         config::ConfigFile},
-            db::config::DataStoreCfg};
+            db::{config::DataStoreCfg,
+                 models::origin::OriginMemberRole}};
 use artifactory_client::config::ArtifactoryCfg;
 use github_api_client::config::GitHubCfg;
 
@@ -37,16 +38,20 @@ pub trait GatewayCfg {
 #[derive(Clone, Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct Config {
-    pub api:         ApiCfg,
-    pub artifactory: ArtifactoryCfg,
-    pub github:      GitHubCfg,
-    pub http:        HttpCfg,
-    pub oauth:       OAuth2Cfg,
-    pub s3:          S3Cfg,
-    pub ui:          UiCfg,
-    pub memcache:    MemcacheCfg,
-    pub datastore:   DataStoreCfg,
-    pub provision:   ProvisionCfg,
+    pub api:               ApiCfg,
+    pub artifactory:       ArtifactoryCfg,
+    pub github:            GitHubCfg,
+    pub http:              HttpCfg,
+    pub oauth:             OAuth2Cfg,
+    pub s3:                S3Cfg,
+    pub ui:                UiCfg,
+    pub memcache:          MemcacheCfg,
+    pub datastore:         DataStoreCfg,
+    pub provision:         ProvisionCfg,
+    pub channel_check:     ChannelCheckCfg,
+    pub webhooks:          WebhookCfg,
+    pub workload_identity: WorkloadIdentityCfg,
+    pub group_mappings:    Vec<GroupMappingCfg>,
 }
 
 #[derive(Debug)]
@@ -116,10 +121,16 @@ pub struct ApiCfg {
     pub saas_bldr_url: String,
     pub suppress_autobuild_origins: Vec<String>,
     pub allowed_users_for_origin_create: Vec<String>,
+    /// Accounts that may use the builder-wide administration api
+    pub admin_users: Vec<String>,
     pub license_server_url: String,
     pub unrestricted_channels: Vec<String>,
     pub partially_unrestricted_channels: Vec<String>,
     pub restricted_if_present: Vec<String>,
+    /// Longest time, in days, that personal access tokens may be valid for. Unlimited if unset
+    pub max_token_lifetime_days: Option<u32>,
+    /// Responses carry a warning once the token they were made with expires within this many days
+    pub token_expiry_warning_days: u32,
 }
 
 mod deserialize_into_vec {
@@ -152,9 +163,12 @@ impl Default for ApiCfg {
                  license_server_url: "http://licensing-acceptance.chef.co".to_string(),
                  suppress_autobuild_origins: vec![],
                  allowed_users_for_origin_create: vec![],
+                 admin_users: vec![],
                  unrestricted_channels: vec![],
                  partially_unrestricted_channels: vec![],
-                 restricted_if_present: vec![] }
+                 restricted_if_present: vec![],
+                 max_token_lifetime_days: None,
+                 token_expiry_warning_days: 7 }
     }
 }
 
@@ -397,6 +411,87 @@ impl Default for ProvisionCfg {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ChannelCheckCfg {
+    /// Seconds between periodic channel consistency checks. Zero disables them.
+    pub interval:          u64,
+    /// Channels checked periodically, each given as `origin/channel`
+    pub channels:          Vec<String>,
+    /// Channels searched, in order, for dependencies absent from the channel being checked
+    pub fallback_channels: Vec<String>,
+}
+
+impl Default for ChannelCheckCfg {
+    fn default() -> Self {
+        ChannelCheckCfg { interval:          3600,
+                          channels:          vec![],
+                          fallback_channels: vec!["stable".to_string()], }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct WebhookCfg {
+    /// Seconds between runs that send queued webhook deliveries. Zero disables delivery.
+    pub interval:     u64,
+    /// Attempts made at a delivery before it is marked as failed
+    pub max_attempts: u32,
+    /// Seconds to wait for a webhook endpoint to respond
+    pub timeout:      u64,
+    /// Most deliveries sent by one instance in a single run
+    pub batch_size:   i64,
+}
+
+impl Default for WebhookCfg {
+    fn default() -> Self {
+        WebhookCfg { interval:     10,
+                     max_attempts: 8,
+                     timeout:      10,
+                     batch_size:   100, }
+    }
+}
+
+/// Identity token issuers, such as CI systems, whose tokens can be exchanged for short-lived
+/// Builder tokens according to the trust rules of origins
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct WorkloadIdentityCfg {
+    /// Audience identity tokens have to be issued for
+    pub audience:               String,
+    /// Minutes that exchanged tokens are valid for
+    pub token_lifetime_minutes: u32,
+    pub issuers:                Vec<OidcIssuerCfg>,
+}
+
+impl Default for WorkloadIdentityCfg {
+    fn default() -> Self {
+        WorkloadIdentityCfg { audience:               "https://bldr.habitat.sh".to_string(),
+                              token_lifetime_minutes: 15,
+                              issuers:                vec![], }
+    }
+}
+
+/// An issuer's signing keys are read from `jwks_path` if given, and otherwise fetched from
+/// `jwks_url`
+#[derive(Clone, Debug, Deserialize)]
+pub struct OidcIssuerCfg {
+    pub issuer:    String,
+    #[serde(default)]
+    pub jwks_url:  Option<String>,
+    #[serde(default)]
+    pub jwks_path: Option<PathBuf>,
+}
+
+/// Grants the members of an identity provider group a role in an origin. Memberships granted
+/// this way are added, updated and removed as their accounts sign in.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GroupMappingCfg {
+    pub group:  String,
+    pub origin: String,
+    pub role:   OriginMemberRole,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -414,6 +509,8 @@ mod tests {
         private_max_age = 400
         suppress_autobuild_origins = ["origin1", "origin2"]
         allowed_users_for_origin_create = ["super1", "super2"]
+        admin_users = ["super1"]
+        max_token_lifetime_days = 90
 
         [http]
         listen = "0:0:0:0:0:0:0:1"
@@ -471,6 +568,29 @@ mod tests {
         provider = "kafka"
         bootstrap_nodes = ["myhost:9092"]
         client_id = "http://myhost"
+
+        [channel_check]
+        interval = 600
+        channels = ["core/stable"]
+        fallback_channels = ["base", "stable"]
+
+        [webhooks]
+        interval = 5
+        max_attempts = 3
+        timeout = 2
+        batch_size = 20
+
+        [workload_identity]
+        audience = "https://bldr.example.com"
+        token_lifetime_minutes = 5
+        [[workload_identity.issuers]]
+        issuer = "https://token.actions.githubusercontent.com"
+        jwks_url = "https://token.actions.githubusercontent.com/.well-known/jwks"
+
+        [[group_mappings]]
+        group = "platform-admins"
+        origin = "acme"
+        role = "administrator"
         "#;
 
         let config = Config::from_raw(content).unwrap();
@@ -487,10 +607,13 @@ mod tests {
 
         assert_eq!(&config.api.allowed_users_for_origin_create,
                    &["super1".to_string(), "super2".to_string()]);
+        assert_eq!(&config.api.admin_users, &["super1".to_string()]);
 
         assert_eq!(&config.api.features_enabled,
                    &["FOO".to_string(), "BAR".to_string()]);
         assert_eq!(config.api.private_max_age, 400);
+        assert_eq!(config.api.max_token_lifetime_days, Some(90));
+        assert_eq!(config.api.token_expiry_warning_days, 7);
 
         assert_eq!(&format!("{}", config.http.listen), "::1");
 
@@ -533,6 +656,26 @@ mod tests {
                    Some("/root_ca.crt".to_string()));
         assert_eq!(config.datastore.ssl_key, Some("/ssl.key".to_string()));
         assert_eq!(config.datastore.ssl_cert, Some("/ssl.crt".to_string()));
+
+        assert_eq!(config.channel_check.interval, 600);
+        assert_eq!(&config.channel_check.channels, &["core/stable".to_string()]);
+        assert_eq!(&config.channel_check.fallback_channels,
+                   &["base".to_string(), "stable".to_string()]);
+
+        assert_eq!(config.webhooks.interval, 5);
+        assert_eq!(config.webhooks.max_attempts, 3);
+        assert_eq!(config.webhooks.timeout, 2);
+        assert_eq!(config.webhooks.batch_size, 20);
+
+        assert_eq!(config.workload_identity.audience, "https://bldr.example.com");
+        assert_eq!(config.workload_identity.token_lifetime_minutes, 5);
+        assert_eq!(config.workload_identity.issuers[0].issuer,
+                   "https://token.actions.githubusercontent.com");
+        assert_eq!(config.workload_identity.issuers[0].jwks_path, None);
+
+        assert_eq!(config.group_mappings[0].group, "platform-admins");
+        assert_eq!(config.group_mappings[0].origin, "acme");
+        assert_eq!(config.group_mappings[0].role, OriginMemberRole::Administrator);
     }
 
     #[test]
@@ -12,15 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::str::FromStr;
+use std::{collections::HashMap,
+          str::FromStr};
 
-use actix_web::{HttpMessage,
+use actix_web::{http::Method,
+                web::Query,
+                HttpMessage,
                 HttpRequest};
 
 use crate::{bldr_core::{access_token::BUILDER_ACCOUNT_ID,
                         metrics::CounterMetric,
                         privilege::*},
-            db::models::origin::*,
+            db::models::{origin::*,
+                         role::{CustomRole,
+                                Permission}},
             protocol::originsrv};
 
 use crate::server::{error::{Error,
@@ -51,6 +56,10 @@ pub fn authorize_session(req: &HttpRequest,
         }
     };
 
+    if session.scope.is_some() {
+        check_token_scope(req, &session, origin_opt)?;
+    }
+
     if let Some(origin) = origin_opt {
         let minimum_req_role = match min_role {
             Some(r) => r,
@@ -61,7 +70,13 @@ pub fn authorize_session(req: &HttpRequest,
                 r
             }
         };
-        match check_origin_member_role(req, origin, session.id()) {
+        let member_role = match session.scope.as_ref().filter(|scope| scope.has_role()) {
+            // Tokens exchanged for a workload's identity carry their role, as no membership
+            // backs them
+            Some(scope) => OriginMemberRole::from_str(scope.role()).ok(),
+            None => check_origin_member_role(req, origin, session.id()),
+        };
+        match member_role {
             Some(member_role) => {
                 if member_role >= minimum_req_role {
                     debug!("authorize_session: account {} has {} permissions in origin {}",
@@ -90,6 +105,218 @@ pub fn authorize_session(req: &HttpRequest,
     Ok(session)
 }
 
+/// Authorizes a session to give a role in the origin, whether to a member, a team or a robot
+/// account, or to change or remove an account or team holding it. Along with the members
+/// permission this takes holding the role, as custom roles may carry the members permission and
+/// their holders mustn't hand out more than they have.
+pub fn authorize_role_grant(req: &HttpRequest,
+                            origin: &str,
+                            role: OriginMemberRole)
+                            -> Result<originsrv::Session> {
+    authorize_permission(req, origin, &Permission::Members)?;
+    authorize_session(req, Some(origin), Some(role))
+}
+
+/// Authorizes a session for the builder-wide administration api, which is limited to accounts
+/// flagged as administrators.
+pub fn authorize_admin(req: &HttpRequest) -> Result<originsrv::Session> {
+    let session = authorize_session(req, None, None)?;
+
+    let flags = FeatureFlags::from_bits(session.flags()).unwrap(); // unwrap Ok
+    if flags.contains(FeatureFlags::ADMIN) {
+        debug!("authorize_admin: account {} is an administrator",
+               session.id());
+        Ok(session)
+    } else {
+        debug!("authorize_admin: account {} is not an administrator",
+               session.id());
+        Err(Error::Authorization)
+    }
+}
+
+/// Authorizes a session for something that takes a permission in the origin. Members hold the
+/// permissions their role carries, along with those of the custom role the origin gave them.
+pub fn authorize_permission(req: &HttpRequest,
+                            origin: &str,
+                            permission: &Permission)
+                            -> Result<originsrv::Session> {
+    let session = authorize_session(req, Some(origin), None)?;
+
+    let flags = FeatureFlags::from_bits(session.flags()).unwrap(); // unwrap Ok
+    if flags.contains(FeatureFlags::BUILD_WORKER) {
+        return Ok(session);
+    }
+
+    let (member_role, custom_permissions) = member_standing(req, origin, &session);
+
+    if member_role.map_or(false, |role| role.grants(permission))
+       || custom_permissions.iter().any(|held| held.covers(permission))
+    {
+        debug!("authorize_permission: account {} has the {} permission in origin {}",
+               session.id(),
+               permission,
+               origin);
+        Ok(session)
+    } else {
+        debug!("authorize_permission: account {} does not have the {} permission in origin {}",
+               session.id(),
+               permission,
+               origin);
+        Err(Error::Authorization)
+    }
+}
+
+/// Authorizes a session for something that takes either a role or a permission in the origin,
+/// such as promoting to a channel whose policy names the role it takes. Members whose custom
+/// role carries the permission are allowed whatever their role.
+pub fn authorize_role_or_permission(req: &HttpRequest,
+                                    origin: &str,
+                                    min_role: OriginMemberRole,
+                                    permission: &Permission)
+                                    -> Result<originsrv::Session> {
+    let session = authorize_session(req, Some(origin), None)?;
+
+    let flags = FeatureFlags::from_bits(session.flags()).unwrap(); // unwrap Ok
+    if flags.contains(FeatureFlags::BUILD_WORKER) {
+        return Ok(session);
+    }
+
+    let (member_role, custom_permissions) = member_standing(req, origin, &session);
+
+    if member_role.map_or(false, |role| role >= min_role)
+       || custom_permissions.iter().any(|held| held.covers(permission))
+    {
+        Ok(session)
+    } else {
+        debug!("authorize_role_or_permission: account {} has neither the {} role nor the {} \
+                permission in origin {}",
+               session.id(),
+               min_role,
+               permission,
+               origin);
+        Err(Error::Authorization)
+    }
+}
+
+// The role of the session in the origin and the permissions of its custom role
+fn member_standing(req: &HttpRequest,
+                   origin: &str,
+                   session: &originsrv::Session)
+                   -> (Option<OriginMemberRole>, Vec<Permission>) {
+    match session.scope.as_ref().filter(|scope| scope.has_role()) {
+        // No membership, and so no custom role, backs the tokens of workloads
+        Some(scope) => (OriginMemberRole::from_str(scope.role()).ok(), Vec::new()),
+        None => {
+            (check_origin_member_role(req, origin, session.id()),
+             check_origin_member_permissions(req, origin, session.id()))
+        }
+    }
+}
+
+// Scoped tokens are limited to some origins, and to the operations of their capabilities. What
+// a request needs is worked out from its route, so that no handler can forget to check.
+fn check_token_scope(req: &HttpRequest,
+                     session: &originsrv::Session,
+                     origin_opt: Option<&str>)
+                     -> Result<()> {
+    let scope = match session.scope.as_ref() {
+        Some(scope) => scope,
+        None => return Ok(()),
+    };
+
+    // A request that isn't about a single origin, such as a package search, could show the
+    // account's other origins, so tokens limited to some origins can't make it.
+    if !scope.origins.is_empty() {
+        match origin_opt.or_else(|| req.match_info().get("origin")) {
+            Some(origin) if scope.origins.iter().any(|o| o == origin) => (),
+            origin => {
+                debug!("authorize_session: token of account {} is not scoped to origin {:?}",
+                       session.id(),
+                       origin);
+                return Err(Error::Authorization);
+            }
+        }
+    }
+
+    let capabilities = TokenCapabilities::from_bits_truncate(scope.capabilities());
+    let required = match required_capability(req) {
+        Some(required) => required,
+        None => {
+            debug!("authorize_session: scoped tokens may not be used for {} {}",
+                   req.method(),
+                   req.path());
+            return Err(Error::Authorization);
+        }
+    };
+    if !capabilities.contains(required) {
+        debug!("authorize_session: token of account {} lacks {:?} for {} {}",
+               session.id(),
+               required,
+               req.method(),
+               req.path());
+        return Err(Error::Authorization);
+    }
+
+    if required == TokenCapabilities::PROMOTE && !scope.channels.is_empty() {
+        match promotion_channel(req) {
+            Some(channel) if scope.channels.contains(&channel) => (),
+            channel => {
+                debug!("authorize_session: token of account {} is not scoped to channel {:?}",
+                       session.id(),
+                       channel);
+                return Err(Error::Authorization);
+            }
+        }
+    }
+    Ok(())
+}
+
+// The capability a scoped token needs for the request, or None when no scoped token may make it
+fn required_capability(req: &HttpRequest) -> Option<TokenCapabilities> {
+    let pattern = req.match_pattern().unwrap_or_default();
+    let method = req.method();
+    let read = method == Method::GET || method == Method::HEAD;
+
+    if pattern.contains("/secret_keys")
+       || pattern.contains("/encryption_key")
+       || pattern.starts_with("/depot/origins/{origin}/secret")
+       || (!read && pattern.starts_with("/depot/origins/{origin}/keys"))
+    {
+        Some(TokenCapabilities::KEYS)
+    } else if read {
+        // Downloading only reaches packages, channels and public keys, not the rest of what
+        // members of the origin can read
+        if pattern.starts_with("/depot/pkgs/")
+           || pattern.starts_with("/depot/channels/")
+           || pattern == "/depot/{origin}/pkgs"
+           || pattern.starts_with("/depot/origins/{origin}/keys")
+        {
+            Some(TokenCapabilities::DOWNLOAD)
+        } else {
+            None
+        }
+    } else if pattern.ends_with("/promote") || pattern.ends_with("/demote") {
+        Some(TokenCapabilities::PROMOTE)
+    } else if method == Method::POST && pattern == "/depot/pkgs/{origin}/{pkg}/{version}/{release}"
+    {
+        Some(TokenCapabilities::UPLOAD)
+    } else {
+        None
+    }
+}
+
+// The channel a promote or demote changes. Whole channel promotions name it in the query.
+fn promotion_channel(req: &HttpRequest) -> Option<String> {
+    let pattern = req.match_pattern().unwrap_or_default();
+    if pattern.ends_with("/pkgs/promote") || pattern.ends_with("/pkgs/demote") {
+        Query::<HashMap<String, String>>::from_query(req.query_string())
+            .ok()
+            .and_then(|query| query.get("channel").cloned())
+    } else {
+        req.match_info().get("channel").map(str::to_string)
+    }
+}
+
 pub fn check_origin_owner(req: &HttpRequest, account_id: u64, origin: &str) -> Result<bool> {
     let mut conn = req_state(req).db.get_conn().map_err(Error::DbError)?;
 
@@ -154,7 +381,7 @@ fn check_origin_member_role(req: &HttpRequest,
         }
         match req_state(req).db.get_conn() {
             Ok(mut conn) => {
-                match OriginMember::member_role(origin, account_id as i64, &mut conn) {
+                match OriginMember::effective_role(origin, account_id as i64, &mut conn) {
                     Ok(member_role) => {
                         memcache.set_origin_member_role(origin,
                                                         account_id,
@@ -178,3 +405,44 @@ fn check_origin_member_role(req: &HttpRequest,
         }
     }
 }
+
+// The permissions of the custom role an account holds in the origin
+fn check_origin_member_permissions(req: &HttpRequest,
+                                   origin: &str,
+                                   account_id: u64)
+                                   -> Vec<Permission> {
+    if account_id == BUILDER_ACCOUNT_ID {
+        return Vec::new();
+    }
+
+    let mut memcache = req_state(req).memcache.borrow_mut();
+    if let Some(val) = memcache.get_origin_member_permissions(origin, account_id) {
+        debug!("Origin member permissions {} {} Cache Hit!",
+               origin, account_id);
+        match serde_json::from_str::<Vec<String>>(&val) {
+            Ok(names) => return names.iter().filter_map(|name| name.parse().ok()).collect(),
+            Err(_) => debug!("Unable to unwrap permissions from memcache!"),
+        }
+    }
+
+    let permissions = match req_state(req).db.get_conn() {
+        Ok(mut conn) => {
+            match CustomRole::member_permissions(origin, account_id as i64, &mut conn) {
+                Ok(permissions) => permissions,
+                Err(err) => {
+                    warn!("Unable to get the permissions of account {} in origin {}: {}",
+                          account_id, origin, err);
+                    return Vec::new();
+                }
+            }
+        }
+        Err(err) => {
+            warn!("Unable to retrieve request state: {}", err);
+            return Vec::new();
+        }
+    };
+
+    let names: Vec<String> = permissions.iter().map(Permission::to_string).collect();
+    memcache.set_origin_member_permissions(origin, account_id, &json!(names).to_string());
+    permissions
+}
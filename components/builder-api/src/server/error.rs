@@ -68,6 +68,7 @@ pub enum Error {
     PackageUpload(Box<SdkError<PutObjectError>>),
     PartialUpload(Box<SdkError<UploadPartError>>),
     PayloadError(actix_web::error::PayloadError),
+    PolicyViolation(String),
     Protobuf(protobuf::Error),
     SerdeJson(serde_json::Error),
     System,
@@ -107,6 +108,7 @@ impl fmt::Display for Error {
             Error::PackageUpload(ref e) => format!("{}", e),
             Error::PartialUpload(ref e) => format!("{}", e),
             Error::PayloadError(ref e) => format!("{}", e),
+            Error::PolicyViolation(ref msg) => msg.to_string(),
             Error::Protobuf(ref e) => format!("{}", e),
             Error::SerdeJson(ref e) => format!("{}", e),
             Error::System => "Internal error".to_string(),
@@ -130,6 +132,7 @@ impl ResponseError for Error {
             Error::Github(_) => HttpResponse::new(StatusCode::FORBIDDEN),
             Error::NotFound => HttpResponse::new(StatusCode::NOT_FOUND),
             Error::OAuth(_) => HttpResponse::new(StatusCode::UNAUTHORIZED),
+            Error::PolicyViolation(ref msg) => HttpResponse::Forbidden().body(msg.to_string()),
             Error::DieselError(ref e) => HttpResponse::new(diesel_err_to_http(e)),
             Error::System => HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR),
             Error::Unprocessable => HttpResponse::new(StatusCode::UNPROCESSABLE_ENTITY),
@@ -164,6 +167,7 @@ impl Into<HttpResponse> for Error {
             Error::HabitatCore(_) => HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR),
             Error::NotFound => HttpResponse::new(StatusCode::NOT_FOUND),
             Error::OAuth(_) => HttpResponse::new(StatusCode::UNAUTHORIZED),
+            Error::PolicyViolation(ref msg) => HttpResponse::Forbidden().body(msg.to_string()),
             Error::BuilderCore(ref e) => HttpResponse::new(bldr_core_err_to_http(e)),
             Error::DieselError(ref e) => HttpResponse::new(diesel_err_to_http(e)),
             Error::System => HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR),
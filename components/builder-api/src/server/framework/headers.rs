@@ -16,8 +16,10 @@ pub const NO_CACHE: &str = "private, no-cache, no-store";
 pub const CACHE: &str = "public, max-age=31536000"; // ONE_YEAR_IN_SECONDS
 
 pub const APPLICATION_JSON: &str = "application/json";
+pub const TEXT_EVENT_STREAM: &str = "text/event-stream";
 
 pub const XFILENAME: &str = "x-filename"; // must be lowercase
+pub const X_ACCEL_BUFFERING: &str = "x-accel-buffering"; // must be lowercase
 
 #[derive(Default)]
 pub enum Cache {
@@ -6,24 +6,29 @@ use crate::{bldr_core::{access_token::{AccessToken,
             protocol::{self,
                        originsrv},
             server::{error,
+                     services::idp_groups,
                      AppState}};
 use actix_web::{body::BoxBody,
                 dev::{Service,
                       ServiceRequest,
                       ServiceResponse},
-                http,
+                http::{self,
+                       header::HeaderValue},
                 web::Data,
                 Error,
                 HttpMessage,
                 HttpResponse};
+use chrono::{TimeZone,
+             Utc};
 use futures::future::{ok,
                       Either,
-                      Future};
+                      Future,
+                      FutureExt};
 use oauth_client::types::OAuth2User;
 use std::env;
 
 lazy_static! {
-    static ref SESSION_DURATION: u32 = 3 * 24 * 60 * 60;
+    pub static ref SESSION_DURATION: u32 = 3 * 24 * 60 * 60;
 }
 
 // Optional Authentication - this middleware does not enforce authentication,
@@ -36,7 +41,7 @@ pub fn authentication_middleware<S>(
 {
     let hdr = match req.headers().get(http::header::AUTHORIZATION) {
         Some(hdr) => hdr.to_str().unwrap(), // unwrap Ok
-        None => return Either::Left(srv.call(req)),
+        None => return Either::Left(srv.call(req).map(with_expiry_warning(None))),
     };
 
     let hdr_components: Vec<&str> = hdr.split_whitespace().collect();
@@ -54,8 +59,40 @@ pub fn authentication_middleware<S>(
         }
     };
 
+    let warning = expiry_warning(&session,
+                                 req.app_data::<Data<AppState>>().expect("request state"));
     req.extensions_mut().insert::<originsrv::Session>(session);
-    Either::Left(srv.call(req))
+    Either::Left(srv.call(req).map(with_expiry_warning(warning)))
+}
+
+// Tokens close to expiring are pointed out on every response, so that clients can replace them
+fn expiry_warning(session: &originsrv::Session, state: &AppState) -> Option<HeaderValue> {
+    if !session.has_expires() || session.id() == BUILDER_ACCOUNT_ID {
+        return None;
+    }
+
+    let remaining = session.expires() - Utc::now().timestamp();
+    if remaining > i64::from(state.config.api.token_expiry_warning_days) * 24 * 60 * 60 {
+        return None;
+    }
+
+    let expires = Utc.timestamp_opt(session.expires(), 0).single()?;
+    let warning = format!("299 - \"Access token expires at {}\"", expires.to_rfc3339());
+    HeaderValue::from_str(&warning).ok()
+}
+
+type ServiceResult = Result<ServiceResponse<BoxBody>, Error>;
+
+fn with_expiry_warning(warning: Option<HeaderValue>)
+                       -> impl FnOnce(ServiceResult) -> ServiceResult {
+    move |res| {
+        res.map(|mut res| {
+               if let Some(warning) = warning {
+                   res.headers_mut().insert(http::header::WARNING, warning);
+               }
+               res
+           })
+    }
 }
 
 fn authenticate(token: &str, state: &AppState) -> error::Result<originsrv::Session> {
@@ -87,6 +124,15 @@ fn handle_test_environment(token: &str,
 fn get_cached_session(token: &str, state: &AppState) -> Option<originsrv::Session> {
     let mut memcache = state.memcache.borrow_mut();
     match memcache.get_session(token) {
+        // A cached session is of no use once its token has expired
+        Some(session) if session.has_expires() && session.expires() < Utc::now().timestamp() => {
+            trace!("Session {} Cache Hit, but its token has expired", token);
+            None
+        }
+        Some(session) if memcache.is_account_disabled(session.id()) => {
+            trace!("Session {} Cache Hit, but its account has been disabled", token);
+            None
+        }
         Some(session) => {
             trace!("Session {} Cache Hit!", token);
             Some(session)
@@ -127,11 +173,22 @@ fn handle_builder_account(session: &mut originsrv::Session,
                           state: &AppState)
                           -> Option<originsrv::Session> {
     if session.id() == BUILDER_ACCOUNT_ID {
-        trace!("Builder token identified");
-        session.set_name(BUILDER_ACCOUNT_NAME.to_owned());
-        state.memcache
-             .borrow_mut()
-             .set_session(token, session, None);
+        // Tokens exchanged for a workload's identity token go by the workload's name
+        match session.scope.as_ref().filter(|scope| scope.has_subject()) {
+            Some(scope) => {
+                trace!("Workload token identified");
+                let name = scope.subject().to_owned();
+                session.set_name(name);
+            }
+            None => {
+                trace!("Builder token identified");
+                session.set_name(BUILDER_ACCOUNT_NAME.to_owned());
+            }
+        }
+        let mut memcache = state.memcache.borrow_mut();
+        let remaining = session.expires() - Utc::now().timestamp();
+        let ttl = remaining.clamp(1, i64::from(memcache.session_ttl())) as u32;
+        memcache.set_session(token, session, Some(ttl));
         return Some(session.clone());
     }
     None
@@ -156,43 +213,62 @@ fn validate_database_token(token: &str,
             }
         };
 
-    validate_token_count_and_match(token, session, &access_tokens, &mut conn, state)
+    validate_token_match(token, session, &access_tokens, &mut conn, state)
 }
 
-fn validate_token_count_and_match(token: &str,
-                                  session: &mut originsrv::Session,
-                                  access_tokens: &[AccountToken],
-                                  conn: &mut diesel::PgConnection,
-                                  state: &AppState)
-                                  -> error::Result<originsrv::Session> {
-    if access_tokens.len() > 1 {
-        error!("Found {} tokens for user {} but there should only be one",
-               access_tokens.len(),
-               session.id());
-        return Err(error::Error::Authorization);
-    }
+fn validate_token_match(token: &str,
+                        session: &mut originsrv::Session,
+                        access_tokens: &[AccountToken],
+                        conn: &mut diesel::PgConnection,
+                        state: &AppState)
+                        -> error::Result<originsrv::Session> {
+    let access_token = match find_account_token(token, access_tokens) {
+        Some(access_token) => access_token,
+        None => {
+            trace!("Token {} not found for user {}. Token is valid but revoked",
+                   token,
+                   session.id());
+            return Err(error::Error::Authorization);
+        }
+    };
 
-    let access_token = access_tokens.first().ok_or_else(|| {
-                                                 trace!("Failed to find token {} in database",
-                                                        token);
-                                                 error::Error::Authorization
-                                             })?;
-
-    let new_token = &access_token.token;
-    if token.trim_end_matches('=') != new_token.trim_end_matches('=') {
-        trace!("Different token {} found for user {}. Token is valid but revoked or otherwise \
-                expired",
-               new_token,
-               session.id());
-        return Err(error::Error::Authorization);
+    // Seconds until the token expires, so that its session isn't cached beyond that
+    let remaining = match access_token.expires_at {
+        Some(expires_at) => {
+            let remaining = (expires_at - Utc::now().naive_utc()).num_seconds();
+            if remaining <= 0 {
+                trace!("Token {} for user {} expired at {}",
+                       access_token.name,
+                       session.id(),
+                       expires_at);
+                return Err(error::Error::Authorization);
+            }
+            Some(remaining)
+        }
+        None => None,
+    };
+
+    if let Err(err) = AccountToken::touch(access_token.id, conn) {
+        debug!("Failed to record use of token {} for user {}: {}",
+               access_token.name,
+               session.id(),
+               err);
     }
 
-    finalize_session_with_account(token, session, new_token, conn, state)
+    finalize_session_with_account(session, access_token, remaining, conn, state)
+}
+
+// An account may hold any number of tokens; the one presented has to be among them
+fn find_account_token<'a>(token: &str,
+                          access_tokens: &'a [AccountToken])
+                          -> Option<&'a AccountToken> {
+    access_tokens.iter()
+                 .find(|t| token.trim_end_matches('=') == t.token.trim_end_matches('='))
 }
 
-fn finalize_session_with_account(_token: &str,
-                                 session: &mut originsrv::Session,
-                                 new_token: &str,
+fn finalize_session_with_account(session: &mut originsrv::Session,
+                                 access_token: &AccountToken,
+                                 remaining: Option<i64>,
                                  conn: &mut diesel::PgConnection,
                                  state: &AppState)
                                  -> error::Result<originsrv::Session> {
@@ -205,16 +281,36 @@ fn finalize_session_with_account(_token: &str,
                                                                    error::Error::Authorization
                                                                })?;
 
-    trace!("Found account for token {} in database", new_token);
+    if account.disabled {
+        trace!("Account {} for token {} is disabled",
+               account.name,
+               access_token.name);
+        return Err(error::Error::Authorization);
+    }
+
+    trace!("Found account for token {} in database", access_token.name);
+    // Tokens keep the flags of the session they were made in, so an account that is no longer
+    // an administrator loses the privilege here rather than when its tokens are replaced
+    if !is_admin_user(&account.name, state) {
+        session.set_flags(session.flags() & !FeatureFlags::ADMIN.bits());
+    }
     session.set_name(account.name);
     session.set_email(account.email);
+    // Tokens created before expiries were encrypted into them only carry theirs in the database
+    if let Some(expires_at) = access_token.expires_at {
+        session.set_expires(session.expires().min(expires_at.and_utc().timestamp()));
+    }
 
-    state.memcache
-         .borrow_mut()
-         .set_session(new_token, session, None);
+    let mut memcache = state.memcache.borrow_mut();
+    let ttl = remaining.map(|remaining| remaining.min(i64::from(memcache.session_ttl())) as u32);
+    memcache.set_session(&access_token.token, session, ttl);
     Ok(session.clone())
 }
 
+fn is_admin_user(name: &str, state: &AppState) -> bool {
+    state.config.api.admin_users.iter().any(|admin| admin == name)
+}
+
 pub fn session_create_oauth(oauth_token: &str,
                             user: &OAuth2User,
                             provider: &str,
@@ -236,7 +332,18 @@ pub fn session_create_oauth(oauth_token: &str,
                                                 email },
                                   &mut conn)
     {
+        // Robot accounts share the account namespace, but are never signed in to
+        Ok(account) if account.is_robot() => {
+            warn!("Refusing OAuth sign in to robot account {}", account.name);
+            Err(error::Error::Authorization)
+        }
+        Ok(account) if account.disabled => {
+            warn!("Refusing OAuth sign in to disabled account {}", account.name);
+            Err(error::Error::Authorization)
+        }
         Ok(account) => {
+            idp_groups::sync_memberships(&account, &user.groups, state, &mut conn);
+
             session_token.set_account_id(account.id as u64);
             session_token.set_extern_id(user.id.to_string());
             session_token.set_token(oauth_token.to_string().into_bytes());
@@ -250,11 +357,17 @@ pub fn session_create_oauth(oauth_token: &str,
                 }
             }
 
+            let flags = if is_admin_user(&account.name, state) {
+                FeatureFlags::ADMIN
+            } else {
+                FeatureFlags::empty()
+            };
+
             let encoded_token = encode_token(&session_token);
             session.set_id(account.id as u64);
             session.set_name(account.name);
             session.set_token(encoded_token);
-            session.set_flags(FeatureFlags::empty().bits());
+            session.set_flags(flags.bits());
             session.set_oauth_token(oauth_token.to_owned());
 
             debug!("issuing session, {:?}", session);
@@ -277,31 +390,36 @@ pub fn session_create_short_circuit(token: &str,
         "bobo" => {
             (OAuth2User { id:       "0".to_string(),
                           email:    Some("bobo@example.com".to_string()),
-                          username: "bobo".to_string(), },
+                          username: "bobo".to_string(),
+                          groups:   Vec::new(), },
              "GitHub")
         }
         "mystique" => {
             (OAuth2User { id:       "1".to_string(),
                           email:    Some("mystique@example.com".to_string()),
-                          username: "mystique".to_string(), },
+                          username: "mystique".to_string(),
+                          groups:   Vec::new(), },
              "GitHub")
         }
         "hank" => {
             (OAuth2User { id:       "2".to_string(),
                           email:    Some("hank@example.com".to_string()),
-                          username: "hank".to_string(), },
+                          username: "hank".to_string(),
+                          groups:   Vec::new(), },
              "GitHub")
         }
         "wesker" => {
             (OAuth2User { id:       "3".to_string(),
                           email:    Some("awesker@umbrella.corp".to_string()),
-                          username: "wesker".to_string(), },
+                          username: "wesker".to_string(),
+                          groups:   Vec::new(), },
              "GitHub")
         }
         "lkennedy" => {
             (OAuth2User { id:       "4".to_string(),
                           email:    Some("lkennedy@rcpd.gov".to_string()),
-                          username: "lkennedy".to_string(), },
+                          username: "lkennedy".to_string(),
+                          groups:   Vec::new(), },
              "GitHub")
         }
         user => {
@@ -317,3 +435,42 @@ fn encode_token(token: &originsrv::SessionToken) -> String {
     let bytes = protocol::message::encode(token).unwrap(); // Unwrap is safe
     habitat_core::base64::encode(bytes)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn account_token(id: i64, name: &str, token: &str) -> AccountToken {
+        AccountToken { id,
+                       account_id: 42,
+                       token: token.to_string(),
+                       created_at: None,
+                       name: name.to_string(),
+                       last_used_at: None,
+                       expires_at: None,
+                       scope: None }
+    }
+
+    #[test]
+    fn any_of_the_account_tokens_is_found() {
+        let tokens = vec![account_token(1, "laptop", "dG9rZW4x"),
+                          account_token(2, "ci", "dG9rZW4y"),];
+        assert_eq!(find_account_token("dG9rZW4x", &tokens).unwrap().name, "laptop");
+        assert_eq!(find_account_token("dG9rZW4y", &tokens).unwrap().name, "ci");
+    }
+
+    #[test]
+    fn token_padding_is_ignored() {
+        let tokens = vec![account_token(1, "laptop", "dG9rZW4xMg==")];
+        assert_eq!(find_account_token("dG9rZW4xMg", &tokens).unwrap().id, 1);
+    }
+
+    #[test]
+    fn revoked_tokens_are_not_found() {
+        let mut tokens = vec![account_token(1, "laptop", "dG9rZW4x"),
+                              account_token(2, "ci", "dG9rZW4y"),];
+        tokens.retain(|t| t.id != 1);
+        assert!(find_account_token("dG9rZW4x", &tokens).is_none());
+        assert_eq!(find_account_token("dG9rZW4y", &tokens).unwrap().id, 2);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::{db::models::{channel::PackageChannelTrigger as PCT,
-                         origin::OriginMemberRole,
+                         origin::{Origin,
+                                  OriginMemberRole},
                          package::PackageVisibility},
             hab_core::package::PackageTarget,
             server::{authorize::authorize_session,
+                     error::Result,
                      AppState}};
 use actix_web::{http::header,
                 web::Query,
@@ -10,13 +12,16 @@ use actix_web::{http::header,
                 HttpResponse};
 use chrono::{NaiveDate,
              NaiveDateTime};
+use diesel::pg::PgConnection;
 use regex::Regex;
 use serde::Serialize;
 use serde_json::Value;
-use std::str::FromStr;
+use std::{collections::HashMap,
+          str::FromStr};
 // TODO - this module should not just be a grab bag of stuff
 
 pub const PAGINATION_RANGE_MAX: isize = 50;
+pub const STATS_RANGE_DEFAULT_DAYS: i64 = 30;
 
 #[derive(Deserialize)]
 pub struct Target {
@@ -38,6 +43,12 @@ pub struct SearchQuery {
     pub query: String,
 }
 
+#[derive(Deserialize)]
+pub struct SearchSort {
+    #[serde(default)]
+    pub sort: String,
+}
+
 #[derive(Serialize)]
 pub struct PaginatedResults<'a, T: 'a> {
     range_start: isize,
@@ -67,6 +78,14 @@ pub struct DateRange {
     pub to_date:   NaiveDateTime,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct StatsDateRange {
+    #[serde(default)]
+    pub from_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub to_date:   Option<NaiveDate>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Role {
     #[serde(default)]
@@ -105,6 +124,16 @@ pub fn extract_pagination(pagination: &Query<Pagination>) -> (isize, isize) {
     (pagination.range, pagination.range + PAGINATION_RANGE_MAX - 1)
 }
 
+// Returns the inclusive date window for download stats, defaulting to the last 30 days
+pub fn extract_stats_range(range: &Query<StatsDateRange>) -> (NaiveDate, NaiveDate) {
+    let to_date = range.to_date
+                       .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let from_date = range.from_date.unwrap_or_else(|| {
+                                        to_date - chrono::Duration::days(STATS_RANGE_DEFAULT_DAYS)
+                                    });
+    (from_date, to_date)
+}
+
 // Returns the page number we are currently on and the per_page size
 pub fn extract_pagination_in_pages(pagination: &Query<Pagination>) -> (isize, isize) {
     #[allow(clippy::integer_division)]
@@ -233,6 +262,25 @@ pub fn visibility_for_optional_session(req: &HttpRequest,
     v
 }
 
+// Packages from an origin whose default visibility isn't public are only visible to members of
+// that origin. Answers are kept in `seen` since callers usually check many packages from a
+// handful of origins.
+pub fn origin_visible_for_session(req: &HttpRequest,
+                                  origin: &str,
+                                  seen: &mut HashMap<String, bool>,
+                                  conn: &mut PgConnection)
+                                  -> Result<bool> {
+    if let Some(visible) = seen.get(origin) {
+        return Ok(*visible);
+    }
+
+    let default_visibility = Origin::get(origin, conn)?.default_package_visibility;
+    let visible = default_visibility == PackageVisibility::Public
+                  || authorize_session(req, Some(origin), Some(OriginMemberRole::Member)).is_ok();
+    seen.insert(origin.to_string(), visible);
+    Ok(visible)
+}
+
 // TED remove function above when it's no longer used anywhere
 pub fn trigger_from_request_model(req: &HttpRequest) -> PCT {
     // TODO: the search strings should be configurable.
@@ -279,3 +327,32 @@ mod ymd_date_format {
         Ok(naive_date.and_hms_opt(0, 0, 0).unwrap())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats_range(from_date: Option<&str>, to_date: Option<&str>) -> Query<StatsDateRange> {
+        Query(StatsDateRange { from_date: from_date.map(|d| d.parse().unwrap()),
+                               to_date:   to_date.map(|d| d.parse().unwrap()), })
+    }
+
+    #[test]
+    fn stats_range_defaults_to_the_last_thirty_days() {
+        let (from_date, to_date) = extract_stats_range(&stats_range(None, None));
+        assert_eq!(to_date, chrono::Utc::now().date_naive());
+        assert_eq!(to_date - from_date, chrono::Duration::days(STATS_RANGE_DEFAULT_DAYS));
+
+        let (from_date, to_date) = extract_stats_range(&stats_range(None, Some("2026-10-18")));
+        assert_eq!(from_date, "2026-09-18".parse::<NaiveDate>().unwrap());
+        assert_eq!(to_date, "2026-10-18".parse::<NaiveDate>().unwrap());
+    }
+
+    #[test]
+    fn stats_range_keeps_the_requested_dates() {
+        let range = stats_range(Some("2026-10-20"), Some("2026-10-01"));
+        let (from_date, to_date) = extract_stats_range(&range);
+        assert_eq!(from_date, "2026-10-20".parse::<NaiveDate>().unwrap());
+        assert_eq!(to_date, "2026-10-01".parse::<NaiveDate>().unwrap());
+    }
+}
@@ -8,17 +8,28 @@ pub mod resources;
 pub mod services;
 
 use self::{framework::middleware::authentication_middleware,
-           resources::{authenticate::Authenticate,
+           resources::{admin::Admin,
+                       authenticate::Authenticate,
                        channels::Channels,
                        events::Events,
                        ext::Ext,
                        origins::Origins,
                        pkgs::Packages,
                        profile::Profile,
+                       robots::Robots,
+                       roles::Roles,
                        settings::Settings,
-                       user::User},
-           services::{memcache::MemcacheClient,
-                      s3::S3Handler}};
+                       teams::Teams,
+                       trust_rules::TrustRules,
+                       user::User,
+                       webhooks::Webhooks},
+           services::{channel_check,
+                      channel_reaper,
+                      event_stream,
+                      memcache::MemcacheClient,
+                      promotion_scheduler,
+                      s3::S3Handler,
+                      webhooks}};
 use crate::{bldr_core::keys,
             config::{Config,
                      GatewayCfg},
@@ -158,6 +169,14 @@ pub async fn run(config: Config) -> error::Result<()> {
         }
     }
 
+    channel_check::start(config.channel_check.clone(), db_pool.clone());
+    promotion_scheduler::start(config.memcache.clone(), db_pool.clone());
+    channel_reaper::start(config.memcache.clone(), db_pool.clone());
+    webhooks::start(config.webhooks.clone(),
+                    config.api.key_path.clone(),
+                    db_pool.clone());
+    let event_stream = event_stream::start(db_pool.clone());
+
     let mut srv = HttpServer::new(move || {
                       let app_state = match AppState::new(&config, db_pool.clone()) {
                           Ok(state) => state,
@@ -169,10 +188,12 @@ pub async fn run(config: Config) -> error::Result<()> {
 
                       App::new()
             .app_data(web::Data::new(app_state))
+            .app_data(web::Data::new(event_stream.clone()))
             .wrap_fn(authentication_middleware)
             .wrap(Logger::default().exclude("/v1/status"))
             .service(
                 web::scope("/v1")
+                    .configure(Admin::register)
                     .configure(Authenticate::register)
                     .configure(Channels::register)
                     .configure(Ext::register)
@@ -180,8 +201,13 @@ pub async fn run(config: Config) -> error::Result<()> {
                     .configure(Origins::register)
                     .configure(Packages::register)
                     .configure(Profile::register)
+                    .configure(Robots::register)
+                    .configure(Roles::register)
                     .configure(Settings::register)
+                    .configure(Teams::register)
+                    .configure(TrustRules::register)
                     .configure(User::register)
+                    .configure(Webhooks::register)
                     .configure(Events::register)
                     .service(
                         web::resource("/status")
@@ -18,6 +18,9 @@ use std::{fs::{self,
           io::Write};
 
 const BLDR_TOKEN_FILE_NAME: &str = "HAB_AUTH_TOKEN";
+// The name tokens created before accounts could hold several were given, so that an already
+// provisioned environment keeps its token.
+const PROVISION_TOKEN_NAME: &str = "default";
 
 /// This function handles the provisioning of the Builder environment.
 /// It performs multiple tasks including:
@@ -72,7 +75,8 @@ pub fn provision_bldr_environment(app_state: &AppState) -> Result<String, Error>
     {
         let new_channel = CreateChannel { name: channel,
                                           origin,
-                                          owner_id: account.id };
+                                          owner_id: account.id,
+                                          expires_at: None };
 
         match Channel::create(&new_channel, &mut conn) {
             Ok(_) => {}
@@ -91,10 +95,9 @@ pub fn provision_bldr_environment(app_state: &AppState) -> Result<String, Error>
     }
 
     let tokens = AccountToken::list(account.id as u64, &mut conn).map_err(Error::DieselError)?;
-    assert!(tokens.len() <= 1); // Can only have max of 1 token
 
     // If a token is already found, return it
-    if let Some(access_token) = tokens.first() {
+    if let Some(access_token) = tokens.iter().find(|t| t.name == PROVISION_TOKEN_NAME) {
         info!("An existing auth token is already present, skipping create");
         return Ok(access_token.token.to_string());
     }
@@ -102,9 +105,13 @@ pub fn provision_bldr_environment(app_state: &AppState) -> Result<String, Error>
     // Create token
     let token = AccessToken::user_token(&app_state.config.api.key_path,
                                         account.id as u64,
-                                        FeatureFlags::all().bits())?;
+                                        FeatureFlags::all().bits(),
+                                        None)?;
     let new_token = NewAccountToken { account_id: account.id,
-                                      token:      &token.to_string(), };
+                                      token:      &token.to_string(),
+                                      name:       PROVISION_TOKEN_NAME,
+                                      expires_at: None,
+                                      scope:      None, };
     AccountToken::create(&new_token, &mut conn).map_err(Error::DieselError)?;
 
     // Store the token in a file
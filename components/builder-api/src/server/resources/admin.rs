@@ -0,0 +1,452 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The builder-wide administration api.
+//!
+//! Only accounts flagged as administrators, by being listed in the `admin_users` config, may use
+//! it. It covers what operators would otherwise change in the database by hand, and every
+//! change it makes is recorded in the audit log.
+use actix_web::{body::BoxBody,
+                http::{self,
+                       StatusCode},
+                web::{self,
+                      Data,
+                      Path,
+                      Query,
+                      ServiceConfig},
+                HttpRequest,
+                HttpResponse};
+use bytes::Bytes;
+
+use crate::{db::models::{account::*,
+                         audit::AuditAction,
+                         origin::{Origin,
+                                  OriginMember,
+                                  OriginMemberRole},
+                         system_stats::SystemStats},
+            server::{authorize::authorize_admin,
+                     error::{Error,
+                             Result},
+                     framework::{headers,
+                                 middleware::SESSION_DURATION},
+                     helpers::{self,
+                               Pagination,
+                               SearchQuery},
+                     resources::{origins::notify_member_change,
+                                 profile::{do_get_access_tokens,
+                                           revoke_account_token}},
+                     services::audit,
+                     AppState}};
+
+pub struct Admin {}
+
+impl Admin {
+    // Route registration
+    //
+    pub fn register(cfg: &mut ServiceConfig) {
+        cfg.service(
+            web::scope("/admin")
+                .route("/accounts", web::get().to(search_accounts))
+                .route("/accounts/{account}", web::get().to(get_account))
+                .route("/accounts/{account}/disable", web::post().to(disable_account))
+                .route("/accounts/{account}/enable", web::post().to(enable_account))
+                .route("/accounts/{account}/tokens", web::get().to(list_account_tokens))
+                .route("/accounts/{account}/tokens/{id}",
+                       web::delete().to(revoke_token))
+                .route("/origins/{origin}/transfer/{user}",
+                       web::post().to(force_transfer_origin))
+                .route("/origins/{origin}/hide", web::post().to(hide_origin))
+                .route("/origins/{origin}/unhide", web::post().to(unhide_origin))
+                .route("/stats", web::get().to(get_stats)),
+        );
+    }
+}
+
+// Route handlers - these functions can return any Responder trait
+//
+#[allow(clippy::needless_pass_by_value)]
+async fn search_accounts(req: HttpRequest,
+                         pagination: Query<Pagination>,
+                         search: Query<SearchQuery>,
+                         state: Data<AppState>)
+                         -> HttpResponse {
+    if let Err(err) = authorize_admin(&req) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let (page, per_page) = helpers::extract_pagination_in_pages(&pagination);
+    let sa = SearchAccounts { query: search.query.trim().to_string(),
+                              page:  page as i64,
+                              limit: per_page as i64, };
+
+    match Account::search(&sa, &mut conn).map_err(Error::DieselError) {
+        Ok((accounts, count)) => {
+            let (start, _) = helpers::extract_pagination(&pagination);
+            let stop = start + accounts.len() as isize - 1;
+            let body = helpers::package_results_json(&accounts, count as isize, start, stop);
+
+            let mut response = if count as isize > (stop + 1) {
+                HttpResponse::PartialContent()
+            } else {
+                HttpResponse::Ok()
+            };
+            response.append_header((http::header::CONTENT_TYPE, headers::APPLICATION_JSON))
+                    .append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                    .body(body)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_account(req: HttpRequest, path: Path<String>, state: Data<AppState>) -> HttpResponse {
+    let name = path.into_inner();
+
+    if let Err(err) = authorize_admin(&req) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let account = match Account::get(&name, &mut conn).map_err(Error::DieselError) {
+        Ok(account) => account,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    match OriginMember::list_for_account(account.id, &mut conn).map_err(Error::DieselError) {
+        Ok(memberships) => {
+            let memberships: Vec<serde_json::Value> =
+                memberships.iter()
+                           .map(|(origin, role, idp_managed)| {
+                               json!({ "origin": origin,
+                                       "role": role,
+                                       "idp_managed": idp_managed })
+                           })
+                           .collect();
+
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(json!({ "account": account, "memberships": memberships }))
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn disable_account(req: HttpRequest,
+                         path: Path<String>,
+                         state: Data<AppState>)
+                         -> HttpResponse {
+    set_account_disabled(&req, &path.into_inner(), true, &state)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn enable_account(req: HttpRequest,
+                        path: Path<String>,
+                        state: Data<AppState>)
+                        -> HttpResponse {
+    set_account_disabled(&req, &path.into_inner(), false, &state)
+}
+
+fn set_account_disabled(req: &HttpRequest,
+                        name: &str,
+                        disabled: bool,
+                        state: &AppState)
+                        -> HttpResponse {
+    let session = match authorize_admin(req) {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    // Administrators locking themselves out would leave no one to undo it
+    if disabled && name == session.name() {
+        let body = Bytes::from_static(b"Cannot disable your own account");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let account = match Account::get(name, &mut conn).map_err(Error::DieselError) {
+        Ok(account) => account,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    if account.disabled == disabled {
+        return HttpResponse::NoContent().finish();
+    }
+
+    match Account::set_disabled(account.id, disabled, &mut conn).map_err(Error::DieselError) {
+        Ok(_) => {
+            let tokens = AccountToken::list(account.id as u64, &mut conn).unwrap_or_default();
+            let mut memcache = state.memcache.borrow_mut();
+            if disabled {
+                memcache.set_account_disabled(account.id as u64, *SESSION_DURATION);
+                for token in tokens.iter() {
+                    memcache.delete_session_key(&token.token);
+                }
+            } else {
+                memcache.clear_account_disabled(account.id as u64);
+            }
+
+            let action = if disabled {
+                AuditAction::AccountDisable
+            } else {
+                AuditAction::AccountEnable
+            };
+            audit::record(req,
+                          None,
+                          action,
+                          &account.name,
+                          Some(json!({ "disabled": account.disabled })),
+                          Some(json!({ "disabled": disabled })),
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn list_account_tokens(req: HttpRequest,
+                             path: Path<String>,
+                             state: Data<AppState>)
+                             -> HttpResponse {
+    let name = path.into_inner();
+
+    if let Err(err) = authorize_admin(&req) {
+        return err.into();
+    }
+
+    let account_id = match get_account_id(&name, &state) {
+        Ok(account_id) => account_id,
+        Err(err) => return err.into(),
+    };
+
+    match do_get_access_tokens(&req, account_id) {
+        Ok(tokens) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(json!({ "tokens": tokens }))
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn revoke_token(req: HttpRequest,
+                      path: Path<(String, String)>,
+                      state: Data<AppState>)
+                      -> HttpResponse {
+    let (name, token_id) = path.into_inner();
+
+    let token_id = match token_id.parse::<u64>() {
+        Ok(id) => id,
+        Err(_) => {
+            let body = Bytes::from_static(b"Error parsing access token.");
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    };
+
+    if let Err(err) = authorize_admin(&req) {
+        return err.into();
+    }
+
+    let account_id = match get_account_id(&name, &state) {
+        Ok(account_id) => account_id,
+        Err(err) => return err.into(),
+    };
+
+    revoke_account_token(&req, account_id, token_id, None, &state)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn force_transfer_origin(req: HttpRequest,
+                               path: Path<(String, String)>,
+                               state: Data<AppState>)
+                               -> HttpResponse {
+    let (origin, user) = path.into_inner();
+
+    if let Err(err) = authorize_admin(&req) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let previous_owner = match Origin::get(&origin, &mut conn).map_err(Error::DieselError) {
+        Ok(origin) => origin.owner_account,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    let recipient = match Account::get(&user, &mut conn).map_err(Error::DieselError) {
+        Ok(account) => account,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    if recipient.is_robot() || recipient.disabled {
+        let body = Bytes::from_static(b"Origins can only be transferred to enabled user accounts");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    if recipient.name == previous_owner {
+        let body = Bytes::from_static(b"The account already owns the origin");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    debug!("Force transferring origin {} to new owner {}",
+           &origin, &recipient.name);
+
+    match Origin::force_transfer(&origin, recipient.id, &mut conn).map_err(Error::DieselError) {
+        Ok(_) => {
+            state.memcache
+                 .borrow_mut()
+                 .clear_cache_for_origin_roles(&origin);
+
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::OwnerTransfer,
+                          &recipient.name,
+                          Some(json!({ "owner": previous_owner })),
+                          Some(json!({ "owner": recipient.name })),
+                          &mut conn);
+            notify_member_change(&origin,
+                                 "owner_transferred",
+                                 &recipient.name,
+                                 Some(OriginMemberRole::Owner),
+                                 &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn hide_origin(req: HttpRequest, path: Path<String>, state: Data<AppState>) -> HttpResponse {
+    set_origin_hidden(&req, &path.into_inner(), true, &state)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn unhide_origin(req: HttpRequest,
+                       path: Path<String>,
+                       state: Data<AppState>)
+                       -> HttpResponse {
+    set_origin_hidden(&req, &path.into_inner(), false, &state)
+}
+
+// Packages of the origin that are already cached keep being served until their cache entries
+// expire, as with any other change to a package.
+fn set_origin_hidden(req: &HttpRequest,
+                     origin: &str,
+                     hidden: bool,
+                     state: &AppState)
+                     -> HttpResponse {
+    if let Err(err) = authorize_admin(req) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match Origin::set_hidden(origin, hidden, &mut conn).map_err(Error::DieselError) {
+        Ok(0) => HttpResponse::NotFound().finish(),
+        Ok(_) => {
+            let action = if hidden {
+                AuditAction::OriginHide
+            } else {
+                AuditAction::OriginUnhide
+            };
+            audit::record(req,
+                          Some(origin),
+                          action,
+                          origin,
+                          None,
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_stats(req: HttpRequest, state: Data<AppState>) -> HttpResponse {
+    if let Err(err) = authorize_admin(&req) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match SystemStats::get(&mut conn).map_err(Error::DieselError) {
+        Ok(stats) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(stats)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+fn get_account_id(name: &str, state: &AppState) -> Result<u64> {
+    let mut conn = state.db.get_conn().map_err(Error::DbError)?;
+    Account::get(name, &mut conn).map(|account| account.id as u64)
+                                 .map_err(Error::DieselError)
+}
@@ -18,10 +18,12 @@ use actix_web::{http::StatusCode,
                 web::{self,
                       Data,
                       Path,
+                      Query,
                       ServiceConfig},
                 HttpResponse};
 
-use oauth_client::error::Error as OAuthError;
+use oauth_client::{error::Error as OAuthError,
+                   types::AuthChallenge};
 
 use crate::{protocol::originsrv,
             server::{error::{Error,
@@ -43,11 +45,14 @@ impl Authenticate {
 // Route handlers - these functions can return any Responder trait
 //
 #[allow(clippy::needless_pass_by_value)]
-async fn authenticate(path: Path<String>, state: Data<AppState>) -> HttpResponse {
+async fn authenticate(path: Path<String>,
+                      challenge: Query<AuthChallenge>,
+                      state: Data<AppState>)
+                      -> HttpResponse {
     let code = path.into_inner();
     debug!("authenticate called, code = {}", code);
 
-    match do_authenticate(&code, &state).await {
+    match do_authenticate(&code, &challenge, &state).await {
         Ok(session) => HttpResponse::Ok().json(session),
         Err(Error::OAuth(OAuthError::HttpResponse(_code, response))) => {
             // Include the oauth provider error response in the HTTP response
@@ -62,13 +67,16 @@ async fn authenticate(path: Path<String>, state: Data<AppState>) -> HttpResponse
 
 // Internal - these functions should return Result<..>
 //
-async fn do_authenticate(code: &str, state: &AppState) -> Result<originsrv::Session> {
+async fn do_authenticate(code: &str,
+                         challenge: &AuthChallenge,
+                         state: &AppState)
+                         -> Result<originsrv::Session> {
     if env::var_os("HAB_FUNC_TEST").is_some() {
         return session_create_short_circuit(code, state);
     }
 
     let oauth = &state.oauth;
-    let (token, user) = oauth.authenticate(code).await?;
+    let (token, user) = oauth.authenticate_with_challenge(code, challenge).await?;
 
     session_create_oauth(&token, &user, &oauth.config.provider, state)
 }
@@ -12,37 +12,59 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::str::FromStr;
+use std::{collections::{BTreeMap,
+                        BTreeSet,
+                        HashMap,
+                        HashSet},
+          str::FromStr};
 
 use actix_web::{body::BoxBody,
                 http::{self,
                        StatusCode},
                 web::{self,
                       Data,
+                      Json,
                       Path,
                       Query,
                       ServiceConfig},
                 HttpRequest,
                 HttpResponse};
 use bytes::Bytes;
-use diesel::{pg::PgConnection,
+use chrono::{DateTime,
+             NaiveDateTime,
+             Utc};
+use diesel::{connection::Connection,
+             pg::PgConnection,
              result::{DatabaseErrorKind,
                       Error::{DatabaseError,
                               NotFound}}};
 
 use crate::{bldr_core::metrics::CounterMetric,
-            hab_core::{package::{PackageIdent,
+            hab_core::{package::{Identifiable,
+                                 PackageIdent,
                                  PackageTarget},
-                       ChannelIdent}};
+                       ChannelIdent},
+            protocol::originsrv};
 
-use crate::db::models::{channel::*,
+use crate::db::models::{audit::AuditAction,
+                        channel::*,
+                        channel_policy::*,
+                        channel_snapshot::*,
                         origin::*,
                         package::{BuilderPackageIdent,
+                                  BuilderPackageTarget,
+                                  GetPackage,
                                   GetPackageGroup,
                                   Package,
-                                  PackageVisibility}};
+                                  PackageVisibility},
+                        promotion_request::*,
+                        role::Permission,
+                        scheduled_promotion::*,
+                        webhook::WebhookEvent};
 
-use crate::server::{authorize::authorize_session,
+use crate::server::{authorize::{authorize_permission,
+                                authorize_role_or_permission,
+                                authorize_session},
                     error::{Error,
                             Result},
                     framework::headers,
@@ -52,7 +74,11 @@ use crate::server::{authorize::authorize_session,
                               Pagination,
                               Target,
                               ToChannel},
-                    services::metrics::Counter,
+                    services::{audit,
+                               channel_check,
+                               memcache::MemcacheClient,
+                               metrics::Counter,
+                               webhooks},
                     AppState};
 
 // Query param containers
@@ -62,6 +88,169 @@ struct SandboxBool {
     sandbox: bool,
 }
 
+// At most one of these may be given. `ttl_hours` is counted from now.
+#[derive(Debug, Default, Clone, Deserialize)]
+struct ChannelExpiryQuery {
+    #[serde(default)]
+    expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    ttl_hours:  Option<u32>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct PromotionRequestFilter {
+    #[serde(default)]
+    state: Option<PromotionRequestState>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct ScheduledPromotionFilter {
+    #[serde(default)]
+    state: Option<ScheduledPromotionState>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct SnapshotDiffQuery {
+    #[serde(default)]
+    against: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChannelHistoryQuery {
+    #[serde(default)]
+    at:     Option<DateTime<Utc>>,
+    #[serde(default)]
+    target: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChannelHistoryDiffQuery {
+    from:   DateTime<Utc>,
+    #[serde(default)]
+    to:     Option<DateTime<Utc>>,
+    #[serde(default)]
+    target: Option<String>,
+}
+
+// Response containers
+#[derive(Debug, Serialize)]
+struct PromotionPlan {
+    channel:  String,
+    target:   String,
+    dry_run:  bool,
+    packages: Vec<BuilderPackageIdent>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChannelHistory {
+    channel:  String,
+    target:   String,
+    at:       DateTime<Utc>,
+    packages: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChannelHistoryDiff {
+    channel: String,
+    target:  String,
+    from:    DateTime<Utc>,
+    to:      DateTime<Utc>,
+    added:   Vec<String>,
+    removed: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChannelSnapshotDetail {
+    #[serde(flatten)]
+    snapshot: ChannelSnapshot,
+    packages: Vec<SnapshotPackage>,
+}
+
+// A `to` of None refers to the live channel
+#[derive(Debug, Serialize)]
+struct ChannelSnapshotDiff {
+    from:    Option<String>,
+    to:      Option<String>,
+    added:   Vec<SnapshotPackage>,
+    removed: Vec<SnapshotPackage>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct PromoteOptions {
+    #[serde(default)]
+    with_deps: bool,
+    #[serde(default)]
+    dry_run:   bool,
+}
+
+// Body containers
+#[derive(Debug, Deserialize)]
+pub struct ChannelPolicyReq {
+    #[serde(default)]
+    pub promote_role:     Option<OriginMemberRole>,
+    #[serde(default)]
+    pub demote_role:      Option<OriginMemberRole>,
+    #[serde(default)]
+    pub source_channel:   Option<String>,
+    #[serde(default)]
+    pub require_tdeps:    bool,
+    #[serde(default)]
+    pub block_demote:     bool,
+    #[serde(default)]
+    pub require_approval: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromotionRequestReq {
+    #[serde(default)]
+    pub target: Option<String>,
+    pub idents: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromotionReviewReq {
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+// Either `run_at`, or both `source_channel` and `delay_hours` to promote that long after each
+// package landed in the source channel, provided it is still there.
+#[derive(Debug, Deserialize)]
+pub struct ScheduledPromotionReq {
+    #[serde(default)]
+    pub target:         Option<String>,
+    pub idents:         Vec<String>,
+    #[serde(default)]
+    pub run_at:         Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub source_channel: Option<String>,
+    #[serde(default)]
+    pub delay_hours:    Option<u32>,
+}
+
+// Labels with a null value are removed, and an empty description clears the description.
+#[derive(Debug, Deserialize)]
+pub struct ChannelMetadataReq {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub labels:      HashMap<String, Option<String>>,
+    #[serde(default)]
+    pub frozen:      Option<bool>,
+    #[serde(default)]
+    pub maintainers: Option<Vec<String>>,
+    // Required when freezing or unfreezing, and recorded in the origin audit log
+    #[serde(default)]
+    pub reason:      Option<String>,
+}
+
+#[derive(Serialize)]
+struct ChannelDetail {
+    #[serde(flatten)]
+    channel: Channel,
+    labels:  BTreeMap<String, String>,
+}
+
 pub struct Channels;
 
 impl Channels {
@@ -71,8 +260,52 @@ impl Channels {
         cfg.route("/depot/channels/{origin}", web::get().to(get_channels))
            .route("/depot/channels/{origin}/{channel}",
                   web::post().to(create_channel))
+           .route("/depot/channels/{origin}/{channel}",
+                  web::patch().to(update_channel))
            .route("/depot/channels/{origin}/{channel}",
                   web::delete().to(delete_channel))
+           .route("/depot/channels/{origin}/{channel}/expiry",
+                  web::put().to(extend_channel_expiry))
+           .route("/depot/channels/{origin}/{channel}/policy",
+                  web::get().to(get_channel_policy))
+           .route("/depot/channels/{origin}/{channel}/policy",
+                  web::put().to(update_channel_policy))
+           .route("/depot/channels/{origin}/{channel}/policy",
+                  web::delete().to(delete_channel_policy))
+           .route("/depot/channels/{origin}/{channel}/promotion_requests",
+                  web::get().to(list_promotion_requests))
+           .route("/depot/channels/{origin}/{channel}/promotion_requests",
+                  web::post().to(create_promotion_request))
+           .route("/depot/channels/{origin}/{channel}/promotion_requests/{request_id}",
+                  web::get().to(get_promotion_request))
+           .route("/depot/channels/{origin}/{channel}/promotion_requests/{request_id}/approve",
+                  web::put().to(approve_promotion_request))
+           .route("/depot/channels/{origin}/{channel}/promotion_requests/{request_id}/reject",
+                  web::put().to(reject_promotion_request))
+           .route("/depot/channels/{origin}/{channel}/scheduled_promotions",
+                  web::get().to(list_scheduled_promotions))
+           .route("/depot/channels/{origin}/{channel}/scheduled_promotions",
+                  web::post().to(create_scheduled_promotions))
+           .route("/depot/channels/{origin}/{channel}/scheduled_promotions/{promotion_id}",
+                  web::get().to(get_scheduled_promotion))
+           .route("/depot/channels/{origin}/{channel}/scheduled_promotions/{promotion_id}",
+                  web::delete().to(cancel_scheduled_promotion))
+           .route("/depot/channels/{origin}/{channel}/history",
+                  web::get().to(get_channel_history))
+           .route("/depot/channels/{origin}/{channel}/history/diff",
+                  web::get().to(diff_channel_history))
+           .route("/depot/channels/{origin}/{channel}/check",
+                  web::get().to(check_channel))
+           .route("/depot/channels/{origin}/{channel}/snapshots",
+                  web::get().to(list_channel_snapshots))
+           .route("/depot/channels/{origin}/{channel}/snapshots/{snapshot}",
+                  web::get().to(get_channel_snapshot))
+           .route("/depot/channels/{origin}/{channel}/snapshots/{snapshot}",
+                  web::post().to(create_channel_snapshot))
+           .route("/depot/channels/{origin}/{channel}/snapshots/{snapshot}/diff",
+                  web::get().to(diff_channel_snapshot))
+           .route("/depot/channels/{origin}/{channel}/snapshots/{snapshot}/restore",
+                  web::put().to(restore_channel_snapshot))
            .route("/depot/channels/{origin}/{channel}/pkgs",
                   web::get().to(get_packages_for_origin_channel))
            .route("/depot/channels/{origin}/{channel}/pkgs/_latest",
@@ -94,7 +327,11 @@ impl Channels {
            .route("/depot/channels/{origin}/{channel}/pkgs/{pkg}/{version}/{release}/promote",
                   web::put().to(promote_package))
            .route("/depot/channels/{origin}/{channel}/pkgs/{pkg}/{version}/{release}/demote",
-                  web::put().to(demote_package));
+                  web::put().to(demote_package))
+           .route("/depot/channels/{origin}/{channel}/pkgs/{pkg_origin}/{pkg}/{version}/{release}/promote",
+                  web::put().to(promote_foreign_package))
+           .route("/depot/channels/{origin}/{channel}/pkgs/{pkg_origin}/{pkg}/{version}/{release}/demote",
+                  web::put().to(demote_foreign_package));
     }
 }
 
@@ -114,15 +351,38 @@ async fn get_channels(path: Path<String>,
 
     match Channel::list(&origin, sandbox.sandbox, &mut conn).map_err(Error::DieselError) {
         Ok(list) => {
+            let ids: Vec<i64> = list.iter().map(|channel| channel.id).collect();
+            let mut labels = match labels_by_channel(&ids, &mut conn) {
+                Ok(labels) => labels,
+                Err(err) => return err.into(),
+            };
+
             // TED: This is to maintain backwards API compat while killing some proto definitions
             // currently the output looks like [{"name": "foo"}] when it probably should be ["foo"]
             #[derive(Serialize)]
             struct Temp {
-                name: String,
+                name:        String,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                expires_at:  Option<NaiveDateTime>,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                description: Option<String>,
+                #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+                labels:      BTreeMap<String, String>,
+                #[serde(skip_serializing_if = "Vec::is_empty")]
+                maintainers: Vec<String>,
+                frozen:      bool,
             }
-            let ident_list: Vec<Temp> = list.iter()
-                                            .map(|channel| Temp { name: channel.name.clone(), })
-                                            .collect();
+            let ident_list: Vec<Temp> =
+                list.into_iter()
+                    .map(|channel| {
+                        Temp { name:        channel.name,
+                               labels:      labels.remove(&channel.id).unwrap_or_default(),
+                               expires_at:  channel.expires_at,
+                               description: channel.description,
+                               maintainers: channel.maintainers,
+                               frozen:      channel.frozen, }
+                    })
+                    .collect();
             HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
                                               headers::Cache::NoCache.to_string()))
                               .json(ident_list)
@@ -138,27 +398,45 @@ async fn get_channels(path: Path<String>,
 #[allow(clippy::needless_pass_by_value)]
 async fn create_channel(req: HttpRequest,
                         path: Path<(String, String)>,
+                        expiry: Query<ChannelExpiryQuery>,
                         state: Data<AppState>)
                         -> HttpResponse {
     let (origin, channel) = path.into_inner();
 
-    let session_id =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
-            Ok(session) => session.id(),
-            Err(_) => return HttpResponse::new(StatusCode::UNAUTHORIZED),
-        };
+    let session_id = match authorize_permission(&req, &origin, &Permission::Channels) {
+        Ok(session) => session.id(),
+        Err(_) => return HttpResponse::new(StatusCode::UNAUTHORIZED),
+    };
+
+    let expires_at = match channel_expiry(&expiry, Utc::now()) {
+        Ok(expires_at) => expires_at,
+        Err(msg) => {
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                           BoxBody::new(Bytes::from_static(msg.as_bytes())))
+        }
+    };
 
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
         Err(err) => return err.into(),
     };
 
-    match Channel::create(&CreateChannel { name:     &channel,
-                                           origin:   &origin,
-                                           owner_id: session_id as i64, },
+    match Channel::create(&CreateChannel { name: &channel,
+                                           origin: &origin,
+                                           owner_id: session_id as i64,
+                                           expires_at },
                           &mut conn)
     {
-        Ok(channel) => HttpResponse::Created().json(channel),
+        Ok(channel) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::ChannelCreate,
+                          &channel.name,
+                          None,
+                          serde_json::to_value(&channel).ok(),
+                          &mut conn);
+            HttpResponse::Created().json(channel)
+        }
         Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
             HttpResponse::Conflict().into()
         }
@@ -177,7 +455,7 @@ async fn delete_channel(req: HttpRequest,
     let (origin, channel) = path.into_inner();
     let channel = ChannelIdent::from(channel);
 
-    if let Err(_err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
+    if let Err(_err) = authorize_permission(&req, &origin, &Permission::Channels) {
         return HttpResponse::new(StatusCode::UNAUTHORIZED);
     }
 
@@ -185,17 +463,30 @@ async fn delete_channel(req: HttpRequest,
         return HttpResponse::new(StatusCode::FORBIDDEN);
     }
 
-    state.memcache
-         .borrow_mut()
-         .clear_cache_for_channel(&origin, &channel);
-
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
         Err(err) => return err.into(),
     };
 
+    if let Err(err) = check_channel_not_frozen(&origin, &channel, &mut conn) {
+        return err.into();
+    }
+
+    state.memcache
+         .borrow_mut()
+         .clear_cache_for_channel(&origin, &channel);
+
     match Channel::delete(&origin, &channel, &mut conn).map_err(Error::DieselError) {
-        Ok(_) => HttpResponse::new(StatusCode::OK),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::ChannelDelete,
+                          channel.as_str(),
+                          None,
+                          None,
+                          &mut conn);
+            HttpResponse::new(StatusCode::OK)
+        }
         Err(err) => {
             debug!("Failed to delete channel, err={}", err);
             err.into()
@@ -204,216 +495,364 @@ async fn delete_channel(req: HttpRequest,
 }
 
 #[allow(clippy::needless_pass_by_value)]
-async fn promote_channel_packages(req: HttpRequest,
-                                  path: Path<(String, String)>,
-                                  state: Data<AppState>,
-                                  to_channel: Query<ToChannel>)
-                                  -> HttpResponse {
+async fn update_channel(req: HttpRequest,
+                        path: Path<(String, String)>,
+                        body: Json<ChannelMetadataReq>,
+                        state: Data<AppState>)
+                        -> HttpResponse {
     let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
 
-    let session = match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
-        Ok(session) => session,
-        Err(_) => return HttpResponse::new(StatusCode::UNAUTHORIZED),
-    };
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Channels) {
+        return err.into();
+    }
+
+    if let Some(key) = body.labels.keys().find(|key| !is_valid_label_key(key)) {
+        let body = Bytes::from(format!("Invalid label key {}", key));
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
 
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
         Err(err) => return err.into(),
     };
 
-    let ch_source = ChannelIdent::from(channel);
-    let ch_target = ChannelIdent::from(to_channel.channel.as_ref());
+    let current = match Channel::get(&origin, &channel, &mut conn) {
+        Ok(current) => current,
+        Err(err) => {
+            debug!("Failed to get channel {}, err={}", channel, err);
+            return Error::DieselError(err).into();
+        }
+    };
 
-    match do_promote_or_demote_channel_packages(&req,
-                                                &ch_source,
-                                                &ch_target,
-                                                &origin,
-                                                true,
-                                                session.id() as i64)
+    let is_owner = || authorize_session(&req, Some(&origin), Some(OriginMemberRole::Owner)).is_ok();
+    let freeze = match freeze_change(&current, &body, is_owner) {
+        Ok(freeze) => freeze,
+        Err(resp) => return resp,
+    };
+
+    // Maintainers must belong to the origin, so that there is always someone to ask
+    if let Some(maintainers) = &body.maintainers {
+        let members = match OriginMember::list(&origin, &mut conn) {
+            Ok(members) => members,
+            Err(err) => return Error::DieselError(err).into(),
+        };
+        if let Some(name) = maintainers.iter().find(|name| !members.contains(name)) {
+            let body = Bytes::from(format!("{} is not a member of origin {}", name, origin));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    }
+
+    let (set_labels, remove_labels) = label_changes(&body.labels);
+
+    let changes = UpdateChannel { description: description_change(body.description.as_deref()),
+                                  frozen:      freeze,
+                                  maintainers: body.maintainers.clone(), };
+
+    let updated = match Channel::update_metadata(current.id,
+                                                 &changes,
+                                                 &set_labels,
+                                                 &remove_labels,
+                                                 &mut conn)
     {
-        Ok(pkg_ids) => {
-            match PackageGroupChannelAudit::audit(
-                PackageGroupChannelAudit {
-                    origin: &origin,
-                    channel: ch_target.as_str(),
-                    package_ids: pkg_ids,
-                    operation: PackageChannelOperation::Promote,
-                    trigger: helpers::trigger_from_request_model(&req),
-                    requester_id: session.id() as i64,
-                    requester_name: session.name(),
-                    group_id: 0_i64,
-                },
-                &mut conn,
-            ) {
-                Ok(_) => {}
-                Err(e) => debug!("Failed to save rank change to audit log: {}", e),
-            };
-            HttpResponse::new(StatusCode::OK)
+        Ok(updated) => updated,
+        Err(err) => {
+            debug!("Failed to update channel {}, err={}", channel, err);
+            return Error::DieselError(err).into();
         }
-        Err(e) => {
-            debug!("Failed to promote channel packages, err={}", e);
-            e.into()
+    };
+
+    audit::record(&req,
+                  Some(&origin),
+                  AuditAction::ChannelUpdate,
+                  channel.as_str(),
+                  serde_json::to_value(&current).ok(),
+                  serde_json::to_value(&updated).ok(),
+                  &mut conn);
+
+    if let Some(frozen) = freeze {
+        let action = if frozen {
+            AuditAction::ChannelFreeze
+        } else {
+            AuditAction::ChannelUnfreeze
+        };
+        audit::record(&req,
+                      Some(&origin),
+                      action,
+                      channel.as_str(),
+                      None,
+                      body.reason.as_ref().map(|reason| json!({ "reason": reason })),
+                      &mut conn);
+    }
+
+    match labels_by_channel(&[updated.id], &mut conn) {
+        Ok(mut labels) => {
+            let labels = labels.remove(&updated.id).unwrap_or_default();
+            HttpResponse::Ok().json(ChannelDetail { channel: updated,
+                                                    labels })
         }
+        Err(err) => err.into(),
     }
 }
 
+// Moves the expiry of an ephemeral channel. Only the channel's owner or an origin owner may do
+// this, and a channel created without an expiry cannot be given one, so permanent channels are
+// never reaped.
 #[allow(clippy::needless_pass_by_value)]
-async fn demote_channel_packages(req: HttpRequest,
-                                 path: Path<(String, String)>,
-                                 state: Data<AppState>,
-                                 to_channel: Query<ToChannel>)
-                                 -> HttpResponse {
+async fn extend_channel_expiry(req: HttpRequest,
+                               path: Path<(String, String)>,
+                               expiry: Query<ChannelExpiryQuery>,
+                               state: Data<AppState>)
+                               -> HttpResponse {
     let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    let session = match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    let expires_at = match channel_expiry(&expiry, Utc::now()) {
+        Ok(Some(expires_at)) => expires_at,
+        Ok(None) => {
+            let body = Bytes::from_static(b"Either expires_at or ttl_hours must be given");
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+        Err(msg) => {
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                           BoxBody::new(Bytes::from_static(msg.as_bytes())))
+        }
+    };
+
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
         Err(err) => return err.into(),
     };
 
-    let session = match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
-        Ok(session) => session,
-        Err(_) => return HttpResponse::new(StatusCode::UNAUTHORIZED),
+    let previous_expiry = match Channel::get(&origin, &channel, &mut conn) {
+        Ok(ch)
+            if ch.owner_id != session.id() as i64
+               && authorize_session(&req, Some(&origin), Some(OriginMemberRole::Owner)).is_err() =>
+        {
+            return Error::Authorization.into();
+        }
+        Ok(ch) if ch.expires_at.is_none() => {
+            let body = Bytes::from(format!("Channel {} does not expire", channel));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+        Ok(ch) => ch.expires_at,
+        Err(err) => {
+            debug!("Failed to get channel {}, err={}", channel, err);
+            return Error::DieselError(err).into();
+        }
     };
 
-    let ch_source = ChannelIdent::from(channel);
-    let ch_target = ChannelIdent::from(to_channel.channel.as_ref());
-
-    match do_promote_or_demote_channel_packages(&req,
-                                                &ch_source,
-                                                &ch_target,
-                                                &origin,
-                                                false,
-                                                session.id() as i64)
-    {
-        Ok(pkg_ids) => {
-            match PackageGroupChannelAudit::audit(
-                PackageGroupChannelAudit {
-                    origin: &origin,
-                    channel: ch_target.as_str(),
-                    package_ids: pkg_ids,
-                    operation: PackageChannelOperation::Demote,
-                    trigger: helpers::trigger_from_request_model(&req),
-                    requester_id: session.id() as i64,
-                    requester_name: session.name(),
-                    group_id: 0_i64,
-                },
-                &mut conn,
-            ) {
-                Ok(_) => {}
-                Err(e) => debug!("Failed to save rank change to audit log: {}", e),
-            };
-            HttpResponse::new(StatusCode::OK)
+    match Channel::set_expiry(&origin, &channel, expires_at, &mut conn) {
+        Ok(updated) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::ChannelExpiryExtend,
+                          channel.as_str(),
+                          Some(json!({ "expires_at": previous_expiry })),
+                          Some(json!({ "expires_at": updated.expires_at })),
+                          &mut conn);
+            HttpResponse::Ok().json(updated)
         }
-        Err(e) => {
-            debug!("Failed to demote channel packages, err={}", e);
-            e.into()
+        Err(err) => {
+            debug!("Failed to extend channel {}, err={}", channel, err);
+            Error::DieselError(err).into()
         }
     }
 }
 
-fn do_promote_or_demote_channel_packages(req: &HttpRequest,
-                                         ch_source: &ChannelIdent,
-                                         ch_target: &ChannelIdent,
-                                         origin: &str,
-                                         promote: bool,
-                                         session_id: i64)
-                                         -> Result<Vec<i64>> {
-    Counter::AtomicChannelRequests.increment();
-    let mut conn = req_state(req).db.get_conn().map_err(Error::DbError)?;
-    let mut pkg_ids = Vec::new();
+#[allow(clippy::needless_pass_by_value)]
+async fn get_channel_policy(req: HttpRequest,
+                            path: Path<(String, String)>,
+                            state: Data<AppState>)
+                            -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
 
-    // Simple guards to protect users from bad decisioning
-    if !promote
-       && (*ch_target == ChannelIdent::unstable() || *ch_source == ChannelIdent::unstable())
-    {
-        return Err(Error::BadRequest);
+    if let Err(err) = authorize_session(&req, Some(&origin), None) {
+        return err.into();
     }
 
-    if *ch_target == *ch_source {
-        return Err(Error::BadRequest);
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match ChannelPolicy::get(&origin, &channel, &mut conn) {
+        Ok(Some(policy)) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(policy)
+        }
+        Ok(None) => HttpResponse::new(StatusCode::NOT_FOUND),
+        Err(err) => {
+            debug!("Failed to get channel policy, err={}", err);
+            Error::DieselError(err).into()
+        }
     }
+}
 
-    if promote && *ch_target == ChannelIdent::unstable() {
-        return Err(Error::BadRequest);
+#[allow(clippy::needless_pass_by_value)]
+async fn update_channel_policy(req: HttpRequest,
+                               path: Path<(String, String)>,
+                               body: Json<ChannelPolicyReq>,
+                               state: Data<AppState>)
+                               -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
     }
 
-    let pkgs = do_get_all_channel_packages(req, origin, ch_source)?;
+    let (promote_role, demote_role, source_channel) = match policy_settings(&channel, &body) {
+        Ok(settings) => settings,
+        Err(msg) => {
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                           BoxBody::new(Bytes::from_static(msg.as_bytes())))
+        }
+    };
 
-    #[rustfmt::skip]
-    let channel = match Channel::get(origin, ch_target, &mut conn) {
-        Ok(channel) => channel,
-        Err(NotFound) => {
-            if (ch_target != &ChannelIdent::stable()) && (ch_target != &ChannelIdent::unstable()) {
-                Channel::create(
-                    &CreateChannel {
-                        name:     ch_target.as_str(),
-                        origin,
-                        owner_id: session_id,
-                    },
-                &mut conn)?
-            } else {
-                warn!("Unable to retrieve target channel: {}", &ch_target);
-                return Err(Error::DieselError(NotFound));
-            }
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let channel_id = match Channel::get(&origin, &channel, &mut conn) {
+        Ok(channel) => channel.id,
+        Err(NotFound) => return HttpResponse::new(StatusCode::NOT_FOUND),
+        Err(err) => {
+            debug!("Failed to get channel, err={}", err);
+            return Error::DieselError(err).into();
         }
-        Err(e) => {
-            info!("Unable to retrieve channel, err: {:?}", e);
-            return Err(Error::DieselError(e));
+    };
+
+    let previous = match ChannelPolicy::get(&origin, &channel, &mut conn) {
+        Ok(previous) => previous,
+        Err(err) => {
+            debug!("Failed to get channel policy, err={}", err);
+            return Error::DieselError(err).into();
         }
     };
 
-    #[rustfmt::skip]
-    let op = Package::get_group(
-        GetPackageGroup {
-            pkgs,
-            visibility: PackageVisibility::all()
-        },
-    &mut conn)?;
+    match ChannelPolicy::create_or_update(&NewChannelPolicy { channel_id,
+                                                              origin: &origin,
+                                                              channel: channel.as_str(),
+                                                              promote_role,
+                                                              demote_role,
+                                                              source_channel,
+                                                              require_tdeps: body.require_tdeps,
+                                                              block_demote: body.block_demote,
+                                                              require_approval:
+                                                                  body.require_approval },
+                                          &mut conn)
+    {
+        Ok(policy) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::ChannelPolicyUpdate,
+                          channel.as_str(),
+                          previous.and_then(|previous| serde_json::to_value(&previous).ok()),
+                          serde_json::to_value(&policy).ok(),
+                          &mut conn);
+            HttpResponse::Ok().json(policy)
+        }
+        Err(err) => {
+            debug!("Failed to update channel policy, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
 
-    let mut ids: Vec<i64> = op.iter().map(|x| x.id).collect();
+#[allow(clippy::needless_pass_by_value)]
+async fn delete_channel_policy(req: HttpRequest,
+                               path: Path<(String, String)>,
+                               state: Data<AppState>)
+                               -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
 
-    pkg_ids.append(&mut ids);
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
 
-    if promote {
-        debug!("Bulk promoting Pkg IDs: {:?}", &pkg_ids);
-        Channel::promote_packages(channel.id, &pkg_ids, &mut conn)?;
-    } else {
-        debug!("Bulk demoting Pkg IDs: {:?}", &pkg_ids);
-        Channel::demote_packages(channel.id, &pkg_ids, &mut conn)?;
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match ChannelPolicy::delete(&origin, &channel, &mut conn) {
+        Ok(0) => HttpResponse::new(StatusCode::NOT_FOUND),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::ChannelPolicyDelete,
+                          channel.as_str(),
+                          None,
+                          None,
+                          &mut conn);
+            HttpResponse::new(StatusCode::NO_CONTENT)
+        }
+        Err(err) => {
+            debug!("Failed to delete channel policy, err={}", err);
+            Error::DieselError(err).into()
+        }
     }
-    Ok(pkg_ids)
 }
 
 #[allow(clippy::needless_pass_by_value)]
-async fn promote_package(req: HttpRequest,
-                         path: Path<(String, String, String, String, String)>,
-                         qtarget: Query<Target>,
-                         state: Data<AppState>)
-                         -> HttpResponse {
-    let (origin, channel, pkg, version, release) = path.into_inner();
+async fn list_promotion_requests(req: HttpRequest,
+                                 path: Path<(String, String)>,
+                                 filter: Query<PromotionRequestFilter>,
+                                 state: Data<AppState>)
+                                 -> HttpResponse {
+    let (origin, channel) = path.into_inner();
     let channel = ChannelIdent::from(channel);
 
-    let session = match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
-        Ok(session) => session,
-        Err(_) => return HttpResponse::new(StatusCode::UNAUTHORIZED),
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
     };
 
-    let ident = PackageIdent::new(origin.clone(), pkg, Some(version), Some(release));
+    match PromotionRequest::list(&origin, &channel, filter.state, &mut conn) {
+        Ok(list) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(list)
+        }
+        Err(err) => {
+            debug!("Failed to list promotion requests, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
 
-    // TODO: Deprecate target from headers
-    let target = match qtarget.target {
-        Some(ref t) => {
-            trace!("Query requested target = {}", t);
-            match PackageTarget::from_str(t) {
-                Ok(t) => t,
-                Err(err) => {
-                    debug!("Invalid target requested: {}, err = {:?}", t, err);
-                    let body = Bytes::from(format!("Invalid package target '{}'", t).into_bytes());
-                    return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
-                                                   BoxBody::new(body));
-                }
-            }
+#[allow(clippy::needless_pass_by_value)]
+async fn get_promotion_request(req: HttpRequest,
+                               path: Path<(String, String, String)>,
+                               state: Data<AppState>)
+                               -> HttpResponse {
+    let (origin, channel, request) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        return err.into();
+    }
+
+    let request_id = match request.parse::<i64>() {
+        Ok(request_id) => request_id,
+        Err(_) => {
+            let body = Bytes::from(format!("Invalid promotion request id '{}'", request));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
         }
-        None => helpers::target_from_headers(&req),
     };
 
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
@@ -421,68 +860,40 @@ async fn promote_package(req: HttpRequest,
         Err(err) => return err.into(),
     };
 
-    let auditevent = PackageChannelAudit { package_ident:  BuilderPackageIdent(ident.clone()),
-                                           channel:        channel.as_str(),
-                                           operation:      PackageChannelOperation::Promote,
-                                           trigger:
-                                               helpers::trigger_from_request_model(&req),
-                                           requester_id:   session.id() as i64,
-                                           requester_name: session.name(),
-                                           origin:         &origin, };
-
-    match OriginChannelPackage::promote(
-        OriginChannelPromote {
-            ident: BuilderPackageIdent(ident.clone()),
-            target,
-            origin: origin.clone(),
-            channel: channel.clone(),
-        },
-        &mut conn,
-    )
-    .map_err(Error::DieselError)
-    {
-        Ok(promoted_count) => {
-            // Note: promoted_count is 0 when attempting to promote a package to a channel where it already exists
-            if promoted_count != 0 {
-                if let Err(e) = PackageChannelAudit::audit(&auditevent, &mut conn) {
-                    debug!("Failed to save rank change to audit log: {}", e);
-                };
-            }
-
-            state.memcache.borrow_mut().clear_cache_for_package(&ident);
-            HttpResponse::new(StatusCode::OK)
+    match PromotionRequest::get(&origin, &channel, request_id, &mut conn) {
+        Ok(promotion) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(promotion)
         }
         Err(err) => {
-            debug!("Failed to promote package, err={}", err);
-            err.into()
+            debug!("Failed to get promotion request, err={}", err);
+            Error::DieselError(err).into()
         }
     }
 }
 
 #[allow(clippy::needless_pass_by_value)]
-async fn demote_package(req: HttpRequest,
-                        path: Path<(String, String, String, String, String)>,
-                        qtarget: Query<Target>,
-                        state: Data<AppState>)
-                        -> HttpResponse {
-    let (origin, channel, pkg, version, release) = path.into_inner();
+async fn create_promotion_request(req: HttpRequest,
+                                  path: Path<(String, String)>,
+                                  body: Json<PromotionRequestReq>,
+                                  state: Data<AppState>)
+                                  -> HttpResponse {
+    let (origin, channel) = path.into_inner();
     let channel = ChannelIdent::from(channel);
 
-    if channel == ChannelIdent::unstable() {
-        return HttpResponse::new(StatusCode::FORBIDDEN);
-    }
-
-    let session = match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
+    let session = match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
         Ok(session) => session,
-        Err(_) => return HttpResponse::new(StatusCode::UNAUTHORIZED),
+        Err(err) => return err.into(),
     };
 
-    let ident = PackageIdent::new(origin.clone(), pkg, Some(version), Some(release));
+    if body.idents.is_empty() {
+        let body = Bytes::from_static(b"A promotion request requires at least one package");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
 
-    // TODO: Deprecate target from headers
-    let target = match qtarget.target {
+    let target = match body.target {
         Some(ref t) => {
-            trace!("Query requested target = {}", t);
             match PackageTarget::from_str(t) {
                 Ok(t) => t,
                 Err(err) => {
@@ -496,228 +907,2511 @@ async fn demote_package(req: HttpRequest,
         None => helpers::target_from_headers(&req),
     };
 
+    let idents = match origin_package_idents(&origin, &body.idents) {
+        Ok(idents) => idents,
+        Err(ident) => {
+            let body = Bytes::from(format!("Invalid package identifier '{}'", ident));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    };
+
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
         Err(err) => return err.into(),
     };
 
-    match OriginChannelPackage::demote(OriginChannelDemote { ident:
-                                                                 BuilderPackageIdent(ident.clone()),
-                                                             target,
-                                                             origin: origin.clone(),
-                                                             channel: channel.clone() },
-                                       &mut conn).map_err(Error::DieselError)
+    if let Err(err) = Channel::get(&origin, &channel, &mut conn) {
+        debug!("Failed to get channel {}, err={}", channel, err);
+        return Error::DieselError(err).into();
+    }
+
+    // Reject requests up front that could never be approved under the current policy
+    let policy = match ChannelPolicy::get(&origin, &channel, &mut conn) {
+        Ok(policy) => policy,
+        Err(err) => return Error::DieselError(err).into(),
+    };
+    if let Err(err) = check_promotion_request(&idents, target, policy.as_ref(), &mut conn) {
+        debug!("Promotion request for {} rejected, err={}", channel, err);
+        return err.into();
+    }
+
+    match PromotionRequest::create(&NewPromotionRequest { origin: &origin,
+                                                          channel: channel.as_str(),
+                                                          target: &target.to_string(),
+                                                          package_idents: idents,
+                                                          requester_id: session.id() as i64,
+                                                          requester_name: session.name() },
+                                   &mut conn)
     {
-        Ok(0) => {
-            debug!("Requested package {} for target {} not present in channel {}",
-                   ident, target, channel);
-            HttpResponse::new(StatusCode::BAD_REQUEST)
+        Ok(promotion) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::PromotionRequestCreate,
+                          &promotion.id.to_string(),
+                          None,
+                          serde_json::to_value(&promotion).ok(),
+                          &mut conn);
+            HttpResponse::Created().json(promotion)
         }
-        Ok(_) => {
-            match PackageChannelAudit::audit(
-                &PackageChannelAudit {
-                    package_ident: BuilderPackageIdent(ident.clone()),
-                    channel: channel.as_str(),
-                    operation: PackageChannelOperation::Demote,
-                    trigger: helpers::trigger_from_request_model(&req),
-                    requester_id: session.id() as i64,
-                    requester_name: session.name(),
-                    origin: &origin,
-                },
-                &mut conn,
-            ) {
-                Ok(_) => {}
-                Err(err) => debug!("Failed to save rank change to audit log: {}", err),
-            };
-            state.memcache.borrow_mut().clear_cache_for_package(&ident);
-            HttpResponse::new(StatusCode::OK)
+        Err(err) => {
+            debug!("Failed to create promotion request, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn approve_promotion_request(req: HttpRequest,
+                                   path: Path<(String, String, String)>,
+                                   body: Option<Json<PromotionReviewReq>>,
+                                   state: Data<AppState>)
+                                   -> HttpResponse {
+    let (origin, channel, request) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+    let comment = body.as_ref().and_then(|b| b.comment.clone());
+
+    match do_review_promotion_request(&req,
+                                      &origin,
+                                      &channel,
+                                      &request,
+                                      PromotionRequestState::Approved,
+                                      comment.as_deref())
+    {
+        Ok(promotion) => {
+            let mut memcache = state.memcache.borrow_mut();
+            for ident in promotion.package_idents.iter() {
+                memcache.clear_cache_for_package(ident);
+            }
+            HttpResponse::Ok().json(promotion)
         }
         Err(err) => {
-            debug!("Failed to demote package, err={}", err);
+            debug!("Failed to approve promotion request {}, err={}", request, err);
             err.into()
         }
     }
 }
 
 #[allow(clippy::needless_pass_by_value)]
-async fn get_packages_for_origin_channel_package_version(req: HttpRequest,
-                                                         path: Path<(String,
-                                                               String,
-                                                               String,
-                                                               String)>,
-                                                         pagination: Query<Pagination>)
-                                                         -> HttpResponse {
-    let (origin, channel, pkg, version) = path.into_inner();
+async fn reject_promotion_request(req: HttpRequest,
+                                  path: Path<(String, String, String)>,
+                                  body: Option<Json<PromotionReviewReq>>)
+                                  -> HttpResponse {
+    let (origin, channel, request) = path.into_inner();
     let channel = ChannelIdent::from(channel);
+    let comment = body.as_ref().and_then(|b| b.comment.clone());
 
-    let ident = PackageIdent::new(origin, pkg, Some(version), None);
-
-    match do_get_channel_packages(&req, &pagination, &ident, &channel) {
-        Ok((packages, count)) => {
-            postprocess_channel_package_list(&req, &packages, count, &pagination)
-        }
-        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
+    match do_review_promotion_request(&req,
+                                      &origin,
+                                      &channel,
+                                      &request,
+                                      PromotionRequestState::Rejected,
+                                      comment.as_deref())
+    {
+        Ok(promotion) => HttpResponse::Ok().json(promotion),
         Err(err) => {
-            debug!("Failed to get packages, err={}", err);
+            debug!("Failed to reject promotion request {}, err={}", request, err);
             err.into()
         }
     }
 }
 
 #[allow(clippy::needless_pass_by_value)]
-async fn get_packages_for_origin_channel_package(req: HttpRequest,
-                                                 path: Path<(String, String, String)>,
-                                                 pagination: Query<Pagination>)
-                                                 -> HttpResponse {
-    let (origin, channel, pkg) = path.into_inner();
+async fn list_scheduled_promotions(req: HttpRequest,
+                                   path: Path<(String, String)>,
+                                   filter: Query<ScheduledPromotionFilter>,
+                                   state: Data<AppState>)
+                                   -> HttpResponse {
+    let (origin, channel) = path.into_inner();
     let channel = ChannelIdent::from(channel);
 
-    let ident = PackageIdent::new(origin, pkg, None, None);
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        return err.into();
+    }
 
-    match do_get_channel_packages(&req, &pagination, &ident, &channel) {
-        Ok((packages, count)) => {
-            postprocess_channel_package_list(&req, &packages, count, &pagination)
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match ScheduledPromotion::list(&origin, &channel, filter.state, &mut conn) {
+        Ok(list) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(list)
         }
-        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
         Err(err) => {
-            debug!("Failed to get packages, err={}", err);
-            err.into()
+            debug!("Failed to list scheduled promotions, err={}", err);
+            Error::DieselError(err).into()
         }
     }
 }
 
 #[allow(clippy::needless_pass_by_value)]
-async fn get_packages_for_origin_channel(req: HttpRequest,
-                                         path: Path<(String, String)>,
-                                         pagination: Query<Pagination>)
-                                         -> HttpResponse {
-    let (origin, channel) = path.into_inner();
+async fn get_scheduled_promotion(req: HttpRequest,
+                                 path: Path<(String, String, String)>,
+                                 state: Data<AppState>)
+                                 -> HttpResponse {
+    let (origin, channel, promotion) = path.into_inner();
     let channel = ChannelIdent::from(channel);
 
-    // It feels 1000x wrong to set the package name to ""
-    let ident = PackageIdent::new(origin, String::from(""), None, None);
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        return err.into();
+    }
 
-    match do_get_channel_packages(&req, &pagination, &ident, &channel) {
-        Ok((packages, count)) => {
-            postprocess_channel_package_list(&req, &packages, count, &pagination)
+    let promotion_id = match promotion.parse::<i64>() {
+        Ok(promotion_id) => promotion_id,
+        Err(_) => {
+            let body = Bytes::from(format!("Invalid scheduled promotion id '{}'", promotion));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match ScheduledPromotion::get(&origin, &channel, promotion_id, &mut conn) {
+        Ok(promotion) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(promotion)
         }
-        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
         Err(err) => {
-            debug!("Failed to get packages, err={}", err);
-            err.into()
+            debug!("Failed to get scheduled promotion, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn create_scheduled_promotions(req: HttpRequest,
+                                     path: Path<(String, String)>,
+                                     body: Json<ScheduledPromotionReq>,
+                                     state: Data<AppState>)
+                                     -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    // Scheduling a promotion requires the same rights as promoting right away
+    let session = match authorize_channel_operation(&req,
+                                                    &origin,
+                                                    &channel,
+                                                    PackageChannelOperation::Promote,
+                                                    &mut conn)
+    {
+        Ok((session, _)) => session,
+        Err(err) => return err.into(),
+    };
+
+    if body.idents.is_empty() {
+        let body = Bytes::from_static(b"A scheduled promotion requires at least one package");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    let (fixed_run_at, delay) = match promotion_schedule(&body) {
+        Ok(schedule) => schedule,
+        Err(msg) => {
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                           BoxBody::new(Bytes::from_static(msg.as_bytes())))
+        }
+    };
+
+    let target = match body.target {
+        Some(ref t) => {
+            match PackageTarget::from_str(t) {
+                Ok(t) => t,
+                Err(err) => {
+                    debug!("Invalid target requested: {}, err = {:?}", t, err);
+                    let body = Bytes::from(format!("Invalid package target '{}'", t).into_bytes());
+                    return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                                   BoxBody::new(body));
+                }
+            }
+        }
+        None => helpers::target_from_headers(&req),
+    };
+
+    let idents = match origin_package_idents(&origin, &body.idents) {
+        Ok(idents) => idents,
+        Err(ident) => {
+            let body = Bytes::from(format!("Invalid package identifier '{}'", ident));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    };
+
+    if let Err(err) = Channel::get(&origin, &channel, &mut conn) {
+        debug!("Failed to get channel {}, err={}", channel, err);
+        return Error::DieselError(err).into();
+    }
+
+    let mut run_at = Vec::new();
+    for ident in idents.iter() {
+        match scheduled_run_at(&origin, ident, target, fixed_run_at, delay.as_ref(), &mut conn) {
+            Ok(Some(at)) => run_at.push(at),
+            Ok(None) => {
+                let body = Bytes::from(format!("{} is not in the source channel",
+                                               ident.to_string()));
+                return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                               BoxBody::new(body));
+            }
+            Err(err) => {
+                debug!("Unable to schedule promotion of {}, err={}",
+                       ident.to_string(),
+                       err);
+                return err.into();
+            }
+        }
+    }
+
+    let target = target.to_string();
+    let source_channel = delay.as_ref().map(|(source, _)| source.as_str());
+    let delay_secs = delay.as_ref().map(|(_, secs)| *secs);
+    let promotions: Vec<NewScheduledPromotion> =
+        idents.into_iter()
+              .zip(run_at)
+              .map(|(ident, run_at)| {
+                  NewScheduledPromotion { origin: &origin,
+                                          channel: channel.as_str(),
+                                          ident,
+                                          target: &target,
+                                          run_at,
+                                          source_channel,
+                                          delay_secs,
+                                          requester_id: session.id() as i64,
+                                          requester_name: session.name() }
+              })
+              .collect();
+
+    match ScheduledPromotion::create(&promotions, &mut conn) {
+        Ok(created) => {
+            for scheduled in created.iter() {
+                audit::record(&req,
+                              Some(&origin),
+                              AuditAction::ScheduledPromotionCreate,
+                              &scheduled.id.to_string(),
+                              None,
+                              serde_json::to_value(scheduled).ok(),
+                              &mut conn);
+            }
+            HttpResponse::Created().json(created)
+        }
+        Err(err) => {
+            debug!("Failed to schedule promotions, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn cancel_scheduled_promotion(req: HttpRequest,
+                                    path: Path<(String, String, String)>,
+                                    state: Data<AppState>)
+                                    -> HttpResponse {
+    let (origin, channel, promotion) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    let session = match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    let promotion_id = match promotion.parse::<i64>() {
+        Ok(promotion_id) => promotion_id,
+        Err(_) => {
+            let body = Bytes::from(format!("Invalid scheduled promotion id '{}'", promotion));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let scheduled = match ScheduledPromotion::get(&origin, &channel, promotion_id, &mut conn) {
+        Ok(scheduled) => scheduled,
+        Err(err) => {
+            debug!("Failed to get scheduled promotion, err={}", err);
+            return Error::DieselError(err).into();
+        }
+    };
+
+    // Anyone may cancel their own scheduled promotions, and those who may promote to the
+    // channel any of them
+    if scheduled.requester_id != session.id() as i64 {
+        let permission = Permission::Promote(channel.to_string());
+        if let Err(err) = authorize_permission(&req, &origin, &permission) {
+            return err.into();
+        }
+    }
+
+    match ScheduledPromotion::cancel(promotion_id, &mut conn) {
+        Ok(0) => Error::Conflict.into(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::ScheduledPromotionCancel,
+                          &promotion,
+                          serde_json::to_value(&scheduled).ok(),
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("Failed to cancel scheduled promotion, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_channel_history(req: HttpRequest,
+                             path: Path<(String, String)>,
+                             query: Query<ChannelHistoryQuery>,
+                             state: Data<AppState>)
+                             -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    if let Err(err) = authorize_session(&req, Some(&origin), None) {
+        return err.into();
+    }
+
+    if channel == ChannelIdent::unstable() {
+        return unstable_history_response();
+    }
+
+    let at = query.at.unwrap_or_else(Utc::now);
+    let target = match history_target(&req, query.target.as_deref()) {
+        Ok(target) => target,
+        Err(resp) => return resp,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match channel_packages_at(&origin, &channel, target, at.naive_utc(), &mut conn) {
+        Ok(packages) => {
+            let history = ChannelHistory { channel: channel.to_string(),
+                                           target: target.to_string(),
+                                           at,
+                                           packages: packages.into_iter().collect() };
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(history)
+        }
+        Err(err) => {
+            debug!("Failed to reconstruct channel history, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn diff_channel_history(req: HttpRequest,
+                              path: Path<(String, String)>,
+                              query: Query<ChannelHistoryDiffQuery>,
+                              state: Data<AppState>)
+                              -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    if let Err(err) = authorize_session(&req, Some(&origin), None) {
+        return err.into();
+    }
+
+    if channel == ChannelIdent::unstable() {
+        return unstable_history_response();
+    }
+
+    let from = query.from;
+    let to = query.to.unwrap_or_else(Utc::now);
+    if from > to {
+        let body = Bytes::from_static(b"The from timestamp must not be after the to timestamp");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+    let target = match history_target(&req, query.target.as_deref()) {
+        Ok(target) => target,
+        Err(resp) => return resp,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let result = channel_packages_at(&origin, &channel, target, from.naive_utc(), &mut conn)
+        .and_then(|before| {
+            channel_packages_at(&origin, &channel, target, to.naive_utc(), &mut conn)
+                .map(|after| (before, after))
+        });
+
+    match result {
+        Ok((before, after)) => {
+            let diff = ChannelHistoryDiff { channel: channel.to_string(),
+                                            target: target.to_string(),
+                                            from,
+                                            to,
+                                            added: after.difference(&before).cloned().collect(),
+                                            removed: before.difference(&after)
+                                                           .cloned()
+                                                           .collect() };
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(diff)
+        }
+        Err(err) => {
+            debug!("Failed to reconstruct channel history, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn check_channel(req: HttpRequest,
+                       path: Path<(String, String)>,
+                       state: Data<AppState>)
+                       -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    if let Err(err) = authorize_session(&req, Some(&origin), None) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    if let Err(err) = Channel::get(&origin, &channel, &mut conn) {
+        debug!("Failed to get channel {}, err={}", channel, err);
+        return Error::DieselError(err).into();
+    }
+
+    let fallbacks = &state.config.channel_check.fallback_channels;
+    match channel_check::check_channel(&origin, &channel, fallbacks, &mut conn) {
+        Ok(report) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(report)
+        }
+        Err(err) => {
+            debug!("Failed to check channel {}, err={}", channel, err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn list_channel_snapshots(req: HttpRequest,
+                                path: Path<(String, String)>,
+                                state: Data<AppState>)
+                                -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    if let Err(err) = authorize_session(&req, Some(&origin), None) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match ChannelSnapshot::list(&origin, &channel, &mut conn) {
+        Ok(list) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(list)
+        }
+        Err(err) => {
+            debug!("Failed to list channel snapshots, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_channel_snapshot(req: HttpRequest,
+                              path: Path<(String, String, String)>,
+                              state: Data<AppState>)
+                              -> HttpResponse {
+    let (origin, channel, snapshot) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    if let Err(err) = authorize_session(&req, Some(&origin), None) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let snapshot = match ChannelSnapshot::get(&origin, &channel, &snapshot, &mut conn) {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            debug!("Failed to get channel snapshot, err={}", err);
+            return Error::DieselError(err).into();
+        }
+    };
+
+    match ChannelSnapshot::list_packages(snapshot.id, &mut conn) {
+        Ok(packages) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(ChannelSnapshotDetail { snapshot, packages })
+        }
+        Err(err) => {
+            debug!("Failed to list channel snapshot packages, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn create_channel_snapshot(req: HttpRequest,
+                                 path: Path<(String, String, String)>,
+                                 state: Data<AppState>)
+                                 -> HttpResponse {
+    let (origin, channel, snapshot) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    let session = match authorize_permission(&req, &origin, &Permission::Channels) {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    if snapshot.trim().is_empty() {
+        let body = Bytes::from_static(b"Snapshot name cannot be empty");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let channel_id = match Channel::get(&origin, &channel, &mut conn) {
+        Ok(channel) => channel.id,
+        Err(err) => {
+            debug!("Failed to get channel {}, err={}", channel, err);
+            return Error::DieselError(err).into();
+        }
+    };
+
+    match ChannelSnapshot::create(&NewChannelSnapshot { channel_id,
+                                                        origin: &origin,
+                                                        channel: channel.as_str(),
+                                                        name: &snapshot,
+                                                        owner_id: session.id() as i64 },
+                                  &mut conn)
+    {
+        Ok(snapshot) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::ChannelSnapshotCreate,
+                          channel.as_str(),
+                          None,
+                          serde_json::to_value(&snapshot).ok(),
+                          &mut conn);
+            HttpResponse::Created().json(snapshot)
+        }
+        Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            HttpResponse::Conflict().into()
+        }
+        Err(err) => {
+            debug!("Failed to create channel snapshot, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn diff_channel_snapshot(req: HttpRequest,
+                               path: Path<(String, String, String)>,
+                               query: Query<SnapshotDiffQuery>,
+                               state: Data<AppState>)
+                               -> HttpResponse {
+    let (origin, channel, snapshot) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    if let Err(err) = authorize_session(&req, Some(&origin), None) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let from = match ChannelSnapshot::get(&origin, &channel, &snapshot, &mut conn) {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            debug!("Failed to get channel snapshot, err={}", err);
+            return Error::DieselError(err).into();
+        }
+    };
+
+    let from_packages = ChannelSnapshot::list_packages(from.id, &mut conn);
+    let to_packages = match query.against {
+        Some(ref against) => {
+            ChannelSnapshot::get(&origin, &channel, against, &mut conn)
+                .and_then(|to| ChannelSnapshot::list_packages(to.id, &mut conn))
+        }
+        None => ChannelSnapshot::live_packages(from.channel_id, &mut conn),
+    };
+
+    match (from_packages, to_packages) {
+        (Ok(from_packages), Ok(to_packages)) => {
+            let (added, removed) = diff_snapshot_packages(&from_packages, &to_packages);
+            let diff = ChannelSnapshotDiff { from: Some(from.name),
+                                             to: query.against.clone(),
+                                             added,
+                                             removed };
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(diff)
+        }
+        (Err(err), _) | (_, Err(err)) => {
+            debug!("Failed to diff channel snapshot, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn restore_channel_snapshot(req: HttpRequest,
+                                  path: Path<(String, String, String)>,
+                                  state: Data<AppState>)
+                                  -> HttpResponse {
+    let (origin, channel, snapshot) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    if channel == ChannelIdent::unstable() {
+        return HttpResponse::new(StatusCode::BAD_REQUEST);
+    }
+
+    let session = match authorize_permission(&req, &origin, &Permission::Channels) {
+        Ok(session) => session,
+        Err(_) => return HttpResponse::new(StatusCode::UNAUTHORIZED),
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    if let Err(err) = check_channel_not_frozen(&origin, &channel, &mut conn) {
+        return err.into();
+    }
+
+    let snapshot = match ChannelSnapshot::get(&origin, &channel, &snapshot, &mut conn) {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            debug!("Failed to get channel snapshot, err={}", err);
+            return Error::DieselError(err).into();
+        }
+    };
+
+    // Deleted packages can't be put back, and a partial restore is worse than none
+    match ChannelSnapshot::missing_packages(snapshot.id, &mut conn) {
+        Ok(ref missing) if missing.is_empty() => (),
+        Ok(missing) => {
+            let idents: Vec<String> = missing.iter().map(|p| p.ident.to_string()).collect();
+            let body = Bytes::from(format!("Snapshot {} contains packages that no longer \
+                                            exist: {}",
+                                           snapshot.name,
+                                           idents.join(", ")));
+            return HttpResponse::with_body(StatusCode::CONFLICT, BoxBody::new(body));
+        }
+        Err(err) => return Error::DieselError(err).into(),
+    }
+
+    let (live, wanted) = match (ChannelSnapshot::live_packages(snapshot.channel_id, &mut conn),
+                                ChannelSnapshot::list_packages(snapshot.id, &mut conn))
+    {
+        (Ok(live), Ok(wanted)) => (live, wanted),
+        (Err(err), _) | (_, Err(err)) => {
+            debug!("Failed to list packages for restore, err={}", err);
+            return Error::DieselError(err).into();
+        }
+    };
+    let (added, removed) = diff_snapshot_packages(&live, &wanted);
+
+    // A restore has to satisfy the channel policy for each kind of change it makes
+    if !removed.is_empty() {
+        if let Err(err) = authorize_channel_operation(&req,
+                                                      &origin,
+                                                      &channel,
+                                                      PackageChannelOperation::Demote,
+                                                      &mut conn)
+        {
+            return err.into();
+        }
+    }
+    if !added.is_empty() {
+        if let Err(err) = authorize_channel_operation(&req,
+                                                      &origin,
+                                                      &channel,
+                                                      PackageChannelOperation::Promote,
+                                                      &mut conn)
+        {
+            return err.into();
+        }
+    }
+
+    let package_ids: Vec<i64> = wanted.iter().map(|p| p.package_id).collect();
+    let trigger = helpers::trigger_from_request_model(&req);
+
+    let result = conn.transaction::<_, Error, _>(|conn| {
+        let (promoted, demoted) =
+            Channel::restore_packages(snapshot.channel_id, &package_ids, conn)?;
+        for (operation, package_ids) in [(PackageChannelOperation::Demote, demoted),
+                                         (PackageChannelOperation::Promote, promoted)]
+        {
+            if package_ids.is_empty() {
+                continue;
+            }
+            let audit = PackageGroupChannelAudit { origin: &origin,
+                                                   channel: channel.as_str(),
+                                                   package_ids,
+                                                   operation,
+                                                   trigger: trigger.clone(),
+                                                   requester_id: session.id() as i64,
+                                                   requester_name: session.name(),
+                                                   group_id: 0_i64 };
+            PackageGroupChannelAudit::audit(audit, conn)?;
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => {
+            let removed_ids: Vec<i64> = removed.iter().map(|p| p.package_id).collect();
+            let added_ids: Vec<i64> = added.iter().map(|p| p.package_id).collect();
+            notify_channel_package_ids(&origin,
+                                       &channel,
+                                       PackageChannelOperation::Demote,
+                                       &removed_ids,
+                                       &mut conn);
+            notify_channel_package_ids(&origin,
+                                       &channel,
+                                       PackageChannelOperation::Promote,
+                                       &added_ids,
+                                       &mut conn);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::ChannelSnapshotRestore,
+                          channel.as_str(),
+                          None,
+                          Some(json!({ "snapshot": snapshot.name,
+                                       "added": added,
+                                       "removed": removed })),
+                          &mut conn);
+
+            let mut memcache = state.memcache.borrow_mut();
+            memcache.clear_cache_for_channel(&origin, &channel);
+            for package in added.iter().chain(removed.iter()) {
+                memcache.clear_cache_for_package(&package.ident);
+            }
+            HttpResponse::Ok().json(ChannelSnapshotDiff { from: None,
+                                                          to: Some(snapshot.name),
+                                                          added,
+                                                          removed })
+        }
+        Err(err) => {
+            debug!("Failed to restore channel snapshot, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn promote_channel_packages(req: HttpRequest,
+                                  path: Path<(String, String)>,
+                                  state: Data<AppState>,
+                                  to_channel: Query<ToChannel>)
+                                  -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let ch_source = ChannelIdent::from(channel);
+    let ch_target = ChannelIdent::from(to_channel.channel.as_ref());
+
+    let (session, policy) = match authorize_channel_operation(&req,
+                                                              &origin,
+                                                              &ch_target,
+                                                              PackageChannelOperation::Promote,
+                                                              &mut conn)
+    {
+        Ok(authorized) => authorized,
+        Err(Error::Authentication) | Err(Error::Authorization) => {
+            return HttpResponse::new(StatusCode::UNAUTHORIZED)
+        }
+        Err(err) => return err.into(),
+    };
+
+    match do_promote_or_demote_channel_packages(&req,
+                                                &ch_source,
+                                                &ch_target,
+                                                &origin,
+                                                true,
+                                                session.id() as i64,
+                                                policy.as_ref())
+    {
+        Ok(pkg_ids) => {
+            notify_channel_package_ids(&origin,
+                                       &ch_target,
+                                       PackageChannelOperation::Promote,
+                                       &pkg_ids,
+                                       &mut conn);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::PackagePromote,
+                          ch_target.as_str(),
+                          None,
+                          Some(json!({ "from": ch_source.as_str(), "packages": pkg_ids.len() })),
+                          &mut conn);
+            match PackageGroupChannelAudit::audit(
+                PackageGroupChannelAudit {
+                    origin: &origin,
+                    channel: ch_target.as_str(),
+                    package_ids: pkg_ids,
+                    operation: PackageChannelOperation::Promote,
+                    trigger: helpers::trigger_from_request_model(&req),
+                    requester_id: session.id() as i64,
+                    requester_name: session.name(),
+                    group_id: 0_i64,
+                },
+                &mut conn,
+            ) {
+                Ok(_) => {}
+                Err(e) => debug!("Failed to save rank change to audit log: {}", e),
+            };
+            HttpResponse::new(StatusCode::OK)
+        }
+        Err(e) => {
+            debug!("Failed to promote channel packages, err={}", e);
+            e.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn demote_channel_packages(req: HttpRequest,
+                                 path: Path<(String, String)>,
+                                 state: Data<AppState>,
+                                 to_channel: Query<ToChannel>)
+                                 -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let ch_source = ChannelIdent::from(channel);
+    let ch_target = ChannelIdent::from(to_channel.channel.as_ref());
+
+    let (session, policy) = match authorize_channel_operation(&req,
+                                                              &origin,
+                                                              &ch_target,
+                                                              PackageChannelOperation::Demote,
+                                                              &mut conn)
+    {
+        Ok(authorized) => authorized,
+        Err(Error::Authentication) | Err(Error::Authorization) => {
+            return HttpResponse::new(StatusCode::UNAUTHORIZED)
+        }
+        Err(err) => return err.into(),
+    };
+
+    match do_promote_or_demote_channel_packages(&req,
+                                                &ch_source,
+                                                &ch_target,
+                                                &origin,
+                                                false,
+                                                session.id() as i64,
+                                                policy.as_ref())
+    {
+        Ok(pkg_ids) => {
+            notify_channel_package_ids(&origin,
+                                       &ch_target,
+                                       PackageChannelOperation::Demote,
+                                       &pkg_ids,
+                                       &mut conn);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::PackageDemote,
+                          ch_target.as_str(),
+                          None,
+                          Some(json!({ "from": ch_source.as_str(), "packages": pkg_ids.len() })),
+                          &mut conn);
+            match PackageGroupChannelAudit::audit(
+                PackageGroupChannelAudit {
+                    origin: &origin,
+                    channel: ch_target.as_str(),
+                    package_ids: pkg_ids,
+                    operation: PackageChannelOperation::Demote,
+                    trigger: helpers::trigger_from_request_model(&req),
+                    requester_id: session.id() as i64,
+                    requester_name: session.name(),
+                    group_id: 0_i64,
+                },
+                &mut conn,
+            ) {
+                Ok(_) => {}
+                Err(e) => debug!("Failed to save rank change to audit log: {}", e),
+            };
+            HttpResponse::new(StatusCode::OK)
+        }
+        Err(e) => {
+            debug!("Failed to demote channel packages, err={}", e);
+            e.into()
+        }
+    }
+}
+
+fn do_promote_or_demote_channel_packages(req: &HttpRequest,
+                                         ch_source: &ChannelIdent,
+                                         ch_target: &ChannelIdent,
+                                         origin: &str,
+                                         promote: bool,
+                                         session_id: i64,
+                                         policy: Option<&ChannelPolicy>)
+                                         -> Result<Vec<i64>> {
+    Counter::AtomicChannelRequests.increment();
+    let mut conn = req_state(req).db.get_conn().map_err(Error::DbError)?;
+    let mut pkg_ids = Vec::new();
+
+    // Simple guards to protect users from bad decisioning
+    if !promote
+       && (*ch_target == ChannelIdent::unstable() || *ch_source == ChannelIdent::unstable())
+    {
+        return Err(Error::BadRequest);
+    }
+
+    if *ch_target == *ch_source {
+        return Err(Error::BadRequest);
+    }
+
+    if promote && *ch_target == ChannelIdent::unstable() {
+        return Err(Error::BadRequest);
+    }
+
+    check_channel_not_frozen(origin, ch_target, &mut conn)?;
+
+    let pkgs = do_get_all_channel_packages(req, origin, ch_source)?;
+
+    #[rustfmt::skip]
+    let op = Package::get_group(
+        GetPackageGroup {
+            pkgs,
+            visibility: PackageVisibility::all()
+        },
+    &mut conn)?;
+
+    // The policy is checked before a missing channel is created, so that a promotion it
+    // rejects leaves nothing behind
+    if let (true, Some(policy)) = (promote, policy) {
+        match policy.source_channel {
+            Some(ref source) if source != ch_source.as_str() => {
+                return Err(Error::PolicyViolation(format!("Channel policy for {} only allows \
+                                                           promotion from {}",
+                                                          ch_target, source)));
+            }
+            _ => (),
+        }
+        if policy.require_tdeps {
+            check_tdeps_in_channel(policy, &op, &mut conn)?;
+        }
+    }
+
+    #[rustfmt::skip]
+    let channel = match Channel::get(origin, ch_target, &mut conn) {
+        Ok(channel) => channel,
+        Err(NotFound) => {
+            if (ch_target != &ChannelIdent::stable()) && (ch_target != &ChannelIdent::unstable()) {
+                Channel::create(
+                    &CreateChannel {
+                        name:       ch_target.as_str(),
+                        origin,
+                        owner_id:   session_id,
+                        expires_at: None,
+                    },
+                &mut conn)?
+            } else {
+                warn!("Unable to retrieve target channel: {}", &ch_target);
+                return Err(Error::DieselError(NotFound));
+            }
+        }
+        Err(e) => {
+            info!("Unable to retrieve channel, err: {:?}", e);
+            return Err(Error::DieselError(e));
+        }
+    };
+
+    let mut ids: Vec<i64> = op.iter().map(|x| x.id).collect();
+
+    pkg_ids.append(&mut ids);
+
+    if promote {
+        debug!("Bulk promoting Pkg IDs: {:?}", &pkg_ids);
+        Channel::promote_packages(channel.id, &pkg_ids, &mut conn)?;
+    } else {
+        debug!("Bulk demoting Pkg IDs: {:?}", &pkg_ids);
+        Channel::demote_packages(channel.id, &pkg_ids, &mut conn)?;
+    }
+    Ok(pkg_ids)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn promote_package(req: HttpRequest,
+                         path: Path<(String, String, String, String, String)>,
+                         qtarget: Query<Target>,
+                         opts: Query<PromoteOptions>,
+                         state: Data<AppState>)
+                         -> HttpResponse {
+    let (origin, channel, pkg, version, release) = path.into_inner();
+    let ident = PackageIdent::new(origin.clone(), pkg, Some(version), Some(release));
+
+    do_promote_package_request(&req,
+                               &origin,
+                               &ChannelIdent::from(channel),
+                               ident,
+                               &qtarget,
+                               &opts,
+                               &state)
+}
+
+// Adds a package from another origin to the channel. The package keeps its own origin, so
+// listings of the channel show where each package came from.
+#[allow(clippy::needless_pass_by_value)]
+async fn promote_foreign_package(req: HttpRequest,
+                                 path: Path<(String, String, String, String, String, String)>,
+                                 qtarget: Query<Target>,
+                                 opts: Query<PromoteOptions>,
+                                 state: Data<AppState>)
+                                 -> HttpResponse {
+    let (origin, channel, pkg_origin, pkg, version, release) = path.into_inner();
+    let ident = PackageIdent::new(pkg_origin, pkg, Some(version), Some(release));
+
+    do_promote_package_request(&req,
+                               &origin,
+                               &ChannelIdent::from(channel),
+                               ident,
+                               &qtarget,
+                               &opts,
+                               &state)
+}
+
+fn do_promote_package_request(req: &HttpRequest,
+                              origin: &str,
+                              channel: &ChannelIdent,
+                              ident: PackageIdent,
+                              qtarget: &Query<Target>,
+                              opts: &Query<PromoteOptions>,
+                              state: &Data<AppState>)
+                              -> HttpResponse {
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let (session, policy) = match authorize_channel_operation(req,
+                                                              origin,
+                                                              channel,
+                                                              PackageChannelOperation::Promote,
+                                                              &mut conn)
+    {
+        Ok(authorized) => authorized,
+        Err(Error::Authentication) | Err(Error::Authorization) => {
+            return HttpResponse::new(StatusCode::UNAUTHORIZED)
+        }
+        Err(err) => return err.into(),
+    };
+
+    if let Err(err) = check_channel_not_frozen(origin, channel, &mut conn) {
+        return err.into();
+    }
+
+    // TODO: Deprecate target from headers
+    let target = match qtarget.target {
+        Some(ref t) => {
+            trace!("Query requested target = {}", t);
+            match PackageTarget::from_str(t) {
+                Ok(t) => t,
+                Err(err) => {
+                    debug!("Invalid target requested: {}, err = {:?}", t, err);
+                    let body = Bytes::from(format!("Invalid package target '{}'", t).into_bytes());
+                    return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                                   BoxBody::new(body));
+                }
+            }
+        }
+        None => helpers::target_from_headers(req),
+    };
+
+    let foreign = ident.origin != origin;
+    if foreign {
+        if opts.with_deps || opts.dry_run {
+            let body = Bytes::from_static(b"with_deps and dry_run are not supported for \
+                                            packages from other origins");
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+        if let Err(err) = check_foreign_package(req, &ident, target, &mut conn) {
+            debug!("Promotion of {} to {}/{} rejected, err={}", ident, origin, channel, err);
+            return err.into();
+        }
+    }
+
+    // Deps promoted alongside the package satisfy the policy's tdeps requirement, so with_deps
+    // only needs the per package source channel check done while planning.
+    if let (Some(policy), false) = (policy.as_ref(), opts.with_deps) {
+        if let Err(err) = check_package_promotion(policy, &ident, target, &mut conn) {
+            debug!("Promotion of {} to {} rejected, err={}", ident, channel, err);
+            return err.into();
+        }
+    }
+
+    if opts.with_deps || opts.dry_run {
+        return do_planned_promote_package(req,
+                                          &session,
+                                          policy.as_ref(),
+                                          channel,
+                                          &ident,
+                                          target,
+                                          opts,
+                                          &mut conn);
+    }
+
+    let promote = OriginChannelPromote { ident: BuilderPackageIdent(ident.clone()),
+                                         target,
+                                         origin: origin.to_string(),
+                                         channel: channel.clone() };
+    let mut memcache = state.memcache.borrow_mut();
+    match do_promote_package(promote,
+                             helpers::trigger_from_request_model(req),
+                             session.id() as i64,
+                             session.name(),
+                             &mut memcache,
+                             &mut conn)
+    {
+        Ok(promoted) => {
+            if promoted != 0 {
+                audit::record(req,
+                              Some(origin),
+                              AuditAction::PackagePromote,
+                              &ident.to_string(),
+                              None,
+                              Some(json!({ "channel": channel.as_str(),
+                                           "target": target.to_string() })),
+                              &mut conn);
+            }
+            // Cached lookups in the channel are keyed by the channel's origin
+            if foreign {
+                memcache.clear_cache_for_channel(origin, channel);
+            }
+            HttpResponse::new(StatusCode::OK)
+        }
+        Err(err) => {
+            debug!("Failed to promote package, err={}", err);
+            err.into()
+        }
+    }
+}
+
+// Promotes a single package once the requester has been authorized and the channel policy
+// checked. Both the promote endpoint and the promotion scheduler go through here so that they
+// audit and invalidate caches in the same way.
+fn do_promote_package(promote: OriginChannelPromote,
+                      trigger: PackageChannelTrigger,
+                      requester_id: i64,
+                      requester_name: &str,
+                      memcache: &mut MemcacheClient,
+                      conn: &mut PgConnection)
+                      -> Result<usize> {
+    let ident = promote.ident.clone();
+    let target = promote.target;
+    let origin = promote.origin.clone();
+    let channel = promote.channel.clone();
+
+    let promoted_count = OriginChannelPackage::promote(promote, conn).map_err(Error::DieselError)?;
+
+    // Note: promoted_count is 0 when attempting to promote a package to a channel where it already
+    // exists
+    if promoted_count != 0 {
+        let auditevent = PackageChannelAudit { package_ident: ident.clone(),
+                                               channel: channel.as_str(),
+                                               operation: PackageChannelOperation::Promote,
+                                               trigger,
+                                               requester_id,
+                                               requester_name,
+                                               origin: &origin,
+                                               target: &target.to_string() };
+        if let Err(e) = PackageChannelAudit::audit(&auditevent, conn) {
+            debug!("Failed to save rank change to audit log: {}", e);
+        };
+        notify_channel_packages(&origin,
+                                &channel,
+                                PackageChannelOperation::Promote,
+                                &[(ident.clone(), BuilderPackageTarget(target))],
+                                conn);
+    }
+
+    memcache.clear_cache_for_package(&ident);
+    Ok(promoted_count)
+}
+
+// Promotes a package along with, when requested, whatever of its dependency closure is missing
+// from the channel. Everything is promoted in one transaction under a single group audit entry.
+// For a dry run, the packages that would be promoted are returned and nothing is changed.
+#[allow(clippy::too_many_arguments)]
+fn do_planned_promote_package(req: &HttpRequest,
+                              session: &originsrv::Session,
+                              policy: Option<&ChannelPolicy>,
+                              channel: &ChannelIdent,
+                              ident: &PackageIdent,
+                              target: PackageTarget,
+                              opts: &PromoteOptions,
+                              conn: &mut PgConnection)
+                              -> HttpResponse {
+    let channel_id = match Channel::get(&ident.origin, channel, conn) {
+        Ok(channel) => channel.id,
+        Err(err) => {
+            debug!("Failed to get channel {}, err={}", channel, err);
+            return Error::DieselError(err).into();
+        }
+    };
+
+    let (packages, unavailable) =
+        match plan_package_promotion(req, policy, channel_id, ident, target, opts.with_deps, conn)
+        {
+            Ok(plan) => plan,
+            Err(err) => {
+                debug!("Unable to plan promotion of {} to {}, err={}", ident, channel, err);
+                return err.into();
+            }
+        };
+
+    if !unavailable.is_empty() {
+        let missing: Vec<String> = unavailable.iter().map(|d| d.to_string()).collect();
+        let body = Bytes::from(format!("Unable to find dependencies of {} for {}: {}",
+                                       ident,
+                                       target,
+                                       missing.join(", ")));
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    let plan = PromotionPlan { channel:  channel.to_string(),
+                               target:   target.to_string(),
+                               dry_run:  opts.dry_run,
+                               packages: packages.iter().map(|p| p.ident.clone()).collect(), };
+
+    if opts.dry_run || packages.is_empty() {
+        return HttpResponse::Ok().json(plan);
+    }
+
+    let package_ids: Vec<i64> = packages.iter().map(|p| p.id).collect();
+    let result = conn.transaction::<_, Error, _>(|conn| {
+        Channel::promote_packages(channel_id, &package_ids, conn)?;
+        let audit = PackageGroupChannelAudit { origin: &ident.origin,
+                                               channel: channel.as_str(),
+                                               package_ids: package_ids.clone(),
+                                               operation: PackageChannelOperation::Promote,
+                                               trigger: helpers::trigger_from_request_model(req),
+                                               requester_id: session.id() as i64,
+                                               requester_name: session.name(),
+                                               group_id: 0_i64 };
+        PackageGroupChannelAudit::audit(audit, conn)?;
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => {
+            audit::record(req,
+                          Some(&ident.origin),
+                          AuditAction::PackagePromote,
+                          &ident.to_string(),
+                          None,
+                          Some(json!({ "channel": channel.as_str(),
+                                       "target": target.to_string(),
+                                       "packages": plan.packages })),
+                          conn);
+            let promoted: Vec<(BuilderPackageIdent, BuilderPackageTarget)> =
+                packages.iter().map(|p| (p.ident.clone(), p.target)).collect();
+            notify_channel_packages(&ident.origin,
+                                    channel,
+                                    PackageChannelOperation::Promote,
+                                    &promoted,
+                                    conn);
+
+            let mut memcache = req_state(req).memcache.borrow_mut();
+            for package in packages.iter() {
+                memcache.clear_cache_for_package(&package.ident);
+            }
+            HttpResponse::Ok().json(plan)
+        }
+        Err(err) => {
+            debug!("Failed to promote {} with dependencies, err={}", ident, err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn demote_package(req: HttpRequest,
+                        path: Path<(String, String, String, String, String)>,
+                        qtarget: Query<Target>,
+                        state: Data<AppState>)
+                        -> HttpResponse {
+    let (origin, channel, pkg, version, release) = path.into_inner();
+    let ident = PackageIdent::new(origin.clone(), pkg, Some(version), Some(release));
+
+    do_demote_package_request(&req, &origin, &ChannelIdent::from(channel), ident, &qtarget, &state)
+}
+
+// Removes a package of another origin from the channel. The package itself is left untouched.
+#[allow(clippy::needless_pass_by_value)]
+async fn demote_foreign_package(req: HttpRequest,
+                                path: Path<(String, String, String, String, String, String)>,
+                                qtarget: Query<Target>,
+                                state: Data<AppState>)
+                                -> HttpResponse {
+    let (origin, channel, pkg_origin, pkg, version, release) = path.into_inner();
+    let ident = PackageIdent::new(pkg_origin, pkg, Some(version), Some(release));
+
+    do_demote_package_request(&req, &origin, &ChannelIdent::from(channel), ident, &qtarget, &state)
+}
+
+fn do_demote_package_request(req: &HttpRequest,
+                             origin: &str,
+                             channel: &ChannelIdent,
+                             ident: PackageIdent,
+                             qtarget: &Query<Target>,
+                             state: &Data<AppState>)
+                             -> HttpResponse {
+    if *channel == ChannelIdent::unstable() {
+        return HttpResponse::new(StatusCode::FORBIDDEN);
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let session = match authorize_channel_operation(req,
+                                                    origin,
+                                                    channel,
+                                                    PackageChannelOperation::Demote,
+                                                    &mut conn)
+    {
+        Ok((session, _)) => session,
+        Err(Error::Authentication) | Err(Error::Authorization) => {
+            return HttpResponse::new(StatusCode::UNAUTHORIZED)
+        }
+        Err(err) => return err.into(),
+    };
+
+    if let Err(err) = check_channel_not_frozen(origin, channel, &mut conn) {
+        return err.into();
+    }
+
+    // TODO: Deprecate target from headers
+    let target = match qtarget.target {
+        Some(ref t) => {
+            trace!("Query requested target = {}", t);
+            match PackageTarget::from_str(t) {
+                Ok(t) => t,
+                Err(err) => {
+                    debug!("Invalid target requested: {}, err = {:?}", t, err);
+                    let body = Bytes::from(format!("Invalid package target '{}'", t).into_bytes());
+                    return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                                   BoxBody::new(body));
+                }
+            }
+        }
+        None => helpers::target_from_headers(req),
+    };
+
+    match OriginChannelPackage::demote(OriginChannelDemote { ident:
+                                                                 BuilderPackageIdent(ident.clone()),
+                                                             target,
+                                                             origin: origin.to_string(),
+                                                             channel: channel.clone() },
+                                       &mut conn).map_err(Error::DieselError)
+    {
+        Ok(0) => {
+            debug!("Requested package {} for target {} not present in channel {}",
+                   ident, target, channel);
+            HttpResponse::new(StatusCode::BAD_REQUEST)
+        }
+        Ok(_) => {
+            audit::record(req,
+                          Some(origin),
+                          AuditAction::PackageDemote,
+                          &ident.to_string(),
+                          None,
+                          Some(json!({ "channel": channel.as_str(),
+                                       "target": target.to_string() })),
+                          &mut conn);
+            match PackageChannelAudit::audit(
+                &PackageChannelAudit {
+                    package_ident: BuilderPackageIdent(ident.clone()),
+                    channel: channel.as_str(),
+                    operation: PackageChannelOperation::Demote,
+                    trigger: helpers::trigger_from_request_model(req),
+                    requester_id: session.id() as i64,
+                    requester_name: session.name(),
+                    origin,
+                    target: &target.to_string(),
+                },
+                &mut conn,
+            ) {
+                Ok(_) => {}
+                Err(err) => debug!("Failed to save rank change to audit log: {}", err),
+            };
+            notify_channel_packages(origin,
+                                    channel,
+                                    PackageChannelOperation::Demote,
+                                    &[(BuilderPackageIdent(ident.clone()),
+                                       BuilderPackageTarget(target))],
+                                    &mut conn);
+            let mut memcache = state.memcache.borrow_mut();
+            memcache.clear_cache_for_package(&ident);
+            // Cached lookups in the channel are keyed by the channel's origin
+            if ident.origin != origin {
+                memcache.clear_cache_for_channel(origin, channel);
+            }
+            HttpResponse::new(StatusCode::OK)
+        }
+        Err(err) => {
+            debug!("Failed to demote package, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_packages_for_origin_channel_package_version(req: HttpRequest,
+                                                         path: Path<(String,
+                                                               String,
+                                                               String,
+                                                               String)>,
+                                                         pagination: Query<Pagination>)
+                                                         -> HttpResponse {
+    let (origin, channel, pkg, version) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    let ident = PackageIdent::new(origin, pkg, Some(version), None);
+
+    match do_get_channel_packages(&req, &pagination, &ident, &channel) {
+        Ok((packages, count)) => {
+            postprocess_channel_package_list(&req, &packages, count, &pagination)
+        }
+        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
+        Err(err) => {
+            debug!("Failed to get packages, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_packages_for_origin_channel_package(req: HttpRequest,
+                                                 path: Path<(String, String, String)>,
+                                                 pagination: Query<Pagination>)
+                                                 -> HttpResponse {
+    let (origin, channel, pkg) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    let ident = PackageIdent::new(origin, pkg, None, None);
+
+    match do_get_channel_packages(&req, &pagination, &ident, &channel) {
+        Ok((packages, count)) => {
+            postprocess_channel_package_list(&req, &packages, count, &pagination)
+        }
+        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
+        Err(err) => {
+            debug!("Failed to get packages, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_packages_for_origin_channel(req: HttpRequest,
+                                         path: Path<(String, String)>,
+                                         pagination: Query<Pagination>)
+                                         -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    // It feels 1000x wrong to set the package name to ""
+    let ident = PackageIdent::new(origin, String::from(""), None, None);
+
+    match do_get_channel_packages(&req, &pagination, &ident, &channel) {
+        Ok((packages, count)) => {
+            postprocess_channel_package_list(&req, &packages, count, &pagination)
+        }
+        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
+        Err(err) => {
+            debug!("Failed to get packages, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_latest_package_for_origin_channel_package(req: HttpRequest,
+                                                       path: Path<(String, String, String)>,
+                                                       qtarget: Query<Target>)
+                                                       -> HttpResponse {
+    let (origin, channel, pkg) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    let ident = PackageIdent::new(origin, pkg, None, None);
+
+    match do_get_channel_package(&req, &qtarget, &ident, &channel) {
+        Ok(json_body) => {
+            HttpResponse::Ok().append_header((http::header::CONTENT_TYPE,
+                                              headers::APPLICATION_JSON))
+                              .append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .body(json_body)
+        }
+        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
+        Err(err) => {
+            debug!("Failed to get latest package, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_latest_package_for_origin_channel_package_version(req: HttpRequest,
+                                                               path: Path<(String,
+                                                                     String,
+                                                                     String,
+                                                                     String)>,
+                                                               qtarget: Query<Target>)
+                                                               -> HttpResponse {
+    let (origin, channel, pkg, version) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    let ident = PackageIdent::new(origin, pkg, Some(version), None);
+
+    match do_get_channel_package(&req, &qtarget, &ident, &channel) {
+        Ok(json_body) => {
+            HttpResponse::Ok().append_header((http::header::CONTENT_TYPE,
+                                              headers::APPLICATION_JSON))
+                              .append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .body(json_body)
+        }
+        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
+        Err(err) => {
+            debug!("Failed to get latest package, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_package_fully_qualified(req: HttpRequest,
+                                     path: Path<(String, String, String, String, String)>,
+                                     qtarget: Query<Target>)
+                                     -> HttpResponse {
+    let (origin, channel, pkg, version, release) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    let ident = PackageIdent::new(origin, pkg, Some(version), Some(release));
+
+    match do_get_channel_package(&req, &qtarget, &ident, &channel) {
+        Ok(json_body) => {
+            HttpResponse::Ok().append_header((http::header::CONTENT_TYPE,
+                                              headers::APPLICATION_JSON))
+                              .append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .body(json_body)
+        }
+        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
+        Err(err) => {
+            debug!("Failed to get package, err={}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_latest_packages_for_origin_channel(req: HttpRequest,
+                                                path: Path<(String, String)>,
+                                                qtarget: Query<Target>)
+                                                -> HttpResponse {
+    let (origin, channel) = path.into_inner();
+    let channel = ChannelIdent::from(channel);
+
+    match do_get_latest_channel_packages(&req, &qtarget, &origin, &channel) {
+        Ok((channel, target, data)) => {
+            let json_body = helpers::channel_listing_results_json(&channel, &target, &data);
+            HttpResponse::Ok().append_header((http::header::CONTENT_TYPE,
+                                              headers::APPLICATION_JSON))
+                              .append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .body(json_body)
+        }
+        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
+        Err(Error::BadRequest) => HttpResponse::new(StatusCode::BAD_REQUEST),
+        Err(err) => {
+            debug!("Failed to get package, err={}", err);
+            err.into()
+        }
+    }
+}
+
+// Internal - these functions should return Result<..>
+//
+
+// Authorizes a promote or demote into the given channel, applying the channel's policy if one
+// exists. Without a policy, the permission to promote to the channel is required. With one,
+// either the role the policy names or that permission is, and members lacking both are told
+// what the policy requires.
+fn authorize_channel_operation(req: &HttpRequest,
+                               origin: &str,
+                               channel: &ChannelIdent,
+                               operation: PackageChannelOperation,
+                               conn: &mut PgConnection)
+                               -> Result<(originsrv::Session, Option<ChannelPolicy>)> {
+    let policy = ChannelPolicy::get(origin, channel, conn)?;
+    let permission = Permission::Promote(channel.to_string());
+
+    let min_role = match policy {
+        Some(ref p) => policy_role(p, operation),
+        None => return Ok((authorize_permission(req, origin, &permission)?, None)),
+    };
+
+    // Only members are told about the policy
+    authorize_session(req, Some(origin), None)?;
+    let session = match authorize_role_or_permission(req, origin, min_role, &permission) {
+        Ok(session) => session,
+        Err(Error::Authorization) => {
+            return Err(Error::PolicyViolation(format!("Channel policy for {} requires the {} \
+                                                       role or the {} permission to {} packages",
+                                                      channel,
+                                                      min_role,
+                                                      permission,
+                                                      operation_verb(operation))));
+        }
+        Err(err) => return Err(err),
+    };
+
+    if let Some(ref p) = policy {
+        check_policy_operation(p, operation)?;
+    }
+
+    Ok((session, policy))
+}
+
+// Packages from other origins can be added to a channel when they are public and their origin
+// is visible to the requester, so that a channel never exposes packages its viewers could not
+// otherwise see
+fn check_foreign_package(req: &HttpRequest,
+                         ident: &PackageIdent,
+                         target: PackageTarget,
+                         conn: &mut PgConnection)
+                         -> Result<()> {
+    let package = Package::get(GetPackage { ident:      BuilderPackageIdent(ident.clone()),
+                                            visibility: PackageVisibility::all(),
+                                            target:     BuilderPackageTarget(target), },
+                               conn)?;
+
+    check_foreign_visibility(ident, &package.visibility, || {
+        helpers::origin_visible_for_session(req, &ident.origin, &mut HashMap::new(), conn)
+    })
+}
+
+fn check_foreign_visibility<F>(ident: &PackageIdent,
+                               visibility: &PackageVisibility,
+                               origin_visible: F)
+                               -> Result<()>
+    where F: FnOnce() -> Result<bool>
+{
+    if *visibility != PackageVisibility::Public || !origin_visible()? {
+        return Err(Error::PolicyViolation(format!("{} is not public, so it can't be added to \
+                                                   channels of other origins",
+                                                  ident)));
+    }
+    Ok(())
+}
+
+// Other origins with packages in the channel whose packages the requester can't see, by the
+// same rule that filters reverse dependencies
+fn hidden_foreign_origins(req: &HttpRequest,
+                          origin: &str,
+                          channel: &ChannelIdent,
+                          conn: &mut PgConnection)
+                          -> Result<Vec<String>> {
+    let mut seen = HashMap::new();
+    let mut hidden = Vec::new();
+    for foreign in Channel::list_foreign_origins(origin, channel, conn)? {
+        if !helpers::origin_visible_for_session(req, &foreign, &mut seen, conn)? {
+            hidden.push(foreign);
+        }
+    }
+    Ok(hidden)
+}
+
+// Frozen channels can't be changed by anyone until an origin owner unfreezes them
+fn check_channel_not_frozen(origin: &str,
+                            channel: &ChannelIdent,
+                            conn: &mut PgConnection)
+                            -> Result<()> {
+    if Channel::is_frozen(origin, channel, conn)? {
+        return Err(Error::PolicyViolation(format!("Channel {} is frozen", channel)));
+    }
+    Ok(())
+}
+
+// Tells the origin's webhooks that packages were added to or removed from one of its channels.
+// Called once the change has been committed, alongside the audit entry for it.
+fn notify_channel_packages(origin: &str,
+                           channel: &ChannelIdent,
+                           operation: PackageChannelOperation,
+                           packages: &[(BuilderPackageIdent, BuilderPackageTarget)],
+                           conn: &mut PgConnection) {
+    if packages.is_empty() {
+        return;
+    }
+
+    let event = match operation {
+        PackageChannelOperation::Promote => WebhookEvent::PackagePromote,
+        PackageChannelOperation::Demote => WebhookEvent::PackageDemote,
+    };
+    let packages: Vec<serde_json::Value> =
+        packages.iter()
+                .map(|(ident, target)| {
+                    json!({ "ident": ident.to_string(), "target": target.to_string() })
+                })
+                .collect();
+
+    webhooks::notify(origin,
+                     event,
+                     json!({ "channel": channel.as_str(), "packages": packages }),
+                     conn);
+}
+
+fn notify_channel_package_ids(origin: &str,
+                              channel: &ChannelIdent,
+                              operation: PackageChannelOperation,
+                              package_ids: &[i64],
+                              conn: &mut PgConnection) {
+    if package_ids.is_empty() {
+        return;
+    }
+
+    match Package::list_idents_by_id(package_ids, conn) {
+        Ok(packages) => notify_channel_packages(origin, channel, operation, &packages, conn),
+        Err(err) => warn!("Unable to load packages for webhooks, err={}", err),
+    }
+}
+
+// Works out whether a metadata update freezes or unfreezes the channel. Either needs a reason,
+// and only an origin owner can lift a freeze.
+fn freeze_change<F>(current: &Channel,
+                    body: &ChannelMetadataReq,
+                    is_owner: F)
+                    -> std::result::Result<Option<bool>, HttpResponse>
+    where F: FnOnce() -> bool
+{
+    let frozen = match body.frozen {
+        Some(frozen) if frozen != current.frozen => frozen,
+        _ => return Ok(None),
+    };
+
+    if !frozen && !is_owner() {
+        return Err(Error::PolicyViolation(format!("Only an origin owner can unfreeze {}",
+                                                  current.name)).into());
+    }
+
+    if body.reason.as_deref().map_or(true, str::is_empty) {
+        let body = Bytes::from_static(b"A reason is required to freeze or unfreeze a channel");
+        return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body)));
+    }
+
+    Ok(Some(frozen))
+}
+
+fn is_valid_label_key(key: &str) -> bool {
+    !key.is_empty()
+    && key.len() <= 63
+    && key.chars()
+          .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '/')
+}
+
+// Splits label updates into the labels to set and the keys of those to remove
+fn label_changes(labels: &HashMap<String, Option<String>>)
+                 -> (Vec<(String, String)>, Vec<String>) {
+    let mut set_labels = Vec::new();
+    let mut remove_labels = Vec::new();
+    for (key, value) in labels.iter() {
+        match value {
+            Some(value) => set_labels.push((key.clone(), value.clone())),
+            None => remove_labels.push(key.clone()),
+        }
+    }
+    (set_labels, remove_labels)
+}
+
+// None leaves the description as it is, and an empty description clears it
+fn description_change(description: Option<&str>) -> Option<Option<&str>> {
+    description.map(|d| if d.is_empty() { None } else { Some(d) })
+}
+
+fn labels_by_channel(channel_ids: &[i64],
+                     conn: &mut PgConnection)
+                     -> Result<HashMap<i64, BTreeMap<String, String>>> {
+    let mut labels: HashMap<i64, BTreeMap<String, String>> = HashMap::new();
+    for label in Channel::list_labels(channel_ids, conn)? {
+        labels.entry(label.channel_id)
+              .or_default()
+              .insert(label.key, label.value);
+    }
+    Ok(labels)
+}
+
+// When a channel created or extended at `now` should expire, if it should
+fn channel_expiry(query: &ChannelExpiryQuery,
+                  now: DateTime<Utc>)
+                  -> std::result::Result<Option<NaiveDateTime>, &'static str> {
+    let expires_at = match (query.expires_at, query.ttl_hours) {
+        (None, None) => return Ok(None),
+        (Some(at), None) => at,
+        (None, Some(hours)) => now + chrono::Duration::hours(i64::from(hours)),
+        (Some(_), Some(_)) => return Err("Only one of expires_at and ttl_hours may be given"),
+    };
+
+    if expires_at <= now {
+        return Err("Channel expiry must be in the future");
+    }
+
+    Ok(Some(expires_at.naive_utc()))
+}
+
+fn operation_verb(operation: PackageChannelOperation) -> &'static str {
+    match operation {
+        PackageChannelOperation::Promote => "promote",
+        PackageChannelOperation::Demote => "demote",
+    }
+}
+
+// The promote and demote roles and the source channel a policy update asks for. Policies can only
+// tighten the default requirement of maintainer for channel operations.
+fn policy_settings<'a>(channel: &ChannelIdent,
+                       body: &'a ChannelPolicyReq)
+                       -> std::result::Result<(OriginMemberRole,
+                                               OriginMemberRole,
+                                               Option<&'a str>),
+                                              &'static str> {
+    let promote_role = body.promote_role.unwrap_or(OriginMemberRole::Maintainer);
+    let demote_role = body.demote_role.unwrap_or(OriginMemberRole::Maintainer);
+    if promote_role < OriginMemberRole::Maintainer || demote_role < OriginMemberRole::Maintainer {
+        return Err("Channel policy roles cannot be lower than maintainer");
+    }
+
+    let source_channel = body.source_channel
+                             .as_ref()
+                             .map(|c| c.trim())
+                             .filter(|c| !c.is_empty());
+    if source_channel == Some(channel.as_str()) {
+        return Err("A channel cannot be its own required source channel");
+    }
+
+    Ok((promote_role, demote_role, source_channel))
+}
+
+fn policy_role(policy: &ChannelPolicy, operation: PackageChannelOperation) -> OriginMemberRole {
+    match operation {
+        PackageChannelOperation::Promote => policy.promote_role,
+        PackageChannelOperation::Demote => policy.demote_role,
+    }
+}
+
+// The parts of a policy that apply whatever the requester's role
+fn check_policy_operation(policy: &ChannelPolicy,
+                          operation: PackageChannelOperation)
+                          -> Result<()> {
+    match operation {
+        PackageChannelOperation::Demote if policy.block_demote => {
+            Err(Error::PolicyViolation(format!("Channel policy for {} does not allow packages to \
+                                                be demoted",
+                                               policy.channel)))
+        }
+        PackageChannelOperation::Promote if policy.require_approval => {
+            Err(Error::PolicyViolation(format!("Channel policy for {} requires an approved \
+                                                promotion request",
+                                               policy.channel)))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_package_promotion(policy: &ChannelPolicy,
+                           ident: &PackageIdent,
+                           target: PackageTarget,
+                           conn: &mut PgConnection)
+                           -> Result<()> {
+    let builder_ident = BuilderPackageIdent(ident.clone());
+
+    check_source_channel(policy, &builder_ident, target, conn)?;
+
+    if policy.require_tdeps {
+        let package = Package::get(GetPackage { ident:      builder_ident,
+                                                visibility: PackageVisibility::all(),
+                                                target:     BuilderPackageTarget(target), },
+                                   conn)?;
+        check_tdeps_in_channel(policy, &[package], conn)?;
+    }
+
+    Ok(())
+}
+
+fn check_source_channel(policy: &ChannelPolicy,
+                        ident: &BuilderPackageIdent,
+                        target: PackageTarget,
+                        conn: &mut PgConnection)
+                        -> Result<()> {
+    if let Some(ref source) = policy.source_channel {
+        let source_channel = ChannelIdent::from(source.as_str());
+        if OriginChannelPackage::promoted_at(&policy.origin, &source_channel, ident, target, conn)?
+           .is_none()
+        {
+            return Err(Error::PolicyViolation(format!("Channel policy for {} only allows \
+                                                       promotion from {}, and {} is not in {}",
+                                                      policy.channel,
+                                                      source,
+                                                      ident.to_string(),
+                                                      source)));
+        }
+    }
+    Ok(())
+}
+
+// Loads the packages named in a promotion request and checks them against the channel policy.
+// The packages are checked as a group, as they will be promoted together once approved.
+fn check_promotion_request(idents: &[BuilderPackageIdent],
+                           target: PackageTarget,
+                           policy: Option<&ChannelPolicy>,
+                           conn: &mut PgConnection)
+                           -> Result<()> {
+    let mut packages = Vec::new();
+    for ident in idents {
+        let package = Package::get(GetPackage { ident:      ident.clone(),
+                                                visibility: PackageVisibility::all(),
+                                                target:     BuilderPackageTarget(target), },
+                                   conn)?;
+        if let Some(policy) = policy {
+            check_source_channel(policy, ident, target, conn)?;
+        }
+        packages.push(package);
+    }
+
+    match policy {
+        Some(policy) if policy.require_tdeps => check_tdeps_in_channel(policy, &packages, conn),
+        _ => Ok(()),
+    }
+}
+
+// The fully qualified idents of packages in the origin that a request names, or the first
+// entry that isn't one
+fn origin_package_idents<'a>(origin: &str,
+                             idents: &'a [String])
+                             -> std::result::Result<Vec<BuilderPackageIdent>, &'a str> {
+    idents.iter()
+          .map(|ident| {
+              match PackageIdent::from_str(ident) {
+                  Ok(parsed) if parsed.fully_qualified() && parsed.origin == origin => {
+                      Ok(BuilderPackageIdent(parsed))
+                  }
+                  _ => Err(ident.as_str()),
+              }
+          })
+          .collect()
+}
+
+// A promotion request can be reviewed once, and never by the member who asked for it
+fn check_reviewable(promotion: &PromotionRequest, reviewer_id: i64) -> Result<()> {
+    if promotion.requester_id == reviewer_id {
+        return Err(Error::PolicyViolation(String::from("Promotion requests must be reviewed by \
+                                                        someone other than the requester")));
+    }
+    if promotion.state != PromotionRequestState::Pending {
+        return Err(Error::Conflict);
+    }
+    Ok(())
+}
+
+// Either a fixed time to promote at, or the source channel packages have to stay in, and for how
+// many seconds, before they're promoted
+fn promotion_schedule(body: &ScheduledPromotionReq)
+                      -> std::result::Result<(Option<NaiveDateTime>, Option<(ChannelIdent, i64)>),
+                                             &'static str> {
+    match (body.run_at, body.source_channel.as_ref(), body.delay_hours) {
+        (Some(at), None, None) => Ok((Some(at.naive_utc()), None)),
+        (None, Some(source), Some(hours)) => {
+            Ok((None, Some((ChannelIdent::from(source.as_str()), i64::from(hours) * 3600))))
+        }
+        _ => Err("Either run_at, or both source_channel and delay_hours, must be given"),
+    }
+}
+
+// When a scheduled promotion of `ident` should run. A delayed promotion runs relative to when
+// the package landed in the source channel, and None is returned when it is not there.
+fn scheduled_run_at(origin: &str,
+                    ident: &BuilderPackageIdent,
+                    target: PackageTarget,
+                    run_at: Option<NaiveDateTime>,
+                    delay: Option<&(ChannelIdent, i64)>,
+                    conn: &mut PgConnection)
+                    -> Result<Option<NaiveDateTime>> {
+    match delay {
+        Some((source, delay_secs)) => {
+            let landed = OriginChannelPackage::promoted_at(origin, source, ident, target, conn)?;
+            Ok(landed.map(|at| at + chrono::Duration::seconds(*delay_secs)))
+        }
+        None => {
+            Package::get(GetPackage { ident:      ident.clone(),
+                                      visibility: PackageVisibility::all(),
+                                      target:     BuilderPackageTarget(target), },
+                         conn)?;
+            Ok(run_at)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ScheduledOutcome {
+    Promoted,
+    Skipped(String),
+    Rescheduled(NaiveDateTime),
+}
+
+// Holds back a delayed promotion whose package left the source channel or landed there again too
+// recently, and returns None once it is due
+fn delay_outcome(ident: &BuilderPackageIdent,
+                 source: &ChannelIdent,
+                 landed: Option<NaiveDateTime>,
+                 delay_secs: i64,
+                 now: NaiveDateTime)
+                 -> Option<ScheduledOutcome> {
+    let landed = match landed {
+        Some(landed) => landed,
+        None => {
+            return Some(ScheduledOutcome::Skipped(format!("{} is no longer in {}",
+                                                          ident.to_string(),
+                                                          source)))
+        }
+    };
+
+    let run_at = landed + chrono::Duration::seconds(delay_secs);
+    if run_at > now {
+        Some(ScheduledOutcome::Rescheduled(run_at))
+    } else {
+        None
+    }
+}
+
+// Runs a scheduled promotion that has come due and records how it went. The requester's role
+// and the channel policy are checked again as either may have changed since it was scheduled.
+pub fn run_scheduled_promotion(scheduled: &ScheduledPromotion,
+                               memcache: &mut MemcacheClient,
+                               conn: &mut PgConnection) {
+    let result = match execute_scheduled_promotion(scheduled, memcache, conn) {
+        Ok(ScheduledOutcome::Rescheduled(run_at)) => {
+            ScheduledPromotion::reschedule(scheduled.id, run_at, conn)
+        }
+        Ok(ScheduledOutcome::Promoted) => {
+            ScheduledPromotion::finish(scheduled.id, ScheduledPromotionState::Completed, None, conn)
+        }
+        Ok(ScheduledOutcome::Skipped(reason)) => {
+            ScheduledPromotion::finish(scheduled.id,
+                                       ScheduledPromotionState::Skipped,
+                                       Some(&reason),
+                                       conn)
+        }
+        Err(err) => {
+            debug!("Scheduled promotion {} failed, err={}", scheduled.id, err);
+            ScheduledPromotion::finish(scheduled.id,
+                                       ScheduledPromotionState::Failed,
+                                       Some(&err.to_string()),
+                                       conn)
+        }
+    };
+
+    if let Err(err) = result {
+        warn!("Unable to record outcome of scheduled promotion {}, err={}",
+              scheduled.id, err);
+    }
+}
+
+fn execute_scheduled_promotion(scheduled: &ScheduledPromotion,
+                               memcache: &mut MemcacheClient,
+                               conn: &mut PgConnection)
+                               -> Result<ScheduledOutcome> {
+    let channel = ChannelIdent::from(scheduled.channel.as_str());
+    let target = PackageTarget::from_str(&scheduled.target)?;
+
+    // A package that was demoted from the source channel is not promoted, and one that was
+    // demoted and then promoted again has its delay counted from when it landed the second time
+    if let (Some(source), Some(delay_secs)) = (&scheduled.source_channel, scheduled.delay_secs) {
+        let source = ChannelIdent::from(source.as_str());
+        let landed = OriginChannelPackage::promoted_at(&scheduled.origin,
+                                                       &source,
+                                                       &scheduled.ident,
+                                                       target,
+                                                       conn)?;
+        if let Some(outcome) = delay_outcome(&scheduled.ident,
+                                             &source,
+                                             landed,
+                                             delay_secs,
+                                             Utc::now().naive_utc())
+        {
+            return Ok(outcome);
+        }
+    }
+
+    check_channel_not_frozen(&scheduled.origin, &channel, conn)?;
+
+    // As when promoting directly, the permission to promote to the channel stands in for the role
+    // its policy names
+    let policy = ChannelPolicy::get(&scheduled.origin, &channel, conn)?;
+    let holds_role = match policy {
+        Some(ref policy) => {
+            OriginMember::effective_role(&scheduled.origin, scheduled.requester_id, conn)
+                .map(|role| role >= policy.promote_role)
+        }
+        None => Ok(false),
+    };
+    let allowed = match holds_role {
+        Ok(false) => {
+            OriginMember::has_permission(&scheduled.origin,
+                                         scheduled.requester_id,
+                                         &Permission::Promote(channel.to_string()),
+                                         conn)
+        }
+        other => other,
+    };
+    match allowed {
+        Ok(true) => (),
+        Ok(false) | Err(NotFound) => {
+            return Err(Error::PolicyViolation(format!("{} is no longer allowed to promote \
+                                                       packages to {}",
+                                                      scheduled.requester_name,
+                                                      channel)));
+        }
+        Err(err) => return Err(Error::DieselError(err)),
+    }
+
+    if let Some(ref policy) = policy {
+        check_policy_operation(policy, PackageChannelOperation::Promote)?;
+        check_package_promotion(policy, &scheduled.ident, target, conn)?;
+    }
+
+    let promote = OriginChannelPromote { ident: scheduled.ident.clone(),
+                                         target,
+                                         origin: scheduled.origin.clone(),
+                                         channel };
+    do_promote_package(promote,
+                       PackageChannelTrigger::Scheduled,
+                       scheduled.requester_id,
+                       &scheduled.requester_name,
+                       memcache,
+                       conn)?;
+    Ok(ScheduledOutcome::Promoted)
+}
+
+// Works out which packages have to be promoted for `ident` to be installable from the channel.
+// With `with_deps` that includes the transitive runtime deps that are missing from the channel
+// for the same target. Deps from other origins are included when they could be added to the
+// channel on their own. Deps with no package for the target are returned separately.
+fn plan_package_promotion(req: &HttpRequest,
+                          policy: Option<&ChannelPolicy>,
+                          channel_id: i64,
+                          ident: &PackageIdent,
+                          target: PackageTarget,
+                          with_deps: bool,
+                          conn: &mut PgConnection)
+                          -> Result<(Vec<Package>, Vec<BuilderPackageIdent>)> {
+    let package = Package::get(GetPackage { ident:      BuilderPackageIdent(ident.clone()),
+                                            visibility: PackageVisibility::all(),
+                                            target:     BuilderPackageTarget(target), },
+                               conn)?;
+
+    let origin = package.origin.clone();
+    let deps = if with_deps { package.tdeps.clone() } else { Vec::new() };
+    let found = if deps.is_empty() {
+        Vec::new()
+    } else {
+        let group = GetPackageGroup { pkgs:       deps.clone(),
+                                      visibility: PackageVisibility::all(), };
+        Package::get_group(group, conn)?
+    };
+
+    let in_channel: HashSet<i64> =
+        Channel::list_all_packages_by_channel_id(channel_id, &PackageVisibility::all(), conn)?
+            .into_iter()
+            .collect();
+    let (missing, unavailable) = split_promotion_candidates(package, &deps, found, &in_channel);
+
+    for package in missing.iter().filter(|p| p.origin != origin) {
+        check_foreign_package(req, &package.ident, target, conn)?;
+    }
+
+    if let Some(policy) = policy {
+        for package in missing.iter() {
+            check_source_channel(policy, &package.ident, target, conn)?;
         }
     }
+
+    Ok((missing, unavailable))
 }
 
-#[allow(clippy::needless_pass_by_value)]
-async fn get_latest_package_for_origin_channel_package(req: HttpRequest,
-                                                       path: Path<(String, String, String)>,
-                                                       qtarget: Query<Target>)
-                                                       -> HttpResponse {
-    let (origin, channel, pkg) = path.into_inner();
-    let channel = ChannelIdent::from(channel);
+// Splits the package and the packages found for its deps into the ones missing from the channel,
+// with the package itself last, and the deps that have no package for the package's target
+fn split_promotion_candidates(package: Package,
+                              deps: &[BuilderPackageIdent],
+                              found: Vec<Package>,
+                              in_channel: &HashSet<i64>)
+                              -> (Vec<Package>, Vec<BuilderPackageIdent>) {
+    let mut candidates: Vec<Package> = found.into_iter()
+                                            .filter(|p| p.target == package.target)
+                                            .collect();
+    let unavailable = deps.iter()
+                          .filter(|d| !candidates.iter().any(|p| p.ident == **d))
+                          .cloned()
+                          .collect();
+    candidates.push(package);
 
-    let ident = PackageIdent::new(origin, pkg, None, None);
+    let missing = candidates.into_iter()
+                            .filter(|p| !in_channel.contains(&p.id))
+                            .collect();
+    (missing, unavailable)
+}
 
-    match do_get_channel_package(&req, &qtarget, &ident, &channel) {
-        Ok(json_body) => {
-            HttpResponse::Ok().append_header((http::header::CONTENT_TYPE,
-                                              headers::APPLICATION_JSON))
-                              .append_header((http::header::CACHE_CONTROL,
-                                              headers::Cache::NoCache.to_string()))
-                              .body(json_body)
-        }
-        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
-        Err(err) => {
-            debug!("Failed to get latest package, err={}", err);
-            err.into()
-        }
+// Every transitive dep has to be in the channel, including deps from other origins, which can be
+// added to the channel alongside this origin's packages. Packages promoted together satisfy each
+// other.
+fn check_tdeps_in_channel(policy: &ChannelPolicy,
+                          packages: &[Package],
+                          conn: &mut PgConnection)
+                          -> Result<()> {
+    let channel = ChannelIdent::from(policy.channel.as_str());
+    let in_channel =
+        Channel::list_all_packages(&ListAllChannelPackages { visibility: &PackageVisibility::all(),
+                                                             channel:    &channel,
+                                                             origin:     &policy.origin, },
+                                   conn)?;
+
+    let promoted: Vec<(&BuilderPackageIdent, &[BuilderPackageIdent])> =
+        packages.iter().map(|p| (&p.ident, p.tdeps.as_slice())).collect();
+    let missing = missing_tdeps(&in_channel, &promoted);
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::PolicyViolation(format!("Channel policy for {} requires dependencies to be \
+                                            in the channel first. Missing: {}",
+                                           channel,
+                                           missing.into_iter().collect::<Vec<_>>().join(", "))))
     }
 }
 
-#[allow(clippy::needless_pass_by_value)]
-async fn get_latest_package_for_origin_channel_package_version(req: HttpRequest,
-                                                               path: Path<(String,
-                                                                     String,
-                                                                     String,
-                                                                     String)>,
-                                                               qtarget: Query<Target>)
-                                                               -> HttpResponse {
-    let (origin, channel, pkg, version) = path.into_inner();
-    let channel = ChannelIdent::from(channel);
+// The transitive deps of the promoted packages, given with their tdeps, that are neither in the
+// channel nor being promoted alongside them
+fn missing_tdeps(in_channel: &[BuilderPackageIdent],
+                 promoted: &[(&BuilderPackageIdent, &[BuilderPackageIdent])])
+                 -> BTreeSet<String> {
+    let mut present: HashSet<String> = in_channel.iter().map(|p| p.to_string()).collect();
+    present.extend(promoted.iter().map(|(ident, _)| ident.to_string()));
 
-    let ident = PackageIdent::new(origin, pkg, Some(version), None);
+    promoted.iter()
+            .flat_map(|(_, tdeps)| tdeps.iter())
+            .map(|d| d.to_string())
+            .filter(|d| !present.contains(d))
+            .collect()
+}
 
-    match do_get_channel_package(&req, &qtarget, &ident, &channel) {
-        Ok(json_body) => {
-            HttpResponse::Ok().append_header((http::header::CONTENT_TYPE,
-                                              headers::APPLICATION_JSON))
-                              .append_header((http::header::CACHE_CONTROL,
-                                              headers::Cache::NoCache.to_string()))
-                              .body(json_body)
+// Replays the audited promotions and demotions for a channel to work out which packages it held
+// for the target at the given time. Changes made before the audit log existed are not known.
+fn channel_packages_at(origin: &str,
+                       channel: &ChannelIdent,
+                       target: PackageTarget,
+                       at: NaiveDateTime,
+                       conn: &mut PgConnection)
+                       -> Result<BTreeSet<String>> {
+    let changes = AuditPackage::list_channel_changes(origin, channel, target, at, conn)?;
+    Ok(replay_channel_changes(changes))
+}
+
+// The packages left in a channel after applying the changes in order
+fn replay_channel_changes(changes: Vec<ChannelMembershipChange>) -> BTreeSet<String> {
+    let mut packages = BTreeSet::new();
+    for change in changes {
+        match change.operation {
+            PackageChannelOperation::Promote => {
+                packages.insert(change.package_ident.to_string());
+            }
+            PackageChannelOperation::Demote => {
+                packages.remove(&change.package_ident.to_string());
+            }
         }
-        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
-        Err(err) => {
-            debug!("Failed to get latest package, err={}", err);
-            err.into()
+    }
+    packages
+}
+
+// The target channel history is replayed for, from the query or else the client's user agent
+fn history_target(req: &HttpRequest,
+                  target: Option<&str>)
+                  -> std::result::Result<PackageTarget, HttpResponse> {
+    match target {
+        Some(t) => {
+            PackageTarget::from_str(t).map_err(|_| {
+                let body = Bytes::from(format!("Invalid package target '{}'", t));
+                HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body))
+            })
         }
+        None => Ok(helpers::target_from_headers(req)),
     }
 }
 
-#[allow(clippy::needless_pass_by_value)]
-async fn get_package_fully_qualified(req: HttpRequest,
-                                     path: Path<(String, String, String, String, String)>,
-                                     qtarget: Query<Target>)
-                                     -> HttpResponse {
-    let (origin, channel, pkg, version, release) = path.into_inner();
-    let channel = ChannelIdent::from(channel);
+// Packages land in unstable on upload, which isn't audited, so its history can't be replayed
+fn unstable_history_response() -> HttpResponse {
+    let body = Bytes::from_static(b"Channel history is not available for the unstable channel");
+    HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body))
+}
 
-    let ident = PackageIdent::new(origin, pkg, Some(version), Some(release));
+// Splits the difference between two package sets into the packages only in `to` (added) and the
+// packages only in `from` (removed).
+fn diff_snapshot_packages(from: &[SnapshotPackage],
+                          to: &[SnapshotPackage])
+                          -> (Vec<SnapshotPackage>, Vec<SnapshotPackage>) {
+    let from_ids: HashSet<i64> = from.iter().map(|p| p.package_id).collect();
+    let to_ids: HashSet<i64> = to.iter().map(|p| p.package_id).collect();
 
-    match do_get_channel_package(&req, &qtarget, &ident, &channel) {
-        Ok(json_body) => {
-            HttpResponse::Ok().append_header((http::header::CONTENT_TYPE,
-                                              headers::APPLICATION_JSON))
-                              .append_header((http::header::CACHE_CONTROL,
-                                              headers::Cache::NoCache.to_string()))
-                              .body(json_body)
-        }
-        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
-        Err(err) => {
-            debug!("Failed to get package, err={}", err);
-            err.into()
-        }
-    }
+    let added = to.iter()
+                  .filter(|p| !from_ids.contains(&p.package_id))
+                  .cloned()
+                  .collect();
+    let removed = from.iter()
+                      .filter(|p| !to_ids.contains(&p.package_id))
+                      .cloned()
+                      .collect();
+    (added, removed)
 }
 
-#[allow(clippy::needless_pass_by_value)]
-async fn get_latest_packages_for_origin_channel(req: HttpRequest,
-                                                path: Path<(String, String)>,
-                                                qtarget: Query<Target>)
-                                                -> HttpResponse {
-    let (origin, channel) = path.into_inner();
-    let channel = ChannelIdent::from(channel);
+// Records the review of a pending promotion request. Approval promotes every package in the
+// request and records it in the package audit log under the approval trigger, all in a single
+// transaction so a request is never left approved with only part of its packages promoted.
+fn do_review_promotion_request(req: &HttpRequest,
+                               origin: &str,
+                               channel: &ChannelIdent,
+                               request: &str,
+                               decision: PromotionRequestState,
+                               comment: Option<&str>)
+                               -> Result<PromotionRequest> {
+    let request_id = request.parse::<i64>().map_err(|_| Error::BadRequest)?;
+    let mut conn = req_state(req).db.get_conn().map_err(Error::DbError)?;
 
-    match do_get_latest_channel_packages(&req, &qtarget, &origin, &channel) {
-        Ok((channel, target, data)) => {
-            let json_body = helpers::channel_listing_results_json(&channel, &target, &data);
-            HttpResponse::Ok().append_header((http::header::CONTENT_TYPE,
-                                              headers::APPLICATION_JSON))
-                              .append_header((http::header::CACHE_CONTROL,
-                                              headers::Cache::NoCache.to_string()))
-                              .body(json_body)
-        }
-        Err(Error::NotFound) => HttpResponse::new(StatusCode::NOT_FOUND),
-        Err(Error::BadRequest) => HttpResponse::new(StatusCode::BAD_REQUEST),
-        Err(err) => {
-            debug!("Failed to get package, err={}", err);
-            err.into()
+    // Reviewers need the same standing as members promoting to the channel directly
+    let policy = ChannelPolicy::get(origin, channel, &mut conn)?;
+    let permission = Permission::Promote(channel.to_string());
+    let session = match policy.as_ref() {
+        Some(policy) => {
+            authorize_role_or_permission(req, origin, policy.promote_role, &permission)?
         }
+        None => authorize_permission(req, origin, &permission)?,
+    };
+
+    let promotion = PromotionRequest::get(origin, channel, request_id, &mut conn)?;
+    check_reviewable(&promotion, session.id() as i64)?;
+    if decision == PromotionRequestState::Approved {
+        check_channel_not_frozen(origin, channel, &mut conn)?;
     }
-}
 
-// Internal - these functions should return Result<..>
-//
+    let target = PackageTarget::from_str(&promotion.target).map_err(|_| Error::BadRequest)?;
+
+    let mut promoted = Vec::new();
+    conn.transaction::<_, Error, _>(|conn| {
+            let review = ReviewPromotionRequest { id: promotion.id,
+                                                  state: decision,
+                                                  reviewer_id: session.id() as i64,
+                                                  reviewer_name: session.name(),
+                                                  review_comment: comment };
+            if PromotionRequest::review(&review, conn)? == 0 {
+                return Err(Error::Conflict);
+            }
+
+            if decision == PromotionRequestState::Approved {
+                check_promotion_request(&promotion.package_idents, target, policy.as_ref(), conn)?;
+                for ident in promotion.package_idents.iter() {
+                    let promote = OriginChannelPromote { ident: ident.clone(),
+                                                         target,
+                                                         origin: origin.to_string(),
+                                                         channel: channel.clone() };
+
+                    // Packages already in the channel have nothing to audit
+                    if OriginChannelPackage::promote(promote, conn)? != 0 {
+                        let audit =
+                            PackageChannelAudit { package_ident: ident.clone(),
+                                                  channel: channel.as_str(),
+                                                  operation: PackageChannelOperation::Promote,
+                                                  trigger: PackageChannelTrigger::Approval,
+                                                  requester_id: session.id() as i64,
+                                                  requester_name: session.name(),
+                                                  origin,
+                                                  target: &target.to_string() };
+                        PackageChannelAudit::audit(&audit, conn)?;
+                        promoted.push((ident.clone(), BuilderPackageTarget(target)));
+                    }
+                }
+            }
+            Ok(())
+        })?;
+
+    let action = if decision == PromotionRequestState::Approved {
+        AuditAction::PromotionRequestApprove
+    } else {
+        AuditAction::PromotionRequestReject
+    };
+    audit::record(req,
+                  Some(origin),
+                  action,
+                  request,
+                  Some(json!({ "state": promotion.state })),
+                  Some(json!({ "state": decision, "comment": comment })),
+                  &mut conn);
+
+    notify_channel_packages(origin,
+                            channel,
+                            PackageChannelOperation::Promote,
+                            &promoted,
+                            &mut conn);
+
+    Ok(PromotionRequest::get(origin, channel, request_id, &mut conn)?)
+}
 
 fn do_get_latest_channel_packages(req: &HttpRequest,
                                   qtarget: &Query<Target>,
@@ -739,6 +3433,7 @@ fn do_get_latest_channel_packages(req: &HttpRequest,
     };
 
     let mut conn = req_state(req).db.get_conn().map_err(Error::DbError)?;
+    let hidden_origins = hidden_foreign_origins(req, origin, channel, &mut conn)?;
 
     Channel::list_latest_packages(
         &ListAllChannelPackagesForTarget {
@@ -746,6 +3441,7 @@ fn do_get_latest_channel_packages(req: &HttpRequest,
             channel,
             origin,
             target,
+            hidden_origins: &hidden_origins,
         },
         &mut conn,
     )
@@ -764,6 +3460,7 @@ fn do_get_channel_packages(req: &HttpRequest,
     let (page, per_page) = helpers::extract_pagination_in_pages(pagination);
 
     let mut conn = req_state(req).db.get_conn().map_err(Error::DbError)?;
+    let hidden_origins = hidden_foreign_origins(req, &ident.origin, channel, &mut conn)?;
 
     Channel::list_packages(
         &ListChannelPackages {
@@ -777,6 +3474,7 @@ fn do_get_channel_packages(req: &HttpRequest,
             channel: channel.clone(),
             page: page as i64,
             limit: per_page as i64,
+            hidden_origins,
         },
         &mut conn,
     )
@@ -864,6 +3562,7 @@ fn do_get_channel_package(req: &HttpRequest,
         Ok(conn_ref) => conn_ref,
         Err(e) => return Err(e.into()),
     };
+    let hidden_origins = hidden_foreign_origins(req, &ident.origin, channel, &mut conn)?;
 
     let pkg: Package = match Channel::get_latest_package(
         &GetLatestPackage {
@@ -875,6 +3574,7 @@ fn do_get_channel_package(req: &HttpRequest,
                 opt_session_id,
                 &ident.origin,
             ),
+            hidden_origins: &hidden_origins,
         },
         &mut conn,
     ) {
@@ -964,3 +3664,537 @@ fn postprocess_channel_package_list(_req: &HttpRequest,
             .append_header((http::header::CACHE_CONTROL, headers::Cache::NoCache.to_string()))
             .body(body)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{db::models::package::BuilderPackageType,
+                hab_core::package::metadata::PackageType};
+
+    fn ident(ident: &str) -> BuilderPackageIdent {
+        BuilderPackageIdent(PackageIdent::from_str(ident).unwrap())
+    }
+
+    fn policy() -> ChannelPolicy {
+        ChannelPolicy { channel_id:       1,
+                        origin:           "neurosis".to_string(),
+                        channel:          "stable".to_string(),
+                        promote_role:     OriginMemberRole::Administrator,
+                        demote_role:      OriginMemberRole::Owner,
+                        source_channel:   None,
+                        require_tdeps:    false,
+                        block_demote:     false,
+                        require_approval: false,
+                        created_at:       None,
+                        updated_at:       None, }
+    }
+
+    fn policy_req(promote_role: Option<OriginMemberRole>,
+                  source_channel: Option<&str>)
+                  -> ChannelPolicyReq {
+        ChannelPolicyReq { promote_role,
+                           demote_role: None,
+                           source_channel: source_channel.map(str::to_string),
+                           require_tdeps: false,
+                           block_demote: false,
+                           require_approval: false }
+    }
+
+    #[test]
+    fn policy_roles_default_to_maintainer() {
+        let channel = ChannelIdent::from("stable");
+        let req = policy_req(None, None);
+        let (promote, demote, source) = policy_settings(&channel, &req).unwrap();
+        assert_eq!(promote, OriginMemberRole::Maintainer);
+        assert_eq!(demote, OriginMemberRole::Maintainer);
+        assert_eq!(source, None);
+    }
+
+    #[test]
+    fn policy_roles_cannot_be_lower_than_maintainer() {
+        let channel = ChannelIdent::from("stable");
+        let req = policy_req(Some(OriginMemberRole::Member), None);
+        assert!(policy_settings(&channel, &req).is_err());
+
+        let req = policy_req(Some(OriginMemberRole::Owner), None);
+        assert!(policy_settings(&channel, &req).is_ok());
+    }
+
+    #[test]
+    fn policy_source_channel_is_trimmed_and_not_the_channel_itself() {
+        let channel = ChannelIdent::from("stable");
+        let req = policy_req(None, Some("  "));
+        assert_eq!(policy_settings(&channel, &req).unwrap().2, None);
+
+        let req = policy_req(None, Some(" beta "));
+        assert_eq!(policy_settings(&channel, &req).unwrap().2, Some("beta"));
+
+        let req = policy_req(None, Some("stable"));
+        assert!(policy_settings(&channel, &req).is_err());
+    }
+
+    #[test]
+    fn policy_role_depends_on_the_operation() {
+        let policy = policy();
+        assert_eq!(policy_role(&policy, PackageChannelOperation::Promote),
+                   OriginMemberRole::Administrator);
+        assert_eq!(policy_role(&policy, PackageChannelOperation::Demote),
+                   OriginMemberRole::Owner);
+    }
+
+    #[test]
+    fn policy_can_block_demotion() {
+        let mut policy = policy();
+        assert!(check_policy_operation(&policy, PackageChannelOperation::Demote).is_ok());
+
+        policy.block_demote = true;
+        assert!(matches!(check_policy_operation(&policy, PackageChannelOperation::Demote),
+                         Err(Error::PolicyViolation(_))));
+        assert!(check_policy_operation(&policy, PackageChannelOperation::Promote).is_ok());
+    }
+
+    #[test]
+    fn policy_can_require_approval_for_promotion() {
+        let mut policy = policy();
+        assert!(check_policy_operation(&policy, PackageChannelOperation::Promote).is_ok());
+
+        policy.require_approval = true;
+        assert!(matches!(check_policy_operation(&policy, PackageChannelOperation::Promote),
+                         Err(Error::PolicyViolation(_))));
+        assert!(check_policy_operation(&policy, PackageChannelOperation::Demote).is_ok());
+    }
+
+    #[test]
+    fn tdeps_in_the_channel_are_not_missing() {
+        let app = ident("neurosis/app/1.0.0/20200101000000");
+        let tdeps = [ident("core/glibc/2.29/20200101000000"),
+                     ident("core/openssl/1.0.2/20200101000000")];
+        let in_channel = [ident("core/glibc/2.29/20200101000000")];
+
+        let missing = missing_tdeps(&in_channel, &[(&app, &tdeps)]);
+        assert_eq!(missing.into_iter().collect::<Vec<_>>(),
+                   vec!["core/openssl/1.0.2/20200101000000".to_string()]);
+    }
+
+    #[test]
+    fn tdeps_promoted_together_satisfy_each_other() {
+        let app = ident("neurosis/app/1.0.0/20200101000000");
+        let lib = ident("neurosis/lib/1.0.0/20200101000000");
+        let app_tdeps = [lib.clone(), ident("core/glibc/2.29/20200101000000")];
+        let lib_tdeps = [ident("core/glibc/2.29/20200101000000")];
+
+        let missing = missing_tdeps(&[], &[(&app, &app_tdeps), (&lib, &lib_tdeps)]);
+        assert_eq!(missing.into_iter().collect::<Vec<_>>(),
+                   vec!["core/glibc/2.29/20200101000000".to_string()]);
+
+        let in_channel = [ident("core/glibc/2.29/20200101000000")];
+        assert!(missing_tdeps(&in_channel, &[(&app, &app_tdeps), (&lib, &lib_tdeps)]).is_empty());
+    }
+
+    fn promotion_request(state: PromotionRequestState) -> PromotionRequest {
+        PromotionRequest { id: 1,
+                           origin: "neurosis".to_string(),
+                           channel: "stable".to_string(),
+                           target: "x86_64-linux".to_string(),
+                           package_idents: vec![ident("neurosis/app/1.0.0/20200101000000")],
+                           state,
+                           requester_id: 10,
+                           requester_name: "wesker".to_string(),
+                           reviewer_id: None,
+                           reviewer_name: None,
+                           review_comment: None,
+                           created_at: None,
+                           reviewed_at: None }
+    }
+
+    #[test]
+    fn promotion_requests_need_fully_qualified_idents_in_the_origin() {
+        let idents = vec!["neurosis/app/1.0.0/20200101000000".to_string(),
+                          "neurosis/lib/2.0.0/20200101000000".to_string()];
+        assert_eq!(origin_package_idents("neurosis", &idents).unwrap(),
+                   vec![ident("neurosis/app/1.0.0/20200101000000"),
+                        ident("neurosis/lib/2.0.0/20200101000000")]);
+
+        let idents = vec!["neurosis/app/1.0.0".to_string()];
+        assert_eq!(origin_package_idents("neurosis", &idents), Err("neurosis/app/1.0.0"));
+
+        let idents = vec!["neurosis/app/1.0.0/20200101000000".to_string(),
+                          "core/glibc/2.29/20200101000000".to_string()];
+        assert_eq!(origin_package_idents("neurosis", &idents),
+                   Err("core/glibc/2.29/20200101000000"));
+
+        let idents = vec!["not an ident".to_string()];
+        assert!(origin_package_idents("neurosis", &idents).is_err());
+    }
+
+    #[test]
+    fn promotion_requests_are_not_reviewed_by_their_requester() {
+        let promotion = promotion_request(PromotionRequestState::Pending);
+        assert!(check_reviewable(&promotion, 20).is_ok());
+        assert!(matches!(check_reviewable(&promotion, 10), Err(Error::PolicyViolation(_))));
+    }
+
+    #[test]
+    fn promotion_requests_are_reviewed_once() {
+        for state in [PromotionRequestState::Approved, PromotionRequestState::Rejected] {
+            let promotion = promotion_request(state);
+            assert!(matches!(check_reviewable(&promotion, 20), Err(Error::Conflict)));
+        }
+    }
+
+    fn snapshot_package(package_id: i64, name: &str) -> SnapshotPackage {
+        SnapshotPackage { package_id,
+                          ident: ident(name),
+                          target: "x86_64-linux".to_string() }
+    }
+
+    #[test]
+    fn snapshot_diff_splits_added_and_removed_packages() {
+        let app = snapshot_package(1, "neurosis/app/1.0.0/20200101000000");
+        let app2 = snapshot_package(2, "neurosis/app/1.0.1/20200102000000");
+        let lib = snapshot_package(3, "neurosis/lib/1.0.0/20200101000000");
+
+        let (added, removed) = diff_snapshot_packages(&[app.clone(), lib.clone()],
+                                                      &[app2.clone(), lib.clone()]);
+        assert_eq!(added, vec![app2]);
+        assert_eq!(removed, vec![app]);
+    }
+
+    #[test]
+    fn snapshot_diff_of_identical_package_sets_is_empty() {
+        let packages = [snapshot_package(1, "neurosis/app/1.0.0/20200101000000"),
+                        snapshot_package(3, "neurosis/lib/1.0.0/20200101000000")];
+        let (added, removed) = diff_snapshot_packages(&packages, &packages);
+        assert!(added.is_empty());
+        assert!(removed.is_empty());
+
+        let (added, removed) = diff_snapshot_packages(&[], &packages);
+        assert_eq!(added, packages.to_vec());
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn snapshot_diff_compares_packages_by_id() {
+        // The same ident built for two targets is two packages
+        let linux = snapshot_package(1, "neurosis/app/1.0.0/20200101000000");
+        let windows = SnapshotPackage { package_id: 2,
+                                        target:     "x86_64-windows".to_string(),
+                                        ..linux.clone() };
+        let (added, removed) = diff_snapshot_packages(&[linux.clone()], &[windows.clone()]);
+        assert_eq!(added, vec![windows]);
+        assert_eq!(removed, vec![linux]);
+    }
+
+    fn change(name: &str, operation: PackageChannelOperation) -> ChannelMembershipChange {
+        ChannelMembershipChange { package_ident: ident(name),
+                                  operation,
+                                  created_at: None }
+    }
+
+    #[test]
+    fn history_replays_promotions_and_demotions_in_order() {
+        let changes = vec![change("neurosis/app/1.0.0/20200101000000",
+                                  PackageChannelOperation::Promote),
+                           change("neurosis/lib/1.0.0/20200101000000",
+                                  PackageChannelOperation::Promote),
+                           change("neurosis/app/1.0.0/20200101000000",
+                                  PackageChannelOperation::Demote),
+                           change("neurosis/app/1.0.1/20200102000000",
+                                  PackageChannelOperation::Promote)];
+        assert_eq!(replay_channel_changes(changes).into_iter().collect::<Vec<_>>(),
+                   vec!["neurosis/app/1.0.1/20200102000000".to_string(),
+                        "neurosis/lib/1.0.0/20200101000000".to_string()]);
+    }
+
+    #[test]
+    fn history_keeps_packages_promoted_again_after_a_demotion() {
+        let changes = vec![change("neurosis/app/1.0.0/20200101000000",
+                                  PackageChannelOperation::Promote),
+                           change("neurosis/app/1.0.0/20200101000000",
+                                  PackageChannelOperation::Demote),
+                           change("neurosis/app/1.0.0/20200101000000",
+                                  PackageChannelOperation::Promote)];
+        assert_eq!(replay_channel_changes(changes).len(), 1);
+    }
+
+    #[test]
+    fn history_ignores_demotions_of_packages_promoted_before_the_audit_log() {
+        let changes = vec![change("neurosis/app/1.0.0/20200101000000",
+                                  PackageChannelOperation::Demote),
+                           change("neurosis/lib/1.0.0/20200101000000",
+                                  PackageChannelOperation::Promote)];
+        assert_eq!(replay_channel_changes(changes).into_iter().collect::<Vec<_>>(),
+                   vec!["neurosis/lib/1.0.0/20200101000000".to_string()]);
+        assert!(replay_channel_changes(Vec::new()).is_empty());
+    }
+
+    fn package(id: i64, name: &str, target: &str, tdeps: &[&str]) -> Package {
+        let parsed = ident(name);
+        Package { id,
+                  owner_id: 1,
+                  name: parsed.name.clone(),
+                  ident_array: Vec::new(),
+                  checksum: String::new(),
+                  manifest: String::new(),
+                  config: String::new(),
+                  target: BuilderPackageTarget(PackageTarget::from_str(target).unwrap()),
+                  deps: Vec::new(),
+                  tdeps: tdeps.iter().copied().map(ident).collect(),
+                  build_deps: Vec::new(),
+                  build_tdeps: Vec::new(),
+                  exposes: Vec::new(),
+                  visibility: PackageVisibility::Public,
+                  created_at: None,
+                  updated_at: None,
+                  origin: parsed.origin.clone(),
+                  package_type: BuilderPackageType(PackageType::Standard),
+                  ident: parsed }
+    }
+
+    #[test]
+    fn promotion_without_deps_plans_only_the_package() {
+        let app = package(1, "neurosis/app/1.0.0/20200101000000", "x86_64-linux", &[]);
+        let (missing, unavailable) =
+            split_promotion_candidates(app, &[], Vec::new(), &HashSet::new());
+        assert_eq!(missing.iter().map(|p| p.id).collect::<Vec<_>>(), vec![1]);
+        assert!(unavailable.is_empty());
+
+        let app = package(1, "neurosis/app/1.0.0/20200101000000", "x86_64-linux", &[]);
+        let in_channel: HashSet<i64> = vec![1].into_iter().collect();
+        let (missing, _) = split_promotion_candidates(app, &[], Vec::new(), &in_channel);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn promotion_with_deps_plans_the_deps_missing_from_the_channel() {
+        let tdeps = ["core/glibc/2.29/20200101000000", "core/openssl/1.0.2/20200101000000"];
+        let app = package(1, "neurosis/app/1.0.0/20200101000000", "x86_64-linux", &tdeps);
+        let deps = app.tdeps.clone();
+        let found = vec![package(2, tdeps[0], "x86_64-linux", &[]),
+                         package(3, tdeps[1], "x86_64-linux", &[])];
+        let in_channel: HashSet<i64> = vec![2].into_iter().collect();
+
+        let (missing, unavailable) = split_promotion_candidates(app, &deps, found, &in_channel);
+        assert_eq!(missing.iter().map(|p| p.id).collect::<Vec<_>>(), vec![3, 1]);
+        assert!(unavailable.is_empty());
+    }
+
+    #[test]
+    fn promotion_with_deps_reports_deps_without_a_package_for_the_target() {
+        let tdeps = ["core/glibc/2.29/20200101000000", "core/openssl/1.0.2/20200101000000"];
+        let app = package(1, "neurosis/app/1.0.0/20200101000000", "x86_64-linux", &tdeps);
+        let deps = app.tdeps.clone();
+        let found = vec![package(2, tdeps[0], "x86_64-linux", &[]),
+                         package(3, tdeps[1], "x86_64-windows", &[])];
+
+        let (missing, unavailable) =
+            split_promotion_candidates(app, &deps, found, &HashSet::new());
+        assert_eq!(missing.iter().map(|p| p.id).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(unavailable, vec![ident(tdeps[1])]);
+    }
+
+    fn schedule_req(run_at: Option<&str>,
+                    source_channel: Option<&str>,
+                    delay_hours: Option<u32>)
+                    -> ScheduledPromotionReq {
+        ScheduledPromotionReq { target: None,
+                                idents: Vec::new(),
+                                run_at: run_at.map(|at| at.parse().unwrap()),
+                                source_channel: source_channel.map(str::to_string),
+                                delay_hours }
+    }
+
+    fn at(timestamp: &str) -> NaiveDateTime {
+        timestamp.parse::<DateTime<Utc>>().unwrap().naive_utc()
+    }
+
+    #[test]
+    fn scheduled_promotions_run_at_a_fixed_time() {
+        let req = schedule_req(Some("2026-11-01T12:00:00Z"), None, None);
+        assert_eq!(promotion_schedule(&req).unwrap(),
+                   (Some(at("2026-11-01T12:00:00Z")), None));
+    }
+
+    #[test]
+    fn delayed_promotions_count_the_delay_in_seconds() {
+        let req = schedule_req(None, Some("beta"), Some(24));
+        assert_eq!(promotion_schedule(&req).unwrap(),
+                   (None, Some((ChannelIdent::from("beta"), 86_400))));
+    }
+
+    #[test]
+    fn scheduled_promotions_need_a_time_or_a_delay_but_not_both() {
+        for req in [schedule_req(None, None, None),
+                    schedule_req(None, Some("beta"), None),
+                    schedule_req(None, None, Some(24)),
+                    schedule_req(Some("2026-11-01T12:00:00Z"), Some("beta"), Some(24))]
+        {
+            assert!(promotion_schedule(&req).is_err());
+        }
+    }
+
+    #[test]
+    fn delayed_promotions_are_skipped_once_the_package_leaves_the_source_channel() {
+        let app = ident("neurosis/app/1.0.0/20200101000000");
+        let beta = ChannelIdent::from("beta");
+        assert!(matches!(delay_outcome(&app, &beta, None, 3600, at("2026-11-01T12:00:00Z")),
+                         Some(ScheduledOutcome::Skipped(_))));
+    }
+
+    #[test]
+    fn delayed_promotions_wait_for_the_delay_since_the_package_landed() {
+        let app = ident("neurosis/app/1.0.0/20200101000000");
+        let beta = ChannelIdent::from("beta");
+        let landed = Some(at("2026-11-01T12:00:00Z"));
+
+        assert_eq!(delay_outcome(&app, &beta, landed, 3600, at("2026-11-01T12:30:00Z")),
+                   Some(ScheduledOutcome::Rescheduled(at("2026-11-01T13:00:00Z"))));
+        assert_eq!(delay_outcome(&app, &beta, landed, 3600, at("2026-11-01T13:00:00Z")),
+                   None);
+        assert_eq!(delay_outcome(&app, &beta, landed, 3600, at("2026-11-02T00:00:00Z")),
+                   None);
+    }
+
+    fn expiry_query(expires_at: Option<&str>, ttl_hours: Option<u32>) -> ChannelExpiryQuery {
+        ChannelExpiryQuery { expires_at: expires_at.map(|at| at.parse().unwrap()),
+                             ttl_hours }
+    }
+
+    #[test]
+    fn channels_without_an_expiry_are_permanent() {
+        let now = "2026-11-01T12:00:00Z".parse().unwrap();
+        assert_eq!(channel_expiry(&expiry_query(None, None), now), Ok(None));
+    }
+
+    #[test]
+    fn channels_expire_at_a_time_or_after_a_ttl() {
+        let now = "2026-11-01T12:00:00Z".parse().unwrap();
+        assert_eq!(channel_expiry(&expiry_query(Some("2026-11-02T00:00:00Z"), None), now),
+                   Ok(Some(at("2026-11-02T00:00:00Z"))));
+        assert_eq!(channel_expiry(&expiry_query(None, Some(48)), now),
+                   Ok(Some(at("2026-11-03T12:00:00Z"))));
+    }
+
+    #[test]
+    fn channel_expiry_must_be_in_the_future() {
+        let now = "2026-11-01T12:00:00Z".parse().unwrap();
+        assert!(channel_expiry(&expiry_query(Some("2026-11-01T12:00:00Z"), None), now).is_err());
+        assert!(channel_expiry(&expiry_query(Some("2026-10-01T00:00:00Z"), None), now).is_err());
+        assert!(channel_expiry(&expiry_query(None, Some(0)), now).is_err());
+    }
+
+    #[test]
+    fn channel_expiry_takes_a_time_or_a_ttl_but_not_both() {
+        let now = "2026-11-01T12:00:00Z".parse().unwrap();
+        let query = expiry_query(Some("2026-11-02T00:00:00Z"), Some(1));
+        assert!(channel_expiry(&query, now).is_err());
+    }
+
+    #[test]
+    fn label_keys_are_short_and_plain() {
+        for key in &["team", "owner.email", "ci/pipeline", "release-train_2"] {
+            assert!(is_valid_label_key(key), "{} should be allowed", key);
+        }
+        let long = "k".repeat(64);
+        for key in &["", "has space", "emoji-\u{1f680}", "semi;colon", long.as_str()] {
+            assert!(!is_valid_label_key(key), "{} should be refused", key);
+        }
+        assert!(is_valid_label_key(&"k".repeat(63)));
+    }
+
+    #[test]
+    fn null_labels_are_removed() {
+        let mut labels = HashMap::new();
+        labels.insert("team".to_string(), Some("release".to_string()));
+        labels.insert("stale".to_string(), None);
+
+        let (set_labels, remove_labels) = label_changes(&labels);
+        assert_eq!(set_labels, vec![("team".to_string(), "release".to_string())]);
+        assert_eq!(remove_labels, vec!["stale".to_string()]);
+    }
+
+    #[test]
+    fn empty_descriptions_clear_the_description() {
+        assert_eq!(description_change(None), None);
+        assert_eq!(description_change(Some("")), Some(None));
+        assert_eq!(description_change(Some("Nightly builds")), Some(Some("Nightly builds")));
+    }
+
+    fn channel(frozen: bool) -> Channel {
+        Channel { id: 1,
+                  owner_id: 1,
+                  name: "bar".to_string(),
+                  created_at: None,
+                  updated_at: None,
+                  origin: "neurosis".to_string(),
+                  expires_at: None,
+                  description: None,
+                  frozen,
+                  maintainers: Vec::new() }
+    }
+
+    fn freeze_req(frozen: Option<bool>, reason: Option<&str>) -> ChannelMetadataReq {
+        ChannelMetadataReq { description: None,
+                             labels: HashMap::new(),
+                             frozen,
+                             maintainers: None,
+                             reason: reason.map(str::to_string) }
+    }
+
+    #[test]
+    fn freezing_needs_a_reason() {
+        let req = freeze_req(Some(true), Some("release"));
+        assert_eq!(freeze_change(&channel(false), &req, || false).ok(), Some(Some(true)));
+
+        for reason in vec![None, Some("")] {
+            let resp = freeze_change(&channel(false), &freeze_req(Some(true), reason), || true);
+            assert_eq!(resp.unwrap_err().status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    #[test]
+    fn only_origin_owners_can_unfreeze() {
+        let req = freeze_req(Some(false), Some("done"));
+        let resp = freeze_change(&channel(true), &req, || false);
+        assert_eq!(resp.unwrap_err().status(), StatusCode::FORBIDDEN);
+        assert_eq!(freeze_change(&channel(true), &req, || true).ok(), Some(Some(false)));
+    }
+
+    #[test]
+    fn unchanged_freezes_are_ignored() {
+        for (current, frozen) in vec![(false, None), (false, Some(false)), (true, Some(true))] {
+            let change = freeze_change(&channel(current), &freeze_req(frozen, None), || false);
+            assert_eq!(change.ok(), Some(None));
+        }
+    }
+
+    #[test]
+    fn only_public_packages_of_visible_origins_can_be_added_elsewhere() {
+        let foreign = PackageIdent::from_str("xmen/testapp/0.1.4/20171206005217").unwrap();
+        let public = check_foreign_visibility(&foreign, &PackageVisibility::Public, || Ok(true));
+        assert!(public.is_ok());
+
+        for visibility in vec![PackageVisibility::Private, PackageVisibility::Hidden] {
+            match check_foreign_visibility(&foreign, &visibility, || Ok(true)) {
+                Err(Error::PolicyViolation(msg)) => {
+                    assert_eq!(msg,
+                               "xmen/testapp/0.1.4/20171206005217 is not public, so it can't be \
+                                added to channels of other origins")
+                }
+                other => panic!("unexpected result {:?}", other),
+            }
+        }
+
+        let hidden = check_foreign_visibility(&foreign, &PackageVisibility::Public, || Ok(false));
+        assert!(matches!(hidden, Err(Error::PolicyViolation(_))));
+    }
+
+    #[test]
+    fn origin_visibility_is_only_checked_for_public_packages() {
+        let foreign = PackageIdent::from_str("xmen/testapp/0.1.4/20171206005217").unwrap();
+        let result = check_foreign_visibility(&foreign, &PackageVisibility::Private, || {
+                         panic!("origin visibility should not be looked up")
+                     });
+        assert!(result.is_err());
+    }
+}
@@ -11,6 +11,7 @@ use crate::{db::models::channel::{AuditPackage,
                                Pagination,
                                SearchQuery,
                                ToChannel},
+                     services::event_stream::EventStream,
                      AppState}};
 use actix_web::{http,
                 web::{self,
@@ -21,6 +22,17 @@ use actix_web::{http,
                 HttpResponse};
 use builder_core::http_client::{HttpClient,
                                 USER_AGENT_BLDR};
+use futures::StreamExt;
+use std::convert::Infallible;
+
+// Query param containers
+#[derive(Debug, Deserialize)]
+struct EventStreamFilter {
+    #[serde(default)]
+    origin:  Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+}
 
 pub struct Events {}
 
@@ -29,6 +41,7 @@ impl Events {
     //
     pub fn register(cfg: &mut ServiceConfig) {
         cfg.route("/depot/events", web::get().to(get_events))
+           .route("/depot/events/stream", web::get().to(get_event_stream))
            .route("/depot/events/saas", web::get().to(get_events_from_saas));
     }
 }
@@ -49,6 +62,34 @@ async fn get_events(req: HttpRequest,
     }
 }
 
+// Streams uploads, promotions and demotions as server-sent events as they happen. Readers see the
+// same events they would from get_events.
+#[allow(clippy::needless_pass_by_value)]
+async fn get_event_stream(req: HttpRequest,
+                          filter: Query<EventStreamFilter>,
+                          events: Data<EventStream>)
+                          -> HttpResponse {
+    let opt_session_id = match authorize_session(&req, None, None) {
+        Ok(session) => Some(session.id() as i64),
+        Err(_) => None,
+    };
+    let origin = filter.origin
+                       .as_ref()
+                       .map(|origin| origin.trim().to_string())
+                       .filter(|origin| !origin.is_empty());
+    let channel = filter.channel
+                        .as_ref()
+                        .map(|channel| channel.trim().to_string())
+                        .filter(|channel| !channel.is_empty());
+
+    let stream = events.subscribe(opt_session_id, origin, channel);
+
+    HttpResponse::Ok().append_header((http::header::CONTENT_TYPE, headers::TEXT_EVENT_STREAM))
+                      .append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                      .append_header((headers::X_ACCEL_BUFFERING, "no"))
+                      .streaming(stream.map(Ok::<_, Infallible>))
+}
+
 #[allow(clippy::needless_pass_by_value)]
 async fn get_events_from_saas(req: HttpRequest,
                               pagination: Query<Pagination>,
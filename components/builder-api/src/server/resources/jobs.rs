@@ -24,13 +24,10 @@ use actix_web::{web::{self,
 
 use crate::protocol::originsrv::OriginPackageIdent;
 
-use crate::db::models::{origin::*,
-                        package::*};
-
-use crate::server::{authorize::authorize_session,
-                    error::{Error,
+use crate::server::{error::{Error,
                             Result},
-                    helpers::{req_state,
+                    helpers::{origin_visible_for_session,
+                              req_state,
                               Target}};
 
 use super::reverse_dependencies::{self,
@@ -87,22 +84,12 @@ fn filtered_rdeps(req: &HttpRequest,
     let mut filtered_rdeps = ReverseDependencies { origin: reverse_dependencies.origin.clone(),
                                                    name:   reverse_dependencies.name.clone(),
                                                    rdeps:  Vec::new(), };
+    let mut conn = req_state(req).db.get_conn().map_err(Error::DbError)?;
 
     for rdep in reverse_dependencies.rdeps.iter() {
         let ident = OriginPackageIdent::from_str(rdep)?;
         let origin_name = ident.origin();
-        let pv = if !origin_map.contains_key(origin_name) {
-            let mut conn = req_state(req).db.get_conn().map_err(Error::DbError)?;
-            let origin = Origin::get(origin_name, &mut conn)?;
-            origin_map.insert(origin_name.to_owned(),
-                              origin.default_package_visibility.clone());
-            origin.default_package_visibility
-        } else {
-            origin_map[origin_name].clone()
-        };
-        if pv != PackageVisibility::Public
-           && authorize_session(req, Some(origin_name), Some(OriginMemberRole::Member)).is_err()
-        {
+        if !origin_visible_for_session(req, origin_name, &mut origin_map, &mut conn)? {
             debug!("Skipping unauthorized non-public origin package: {origin_name}");
             continue; // Skip any unauthorized origin packages
         }
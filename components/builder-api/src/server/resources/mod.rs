@@ -1,3 +1,4 @@
+pub mod admin;
 pub mod authenticate;
 pub mod channels;
 pub mod events;
@@ -7,5 +8,10 @@ pub mod origins;
 pub mod pkgs;
 pub mod profile;
 pub(crate) mod reverse_dependencies;
+pub mod robots;
+pub mod roles;
 pub mod settings;
+pub mod teams;
+pub mod trust_rules;
 pub mod user;
+pub mod webhooks;
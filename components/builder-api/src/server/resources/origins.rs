@@ -3,7 +3,12 @@
 
 use crate::{bldr_core::crypto,
             db::models::{account::*,
+                         audit::{AuditAction,
+                                 AuditEvent,
+                                 ListAuditEvents},
                          channel::Channel,
+                         download_stats::{ListDownloadStats,
+                                          PackageDownloadStat},
                          integration::*,
                          invitations::*,
                          keys as db_keys,
@@ -13,10 +18,14 @@ use crate::{bldr_core::crypto,
                                    Package,
                                    PackageVisibility},
                          projects::Project,
+                         role::Permission,
                          secrets::*,
-                         settings::OriginPackageSettings},
+                         settings::OriginPackageSettings,
+                         webhook::WebhookEvent},
             protocol::originsrv::OriginKeyIdent,
-            server::{authorize::{authorize_session,
+            server::{authorize::{authorize_permission,
+                                 authorize_role_grant,
+                                 authorize_session,
                                  check_origin_member,
                                  check_origin_owner},
                      error::{Error,
@@ -25,8 +34,11 @@ use crate::{bldr_core::crypto,
                      helpers::{self,
                                role_results_json,
                                Pagination,
-                               Role},
+                               Role,
+                               StatsDateRange},
                      resources::pkgs::postprocess_package_list,
+                     services::{audit,
+                                webhooks},
                      AppState}};
 use actix_web::{body::BoxBody,
                 http::{self,
@@ -47,6 +59,8 @@ use actix_web::{body::BoxBody,
                 HttpResponse};
 use builder_core::Error::OriginDeleteError;
 use bytes::Bytes;
+use chrono::{NaiveDate,
+             NaiveTime};
 use diesel::{pg::PgConnection,
              result::Error::NotFound};
 use habitat_core::{crypto::keys::{self as core_keys,
@@ -81,6 +95,29 @@ pub struct UpdateOriginHandlerReq {
     pub default_package_visibility: Option<PackageVisibility>,
 }
 
+#[derive(Deserialize)]
+struct AuditQuery {
+    #[serde(default)]
+    action:    Option<AuditAction>,
+    #[serde(default)]
+    actor:     Option<String>,
+    #[serde(default)]
+    target:    Option<String>,
+    #[serde(default)]
+    from_date: Option<NaiveDate>,
+    #[serde(default)]
+    to_date:   Option<NaiveDate>,
+}
+
+// An origin member, marked when it's a robot account or its membership is granted by the groups
+// of its identity provider
+#[derive(Debug, PartialEq, Serialize)]
+struct OriginMemberEntry {
+    name:        String,
+    robot:       bool,
+    idp_managed: bool,
+}
+
 pub struct Origins {}
 
 impl Origins {
@@ -92,6 +129,10 @@ impl Origins {
            .route("/depot/origins/{origin}", web::put().to(update_origin))
            .route("/depot/origins/{origin}", web::delete().to(delete_origin))
            .route("/depot/origins", web::post().to(create_origin))
+           .route("/depot/origins/{origin}/stats",
+                  web::get().to(get_origin_download_stats))
+           .route("/depot/origins/{origin}/audit",
+                  web::get().to(list_origin_audit_events))
            .route("/depot/origins/{origin}/users",
                   web::get().to(list_origin_members))
            .route("/depot/origins/{origin}/users/{user}",
@@ -172,6 +213,111 @@ async fn get_origin(path: Path<String>, state: Data<AppState>) -> HttpResponse {
     }
 }
 
+#[allow(clippy::needless_pass_by_value)]
+async fn get_origin_download_stats(req: HttpRequest,
+                                   path: Path<String>,
+                                   range: Query<StatsDateRange>,
+                                   state: Data<AppState>)
+                                   -> HttpResponse {
+    let origin = path.into_inner();
+
+    let opt_session_id = match authorize_session(&req, None, None) {
+        Ok(session) => Some(session.id()),
+        Err(_) => None,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let (from_date, to_date) = helpers::extract_stats_range(&range);
+    if from_date > to_date {
+        return HttpResponse::new(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let visibility = helpers::visibility_for_optional_session(&req, opt_session_id, &origin);
+    let lds = ListDownloadStats { origin: &origin,
+                                  from_date,
+                                  to_date,
+                                  visibility };
+
+    match PackageDownloadStat::list_for_origin(&lds, &mut conn) {
+        Ok(packages) => {
+            let total: i64 = packages.iter().map(|p| p.downloads).sum();
+            let body = json!({
+                "origin": origin,
+                "from_date": from_date,
+                "to_date": to_date,
+                "total": total,
+                "packages": packages,
+            });
+
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(body)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn list_origin_audit_events(req: HttpRequest,
+                                  path: Path<String>,
+                                  pagination: Query<Pagination>,
+                                  filter: Query<AuditQuery>,
+                                  state: Data<AppState>)
+                                  -> HttpResponse {
+    let origin = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let (page, per_page) = helpers::extract_pagination_in_pages(&pagination);
+    // Both dates are inclusive, so the range ends at the start of the day after to_date
+    let from_date = filter.from_date.map(|date| date.and_time(NaiveTime::MIN));
+    let to_date = filter.to_date
+                        .map(|date| (date + chrono::Duration::days(1)).and_time(NaiveTime::MIN));
+
+    let le = ListAuditEvents { origin,
+                               action: filter.action,
+                               actor: filter.actor.clone(),
+                               target: filter.target.clone(),
+                               from_date,
+                               to_date,
+                               page: page as i64,
+                               limit: per_page as i64 };
+
+    match AuditEvent::list(&le, &mut conn).map_err(Error::DieselError) {
+        Ok((events, count)) => {
+            let (start, _) = helpers::extract_pagination(&pagination);
+            let stop = start + events.len() as isize - 1;
+            let body = helpers::package_results_json(&events, count as isize, start, stop);
+
+            let mut response = if count as isize > (stop + 1) {
+                HttpResponse::PartialContent()
+            } else {
+                HttpResponse::Ok()
+            };
+            response.append_header((http::header::CONTENT_TYPE, headers::APPLICATION_JSON))
+                    .append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                    .body(body)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
 #[allow(clippy::needless_pass_by_value)]
 async fn create_origin(req: HttpRequest,
                        body: Json<CreateOriginHandlerReq>,
@@ -210,12 +356,13 @@ async fn create_origin(req: HttpRequest,
 
     match Origin::create(&new_origin, &mut conn).map_err(Error::DieselError) {
         Ok(origin) => {
-            origin_audit(&body.0.name,
-                         OriginOperation::OriginCreate,
-                         &body.0.name,
-                         session.id() as i64,
-                         session.name(),
-                         &mut conn);
+            audit::record(&req,
+                          Some(&origin.name),
+                          AuditAction::OriginCreate,
+                          &origin.name,
+                          None,
+                          Some(json!({ "default_package_visibility": dpv })),
+                          &mut conn);
             HttpResponse::Created().json(origin)
         }
         Err(err) => {
@@ -233,8 +380,7 @@ async fn update_origin(req: HttpRequest,
                        -> HttpResponse {
     let origin = path.into_inner();
 
-    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator))
-    {
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
         return err.into();
     }
 
@@ -248,8 +394,22 @@ async fn update_origin(req: HttpRequest,
         None => PackageVisibility::Public,
     };
 
-    match Origin::update(&origin, dpv, &mut conn).map_err(Error::DieselError) {
-        Ok(_) => HttpResponse::NoContent().into(),
+    let before = match Origin::get(&origin, &mut conn).map_err(Error::DieselError) {
+        Ok(current) => current.default_package_visibility,
+        Err(err) => return err.into(),
+    };
+
+    match Origin::update(&origin, dpv.clone(), &mut conn).map_err(Error::DieselError) {
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::OriginUpdate,
+                          &origin,
+                          Some(json!({ "default_package_visibility": before })),
+                          Some(json!({ "default_package_visibility": dpv })),
+                          &mut conn);
+            HttpResponse::NoContent().into()
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -286,12 +446,13 @@ async fn delete_origin(req: HttpRequest,
         Ok(_) => {
             match Origin::delete(&origin, &mut conn).map_err(Error::DieselError) {
                 Ok(_) => {
-                    origin_audit(&origin,
-                                 OriginOperation::OriginDelete,
-                                 &origin,
-                                 session.id() as i64,
-                                 session.name(),
-                                 &mut conn);
+                    audit::record(&req,
+                                  Some(&origin),
+                                  AuditAction::OriginDelete,
+                                  &origin,
+                                  None,
+                                  None,
+                                  &mut conn);
                     HttpResponse::NoContent().into()
                 }
                 Err(err) => {
@@ -408,11 +569,10 @@ fn origin_delete_preflight(origin: &str, conn: &mut PgConnection) -> Result<()>
 async fn create_keys(req: HttpRequest, path: Path<String>, state: Data<AppState>) -> HttpResponse {
     let origin = path.into_inner();
 
-    let account_id =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator)) {
-            Ok(session) => session.id(),
-            Err(err) => return err.into(),
-        };
+    let account_id = match authorize_permission(&req, &origin, &Permission::Keys) {
+        Ok(session) => session.id(),
+        Err(err) => return err.into(),
+    };
 
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
@@ -452,6 +612,17 @@ async fn create_keys(req: HttpRequest, path: Path<String>, state: Data<AppState>
         return e.into();
     }
 
+    audit::record(&req,
+                  Some(&origin),
+                  AuditAction::KeyGenerate,
+                  &public.named_revision().to_string(),
+                  None,
+                  None,
+                  &mut conn);
+    notify_key_upload(&origin,
+                      "pair",
+                      public.named_revision().revision(),
+                      &mut conn);
     HttpResponse::Created().finish()
 }
 
@@ -516,18 +687,17 @@ async fn upload_origin_key(req: HttpRequest,
     } else {
         // In this case we are checking if the user actually has permissions to write a
         // NEW key into the origin_public_keys data table
-        let account_id =
-            match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator)) {
-                Ok(session) => session.id(),
-                Err(_) => {
-                    debug!("Unable to upload origin public signing key due to lack of permissions");
-                    let body = Bytes::from(format!("You do not have permissions to upload a new \
-                                                    origin signing public key: {}-{}",
-                                                   origin, revision).into_bytes());
-                    let body = BoxBody::new(body);
-                    return HttpResponse::with_body(StatusCode::FORBIDDEN, body);
-                }
-            };
+        let account_id = match authorize_permission(&req, &origin, &Permission::Keys) {
+            Ok(session) => session.id(),
+            Err(_) => {
+                debug!("Unable to upload origin public signing key due to lack of permissions");
+                let body = Bytes::from(format!("You do not have permissions to upload a new \
+                                                origin signing public key: {}-{}",
+                                               origin, revision).into_bytes());
+                let body = BoxBody::new(body);
+                return HttpResponse::with_body(StatusCode::FORBIDDEN, body);
+            }
+        };
         let key = match body.parse::<core_keys::PublicOriginSigningKey>() {
             Ok(key) => key,
             Err(e) => {
@@ -540,6 +710,17 @@ async fn upload_origin_key(req: HttpRequest,
 
         match save_public_origin_signing_key(account_id, &origin, &key, &mut conn) {
             Ok(_) => {
+                audit::record(&req,
+                              Some(&origin),
+                              AuditAction::PublicKeyUpload,
+                              &key.named_revision().to_string(),
+                              None,
+                              None,
+                              &mut conn);
+                notify_key_upload(&origin,
+                                  "public",
+                                  key.named_revision().revision(),
+                                  &mut conn);
                 HttpResponse::Created().append_header((http::header::LOCATION,
                                                        format!("{}", req.uri())))
                                        .body(format!("/origins/{}/keys/{}",
@@ -601,8 +782,7 @@ async fn list_origin_secrets(req: HttpRequest,
                              -> HttpResponse {
     let origin = path.into_inner();
 
-    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator))
-    {
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Secrets) {
         return err.into();
     }
 
@@ -634,11 +814,10 @@ async fn create_origin_secret(req: HttpRequest,
                               -> HttpResponse {
     let origin = path.into_inner();
 
-    let account_id =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator)) {
-            Ok(session) => session.id() as i64,
-            Err(err) => return err.into(),
-        };
+    let account_id = match authorize_permission(&req, &origin, &Permission::Secrets) {
+        Ok(session) => session.id() as i64,
+        Err(err) => return err.into(),
+    };
 
     if body.name.is_empty() {
         let body = Bytes::from_static(b"Missing value for field `name`");
@@ -700,7 +879,16 @@ async fn create_origin_secret(req: HttpRequest,
                                                   owner_id: account_id, },
                                &mut conn).map_err(Error::DieselError)
     {
-        Ok(_) => HttpResponse::Created().finish(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::SecretCreate,
+                          &body.name,
+                          None,
+                          None,
+                          &mut conn);
+            HttpResponse::Created().finish()
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -715,8 +903,7 @@ async fn delete_origin_secret(req: HttpRequest,
                               -> HttpResponse {
     let (origin, secret) = path.into_inner();
 
-    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator))
-    {
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Secrets) {
         return err.into();
     }
 
@@ -726,7 +913,18 @@ async fn delete_origin_secret(req: HttpRequest,
     };
 
     match OriginSecret::delete(&origin, &secret, &mut conn).map_err(Error::DieselError) {
-        Ok(_) => HttpResponse::Ok().finish(),
+        Ok(deleted) => {
+            if deleted > 0 {
+                audit::record(&req,
+                              Some(&origin),
+                              AuditAction::SecretDelete,
+                              &secret,
+                              None,
+                              None,
+                              &mut conn);
+            }
+            HttpResponse::Ok().finish()
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -742,11 +940,10 @@ async fn upload_origin_secret_key(req: HttpRequest,
                                   -> HttpResponse {
     let (origin, _revision) = path.into_inner();
 
-    let account_id =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator)) {
-            Ok(session) => session.id(),
-            Err(err) => return err.into(),
-        };
+    let account_id = match authorize_permission(&req, &origin, &Permission::Keys) {
+        Ok(session) => session.id(),
+        Err(err) => return err.into(),
+    };
 
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
@@ -783,6 +980,17 @@ async fn upload_origin_secret_key(req: HttpRequest,
         return e.into();
     }
 
+    audit::record(&req,
+                  Some(&origin),
+                  AuditAction::SecretKeyUpload,
+                  &key.named_revision().to_string(),
+                  None,
+                  None,
+                  &mut conn);
+    notify_key_upload(&origin,
+                      "secret",
+                      key.named_revision().revision(),
+                      &mut conn);
     HttpResponse::Created().finish()
 }
 
@@ -907,11 +1115,10 @@ async fn invite_to_origin(req: HttpRequest,
                           -> HttpResponse {
     let (origin, user) = path.into_inner();
 
-    let account_id =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
-            Ok(session) => session.id(),
-            Err(err) => return err.into(),
-        };
+    let account_id = match authorize_permission(&req, &origin, &Permission::Invite) {
+        Ok(session) => session.id(),
+        Err(err) => return err.into(),
+    };
 
     debug!("Creating invitation for user {} origin {}", &user, &origin);
 
@@ -920,14 +1127,20 @@ async fn invite_to_origin(req: HttpRequest,
         Err(err) => return err.into(),
     };
 
-    let (recipient_id, recipient_name) =
-        match Account::get(&user, &mut conn).map_err(Error::DieselError) {
-            Ok(account) => (account.id, account.name),
-            Err(err) => {
-                debug!("{}", err);
-                return err.into();
-            }
-        };
+    let recipient = match Account::get(&user, &mut conn).map_err(Error::DieselError) {
+        Ok(account) => account,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    // Robot accounts only ever belong to the origin they were created in
+    if recipient.is_robot() {
+        let body = Bytes::from_static(b"Robot accounts cannot be invited to origins");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+    let (recipient_id, recipient_name) = (recipient.id, recipient.name);
 
     let new_invitation = NewOriginInvitation { origin:       &origin,
                                                account_id:   recipient_id,
@@ -936,7 +1149,16 @@ async fn invite_to_origin(req: HttpRequest,
 
     // store invitations in the originsrv
     match OriginInvitation::create(&new_invitation, &mut conn).map_err(Error::DieselError) {
-        Ok(invitation) => HttpResponse::Created().json(&invitation),
+        Ok(invitation) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::MemberInvite,
+                          &recipient_name,
+                          None,
+                          None,
+                          &mut conn);
+            HttpResponse::Created().json(&invitation)
+        }
         // TODO (SA): Check for error case where invitation already exists
         Err(err) => {
             debug!("{}", err);
@@ -952,10 +1174,11 @@ async fn accept_invitation(req: HttpRequest,
                            -> HttpResponse {
     let (origin, invitation) = path.into_inner();
 
-    let account_id = match authorize_session(&req, None, None) {
-        Ok(session) => session.id(),
+    let session = match authorize_session(&req, None, None) {
+        Ok(session) => session,
         Err(err) => return err.into(),
     };
+    let account_id = session.id();
 
     let invitation_id = match invitation.parse::<u64>() {
         Ok(invitation_id) => invitation_id,
@@ -974,7 +1197,17 @@ async fn accept_invitation(req: HttpRequest,
     };
 
     match OriginInvitation::accept(invitation_id, false, &mut conn).map_err(Error::DieselError) {
-        Ok(_) => HttpResponse::NoContent().finish(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::InvitationAccept,
+                          &invitation,
+                          None,
+                          None,
+                          &mut conn);
+            notify_member_change(&origin, "added", session.name(), None, &mut conn);
+            HttpResponse::NoContent().finish()
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -1012,7 +1245,16 @@ async fn ignore_invitation(req: HttpRequest,
            invitation_id, &origin);
 
     match OriginInvitation::ignore(invitation_id, &mut conn).map_err(Error::DieselError) {
-        Ok(_) => HttpResponse::NoContent().finish(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::InvitationIgnore,
+                          &invitation,
+                          None,
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -1050,7 +1292,16 @@ async fn rescind_invitation(req: HttpRequest,
     };
 
     match OriginInvitation::rescind(invitation_id, &mut conn).map_err(Error::DieselError) {
-        Ok(_) => HttpResponse::NoContent().finish(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::InvitationRescind,
+                          &invitation,
+                          None,
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -1154,11 +1405,10 @@ async fn update_origin_member_role(req: HttpRequest,
     };
 
     // Account id of the user making the request
-    let account_id =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator)) {
-            Ok(session) => session.id(),
-            Err(err) => return err.into(),
-        };
+    let account_id = match authorize_role_grant(&req, &origin, target_role) {
+        Ok(session) => session.id(),
+        Err(err) => return err.into(),
+    };
 
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
@@ -1171,7 +1421,7 @@ async fn update_origin_member_role(req: HttpRequest,
     }
 
     // The account id of the user being requested
-    let (target_user_id, _) = match Account::get(&username, &mut conn) {
+    let (target_user_id, target_user_name) = match Account::get(&username, &mut conn) {
         Ok(account) => (account.id, account.name),
         Err(err) => {
             debug!("{}", err);
@@ -1189,13 +1439,38 @@ async fn update_origin_member_role(req: HttpRequest,
         return HttpResponse::new(StatusCode::FORBIDDEN);
     }
 
+    if OriginMember::is_idp_managed(&origin, target_user_id, &mut conn).unwrap_or(false) {
+        return idp_managed_response();
+    }
+
+    let current_role = OriginMember::member_role(&origin, target_user_id, &mut conn).ok();
+    if let Some(current_role) = current_role {
+        if let Err(err) = authorize_role_grant(&req, &origin, current_role) {
+            return err.into();
+        }
+    }
+
     state.memcache
          .borrow_mut()
          .clear_cache_for_member_role(&origin, target_user_id as u64);
 
     match OriginMember::update_member_role(&origin, target_user_id, &mut conn, target_role) {
         Ok(0) => HttpResponse::NotFound().into(),
-        Ok(_) => HttpResponse::NoContent().into(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::MemberRoleUpdate,
+                          &target_user_name,
+                          current_role.map(|role| json!({ "role": role })),
+                          Some(json!({ "role": target_role })),
+                          &mut conn);
+            notify_member_change(&origin,
+                                 "role_changed",
+                                 &target_user_name,
+                                 Some(target_role),
+                                 &mut conn);
+            HttpResponse::NoContent().into()
+        }
         Err(err) => {
             debug!("{}", err);
             Error::DieselError(err).into()
@@ -1232,7 +1507,7 @@ async fn transfer_origin_ownership(req: HttpRequest,
         Err(err) => return err.into(),
     };
 
-    let (recipient_id, _recipient_name) =
+    let (recipient_id, recipient_name) =
         match Account::get(&user, &mut conn).map_err(Error::DieselError) {
             Ok(account) => (account.id, account.name),
             Err(err) => {
@@ -1248,12 +1523,18 @@ async fn transfer_origin_ownership(req: HttpRequest,
 
     match Origin::transfer(&origin, recipient_id, &mut conn).map_err(Error::DieselError) {
         Ok(_) => {
-            origin_audit(&origin,
-                         OriginOperation::OwnerTransfer,
-                         &recipient_id.to_string(),
-                         session.id() as i64,
-                         session.name(),
-                         &mut conn);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::OwnerTransfer,
+                          &recipient_name,
+                          Some(json!({ "owner": session.name() })),
+                          Some(json!({ "owner": recipient_name })),
+                          &mut conn);
+            notify_member_change(&origin,
+                                 "owner_transferred",
+                                 &recipient_name,
+                                 Some(OriginMemberRole::Owner),
+                                 &mut conn);
             HttpResponse::NoContent().finish()
         }
         Err(err) => {
@@ -1296,7 +1577,17 @@ async fn depart_from_origin(req: HttpRequest,
     debug!("Departing user {} from origin {}", session.name(), &origin);
 
     match Origin::depart(&origin, session.id() as i64, &mut conn).map_err(Error::DieselError) {
-        Ok(_) => HttpResponse::NoContent().finish(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::MemberDepart,
+                          session.name(),
+                          None,
+                          None,
+                          &mut conn);
+            notify_member_change(&origin, "departed", session.name(), None, &mut conn);
+            HttpResponse::NoContent().finish()
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -1320,11 +1611,29 @@ async fn list_origin_members(req: HttpRequest,
         Err(err) => return err.into(),
     };
 
+    // Robot accounts are members too, and are marked as such
+    let robots = match Account::list_robots(&origin, &mut conn).map_err(Error::DieselError) {
+        Ok(robots) => robots.into_iter().map(|robot| robot.name).collect::<Vec<_>>(),
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    // As are the members whose identity provider's groups grant their membership
+    let idp_managed = match OriginMember::list_idp_managed(&origin, &mut conn) {
+        Ok(members) => members,
+        Err(err) => {
+            debug!("{}", err);
+            return Error::DieselError(err).into();
+        }
+    };
+
     match OriginMember::list(&origin, &mut conn).map_err(Error::DieselError) {
         Ok(users) => {
             let json = json!({
                 "origin": &origin,
-                "members": serde_json::to_value(users).unwrap()
+                "members": origin_member_entries(users, &robots, &idp_managed)
             });
 
             HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
@@ -1337,6 +1646,19 @@ async fn list_origin_members(req: HttpRequest,
     }
 }
 
+fn origin_member_entries(names: Vec<String>,
+                         robots: &[String],
+                         idp_managed: &[String])
+                         -> Vec<OriginMemberEntry> {
+    names.into_iter()
+         .map(|name| {
+             OriginMemberEntry { robot: robots.contains(&name),
+                                 idp_managed: idp_managed.contains(&name),
+                                 name }
+         })
+         .collect()
+}
+
 #[allow(clippy::needless_pass_by_value)]
 async fn origin_member_delete(req: HttpRequest,
                               path: Path<(String, String)>,
@@ -1344,11 +1666,10 @@ async fn origin_member_delete(req: HttpRequest,
                               -> HttpResponse {
     let (origin, user) = path.into_inner();
 
-    let session =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator)) {
-            Ok(session) => session,
-            Err(err) => return err.into(),
-        };
+    let session = match authorize_permission(&req, &origin, &Permission::Members) {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
 
     if !check_origin_owner(&req, session.id(), &origin).unwrap_or(false) {
         return HttpResponse::new(StatusCode::FORBIDDEN);
@@ -1376,11 +1697,25 @@ async fn origin_member_delete(req: HttpRequest,
             }
         };
 
+    if OriginMember::is_idp_managed(&origin, target_account_id, &mut conn).unwrap_or(false) {
+        return idp_managed_response();
+    }
+
+    let current_role = OriginMember::member_role(&origin, target_account_id, &mut conn).ok();
+
     match OriginMember::delete(&origin, &user, &mut conn).map_err(Error::DieselError) {
         Ok(_) => {
             state.memcache
                  .borrow_mut()
                  .clear_cache_for_member_role(&origin, target_account_id as u64);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::MemberRemove,
+                          &user,
+                          current_role.map(|role| json!({ "role": role })),
+                          None,
+                          &mut conn);
+            notify_member_change(&origin, "removed", &user, None, &mut conn);
             HttpResponse::NoContent().finish()
         }
         Err(err) => {
@@ -1491,7 +1826,16 @@ async fn create_origin_integration(req: HttpRequest,
                                      body:        &encrypted, };
 
     match OriginIntegration::create(&noi, &mut conn).map_err(Error::DieselError) {
-        Ok(_) => HttpResponse::Created().finish(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::IntegrationCreate,
+                          &format!("{}/{}", integration, name),
+                          None,
+                          None,
+                          &mut conn);
+            HttpResponse::Created().finish()
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -1518,7 +1862,16 @@ async fn delete_origin_integration(req: HttpRequest,
     match OriginIntegration::delete(&origin, &integration, &name, &mut conn)
         .map_err(Error::DieselError)
     {
-        Ok(_) => HttpResponse::NoContent().finish(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::IntegrationDelete,
+                          &format!("{}/{}", integration, name),
+                          None,
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -1583,6 +1936,33 @@ async fn get_origin_integration(req: HttpRequest,
 // Internal helpers
 //
 
+// Memberships granted by an identity provider's groups follow the groups, and would be put back
+// as they were the next time the member signs in
+fn idp_managed_response() -> HttpResponse {
+    let body = Bytes::from_static(b"The membership is managed by the groups of the identity \
+                                    provider");
+    HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body))
+}
+
+// Tells the origin's webhooks about a change to its membership
+pub(crate) fn notify_member_change(origin: &str,
+                                   action: &str,
+                                   member: &str,
+                                   role: Option<OriginMemberRole>,
+                                   conn: &mut PgConnection) {
+    webhooks::notify(origin,
+                     WebhookEvent::MemberChange,
+                     json!({ "action": action, "member": member, "role": role }),
+                     conn);
+}
+
+fn notify_key_upload(origin: &str, key_type: &str, revision: &str, conn: &mut PgConnection) {
+    webhooks::notify(origin,
+                     WebhookEvent::KeyUpload,
+                     json!({ "key_type": key_type, "revision": revision }),
+                     conn);
+}
+
 /// Return a Habitat key as a file.
 ///
 /// This is essentially doing what an `actix_web::Responder`
@@ -1760,3 +2140,35 @@ fn get_latest_secret_origin_signing_key(origin: &str,
 
     Ok(key)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> { names.iter().map(|name| name.to_string()).collect() }
+
+    #[test]
+    fn origin_members_are_marked_inline() {
+        let entries = origin_member_entries(names(&["bobo", "core.release", "wesker"]),
+                                            &names(&["core.release"]),
+                                            &names(&["wesker"]));
+        assert_eq!(entries,
+                   vec![OriginMemberEntry { name:        "bobo".to_string(),
+                                            robot:       false,
+                                            idp_managed: false, },
+                        OriginMemberEntry { name:        "core.release".to_string(),
+                                            robot:       true,
+                                            idp_managed: false, },
+                        OriginMemberEntry { name:        "wesker".to_string(),
+                                            robot:       false,
+                                            idp_managed: true, }]);
+    }
+
+    #[test]
+    fn origin_member_entries_serialize_by_name() {
+        let robots = names(&["core.release"]);
+        let entries = origin_member_entries(names(&["core.release"]), &robots, &[]);
+        assert_eq!(serde_json::to_value(entries).unwrap(),
+                   json!([{ "name": "core.release", "robot": true, "idp_managed": false }]));
+    }
+}
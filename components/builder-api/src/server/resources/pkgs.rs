@@ -14,8 +14,12 @@
 
 use super::reverse_dependencies::{self};
 use crate::{bldr_core::metrics::CounterMetric,
-            db::models::{channel::{Channel,
+            db::models::{audit::AuditAction,
+                         channel::{Channel,
                                    ChannelWithPromotion},
+                         download_stats::{ListDownloadStats,
+                                          NewPackageDownload,
+                                          PackageDownloadStat},
                          license_keys::*,
                          origin::*,
                          package::{BuilderPackageIdent,
@@ -28,17 +32,21 @@ use crate::{bldr_core::metrics::CounterMetric,
                                    Package,
                                    PackageIdentWithChannelPlatform,
                                    PackageVisibility,
-                                   SearchPackages},
+                                   SearchPackages,
+                                   SearchPackagesOrder},
+                         role::Permission,
                          settings::{GetOriginPackageSettings,
                                     NewOriginPackageSettings,
-                                    OriginPackageSettings}},
+                                    OriginPackageSettings},
+                         webhook::WebhookEvent},
             hab_core::{package::{FromArchive,
                                  Identifiable,
                                  PackageArchive,
                                  PackageIdent,
                                  PackageTarget},
                        ChannelIdent},
-            server::{authorize::authorize_session,
+            server::{authorize::{authorize_permission,
+                                 authorize_session},
                      error::{Error,
                              Result},
                      feat,
@@ -47,9 +55,13 @@ use crate::{bldr_core::metrics::CounterMetric,
                                fetch_license_expiration,
                                req_state,
                                Pagination,
+                               SearchSort,
+                               StatsDateRange,
                                Target},
                      resources::channels::channels_for_package_ident,
-                     services::metrics::Counter,
+                     services::{audit,
+                                metrics::Counter,
+                                webhooks},
                      AppState}};
 use actix_web::{body::BoxBody,
                 http::{self,
@@ -111,6 +123,8 @@ impl Packages {
                   web::get().to(get_latest_package_for_origin_package))
            .route("/depot/pkgs/{origin}/{pkg}/versions",
                   web::get().to(list_package_versions))
+           .route("/depot/pkgs/{origin}/{pkg}/stats",
+                  web::get().to(get_package_download_stats))
            .route("/depot/pkgs/{origin}/{pkg}/{version}",
                   web::get().to(get_packages_for_origin_package_version))
            .route("/depot/pkgs/{origin}/{pkg}/{version}/latest",
@@ -272,7 +286,7 @@ async fn delete_package(req: HttpRequest,
                         -> HttpResponse {
     let (origin, pkg, version, release) = path.into_inner();
 
-    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Delete) {
         return err.into();
     }
 
@@ -406,6 +420,19 @@ async fn delete_package(req: HttpRequest,
         return err.into();
     }
 
+    audit::record(&req,
+                  Some(&origin),
+                  AuditAction::PackageDelete,
+                  &ident.to_string(),
+                  Some(json!({ "target": target.to_string(),
+                               "checksum": pkg.checksum })),
+                  None,
+                  &mut conn);
+    webhooks::notify(&origin,
+                     WebhookEvent::PackageDelete,
+                     json!({ "ident": ident.to_string(), "target": target.to_string() }),
+                     &mut conn);
+
     state.memcache.borrow_mut().clear_cache_for_package(&ident);
     HttpResponse::NoContent().finish()
 }
@@ -560,6 +587,7 @@ async fn download_package(req: HttpRequest,
                            .await
                 {
                     Ok(archive) => {
+                        record_package_download(&state, &package);
                         download_response_for_archive(&archive, &file_path, is_private, &state)
                     }
                     Err(e) => {
@@ -574,6 +602,7 @@ async fn download_package(req: HttpRequest,
                            .await
                 {
                     Ok(archive) => {
+                        record_package_download(&state, &package);
                         download_response_for_archive(&archive, &file_path, is_private, &state)
                     }
                     Err(e) => {
@@ -729,16 +758,81 @@ async fn list_package_versions(req: HttpRequest,
     }
 }
 
+#[allow(clippy::needless_pass_by_value)]
+async fn get_package_download_stats(req: HttpRequest,
+                                    path: Path<(String, String)>,
+                                    range: Query<StatsDateRange>,
+                                    state: Data<AppState>)
+                                    -> HttpResponse {
+    let (origin, name) = path.into_inner();
+
+    let opt_session_id = match authorize_session(&req, None, None) {
+        Ok(session) => Some(session.id()),
+        Err(_) => None,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let (from_date, to_date) = helpers::extract_stats_range(&range);
+    if from_date > to_date {
+        return HttpResponse::new(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let visibility = helpers::visibility_for_optional_session(&req, opt_session_id, &origin);
+    let lds = ListDownloadStats { origin: &origin,
+                                  from_date,
+                                  to_date,
+                                  visibility };
+
+    match PackageDownloadStat::list_for_package(&name, &lds, &mut conn) {
+        Ok(downloads) => {
+            let total: i64 = downloads.iter().map(|d| d.downloads).sum();
+            let body = json!({
+                "origin": origin,
+                "name": name,
+                "from_date": from_date,
+                "to_date": to_date,
+                "total": total,
+                "downloads": downloads,
+            });
+
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(body)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
 #[allow(clippy::needless_pass_by_value)]
 async fn search_packages(req: HttpRequest,
                          path: Path<String>,
                          pagination: Query<Pagination>,
+                         sort: Query<SearchSort>,
                          state: Data<AppState>)
                          -> HttpResponse {
     Counter::SearchPackages.increment();
 
     let query = path.into_inner();
 
+    let order = if sort.sort.is_empty() {
+        SearchPackagesOrder::default()
+    } else {
+        match SearchPackagesOrder::from_str(&sort.sort) {
+            Ok(order) => order,
+            Err(_) => {
+                let body = Bytes::from(format!("Invalid sort order '{}'", sort.sort).into_bytes());
+                return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                               BoxBody::new(body));
+            }
+        }
+    };
+
     let opt_session_id = match authorize_session(&req, None, None) {
         Ok(session) => Some(session.id() as i64),
         Err(_) => None,
@@ -775,7 +869,8 @@ async fn search_packages(req: HttpRequest,
     let search_packages = SearchPackages { query:      decoded_query,
                                            page:       page as i64,
                                            limit:      per_page as i64,
-                                           account_id: opt_session_id, };
+                                           account_id: opt_session_id,
+                                           order, };
 
     if pagination.distinct {
         return match Package::search_distinct(&search_packages, &mut conn) {
@@ -821,7 +916,7 @@ async fn package_privacy_toggle(req: HttpRequest,
         }
     };
 
-    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Visibility) {
         return err.into();
     }
 
@@ -836,8 +931,21 @@ async fn package_privacy_toggle(req: HttpRequest,
         return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
     }
 
+    let previous = Package::get_without_target(BuilderPackageIdent(ident.clone()),
+                                               PackageVisibility::all(),
+                                               &mut conn).ok()
+                                                         .map(|pkg| pkg.visibility);
+    let after = json!({ "visibility": pv });
+
     match Package::update_visibility(pv, BuilderPackageIdent(ident.clone()), &mut conn) {
         Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::PackageVisibilityUpdate,
+                          &ident.to_string(),
+                          previous.map(|visibility| json!({ "visibility": visibility })),
+                          Some(after),
+                          &mut conn);
             trace!("Clearing cache for {}", ident);
             state.memcache.borrow_mut().clear_cache_for_package(&ident);
             HttpResponse::Ok().finish()
@@ -976,7 +1084,7 @@ fn do_upload_package_start(req: &HttpRequest,
                            qupload: &Query<Upload>,
                            ident: &PackageIdent)
                            -> Result<(PathBuf, BufWriter<File>)> {
-    authorize_session(req, Some(&ident.origin), Some(OriginMemberRole::Member))?;
+    authorize_permission(req, &ident.origin, &Permission::Upload)?;
 
     let mut conn = req_state(req).db.get_conn().map_err(Error::DbError)?;
 
@@ -1249,7 +1357,23 @@ async fn do_upload_package_finish(req: &HttpRequest,
 
     // Re-create origin package as needed (eg, checksum update)
     match Package::create(&package, &mut conn) {
-        Ok(_) => {}
+        Ok(created) => {
+            audit::record(req,
+                          Some(&created.origin),
+                          AuditAction::PackageUpload,
+                          &created.ident.to_string(),
+                          None,
+                          Some(json!({ "target": created.target.to_string(),
+                                       "checksum": created.checksum,
+                                       "visibility": created.visibility })),
+                          &mut conn);
+            webhooks::notify(&created.origin,
+                             WebhookEvent::PackageUpload,
+                             json!({ "ident": created.ident.to_string(),
+                                     "target": created.target.to_string(),
+                                     "visibility": created.visibility }),
+                             &mut conn);
+        }
         Err(NotFound) => {
             debug!("Package::create returned NotFound (DB conflict handled)");
         }
@@ -1458,6 +1582,40 @@ fn archive_name(ident: &PackageIdent, target: PackageTarget) -> PathBuf {
                                                         }))
 }
 
+// Download counts are written off the request path so that a slow or unavailable database
+// never holds up the archive itself.
+fn record_package_download(state: &AppState, package: &Package) {
+    let db = state.db.clone();
+    let package_id = package.id;
+    let origin = package.origin.clone();
+    let name = package.name.clone();
+    let target = package.target.to_string();
+
+    actix_rt::spawn(async move {
+        let result = web::block(move || {
+                         let mut conn = db.get_conn().map_err(Error::DbError)?;
+                         let download = NewPackageDownload { package_id,
+                                                             origin: &origin,
+                                                             name: &name,
+                                                             target: &target };
+                         PackageDownloadStat::record(&download, &mut conn)
+                             .map_err(Error::DieselError)
+                     }).await;
+
+        match result {
+            Ok(Ok(_)) => (),
+            Ok(Err(err)) => {
+                warn!("Unable to record download for package {}, err={}",
+                      package_id, err)
+            }
+            Err(err) => {
+                warn!("Unable to record download for package {}, err={}",
+                      package_id, err)
+            }
+        }
+    });
+}
+
 fn download_response_for_archive(archive: &PackageArchive,
                                  file_path: &path::Path,
                                  is_private: bool,
@@ -1,5 +1,6 @@
 use crate::{bldr_core,
             db::models::{account::*,
+                         audit::AuditAction,
                          license_keys::*},
             protocol::originsrv,
             server::{authorize::authorize_session,
@@ -8,6 +9,7 @@ use crate::{bldr_core,
                      framework::headers,
                      helpers::{fetch_license_expiration,
                                req_state},
+                     services::audit,
                      AppState}};
 use actix_web::{body::BoxBody,
                 http::{self,
@@ -17,11 +19,18 @@ use actix_web::{body::BoxBody,
                       Json,
                       Path,
                       ServiceConfig},
-                HttpMessage,
                 HttpRequest,
                 HttpResponse};
-use bldr_core::access_token::AccessToken as CoreAccessToken;
+use bldr_core::{access_token::AccessToken as CoreAccessToken,
+                privilege::{FeatureFlags,
+                            TokenCapabilities}};
 use bytes::Bytes;
+use chrono::{DateTime,
+             Duration,
+             Utc};
+
+const MAX_ACCESS_TOKENS: usize = 50;
+const MAX_TOKEN_NAME_LENGTH: usize = 64;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UserUpdateReq {
@@ -29,6 +38,34 @@ pub struct UserUpdateReq {
     pub email: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AccessTokenReq {
+    #[serde(default)]
+    pub name:       Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub scope:      Option<TokenScopeReq>,
+}
+
+/// Limits a token to some origins and operations. Channels only restrict promote.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenScopeReq {
+    #[serde(default)]
+    pub origins:      Vec<String>,
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub channels:     Vec<String>,
+}
+
+// A newly created token is the only time its value is returned
+#[derive(Serialize)]
+struct AccountTokenWithValue<'a> {
+    #[serde(flatten)]
+    account_token: &'a AccountToken,
+    token:         String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LicensePayload {
     pub account_id:  String,
@@ -101,12 +138,66 @@ async fn get_access_tokens(req: HttpRequest) -> HttpResponse {
 }
 
 #[allow(clippy::needless_pass_by_value)]
-async fn generate_access_token(req: HttpRequest, state: Data<AppState>) -> HttpResponse {
-    let account_id = match authorize_session(&req, None, None) {
-        Ok(session) => session.id(),
+async fn generate_access_token(req: HttpRequest,
+                               body: Option<Json<AccessTokenReq>>,
+                               state: Data<AppState>)
+                               -> HttpResponse {
+    let session = match authorize_session(&req, None, None) {
+        Ok(session) => session,
         Err(err) => return err.into(),
     };
 
+    let body = body.map(Json::into_inner);
+    generate_account_token(&req, session.id(), session.flags(), None, body, &state)
+}
+
+/// Generates a token for an account, which is either the requester's own or, when an origin is
+/// given, one of the origin's robot accounts.
+pub fn generate_account_token(req: &HttpRequest,
+                              account_id: u64,
+                              flags: u32,
+                              origin: Option<&str>,
+                              body: Option<AccessTokenReq>,
+                              state: &AppState)
+                              -> HttpResponse {
+    let (name, expires_at, scope) = match body {
+        Some(body) => (body.name, body.expires_at, body.scope),
+        None => (None, None, None),
+    };
+
+    let name = match token_name(name) {
+        Ok(name) => name,
+        Err(resp) => return resp,
+    };
+
+    if matches!(expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+        let body = Bytes::from_static(b"Token expiry must be in the future");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    // With a maximum lifetime configured, tokens created without an expiry get the longest one
+    let expires_at = match state.config.api.max_token_lifetime_days {
+        Some(days) => {
+            let latest = Utc::now() + Duration::days(i64::from(days));
+            match expires_at {
+                Some(expires_at) if expires_at > latest => {
+                    let body =
+                        Bytes::from(format!("Tokens may be valid for at most {} days", days));
+                    return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                                   BoxBody::new(body));
+                }
+                Some(expires_at) => Some(expires_at),
+                None => Some(latest),
+            }
+        }
+        None => expires_at,
+    };
+
+    let scope = match scope.map(token_scope).transpose() {
+        Ok(scope) => scope,
+        Err(resp) => return resp,
+    };
+
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
         Err(err) => return err.into(),
@@ -121,13 +212,24 @@ async fn generate_access_token(req: HttpRequest, state: Data<AppState>) -> HttpR
         }
     };
 
-    let flags = {
-        let extension = req.extensions();
-        let session = extension.get::<originsrv::Session>().unwrap();
-        session.flags()
-    };
+    if let Err(resp) = check_token_limit(access_tokens.len()) {
+        return resp;
+    }
 
-    let token = match CoreAccessToken::user_token(&state.config.api.key_path, account_id, flags) {
+    let key_path = &state.config.api.key_path;
+    let token = match scope {
+        // Scoped tokens never carry the account's administrative privileges
+        Some((ref scope, _)) => {
+            let flags = flags & !FeatureFlags::ADMIN.bits();
+            CoreAccessToken::scoped_user_token(key_path,
+                                               account_id,
+                                               flags,
+                                               expires_at,
+                                               scope.clone())
+        }
+        None => CoreAccessToken::user_token(key_path, account_id, flags, expires_at),
+    };
+    let token = match token {
         Ok(token) => token.to_string(),
         Err(err) => {
             debug!("{}", err);
@@ -136,15 +238,25 @@ async fn generate_access_token(req: HttpRequest, state: Data<AppState>) -> HttpR
     };
 
     let new_token = NewAccountToken { account_id: account_id as i64,
-                                      token:      &token, };
+                                      token:      &token,
+                                      name:       &name,
+                                      expires_at: expires_at.map(|e| e.naive_utc()),
+                                      scope:      scope.map(|(_, json)| json), };
 
+    // Other tokens of the account stay valid, so there are no sessions to clear
     match AccountToken::create(&new_token, &mut conn).map_err(Error::DieselError) {
         Ok(account_token) => {
-            let mut memcache = state.memcache.borrow_mut();
-            for token in access_tokens {
-                memcache.delete_session_key(&token.token)
-            }
-            HttpResponse::Ok().json(account_token)
+            audit::record(req,
+                          origin,
+                          AuditAction::TokenCreate,
+                          &account_token.id.to_string(),
+                          None,
+                          Some(json!({ "name": account_token.name,
+                                       "expires_at": account_token.expires_at,
+                                       "scope": account_token.scope })),
+                          &mut conn);
+            HttpResponse::Ok().json(AccountTokenWithValue { account_token: &account_token,
+                                                            token })
         }
         Err(err) => {
             debug!("{}", err);
@@ -153,6 +265,60 @@ async fn generate_access_token(req: HttpRequest, state: Data<AppState>) -> HttpR
     }
 }
 
+// Tokens created without a name, as older clients do, are named after when they were made
+fn token_name(name: Option<String>) -> std::result::Result<String, HttpResponse> {
+    let name = match name {
+        Some(name) => name.trim().to_string(),
+        None => Utc::now().format("token-%Y%m%d%H%M%S").to_string(),
+    };
+    if name.is_empty() || name.len() > MAX_TOKEN_NAME_LENGTH {
+        let body = Bytes::from(format!("Token name must be between 1 and {} characters",
+                                       MAX_TOKEN_NAME_LENGTH));
+        return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body)));
+    }
+    Ok(name)
+}
+
+fn check_token_limit(held: usize) -> std::result::Result<(), HttpResponse> {
+    if held >= MAX_ACCESS_TOKENS {
+        let body = Bytes::from(format!("Accounts may hold at most {} access tokens. Revoke one \
+                                        that is no longer used first.",
+                                       MAX_ACCESS_TOKENS));
+        return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body)));
+    }
+    Ok(())
+}
+
+// Validates a requested scope, giving both the scope to embed in the token and how it is shown
+// in the token listing.
+fn token_scope(req: TokenScopeReq)
+               -> std::result::Result<(originsrv::TokenScope, serde_json::Value), HttpResponse> {
+    let capabilities = match TokenCapabilities::from_names(&req.capabilities) {
+        Some(capabilities) if !capabilities.is_empty() => capabilities,
+        _ => {
+            let body = Bytes::from_static(b"Scoped tokens need at least one of the capabilities \
+                                            download, upload, promote and keys");
+            return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                               BoxBody::new(body)));
+        }
+    };
+
+    if req.origins.iter().chain(req.channels.iter()).any(|n| n.trim().is_empty()) {
+        let body = Bytes::from_static(b"Token scope origins and channels cannot be empty");
+        return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body)));
+    }
+
+    let mut scope = originsrv::TokenScope::new();
+    scope.set_capabilities(capabilities.bits());
+    scope.origins = req.origins.clone();
+    scope.channels = req.channels.clone();
+
+    let json = json!({ "origins": req.origins,
+                       "capabilities": capabilities.names(),
+                       "channels": req.channels });
+    Ok((scope, json))
+}
+
 #[allow(clippy::needless_pass_by_value)]
 async fn revoke_access_token(req: HttpRequest,
                              path: Path<String>,
@@ -172,6 +338,17 @@ async fn revoke_access_token(req: HttpRequest,
         Err(err) => return err.into(),
     };
 
+    revoke_account_token(&req, account_id, token_id, None, &state)
+}
+
+/// Revokes one of an account's tokens, which belongs to either the requester or, when an origin
+/// is given, one of the origin's robot accounts.
+pub fn revoke_account_token(req: &HttpRequest,
+                            account_id: u64,
+                            token_id: u64,
+                            origin: Option<&str>,
+                            state: &AppState)
+                            -> HttpResponse {
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
         Err(err) => return err.into(),
@@ -186,20 +363,29 @@ async fn revoke_access_token(req: HttpRequest,
         }
     };
 
-    let valid_token = access_tokens.iter()
-                                   .find(|token| token.id == token_id as i64);
-
-    if valid_token.is_none() {
-        let body = Bytes::from_static(b"Unauthorized access.");
-        return HttpResponse::with_body(StatusCode::UNAUTHORIZED, BoxBody::new(body));
-    }
+    let revoked = match access_tokens.iter()
+                                     .find(|token| token.id == token_id as i64)
+    {
+        Some(token) => token,
+        None => {
+            let body = Bytes::from_static(b"Unauthorized access.");
+            return HttpResponse::with_body(StatusCode::UNAUTHORIZED, BoxBody::new(body));
+        }
+    };
 
     match AccountToken::delete(token_id, &mut conn).map_err(Error::DieselError) {
         Ok(_) => {
-            let mut memcache = state.memcache.borrow_mut();
-            for token in access_tokens {
-                memcache.delete_session_key(&token.token)
-            }
+            audit::record(req,
+                          origin,
+                          AuditAction::TokenRevoke,
+                          &token_id.to_string(),
+                          Some(json!({ "name": revoked.name })),
+                          None,
+                          &mut conn);
+            // Only the revoked token's session goes, the account's other tokens keep working
+            state.memcache
+                 .borrow_mut()
+                 .delete_session_key(&revoked.token);
             HttpResponse::Ok().finish()
         }
         Err(err) => {
@@ -235,9 +421,21 @@ async fn set_license(req: HttpRequest,
                 NewLicenseKey { account_id: payload.account_id.trim().parse::<i64>().unwrap(),
                                 license_key: &payload.license_key,
                                 expiration_date };
+            let before = LicenseKey::get_by_account_id(new_license.account_id, &mut conn)
+                .ok()
+                .flatten()
+                .map(|license| json!({ "expiration_date": license.expiration_date.to_string() }));
 
             match LicenseKey::create(&new_license, &mut conn).map_err(Error::DieselError) {
                 Ok(license) => {
+                    audit::record(&req,
+                                  None,
+                                  AuditAction::LicenseUpdate,
+                                  &new_license.account_id.to_string(),
+                                  before,
+                                  Some(json!({ "expiration_date":
+                                                   license.expiration_date.to_string() })),
+                                  &mut conn);
                     HttpResponse::Ok().json(json!({
                               "expiration_date": license.expiration_date.to_string()
                           }))
@@ -324,11 +522,95 @@ async fn update_account(req: HttpRequest,
         Err(err) => return err.into(),
     };
 
+    let before = Account::get_by_id(account_id as i64, &mut conn)
+        .ok()
+        .map(|account| json!({ "email": account.email }));
+
     match Account::update(account_id, &body.email, &mut conn).map_err(Error::DieselError) {
-        Ok(_) => HttpResponse::new(StatusCode::OK),
+        Ok(_) => {
+            audit::record(&req,
+                          None,
+                          AuditAction::AccountUpdate,
+                          &account_id.to_string(),
+                          before,
+                          Some(json!({ "email": body.email })),
+                          &mut conn);
+            HttpResponse::new(StatusCode::OK)
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scope_req(origins: &[&str], capabilities: &[&str], channels: &[&str]) -> TokenScopeReq {
+        let to_vec = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        TokenScopeReq { origins:      to_vec(origins),
+                        capabilities: to_vec(capabilities),
+                        channels:     to_vec(channels), }
+    }
+
+    #[test]
+    fn token_name_is_trimmed() {
+        assert_eq!(token_name(Some("  ci  ".to_string())).unwrap(), "ci");
+    }
+
+    #[test]
+    fn unnamed_tokens_are_named_after_their_creation() {
+        assert!(token_name(None).unwrap().starts_with("token-"));
+    }
+
+    #[test]
+    fn token_name_length_is_checked() {
+        let too_long = "t".repeat(MAX_TOKEN_NAME_LENGTH + 1);
+        for name in vec!["", "   ", too_long.as_str()].into_iter() {
+            let resp = token_name(Some(name.to_string())).unwrap_err();
+            assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+        assert!(token_name(Some("t".repeat(MAX_TOKEN_NAME_LENGTH))).is_ok());
+    }
+
+    #[test]
+    fn accounts_hold_at_most_max_access_tokens() {
+        assert!(check_token_limit(0).is_ok());
+        assert!(check_token_limit(MAX_ACCESS_TOKENS - 1).is_ok());
+        for held in vec![MAX_ACCESS_TOKENS, MAX_ACCESS_TOKENS + 1].into_iter() {
+            let resp = check_token_limit(held).unwrap_err();
+            assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    #[test]
+    fn token_scope_needs_a_capability() {
+        let resp = token_scope(scope_req(&["neurosis"], &[], &[])).unwrap_err();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let resp = token_scope(scope_req(&["neurosis"], &["teleport"], &[])).unwrap_err();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn token_scope_rejects_empty_names() {
+        let resp = token_scope(scope_req(&[" "], &["download"], &[])).unwrap_err();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let resp = token_scope(scope_req(&["neurosis"], &["promote"], &[""])).unwrap_err();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn token_scope_is_listed_as_requested() {
+        let (scope, json) =
+            token_scope(scope_req(&["neurosis"], &["download", "promote"], &["stable"])).unwrap();
+        assert_eq!(scope.origins, vec!["neurosis".to_string()]);
+        assert_eq!(scope.channels, vec!["stable".to_string()]);
+        assert_eq!(json["origins"], json!(["neurosis"]));
+        assert_eq!(json["channels"], json!(["stable"]));
+        assert_eq!(json["capabilities"], json!(["download", "promote"]));
+    }
+}
@@ -0,0 +1,381 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use actix_web::{body::BoxBody,
+                http::{self,
+                       StatusCode},
+                web::{self,
+                      Data,
+                      Json,
+                      Path,
+                      ServiceConfig},
+                HttpRequest,
+                HttpResponse};
+use bytes::Bytes;
+use habitat_core::package::ident;
+
+use crate::{bldr_core::privilege::FeatureFlags,
+            db::models::{account::*,
+                         audit::AuditAction,
+                         origin::{OriginMember,
+                                  OriginMemberRole},
+                         role::Permission},
+            server::{authorize::{authorize_permission,
+                                 authorize_role_grant},
+                     error::{Error,
+                             Result},
+                     framework::headers,
+                     resources::profile::{do_get_access_tokens,
+                                          generate_account_token,
+                                          revoke_account_token,
+                                          AccessTokenReq},
+                     services::audit,
+                     AppState}};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RobotReq {
+    pub name: String,
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+pub struct Robots {}
+
+impl Robots {
+    // Route registration
+    //
+    pub fn register(cfg: &mut ServiceConfig) {
+        cfg.route("/depot/origins/{origin}/robots", web::get().to(list_robots))
+           .route("/depot/origins/{origin}/robots", web::post().to(create_robot))
+           .route("/depot/origins/{origin}/robots/{robot}",
+                  web::delete().to(delete_robot))
+           .route("/depot/origins/{origin}/robots/{robot}/tokens",
+                  web::get().to(list_robot_tokens))
+           .route("/depot/origins/{origin}/robots/{robot}/tokens",
+                  web::post().to(generate_robot_token))
+           .route("/depot/origins/{origin}/robots/{robot}/tokens/{id}",
+                  web::delete().to(revoke_robot_token));
+    }
+}
+
+// Robot accounts live in the same namespace as everyone else's, so their names are qualified
+// with the origin. The dot keeps them apart from OAuth usernames.
+fn robot_account_name(origin: &str, name: &str) -> String { format!("{}.{}", origin, name) }
+
+// The name a robot was created with, which its routes take
+fn robot_short_name<'a>(origin: &str, account_name: &'a str) -> &'a str {
+    account_name.strip_prefix(origin)
+                .and_then(|name| name.strip_prefix('.'))
+                .unwrap_or(account_name)
+}
+
+fn robot_json(origin: &str, robot: &Account, role: Option<OriginMemberRole>) -> serde_json::Value {
+    json!({ "name": robot_short_name(origin, &robot.name),
+            "full_name": robot.name,
+            "role": role,
+            "created_at": robot.created_at })
+}
+
+// Route handlers - these functions can return any Responder trait
+//
+#[allow(clippy::needless_pass_by_value)]
+async fn list_robots(req: HttpRequest, path: Path<String>, state: Data<AppState>) -> HttpResponse {
+    let origin = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Members) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match Account::list_robots(&origin, &mut conn).map_err(Error::DieselError) {
+        Ok(robots) => {
+            let robots: Vec<serde_json::Value> =
+                robots.iter()
+                      .map(|robot| {
+                          let role = OriginMember::member_role(&origin, robot.id, &mut conn).ok();
+                          robot_json(&origin, robot, role)
+                      })
+                      .collect();
+
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(json!({ "origin": origin, "robots": robots }))
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn create_robot(req: HttpRequest,
+                      path: Path<String>,
+                      body: Json<RobotReq>,
+                      state: Data<AppState>)
+                      -> HttpResponse {
+    let origin = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Members) {
+        return err.into();
+    }
+
+    if !ident::is_valid_origin_name(&body.name) {
+        let body = Bytes::from_static(b"Robot names may only contain lowercase letters, \
+                                        numbers, hyphens and underscores");
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    let role = match body.role.as_deref().map(OriginMemberRole::from_str) {
+        None => OriginMemberRole::Member,
+        Some(Ok(role)) if role != OriginMemberRole::Owner => role,
+        Some(_) => {
+            let body = Bytes::from(format!("Invalid robot role '{}'",
+                                           body.role.as_deref().unwrap_or_default()));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    };
+
+    if let Err(err) = authorize_role_grant(&req, &origin, role) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let name = robot_account_name(&origin, &body.name);
+    let new_robot = NewRobotAccount { email:        "",
+                                      name:         &name,
+                                      robot_origin: &origin, };
+
+    match Account::create_robot(&new_robot, role, &mut conn).map_err(Error::DieselError) {
+        Ok(robot) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::RobotCreate,
+                          &robot.name,
+                          None,
+                          Some(json!({ "role": role })),
+                          &mut conn);
+            HttpResponse::Created().json(robot_json(&origin, &robot, Some(role)))
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn delete_robot(req: HttpRequest,
+                      path: Path<(String, String)>,
+                      state: Data<AppState>)
+                      -> HttpResponse {
+    let (origin, robot) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Members) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let name = robot_account_name(&origin, &robot);
+    let robot = match Account::get_robot(&origin, &name, &mut conn).map_err(Error::DieselError) {
+        Ok(robot) => robot,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    // Gathered up front, as the tokens and membership go along with the robot
+    let role = OriginMember::member_role(&origin, robot.id, &mut conn).ok();
+    if let Some(role) = role {
+        if let Err(err) = authorize_role_grant(&req, &origin, role) {
+            return err.into();
+        }
+    }
+    let tokens = AccountToken::list(robot.id as u64, &mut conn).unwrap_or_default();
+
+    match Account::delete_robot(robot.id, &mut conn).map_err(Error::DieselError) {
+        Ok(_) => {
+            let mut memcache = state.memcache.borrow_mut();
+            for token in tokens.iter() {
+                memcache.delete_session_key(&token.token);
+            }
+            memcache.clear_cache_for_member_role(&origin, robot.id as u64);
+
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::RobotDelete,
+                          &robot.name,
+                          role.map(|role| json!({ "role": role })),
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn list_robot_tokens(req: HttpRequest,
+                           path: Path<(String, String)>,
+                           state: Data<AppState>)
+                           -> HttpResponse {
+    let (origin, robot) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Members) {
+        return err.into();
+    }
+
+    let robot_id = match get_robot_id(&origin, &robot, &state) {
+        Ok(robot_id) => robot_id,
+        Err(err) => return err.into(),
+    };
+
+    match do_get_access_tokens(&req, robot_id) {
+        Ok(tokens) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(json!({ "tokens": tokens }))
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn generate_robot_token(req: HttpRequest,
+                              path: Path<(String, String)>,
+                              body: Option<Json<AccessTokenReq>>,
+                              state: Data<AppState>)
+                              -> HttpResponse {
+    let (origin, robot) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Members) {
+        return err.into();
+    }
+
+    let robot_id = match get_robot_id(&origin, &robot, &state) {
+        Ok(robot_id) => robot_id,
+        Err(err) => return err.into(),
+    };
+
+    // A robot's token carries the robot's role, so minting one is as good as being given it
+    if let Err(err) = authorize_robot_role(&req, &origin, robot_id, &state) {
+        return err.into();
+    }
+
+    generate_account_token(&req,
+                           robot_id,
+                           FeatureFlags::empty().bits(),
+                           Some(&origin),
+                           body.map(Json::into_inner),
+                           &state)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn revoke_robot_token(req: HttpRequest,
+                            path: Path<(String, String, String)>,
+                            state: Data<AppState>)
+                            -> HttpResponse {
+    let (origin, robot, token_id) = path.into_inner();
+
+    let token_id = match token_id.parse::<u64>() {
+        Ok(id) => id,
+        Err(_) => {
+            let body = Bytes::from_static(b"Error parsing access token.");
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    };
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Members) {
+        return err.into();
+    }
+
+    let robot_id = match get_robot_id(&origin, &robot, &state) {
+        Ok(robot_id) => robot_id,
+        Err(err) => return err.into(),
+    };
+
+    revoke_account_token(&req, robot_id, token_id, Some(&origin), &state)
+}
+
+fn authorize_robot_role(req: &HttpRequest,
+                        origin: &str,
+                        robot_id: u64,
+                        state: &AppState)
+                        -> Result<()> {
+    let mut conn = state.db.get_conn().map_err(Error::DbError)?;
+    let role = OriginMember::member_role(origin, robot_id as i64, &mut conn)
+        .map_err(Error::DieselError)?;
+    authorize_role_grant(req, origin, role).map(|_| ())
+}
+
+// Robot routes name the robot without its origin, as it was given when the robot was created
+fn get_robot_id(origin: &str, robot: &str, state: &AppState) -> Result<u64> {
+    let mut conn = state.db.get_conn().map_err(Error::DbError)?;
+    Account::get_robot(origin, &robot_account_name(origin, robot), &mut conn)
+        .map(|robot| robot.id as u64)
+        .map_err(Error::DieselError)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn robot_names_round_trip() {
+        let account_name = robot_account_name("core", "release");
+        assert_eq!(account_name, "core.release");
+        assert_eq!(robot_short_name("core", &account_name), "release");
+    }
+
+    #[test]
+    fn robot_short_names_only_strip_their_own_origin() {
+        assert_eq!(robot_short_name("core", "core.release.nightly"), "release.nightly");
+        assert_eq!(robot_short_name("core", "corelib.release"), "corelib.release");
+        assert_eq!(robot_short_name("core", "other.release"), "other.release");
+    }
+
+    #[test]
+    fn robots_are_shown_by_their_short_name() {
+        let robot = Account { id:           42,
+                              email:        String::new(),
+                              name:         robot_account_name("core", "release"),
+                              created_at:   None,
+                              updated_at:   None,
+                              robot_origin: Some("core".to_string()),
+                              disabled:     false, };
+        let json = robot_json("core", &robot, Some(OriginMemberRole::Maintainer));
+        assert_eq!(json["name"], "release");
+        assert_eq!(json["full_name"], "core.release");
+        assert_eq!(json["role"], "maintainer");
+    }
+}
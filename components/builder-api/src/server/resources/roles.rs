@@ -0,0 +1,442 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use actix_web::{body::BoxBody,
+                http::{self,
+                       StatusCode},
+                web::{self,
+                      Data,
+                      Json,
+                      Path,
+                      ServiceConfig},
+                HttpRequest,
+                HttpResponse};
+use bytes::Bytes;
+use diesel::pg::PgConnection;
+use habitat_core::package::ident;
+
+use crate::{db::models::{account::Account,
+                         audit::AuditAction,
+                         origin::OriginMemberRole,
+                         role::*},
+            server::{authorize::authorize_session,
+                     error::Error,
+                     framework::headers,
+                     services::audit,
+                     AppState}};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CustomRoleReq {
+    pub name:        String,
+    #[serde(default)]
+    pub description: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct UpdateCustomRoleReq {
+    pub description: Option<String>,
+    pub permissions: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct CustomRoleWithMembers {
+    #[serde(flatten)]
+    role:    CustomRole,
+    members: Vec<String>,
+}
+
+pub struct Roles {}
+
+impl Roles {
+    // Route registration
+    //
+    pub fn register(cfg: &mut ServiceConfig) {
+        cfg.route("/depot/origins/{origin}/roles", web::get().to(list_roles))
+           .route("/depot/origins/{origin}/roles", web::post().to(create_role))
+           .route("/depot/origins/{origin}/roles/{role}", web::get().to(get_role))
+           .route("/depot/origins/{origin}/roles/{role}",
+                  web::patch().to(update_role))
+           .route("/depot/origins/{origin}/roles/{role}",
+                  web::delete().to(delete_role))
+           .route("/depot/origins/{origin}/roles/{role}/members/{user}",
+                  web::put().to(assign_role))
+           .route("/depot/origins/{origin}/roles/{role}/members/{user}",
+                  web::delete().to(unassign_role));
+    }
+}
+
+// Route handlers - these functions can return any Responder trait
+//
+// Custom roles can bundle any permission, so only administrators may define or give them out,
+// whatever permissions their own custom roles carry.
+#[allow(clippy::needless_pass_by_value)]
+async fn list_roles(req: HttpRequest, path: Path<String>, state: Data<AppState>) -> HttpResponse {
+    let origin = path.into_inner();
+
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match CustomRole::list(&origin, &mut conn).map_err(Error::DieselError) {
+        Ok(roles) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(json!({ "origin": origin, "roles": roles }))
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn create_role(req: HttpRequest,
+                     path: Path<String>,
+                     body: Json<CustomRoleReq>,
+                     state: Data<AppState>)
+                     -> HttpResponse {
+    let origin = path.into_inner();
+
+    let session = match authorize_session(&req,
+                                          Some(&origin),
+                                          Some(OriginMemberRole::Administrator))
+    {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    // Role names share the rules of origin names, so that they are safe in URLs
+    if !ident::is_valid_origin_name(&body.name) {
+        let body = Bytes::from(format!("Invalid role name '{}'", body.name));
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    let permissions = match parse_permissions(&body.permissions) {
+        Ok(permissions) => permissions,
+        Err(resp) => return resp,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let new_role = NewCustomRole { origin: &origin,
+                                   name: &body.name,
+                                   description: &body.description,
+                                   permissions,
+                                   owner_id: session.id() as i64 };
+
+    match CustomRole::create(&new_role, &mut conn).map_err(Error::DieselError) {
+        Ok(role) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::RoleCreate,
+                          &role.name,
+                          None,
+                          serde_json::to_value(&role).ok(),
+                          &mut conn);
+            HttpResponse::Created().json(role)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_role(req: HttpRequest,
+                  path: Path<(String, String)>,
+                  state: Data<AppState>)
+                  -> HttpResponse {
+    let (origin, name) = path.into_inner();
+
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let role = match CustomRole::get(&origin, &name, &mut conn).map_err(Error::DieselError) {
+        Ok(role) => role,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    match CustomRole::list_members(role.id, &mut conn).map_err(Error::DieselError) {
+        Ok(members) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(CustomRoleWithMembers { role, members })
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn update_role(req: HttpRequest,
+                     path: Path<(String, String)>,
+                     body: Json<UpdateCustomRoleReq>,
+                     state: Data<AppState>)
+                     -> HttpResponse {
+    let (origin, name) = path.into_inner();
+
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator))
+    {
+        return err.into();
+    }
+
+    let permissions = match body.permissions {
+        Some(ref permissions) => {
+            match parse_permissions(permissions) {
+                Ok(permissions) => Some(permissions),
+                Err(resp) => return resp,
+            }
+        }
+        None => None,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let before = match CustomRole::get(&origin, &name, &mut conn).map_err(Error::DieselError) {
+        Ok(role) => role,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    let changes = UpdateCustomRole { description: body.description.as_deref(),
+                                     permissions };
+
+    match CustomRole::update(before.id, &changes, &mut conn).map_err(Error::DieselError) {
+        Ok(role) => {
+            state.memcache.borrow_mut().clear_cache_for_origin_roles(&origin);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::RoleUpdate,
+                          &role.name,
+                          serde_json::to_value(&before).ok(),
+                          serde_json::to_value(&role).ok(),
+                          &mut conn);
+            HttpResponse::Ok().json(role)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn delete_role(req: HttpRequest,
+                     path: Path<(String, String)>,
+                     state: Data<AppState>)
+                     -> HttpResponse {
+    let (origin, name) = path.into_inner();
+
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator))
+    {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let role = match CustomRole::get(&origin, &name, &mut conn).map_err(Error::DieselError) {
+        Ok(role) => role,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    let members = CustomRole::list_members(role.id, &mut conn).unwrap_or_default();
+
+    match CustomRole::delete(role.id, &mut conn).map_err(Error::DieselError) {
+        Ok(_) => {
+            state.memcache.borrow_mut().clear_cache_for_origin_roles(&origin);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::RoleDelete,
+                          &role.name,
+                          Some(json!({ "role": role, "members": members })),
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn assign_role(req: HttpRequest,
+                     path: Path<(String, String, String)>,
+                     state: Data<AppState>)
+                     -> HttpResponse {
+    let (origin, name, username) = path.into_inner();
+
+    let session = match authorize_session(&req,
+                                          Some(&origin),
+                                          Some(OriginMemberRole::Administrator))
+    {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let (role, account) = match role_and_account(&origin, &name, &username, &mut conn) {
+        Ok(found) => found,
+        Err(err) => return err.into(),
+    };
+
+    // We cannot allow a user to change their own role
+    if account.id == session.id() as i64 {
+        return HttpResponse::new(StatusCode::FORBIDDEN);
+    }
+
+    match role.assign(account.id, &mut conn).map_err(Error::DieselError) {
+        // Custom roles add to the access of the origin's members, they don't make anyone a member
+        Ok(0) => {
+            let body = Bytes::from(format!("{} is not a member of the origin {}",
+                                           account.name, origin));
+            HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body))
+        }
+        Ok(_) => {
+            state.memcache
+                 .borrow_mut()
+                 .clear_cache_for_member_role(&origin, account.id as u64);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::RoleAssign,
+                          &account.name,
+                          None,
+                          Some(json!({ "role": role.name, "permissions": role.permissions })),
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn unassign_role(req: HttpRequest,
+                       path: Path<(String, String, String)>,
+                       state: Data<AppState>)
+                       -> HttpResponse {
+    let (origin, name, username) = path.into_inner();
+
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Administrator))
+    {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let (role, account) = match role_and_account(&origin, &name, &username, &mut conn) {
+        Ok(found) => found,
+        Err(err) => return err.into(),
+    };
+
+    match role.unassign(account.id, &mut conn).map_err(Error::DieselError) {
+        Ok(0) => HttpResponse::NotFound().finish(),
+        Ok(_) => {
+            state.memcache
+                 .borrow_mut()
+                 .clear_cache_for_member_role(&origin, account.id as u64);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::RoleUnassign,
+                          &account.name,
+                          Some(json!({ "role": role.name, "permissions": role.permissions })),
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+// Internal helpers
+//
+// Checks the names of the permissions, giving them back in their canonical form
+fn parse_permissions(names: &[String]) -> std::result::Result<Vec<String>, HttpResponse> {
+    if names.is_empty() {
+        let body = Bytes::from_static(b"Roles need at least one permission");
+        return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body)));
+    }
+
+    let mut permissions = Vec::new();
+    for name in names {
+        match name.parse::<Permission>() {
+            Ok(permission) => {
+                let permission = permission.to_string();
+                if !permissions.contains(&permission) {
+                    permissions.push(permission);
+                }
+            }
+            Err(err) => {
+                let body = Bytes::from(err.to_string());
+                return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                                   BoxBody::new(body)));
+            }
+        }
+    }
+    Ok(permissions)
+}
+
+fn role_and_account(origin: &str,
+                    name: &str,
+                    username: &str,
+                    conn: &mut PgConnection)
+                    -> std::result::Result<(CustomRole, Account), Error> {
+    let role = CustomRole::get(origin, name, conn).map_err(Error::DieselError)?;
+    let account = Account::get(username, conn).map_err(Error::DieselError)?;
+    Ok((role, account))
+}
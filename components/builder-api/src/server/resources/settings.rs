@@ -24,13 +24,17 @@ use actix_web::{body::BoxBody,
 
 use builder_core::Error::PackageSettingDeleteError;
 
-use crate::{db::models::{origin::*,
+use crate::{db::models::{audit::AuditAction,
+                         origin::*,
                          package::*,
+                         role::Permission,
                          settings::*},
-            server::{authorize::authorize_session,
+            server::{authorize::{authorize_permission,
+                                 authorize_session},
                      error::{Error,
                              Result},
                      helpers::req_state,
+                     services::audit,
                      AppState}};
 
 use bytes::Bytes;
@@ -95,11 +99,10 @@ async fn create_origin_package_settings(req: HttpRequest,
                                         -> HttpResponse {
     let (origin, pkg) = path.into_inner();
 
-    let account_id =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
-            Ok(session) => session.id(),
-            Err(err) => return err.into(),
-        };
+    let account_id = match authorize_permission(&req, &origin, &Permission::Visibility) {
+        Ok(session) => session.id(),
+        Err(err) => return err.into(),
+    };
 
     let mut conn = match state.db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
@@ -126,7 +129,16 @@ async fn create_origin_package_settings(req: HttpRequest,
     )
     .map_err(Error::DieselError)
     {
-        Ok(ops) => HttpResponse::Created().json(ops),
+        Ok(ops) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::PackageSettingsCreate,
+                          &pkg,
+                          None,
+                          serde_json::to_value(&ops).ok(),
+                          &mut conn);
+            HttpResponse::Created().json(ops)
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -141,11 +153,10 @@ async fn update_origin_package_settings(req: HttpRequest,
                                         -> HttpResponse {
     let (origin, pkg) = path.into_inner();
 
-    let account_id =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
-            Ok(session) => session.id(),
-            Err(err) => return err.into(),
-        };
+    let account_id = match authorize_permission(&req, &origin, &Permission::Visibility) {
+        Ok(session) => session.id(),
+        Err(err) => return err.into(),
+    };
 
     if body.0.visibility.is_empty() {
         let body = Bytes::from_static(b"Missing required package visibility");
@@ -165,6 +176,10 @@ async fn update_origin_package_settings(req: HttpRequest,
         }
     };
 
+    let before = OriginPackageSettings::get(&GetOriginPackageSettings { origin: &origin,
+                                                                        name:   &pkg, },
+                                            &mut conn).ok();
+
     match OriginPackageSettings::update(&UpdateOriginPackageSettings { origin:     &origin,
                                                                        name:       &pkg,
                                                                        visibility: &pv,
@@ -172,7 +187,16 @@ async fn update_origin_package_settings(req: HttpRequest,
                                                                                    as i64, },
                                         &mut conn).map_err(Error::DieselError)
     {
-        Ok(ups) => HttpResponse::Ok().json(ups),
+        Ok(ups) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::PackageSettingsUpdate,
+                          &pkg,
+                          before.and_then(|settings| serde_json::to_value(settings).ok()),
+                          serde_json::to_value(&ups).ok(),
+                          &mut conn);
+            HttpResponse::Ok().json(ups)
+        }
         Err(err) => {
             debug!("{}", err);
             err.into()
@@ -186,11 +210,10 @@ async fn delete_origin_package_settings(req: HttpRequest,
                                         -> HttpResponse {
     let (origin, pkg) = path.into_inner();
 
-    let account_id =
-        match authorize_session(&req, Some(&origin), Some(OriginMemberRole::Maintainer)) {
-            Ok(session) => session.id(),
-            Err(err) => return err.into(),
-        };
+    let account_id = match authorize_permission(&req, &origin, &Permission::Visibility) {
+        Ok(session) => session.id(),
+        Err(err) => return err.into(),
+    };
 
     let mut conn = match req_state(&req).db.get_conn().map_err(Error::DbError) {
         Ok(conn_ref) => conn_ref,
@@ -201,6 +224,10 @@ async fn delete_origin_package_settings(req: HttpRequest,
     // that the user has already cleaned up any existing packages.
     match package_settings_delete_preflight(&origin, &pkg, &mut conn) {
         Ok(_) => {
+            let before = OriginPackageSettings::get(&GetOriginPackageSettings { origin: &origin,
+                                                                                name:   &pkg, },
+                                                    &mut conn).ok();
+
             // Delete the package setting
             match OriginPackageSettings::delete(&DeleteOriginPackageSettings { origin:   &origin,
                                                                                name:     &pkg,
@@ -208,7 +235,16 @@ async fn delete_origin_package_settings(req: HttpRequest,
                                                                                          as i64, },
                                                 &mut conn).map_err(Error::DieselError)
             {
-                Ok(_) => HttpResponse::new(StatusCode::NO_CONTENT),
+                Ok(_) => {
+                    audit::record(&req,
+                                  Some(&origin),
+                                  AuditAction::PackageSettingsDelete,
+                                  &pkg,
+                                  before.and_then(|settings| serde_json::to_value(settings).ok()),
+                                  None,
+                                  &mut conn);
+                    HttpResponse::new(StatusCode::NO_CONTENT)
+                }
                 Err(err) => {
                     debug!("{}", err);
                     err.into()
@@ -0,0 +1,450 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use actix_web::{body::BoxBody,
+                http::{self,
+                       StatusCode},
+                web::{self,
+                      Data,
+                      Json,
+                      Path,
+                      ServiceConfig},
+                HttpRequest,
+                HttpResponse};
+use bytes::Bytes;
+use diesel::pg::PgConnection;
+use habitat_core::package::ident;
+
+use crate::{db::models::{account::Account,
+                         audit::AuditAction,
+                         origin::{Origin,
+                                  OriginMemberRole},
+                         role::Permission,
+                         team::*},
+            server::{authorize::{authorize_permission,
+                                 authorize_role_grant,
+                                 authorize_session},
+                     error::Error,
+                     framework::headers,
+                     services::audit,
+                     AppState}};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TeamReq {
+    pub name:        String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub role:        Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct UpdateTeamReq {
+    pub description: Option<String>,
+    pub role:        Option<String>,
+}
+
+#[derive(Serialize)]
+struct TeamWithMembers {
+    #[serde(flatten)]
+    team:    Team,
+    members: Vec<String>,
+}
+
+pub struct Teams {}
+
+impl Teams {
+    // Route registration
+    //
+    pub fn register(cfg: &mut ServiceConfig) {
+        cfg.route("/depot/origins/{origin}/teams", web::get().to(list_teams))
+           .route("/depot/origins/{origin}/teams", web::post().to(create_team))
+           .route("/depot/origins/{origin}/teams/{team}", web::get().to(get_team))
+           .route("/depot/origins/{origin}/teams/{team}",
+                  web::patch().to(update_team))
+           .route("/depot/origins/{origin}/teams/{team}",
+                  web::delete().to(delete_team))
+           .route("/depot/origins/{origin}/teams/{team}/members/{user}",
+                  web::put().to(add_team_member))
+           .route("/depot/origins/{origin}/teams/{team}/members/{user}",
+                  web::delete().to(remove_team_member));
+    }
+}
+
+// Route handlers - these functions can return any Responder trait
+//
+#[allow(clippy::needless_pass_by_value)]
+async fn list_teams(req: HttpRequest, path: Path<String>, state: Data<AppState>) -> HttpResponse {
+    let origin = path.into_inner();
+
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match Team::list(&origin, &mut conn).map_err(Error::DieselError) {
+        Ok(teams) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(json!({ "origin": origin, "teams": teams }))
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn create_team(req: HttpRequest,
+                     path: Path<String>,
+                     body: Json<TeamReq>,
+                     state: Data<AppState>)
+                     -> HttpResponse {
+    let origin = path.into_inner();
+
+    let session = match authorize_permission(&req, &origin, &Permission::Members) {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    // Team names share the rules of origin names, so that they are safe in URLs
+    if !ident::is_valid_origin_name(&body.name) {
+        let body = Bytes::from(format!("Invalid team name '{}'", body.name));
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+    }
+
+    let role = match parse_team_role(body.role.as_deref()) {
+        Ok(role) => role.unwrap_or(OriginMemberRole::Member),
+        Err(resp) => return resp,
+    };
+
+    if let Err(err) = authorize_role_grant(&req, &origin, role) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let new_team = NewTeam { origin:      &origin,
+                             name:        &body.name,
+                             description: &body.description,
+                             member_role: role,
+                             owner_id:    session.id() as i64, };
+
+    match Team::create(&new_team, &mut conn).map_err(Error::DieselError) {
+        Ok(team) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::TeamCreate,
+                          &team.name,
+                          None,
+                          serde_json::to_value(&team).ok(),
+                          &mut conn);
+            HttpResponse::Created().json(team)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_team(req: HttpRequest,
+                  path: Path<(String, String)>,
+                  state: Data<AppState>)
+                  -> HttpResponse {
+    let (origin, name) = path.into_inner();
+
+    if let Err(err) = authorize_session(&req, Some(&origin), Some(OriginMemberRole::Member)) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let team = match Team::get(&origin, &name, &mut conn).map_err(Error::DieselError) {
+        Ok(team) => team,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    match Team::list_members(team.id, &mut conn).map_err(Error::DieselError) {
+        Ok(members) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(TeamWithMembers { team, members })
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn update_team(req: HttpRequest,
+                     path: Path<(String, String)>,
+                     body: Json<UpdateTeamReq>,
+                     state: Data<AppState>)
+                     -> HttpResponse {
+    let (origin, name) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Members) {
+        return err.into();
+    }
+
+    let role = match parse_team_role(body.role.as_deref()) {
+        Ok(role) => role,
+        Err(resp) => return resp,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let before = match Team::get(&origin, &name, &mut conn).map_err(Error::DieselError) {
+        Ok(team) => team,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    for role in Some(before.member_role).into_iter().chain(role) {
+        if let Err(err) = authorize_role_grant(&req, &origin, role) {
+            return err.into();
+        }
+    }
+
+    let changes = UpdateTeam { description: body.description.as_deref(),
+                               member_role: role, };
+
+    match Team::update(before.id, &changes, &mut conn).map_err(Error::DieselError) {
+        Ok(team) => {
+            state.memcache.borrow_mut().clear_cache_for_origin_roles(&origin);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::TeamUpdate,
+                          &team.name,
+                          serde_json::to_value(&before).ok(),
+                          serde_json::to_value(&team).ok(),
+                          &mut conn);
+            HttpResponse::Ok().json(team)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn delete_team(req: HttpRequest,
+                     path: Path<(String, String)>,
+                     state: Data<AppState>)
+                     -> HttpResponse {
+    let (origin, name) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Members) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let team = match Team::get(&origin, &name, &mut conn).map_err(Error::DieselError) {
+        Ok(team) => team,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    if let Err(err) = authorize_role_grant(&req, &origin, team.member_role) {
+        return err.into();
+    }
+
+    let members = Team::list_members(team.id, &mut conn).unwrap_or_default();
+
+    match Team::delete(team.id, &mut conn).map_err(Error::DieselError) {
+        Ok(_) => {
+            state.memcache.borrow_mut().clear_cache_for_origin_roles(&origin);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::TeamDelete,
+                          &team.name,
+                          Some(json!({ "team": team, "members": members })),
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn add_team_member(req: HttpRequest,
+                         path: Path<(String, String, String)>,
+                         state: Data<AppState>)
+                         -> HttpResponse {
+    let (origin, name, username) = path.into_inner();
+
+    let session = match authorize_permission(&req, &origin, &Permission::Members) {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let (team, account) = match team_and_account(&origin, &name, &username, &mut conn) {
+        Ok(found) => found,
+        Err(err) => return err.into(),
+    };
+
+    if let Err(err) = authorize_role_grant(&req, &origin, team.member_role) {
+        return err.into();
+    }
+
+    // As with their role, members cannot raise their own access by joining a team
+    if account.id == session.id() as i64 {
+        return HttpResponse::new(StatusCode::FORBIDDEN);
+    }
+
+    // Teams only raise the roles of the origin's members, they don't make anyone a member
+    match Origin::check_membership(&origin, account.id, &mut conn) {
+        Ok(true) => (),
+        Ok(false) => {
+            let body = Bytes::from(format!("{} is not a member of the origin {}",
+                                           account.name, origin));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+        Err(err) => {
+            debug!("{}", err);
+            return Error::DieselError(err).into();
+        }
+    }
+
+    match team.add_member(account.id, &mut conn).map_err(Error::DieselError) {
+        Ok(_) => {
+            state.memcache
+                 .borrow_mut()
+                 .clear_cache_for_member_role(&origin, account.id as u64);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::TeamMemberAdd,
+                          &account.name,
+                          None,
+                          Some(json!({ "team": team.name, "role": team.member_role })),
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn remove_team_member(req: HttpRequest,
+                            path: Path<(String, String, String)>,
+                            state: Data<AppState>)
+                            -> HttpResponse {
+    let (origin, name, username) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Members) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let (team, account) = match team_and_account(&origin, &name, &username, &mut conn) {
+        Ok(found) => found,
+        Err(err) => return err.into(),
+    };
+
+    if let Err(err) = authorize_role_grant(&req, &origin, team.member_role) {
+        return err.into();
+    }
+
+    match team.remove_member(account.id, &mut conn).map_err(Error::DieselError) {
+        Ok(0) => HttpResponse::NotFound().finish(),
+        Ok(_) => {
+            state.memcache
+                 .borrow_mut()
+                 .clear_cache_for_member_role(&origin, account.id as u64);
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::TeamMemberRemove,
+                          &account.name,
+                          Some(json!({ "team": team.name, "role": team.member_role })),
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+// Internal helpers
+//
+fn parse_team_role(role: Option<&str>)
+                   -> std::result::Result<Option<OriginMemberRole>, HttpResponse> {
+    match role.map(OriginMemberRole::from_str) {
+        None => Ok(None),
+        // Origins only change owners by transfer
+        Some(Ok(r)) if r != OriginMemberRole::Owner => Ok(Some(r)),
+        Some(_) => {
+            let body = Bytes::from(format!("Invalid team role '{}'", role.unwrap_or_default()));
+            Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body)))
+        }
+    }
+}
+
+fn team_and_account(origin: &str,
+                    name: &str,
+                    username: &str,
+                    conn: &mut PgConnection)
+                    -> std::result::Result<(Team, Account), Error> {
+    let team = Team::get(origin, name, conn).map_err(Error::DieselError)?;
+    let account = Account::get(username, conn).map_err(Error::DieselError)?;
+    Ok((team, account))
+}
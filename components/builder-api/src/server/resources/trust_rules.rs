@@ -0,0 +1,322 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use actix_web::{body::BoxBody,
+                http::{self,
+                       StatusCode},
+                web::{self,
+                      Data,
+                      Json,
+                      Path,
+                      ServiceConfig},
+                HttpRequest,
+                HttpResponse};
+use bytes::Bytes;
+use chrono::{Duration,
+             Utc};
+
+use crate::{bldr_core::{access_token::AccessToken as CoreAccessToken,
+                        jwt,
+                        privilege::TokenCapabilities},
+            db::models::{audit::AuditAction,
+                         origin::OriginMemberRole,
+                         role::Permission,
+                         trust_rule::*},
+            protocol::originsrv,
+            server::{authorize::{authorize_permission,
+                                 authorize_role_grant},
+                     error::Error,
+                     framework::headers,
+                     services::{audit,
+                                workload_identity},
+                     AppState}};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TrustRuleReq {
+    pub issuer:       String,
+    pub claims:       serde_json::Value,
+    #[serde(default)]
+    pub role:         Option<String>,
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub channels:     Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TokenExchangeReq {
+    pub token: String,
+}
+
+pub struct TrustRules {}
+
+impl TrustRules {
+    // Route registration
+    //
+    pub fn register(cfg: &mut ServiceConfig) {
+        cfg.route("/depot/origins/{origin}/trust-rules", web::get().to(list_trust_rules))
+           .route("/depot/origins/{origin}/trust-rules", web::post().to(create_trust_rule))
+           .route("/depot/origins/{origin}/trust-rules/{id}",
+                  web::delete().to(delete_trust_rule))
+           .route("/depot/origins/{origin}/token-exchange",
+                  web::post().to(exchange_token));
+    }
+}
+
+// Route handlers - these functions can return any Responder trait
+//
+#[allow(clippy::needless_pass_by_value)]
+async fn list_trust_rules(req: HttpRequest,
+                          path: Path<String>,
+                          state: Data<AppState>)
+                          -> HttpResponse {
+    let origin = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match TrustRule::list(&origin, &mut conn).map_err(Error::DieselError) {
+        Ok(rules) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                              .json(json!({ "origin": origin, "trust_rules": rules }))
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn create_trust_rule(req: HttpRequest,
+                           path: Path<String>,
+                           body: Json<TrustRuleReq>,
+                           state: Data<AppState>)
+                           -> HttpResponse {
+    let origin = path.into_inner();
+
+    let session = match authorize_permission(&req, &origin, &Permission::Settings) {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    let role = match body.role.as_deref().map(OriginMemberRole::from_str) {
+        None => OriginMemberRole::Member,
+        Some(Ok(role)) if role != OriginMemberRole::Owner => role,
+        Some(_) => {
+            let body = Bytes::from(format!("Invalid trust rule role '{}'",
+                                           body.role.as_deref().unwrap_or_default()));
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    };
+
+    // Tokens exchanged through the rule carry its role, so it's handed out like a member's
+    if let Err(err) = authorize_role_grant(&req, &origin, role) {
+        return err.into();
+    }
+
+    if let Err(msg) = validate_trust_rule(&body, &state) {
+        return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                       BoxBody::new(Bytes::from(msg)));
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let new_rule = NewTrustRule { origin:       &origin,
+                                  issuer:       &body.issuer,
+                                  claims:       body.claims.clone(),
+                                  member_role:  role,
+                                  capabilities: body.capabilities.clone(),
+                                  channels:     body.channels.clone(),
+                                  owner_id:     session.id() as i64, };
+
+    match TrustRule::create(&new_rule, &mut conn).map_err(Error::DieselError) {
+        Ok(rule) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::TrustRuleCreate,
+                          &rule.id.to_string(),
+                          None,
+                          Some(json!(rule)),
+                          &mut conn);
+            HttpResponse::Created().json(rule)
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn delete_trust_rule(req: HttpRequest,
+                           path: Path<(String, String)>,
+                           state: Data<AppState>)
+                           -> HttpResponse {
+    let (origin, id) = path.into_inner();
+
+    let id = match id.parse::<u64>() {
+        Ok(id) => id as i64,
+        Err(_) => {
+            let body = Bytes::from_static(b"Error parsing trust rule id.");
+            return HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body));
+        }
+    };
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let rule = match TrustRule::get(&origin, id, &mut conn).map_err(Error::DieselError) {
+        Ok(rule) => rule,
+        Err(err) => {
+            debug!("{}", err);
+            return err.into();
+        }
+    };
+
+    if let Err(err) = authorize_role_grant(&req, &origin, rule.member_role) {
+        return err.into();
+    }
+
+    match TrustRule::delete(&origin, id, &mut conn).map_err(Error::DieselError) {
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::TrustRuleDelete,
+                          &rule.id.to_string(),
+                          Some(json!(rule)),
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("{}", err);
+            err.into()
+        }
+    }
+}
+
+// Exchanges a workload's identity token for a short-lived origin token. No session is needed:
+// the identity token is the credential, and the origin's trust rules decide what it gets.
+#[allow(clippy::needless_pass_by_value)]
+async fn exchange_token(req: HttpRequest,
+                        path: Path<String>,
+                        body: Json<TokenExchangeReq>,
+                        state: Data<AppState>)
+                        -> HttpResponse {
+    let origin = path.into_inner();
+    let cfg = &state.config.workload_identity;
+
+    let (issuer, claims) = match workload_identity::verify(&body.token, cfg).await {
+        Ok(verified) => verified,
+        Err(err) => return err.into(),
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let rules = match TrustRule::list_for_issuer(&origin, &issuer, &mut conn) {
+        Ok(rules) => rules,
+        Err(err) => {
+            debug!("{}", err);
+            return Error::DieselError(err).into();
+        }
+    };
+
+    let rule = match rules.iter().find(|rule| rule.matches(&claims)) {
+        Some(rule) => rule,
+        None => {
+            debug!("No trust rule of origin {} matches the identity token from {}",
+                   origin, issuer);
+            return Error::Authorization.into();
+        }
+    };
+
+    let subject = jwt::claim_string(&claims, "sub").unwrap_or_else(|| issuer.clone());
+    let capabilities =
+        TokenCapabilities::from_names(&rule.capabilities).unwrap_or_else(TokenCapabilities::empty);
+
+    let mut scope = originsrv::TokenScope::new();
+    scope.origins = vec![origin.clone()];
+    scope.set_capabilities(capabilities.bits());
+    scope.channels = rule.channels.clone();
+    scope.set_role(rule.member_role.to_string());
+    scope.set_subject(subject.clone());
+
+    let lifetime = Duration::minutes(i64::from(cfg.token_lifetime_minutes));
+    let token = match CoreAccessToken::workload_token(&state.config.api.key_path, scope, lifetime)
+    {
+        Ok(token) => token.to_string(),
+        Err(err) => return Error::BuilderCore(err).into(),
+    };
+
+    audit::record(&req,
+                  Some(&origin),
+                  AuditAction::TokenExchange,
+                  &subject,
+                  None,
+                  Some(json!({ "issuer": issuer, "trust_rule": rule.id })),
+                  &mut conn);
+
+    HttpResponse::Ok().append_header((http::header::CACHE_CONTROL, headers::NO_CACHE))
+                      .json(json!({ "token": token,
+                                    "expires_at": Utc::now() + lifetime }))
+}
+
+fn validate_trust_rule(body: &TrustRuleReq, state: &AppState) -> Result<(), String> {
+    let cfg = &state.config.workload_identity;
+    if !cfg.issuers.iter().any(|i| i.issuer == body.issuer) {
+        return Err(format!("Identity tokens from {} are not trusted by this Builder",
+                           body.issuer));
+    }
+
+    match body.claims.as_object() {
+        Some(claims) if !claims.is_empty() && claims.values().all(|v| v.is_string()) => (),
+        _ => {
+            return Err(String::from("Trust rules need at least one required claim, and \
+                                     every claim value must be a string"));
+        }
+    }
+
+    match TokenCapabilities::from_names(&body.capabilities) {
+        Some(capabilities) if !capabilities.is_empty() => (),
+        _ => {
+            return Err(String::from("Trust rules need at least one of the capabilities \
+                                     download, upload, promote and keys"));
+        }
+    }
+
+    if body.channels.iter().any(|c| c.trim().is_empty()) {
+        return Err("Trust rule channels cannot be empty".to_string());
+    }
+    Ok(())
+}
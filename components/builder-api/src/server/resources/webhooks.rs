@@ -0,0 +1,521 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeSet,
+          str::FromStr};
+
+use actix_web::{body::BoxBody,
+                http::{self,
+                       StatusCode},
+                web::{self,
+                      Data,
+                      Json,
+                      Path,
+                      Query,
+                      ServiceConfig},
+                HttpRequest,
+                HttpResponse};
+use bytes::Bytes;
+use rand::{self,
+           Rng};
+use reqwest::Url;
+
+use crate::{bldr_core::crypto,
+            db::models::{audit::AuditAction,
+                         role::Permission,
+                         webhook::*},
+            server::{authorize::authorize_permission,
+                     error::Error,
+                     framework::headers,
+                     services::{audit,
+                               webhooks},
+                     AppState}};
+
+const MIN_SECRET_LEN: usize = 16;
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 200;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WebhookReq {
+    pub url:    String,
+    #[serde(default)]
+    pub events: Vec<String>,
+    pub secret: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct UpdateWebhookReq {
+    pub url:    Option<String>,
+    pub events: Option<Vec<String>>,
+    pub secret: Option<String>,
+    pub active: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct DeliveriesQuery {
+    pub limit: Option<i64>,
+}
+
+// The secret is only ever returned in the response that creates the webhook
+#[derive(Serialize)]
+struct WebhookWithSecret {
+    #[serde(flatten)]
+    webhook: Webhook,
+    secret:  String,
+}
+
+pub struct Webhooks {}
+
+impl Webhooks {
+    // Route registration
+    //
+    pub fn register(cfg: &mut ServiceConfig) {
+        cfg.route("/depot/origins/{origin}/webhooks",
+                  web::get().to(list_webhooks))
+           .route("/depot/origins/{origin}/webhooks",
+                  web::post().to(create_webhook))
+           .route("/depot/origins/{origin}/webhooks/{id}",
+                  web::get().to(get_webhook))
+           .route("/depot/origins/{origin}/webhooks/{id}",
+                  web::patch().to(update_webhook))
+           .route("/depot/origins/{origin}/webhooks/{id}",
+                  web::delete().to(delete_webhook))
+           .route("/depot/origins/{origin}/webhooks/{id}/deliveries",
+                  web::get().to(list_webhook_deliveries))
+           .route("/depot/origins/{origin}/webhooks/{id}/deliveries/{delivery}/redeliver",
+                  web::post().to(redeliver_webhook_delivery));
+    }
+}
+
+// Route handlers - these functions can return any Responder trait
+//
+#[allow(clippy::needless_pass_by_value)]
+async fn list_webhooks(req: HttpRequest,
+                       path: Path<String>,
+                       state: Data<AppState>)
+                       -> HttpResponse {
+    let origin = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match Webhook::list(&origin, &mut conn) {
+        Ok(webhooks) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(webhooks)
+        }
+        Err(err) => {
+            debug!("Failed to list webhooks, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn create_webhook(req: HttpRequest,
+                        path: Path<String>,
+                        body: Json<WebhookReq>,
+                        state: Data<AppState>)
+                        -> HttpResponse {
+    let origin = path.into_inner();
+
+    let session = match authorize_permission(&req, &origin, &Permission::Settings) {
+        Ok(session) => session,
+        Err(err) => return err.into(),
+    };
+
+    if let Err(resp) = validate_url(&body.url) {
+        return resp;
+    }
+
+    let events = match validate_events(&body.events) {
+        Ok(events) => events,
+        Err(resp) => return resp,
+    };
+
+    let secret = match body.secret {
+        Some(ref secret) => {
+            if let Err(resp) = validate_secret(secret) {
+                return resp;
+            }
+            secret.to_string()
+        }
+        None => generate_secret(),
+    };
+
+    let encrypted = match encrypt_secret(&state, &secret) {
+        Ok(encrypted) => encrypted,
+        Err(err) => return err.into(),
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let new_webhook = NewWebhook { origin: &origin,
+                                   url: &body.url,
+                                   secret: &encrypted,
+                                   events,
+                                   owner_id: session.id() as i64 };
+
+    match Webhook::create(&new_webhook, &mut conn) {
+        Ok(webhook) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::WebhookCreate,
+                          &webhook.id.to_string(),
+                          None,
+                          serde_json::to_value(&webhook).ok(),
+                          &mut conn);
+            HttpResponse::Created().json(WebhookWithSecret { webhook, secret })
+        }
+        Err(err) => {
+            debug!("Failed to create webhook, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn get_webhook(req: HttpRequest,
+                     path: Path<(String, String)>,
+                     state: Data<AppState>)
+                     -> HttpResponse {
+    let (origin, id) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
+
+    let webhook_id = match parse_id("webhook", &id) {
+        Ok(webhook_id) => webhook_id,
+        Err(resp) => return resp,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    match Webhook::get(&origin, webhook_id, &mut conn) {
+        Ok(webhook) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(webhook)
+        }
+        Err(err) => {
+            debug!("Failed to get webhook, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn update_webhook(req: HttpRequest,
+                        path: Path<(String, String)>,
+                        body: Json<UpdateWebhookReq>,
+                        state: Data<AppState>)
+                        -> HttpResponse {
+    let (origin, id) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
+
+    let webhook_id = match parse_id("webhook", &id) {
+        Ok(webhook_id) => webhook_id,
+        Err(resp) => return resp,
+    };
+
+    if let Some(ref url) = body.url {
+        if let Err(resp) = validate_url(url) {
+            return resp;
+        }
+    }
+
+    let events = match body.events {
+        Some(ref events) => {
+            match validate_events(events) {
+                Ok(events) => Some(events),
+                Err(resp) => return resp,
+            }
+        }
+        None => None,
+    };
+
+    let encrypted = match body.secret {
+        Some(ref secret) => {
+            if let Err(resp) = validate_secret(secret) {
+                return resp;
+            }
+            match encrypt_secret(&state, secret) {
+                Ok(encrypted) => Some(encrypted),
+                Err(err) => return err.into(),
+            }
+        }
+        None => None,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let before = match Webhook::get(&origin, webhook_id, &mut conn) {
+        Ok(webhook) => webhook,
+        Err(err) => {
+            debug!("Failed to get webhook, err={}", err);
+            return Error::DieselError(err).into();
+        }
+    };
+
+    let changes = UpdateWebhook { url: body.url.as_deref(),
+                                  secret: encrypted.as_deref(),
+                                  events,
+                                  active: body.active };
+
+    match Webhook::update(webhook_id, &changes, &mut conn) {
+        Ok(webhook) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::WebhookUpdate,
+                          &id,
+                          serde_json::to_value(&before).ok(),
+                          serde_json::to_value(&webhook).ok(),
+                          &mut conn);
+            HttpResponse::Ok().json(webhook)
+        }
+        Err(err) => {
+            debug!("Failed to update webhook, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn delete_webhook(req: HttpRequest,
+                        path: Path<(String, String)>,
+                        state: Data<AppState>)
+                        -> HttpResponse {
+    let (origin, id) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
+
+    let webhook_id = match parse_id("webhook", &id) {
+        Ok(webhook_id) => webhook_id,
+        Err(resp) => return resp,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    let before = Webhook::get(&origin, webhook_id, &mut conn).ok();
+
+    match Webhook::delete(&origin, webhook_id, &mut conn) {
+        Ok(0) => HttpResponse::NotFound().finish(),
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::WebhookDelete,
+                          &id,
+                          before.and_then(|webhook| serde_json::to_value(webhook).ok()),
+                          None,
+                          &mut conn);
+            HttpResponse::NoContent().finish()
+        }
+        Err(err) => {
+            debug!("Failed to delete webhook, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn list_webhook_deliveries(req: HttpRequest,
+                                 path: Path<(String, String)>,
+                                 query: Query<DeliveriesQuery>,
+                                 state: Data<AppState>)
+                                 -> HttpResponse {
+    let (origin, id) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
+
+    let webhook_id = match parse_id("webhook", &id) {
+        Ok(webhook_id) => webhook_id,
+        Err(resp) => return resp,
+    };
+
+    let limit = query.limit
+                     .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+                     .clamp(1, MAX_DELIVERY_LIMIT);
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    if let Err(err) = Webhook::get(&origin, webhook_id, &mut conn) {
+        debug!("Failed to get webhook, err={}", err);
+        return Error::DieselError(err).into();
+    }
+
+    match WebhookDelivery::list(webhook_id, limit, &mut conn) {
+        Ok(deliveries) => {
+            HttpResponse::Ok().append_header((http::header::CACHE_CONTROL,
+                                              headers::Cache::NoCache.to_string()))
+                              .json(deliveries)
+        }
+        Err(err) => {
+            debug!("Failed to list webhook deliveries, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+async fn redeliver_webhook_delivery(req: HttpRequest,
+                                    path: Path<(String, String, String)>,
+                                    state: Data<AppState>)
+                                    -> HttpResponse {
+    let (origin, id, delivery) = path.into_inner();
+
+    if let Err(err) = authorize_permission(&req, &origin, &Permission::Settings) {
+        return err.into();
+    }
+
+    let webhook_id = match parse_id("webhook", &id) {
+        Ok(webhook_id) => webhook_id,
+        Err(resp) => return resp,
+    };
+
+    let delivery_id = match parse_id("delivery", &delivery) {
+        Ok(delivery_id) => delivery_id,
+        Err(resp) => return resp,
+    };
+
+    let mut conn = match state.db.get_conn().map_err(Error::DbError) {
+        Ok(conn_ref) => conn_ref,
+        Err(err) => return err.into(),
+    };
+
+    if let Err(err) = Webhook::get(&origin, webhook_id, &mut conn).and_then(|_| {
+                          WebhookDelivery::get(webhook_id, delivery_id, &mut conn)
+                      })
+    {
+        debug!("Failed to get webhook delivery, err={}", err);
+        return Error::DieselError(err).into();
+    }
+
+    match WebhookDelivery::redeliver(delivery_id, &mut conn) {
+        Ok(0) => {
+            let body = Bytes::from_static(b"Delivery is already being sent");
+            HttpResponse::with_body(StatusCode::CONFLICT, BoxBody::new(body))
+        }
+        Ok(_) => {
+            audit::record(&req,
+                          Some(&origin),
+                          AuditAction::WebhookRedeliver,
+                          &id,
+                          None,
+                          Some(json!({ "delivery": delivery })),
+                          &mut conn);
+            HttpResponse::Accepted().finish()
+        }
+        Err(err) => {
+            debug!("Failed to redeliver webhook delivery, err={}", err);
+            Error::DieselError(err).into()
+        }
+    }
+}
+
+// Internal helpers
+//
+fn parse_id(kind: &str, value: &str) -> std::result::Result<i64, HttpResponse> {
+    value.parse::<i64>().map_err(|_| {
+                            let body = Bytes::from(format!("Invalid {} id '{}'", kind, value));
+                            HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                                    BoxBody::new(body))
+                        })
+}
+
+fn validate_url(url: &str) -> std::result::Result<(), HttpResponse> {
+    let body = match Url::parse(url) {
+        Ok(parsed) if parsed.scheme() == "https" || parsed.scheme() == "http" => {
+            match webhooks::resolve_destination(url) {
+                Ok(_) => return Ok(()),
+                Err(err) => Bytes::from(err.to_string()),
+            }
+        }
+        _ => Bytes::from(format!("Webhook url '{}' must be an http or https url", url)),
+    };
+    Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body)))
+}
+
+// Events are validated and de-duplicated, and stored by name so the filter can be matched in SQL
+fn validate_events(events: &[String]) -> std::result::Result<Vec<String>, HttpResponse> {
+    if events.is_empty() {
+        let body = Bytes::from_static(b"At least one webhook event is required");
+        return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body)));
+    }
+
+    let mut names = BTreeSet::new();
+    for event in events {
+        match WebhookEvent::from_str(event) {
+            Ok(event) => {
+                names.insert(event.to_string());
+            }
+            Err(err) => {
+                let body = Bytes::from(err);
+                return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY,
+                                                   BoxBody::new(body)));
+            }
+        }
+    }
+    Ok(names.into_iter().collect())
+}
+
+fn validate_secret(secret: &str) -> std::result::Result<(), HttpResponse> {
+    if secret.len() < MIN_SECRET_LEN {
+        let body = Bytes::from(format!("Webhook secret must be at least {} characters",
+                                       MIN_SECRET_LEN));
+        return Err(HttpResponse::with_body(StatusCode::UNPROCESSABLE_ENTITY, BoxBody::new(body)));
+    }
+    Ok(())
+}
+
+fn generate_secret() -> String {
+    rand::rng().random::<[u8; 32]>()
+               .iter()
+               .map(|b| format!("{:02x}", b))
+               .collect()
+}
+
+fn encrypt_secret(state: &AppState, secret: &str) -> std::result::Result<String, Error> {
+    crypto::encrypt(&state.config.api.key_path, secret.as_bytes()).map(|(encrypted, _)| encrypted)
+                                                                  .map_err(Error::BuilderCore)
+}
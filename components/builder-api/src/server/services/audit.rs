@@ -0,0 +1,98 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The audit log.
+//!
+//! Every change made through the api is recorded with the account that made it, what it
+//! touched, the state before and after where there is one, and where the request came from.
+//! The log is append-only: the table rejects updates and deletes.
+use std::net::SocketAddr;
+
+use actix_web::{HttpMessage,
+                HttpRequest};
+use diesel::pg::PgConnection;
+use serde_json::Value;
+
+use crate::{db::models::{account::Account,
+                         audit::{AuditAction,
+                                 AuditEvent,
+                                 NewAuditEvent},
+                         channel::PackageChannelTrigger},
+            protocol::originsrv,
+            server::helpers::trigger_from_request_model};
+
+/// Records a change made by the request's session. As with webhooks, a failure to write the
+/// record is logged and never fails the request that made the change.
+pub fn record(req: &HttpRequest,
+              origin: Option<&str>,
+              action: AuditAction,
+              target: &str,
+              before: Option<Value>,
+              after: Option<Value>,
+              conn: &mut PgConnection) {
+    let (actor_id, actor_name) = match req.extensions().get::<originsrv::Session>() {
+        Some(session) => (session.id() as i64, session.name().to_string()),
+        None => (0, String::new()),
+    };
+    let source_ip = source_ip(req);
+
+    let event = NewAuditEvent { origin,
+                                actor_id,
+                                actor_name: &actor_name,
+                                action,
+                                target,
+                                before,
+                                after,
+                                source_ip: source_ip.as_deref(),
+                                trigger: trigger_from_request_model(req) };
+    create(&event, conn);
+}
+
+/// Records a change made on an account's behalf outside of any request it made, such as the
+/// memberships its identity provider's groups grant it when it signs in.
+pub fn record_for_account(account: &Account,
+                          origin: Option<&str>,
+                          action: AuditAction,
+                          target: &str,
+                          before: Option<Value>,
+                          after: Option<Value>,
+                          conn: &mut PgConnection) {
+    let event = NewAuditEvent { origin,
+                                actor_id: account.id,
+                                actor_name: &account.name,
+                                action,
+                                target,
+                                before,
+                                after,
+                                source_ip: None,
+                                trigger: PackageChannelTrigger::Unknown };
+    create(&event, conn);
+}
+
+fn create(event: &NewAuditEvent, conn: &mut PgConnection) {
+    if let Err(err) = AuditEvent::create(event, conn) {
+        warn!("Unable to record {:?} of {} in the audit log, err={}",
+              event.action, event.target, err);
+    }
+}
+
+// The client's address, as reported by the proxy in front of the api when there is one.
+fn source_ip(req: &HttpRequest) -> Option<String> {
+    let info = req.connection_info();
+    let addr = info.realip_remote_addr()?;
+    match addr.parse::<SocketAddr>() {
+        Ok(socket) => Some(socket.ip().to_string()),
+        Err(_) => Some(addr.to_string()),
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Channel consistency checking.
+//!
+//! A channel is consistent when every transitive dependency of every package in it can be
+//! resolved from the same channel of the dependency's origin, or from one of the configured
+//! fallback channels, and none of those dependencies have been deleted or hidden. Supervisors
+//! following an inconsistent channel will fail to update, so the check can be run on demand
+//! or periodically against a configured list of channels.
+use std::{collections::{hash_map::Entry,
+                        HashMap,
+                        HashSet},
+          time::Duration};
+
+use actix_web::web;
+use diesel::pg::PgConnection;
+
+use crate::{config::ChannelCheckCfg,
+            db::{models::{channel::{Channel,
+                                    ListAllChannelPackages},
+                          package::{BuilderPackageIdent,
+                                    GetPackageGroup,
+                                    Package,
+                                    PackageVisibility}},
+                 DbPool},
+            hab_core::ChannelIdent,
+            server::error::{Error,
+                            Result}};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyProblemKind {
+    Missing,
+    Deleted,
+    Yanked,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyProblem {
+    pub package:    BuilderPackageIdent,
+    pub dependency: BuilderPackageIdent,
+    pub reason:     DependencyProblemKind,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChannelCheckReport {
+    pub origin:    String,
+    pub channel:   String,
+    pub fallbacks: Vec<String>,
+    pub checked:   usize,
+    pub problems:  Vec<DependencyProblem>,
+}
+
+pub fn check_channel(origin: &str,
+                     channel: &ChannelIdent,
+                     fallbacks: &[String],
+                     conn: &mut PgConnection)
+                     -> Result<ChannelCheckReport> {
+    let idents = channel_members(origin, channel, conn)?;
+
+    let group = GetPackageGroup { pkgs:       idents.into_iter().collect(),
+                                  visibility: PackageVisibility::all(), };
+    let packages = Package::get_group(group, conn).map_err(Error::DieselError)?;
+
+    let deps: Vec<BuilderPackageIdent> = packages.iter()
+                                                 .flat_map(|p| p.tdeps.iter().cloned())
+                                                 .collect::<HashSet<_>>()
+                                                 .into_iter()
+                                                 .collect();
+    let hidden: HashMap<String, bool> =
+        Package::list_hidden_states(&deps, conn).map_err(Error::DieselError)?
+                                                 .into_iter()
+                                                 .map(|(ident, hidden)| (ident.to_string(), hidden))
+                                                 .collect();
+
+    let mut search = vec![channel.as_str()];
+    search.extend(fallbacks.iter().map(String::as_str));
+    let mut members = HashMap::new();
+    let mut problems = Vec::new();
+
+    for package in &packages {
+        for dep in &package.tdeps {
+            let known = hidden.get(&dep.to_string()).copied();
+            let resolvable = || is_resolvable(dep, &search, &mut members, conn);
+            let reason = match dependency_problem(known, resolvable)? {
+                Some(reason) => reason,
+                None => continue,
+            };
+
+            problems.push(DependencyProblem { package: package.ident.clone(),
+                                              dependency: dep.clone(),
+                                              reason });
+        }
+    }
+
+    Ok(ChannelCheckReport { origin: origin.to_string(),
+                            channel: channel.to_string(),
+                            fallbacks: fallbacks.to_vec(),
+                            checked: packages.len(),
+                            problems })
+}
+
+// What keeps a dep from being installed, given whether it is hidden, or None when it isn't known
+// to the depot. Whether it can be resolved from the channels searched is only asked of deps that
+// are still available.
+fn dependency_problem<F>(hidden: Option<bool>,
+                         resolvable: F)
+                         -> Result<Option<DependencyProblemKind>>
+    where F: FnOnce() -> Result<bool>
+{
+    match hidden {
+        None => Ok(Some(DependencyProblemKind::Deleted)),
+        Some(true) => Ok(Some(DependencyProblemKind::Yanked)),
+        Some(false) if !resolvable()? => Ok(Some(DependencyProblemKind::Missing)),
+        Some(false) => Ok(None),
+    }
+}
+
+// Membership of each (origin, channel) pair is loaded at most once per check, since most
+// dependencies share a handful of origins
+fn is_resolvable(dep: &BuilderPackageIdent,
+                 channels: &[&str],
+                 members: &mut HashMap<(String, String), HashSet<String>>,
+                 conn: &mut PgConnection)
+                 -> Result<bool> {
+    for name in channels {
+        let set = match members.entry((dep.origin.clone(), name.to_string())) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(channel_members(&dep.origin, &ChannelIdent::from(*name), conn)?)
+            }
+        };
+        if set.contains(&dep.to_string()) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn channel_members(origin: &str,
+                   channel: &ChannelIdent,
+                   conn: &mut PgConnection)
+                   -> Result<HashSet<String>> {
+    let lacp = ListAllChannelPackages { visibility: &PackageVisibility::all(),
+                                        channel,
+                                        origin };
+    let idents = Channel::list_all_packages(&lacp, conn).map_err(Error::DieselError)?;
+    Ok(idents.into_iter().map(|i| i.to_string()).collect())
+}
+
+// Runs the configured checks on an interval for the life of the server, logging each problem
+// found so that broken channels surface before supervisors fail to update from them.
+pub fn start(config: ChannelCheckCfg, db: DbPool) {
+    if config.interval == 0 || config.channels.is_empty() {
+        return;
+    }
+
+    info!("Checking {} channel(s) for consistency every {}s",
+          config.channels.len(),
+          config.interval);
+
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(Duration::from_secs(config.interval));
+        loop {
+            interval.tick().await;
+
+            let cfg = config.clone();
+            let db = db.clone();
+            if let Err(err) = web::block(move || run_checks(&cfg, &db)).await {
+                warn!("Channel consistency check did not complete, err={}", err);
+            }
+        }
+    });
+}
+
+fn run_checks(config: &ChannelCheckCfg, db: &DbPool) {
+    let mut conn = match db.get_conn().map_err(Error::DbError) {
+        Ok(conn) => conn,
+        Err(err) => {
+            warn!("Unable to check channels, err={}", err);
+            return;
+        }
+    };
+
+    for entry in &config.channels {
+        let (origin, channel) = match entry.split_once('/') {
+            Some(parts) => parts,
+            None => {
+                warn!("Skipping channel check for {}, expected origin/channel", entry);
+                continue;
+            }
+        };
+
+        match check_channel(origin,
+                            &ChannelIdent::from(channel),
+                            &config.fallback_channels,
+                            &mut conn)
+        {
+            Ok(report) => {
+                for problem in &report.problems {
+                    warn!("Channel {}/{}: {} depends on {} which is {:?}",
+                          report.origin,
+                          report.channel,
+                          problem.package.to_string(),
+                          problem.dependency.to_string(),
+                          problem.reason);
+                }
+                debug!("Checked {} packages in {}/{}, {} problem(s)",
+                       report.checked,
+                       report.origin,
+                       report.channel,
+                       report.problems.len());
+            }
+            Err(err) => warn!("Unable to check channel {}, err={}", entry, err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deps_unknown_to_the_depot_are_deleted() {
+        let problem = dependency_problem(None, || panic!("deleted deps are not resolved"));
+        assert_eq!(problem.unwrap(), Some(DependencyProblemKind::Deleted));
+    }
+
+    #[test]
+    fn hidden_deps_are_yanked() {
+        let problem = dependency_problem(Some(true), || panic!("yanked deps are not resolved"));
+        assert_eq!(problem.unwrap(), Some(DependencyProblemKind::Yanked));
+    }
+
+    #[test]
+    fn deps_missing_from_the_searched_channels_are_missing() {
+        assert_eq!(dependency_problem(Some(false), || Ok(false)).unwrap(),
+                   Some(DependencyProblemKind::Missing));
+        assert_eq!(dependency_problem(Some(false), || Ok(true)).unwrap(), None);
+    }
+
+    #[test]
+    fn resolution_errors_are_passed_on() {
+        assert!(dependency_problem(Some(false), || Err(Error::BadRequest)).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Deletes ephemeral channels once their expiry has passed.
+//!
+//! Expired channels are removed along with their package memberships, and the cached channel
+//! contents are cleared so clients stop seeing them straight away.
+use std::time::Duration;
+
+use actix_web::web;
+
+use crate::{config::MemcacheCfg,
+            db::{models::channel::Channel,
+                 DbPool},
+            hab_core::ChannelIdent,
+            server::{error::Error,
+                     services::memcache::MemcacheClient}};
+
+const REAPER_INTERVAL_SECS: u64 = 300;
+
+pub fn start(memcache_cfg: MemcacheCfg, db: DbPool) {
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(Duration::from_secs(REAPER_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+
+            let cfg = memcache_cfg.clone();
+            let db = db.clone();
+            if let Err(err) = web::block(move || reap_expired_channels(&cfg, &db)).await {
+                warn!("Channel reaper did not complete, err={}", err);
+            }
+        }
+    });
+}
+
+fn reap_expired_channels(memcache_cfg: &MemcacheCfg, db: &DbPool) {
+    let mut conn = match db.get_conn().map_err(Error::DbError) {
+        Ok(conn) => conn,
+        Err(err) => {
+            warn!("Unable to reap expired channels, err={}", err);
+            return;
+        }
+    };
+
+    let expired = match Channel::list_expired(&mut conn) {
+        Ok(expired) => expired,
+        Err(err) => {
+            warn!("Unable to list expired channels, err={}", err);
+            return;
+        }
+    };
+
+    if expired.is_empty() {
+        return;
+    }
+
+    let mut memcache = MemcacheClient::new(memcache_cfg);
+    for channel in expired.iter() {
+        match Channel::delete_expired(channel.id, &mut conn) {
+            Ok(0) => {
+                debug!("Channel {}/{} was extended or frozen, skipping",
+                       channel.origin, channel.name)
+            }
+            Ok(_) => {
+                info!("Deleted expired channel {}/{}", channel.origin, channel.name);
+                memcache.clear_cache_for_channel(&channel.origin,
+                                                 &ChannelIdent::from(channel.name.as_str()));
+            }
+            Err(err) => {
+                warn!("Unable to delete expired channel {}/{}, err={}",
+                      channel.origin,
+                      channel.name,
+                      err)
+            }
+        }
+    }
+}
@@ -0,0 +1,432 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Live depot events for server-sent event streams.
+//!
+//! While anyone is listening, each api instance polls for the uploads and channel changes
+//! recorded since its last look and fans them out to the streams it is serving. Reading them back
+//! from the database, rather than hooking the handlers, means every stream sees the activity of
+//! every instance. Each poll looks back a little before the newest event it has seen, since a
+//! change is only visible once its transaction commits, which can be after newer ones; events
+//! already sent are remembered for that long so that none is sent twice. A reader sees an event
+//! under the same rules as the events list: public packages are seen by everyone, private ones by
+//! members of the package's origin and by whoever made the change.
+use std::{cmp,
+          collections::{HashMap,
+                        HashSet},
+          sync::{Arc,
+                 Mutex},
+          time::Duration};
+
+use actix_web::web::{self,
+                     Bytes};
+use chrono::{NaiveDateTime,
+             Utc};
+use futures::channel::mpsc;
+
+use crate::{db::{models::{channel::{AuditPackage,
+                                    AuditPackageEvent,
+                                    PackageChannelOperation},
+                          origin::OriginMember,
+                          package::{BuilderPackageIdent,
+                                    BuilderPackageTarget,
+                                    Package,
+                                    PackageVisibility}},
+                 DbPool},
+            hab_core::ChannelIdent,
+            server::error::{Error,
+                            Result}};
+
+const POLL_INTERVAL_MILLIS: u64 = 1000;
+const BATCH_SIZE: i64 = 500;
+// How far before the newest event seen each poll starts reading again
+const OVERLAP_SECS: i64 = 10;
+// Sent as a comment every this many polls, so that proxies keep idle streams open and streams
+// whose client has gone away are noticed.
+const KEEP_ALIVE_POLLS: u64 = 15;
+// How far a reader may fall behind before its stream is closed. Clients reconnect on their own.
+const STREAM_BUFFER: usize = 256;
+const RETRY_MILLIS: u64 = 5000;
+
+#[derive(Serialize)]
+struct UploadEvent<'a> {
+    origin:        &'a str,
+    channel:       &'a str,
+    package_ident: &'a BuilderPackageIdent,
+    target:        &'a BuilderPackageTarget,
+    created_at:    Option<NaiveDateTime>,
+}
+
+struct DepotEvent {
+    name:           &'static str,
+    origin:         String,
+    channel:        String,
+    package_origin: String,
+    visibility:     PackageVisibility,
+    actor_id:       i64,
+    created_at:     Option<NaiveDateTime>,
+    data:           String,
+}
+
+impl DepotEvent {
+    fn message(&self) -> Bytes {
+        Bytes::from(format!("event: {}\ndata: {}\n\n", self.name, self.data))
+    }
+}
+
+// Where polling picks up: the newest upload and channel change seen, and the events seen within
+// the overlap before them. Nothing recorded before the stream started is sent.
+#[derive(Clone)]
+struct Cursor {
+    start:   NaiveDateTime,
+    uploads: NaiveDateTime,
+    changes: NaiveDateTime,
+    seen:    HashMap<String, NaiveDateTime>,
+}
+
+impl Cursor {
+    fn now() -> Cursor {
+        let now = Utc::now().naive_utc();
+        Cursor { start:   now,
+                 uploads: now,
+                 changes: now,
+                 seen:    HashMap::new(), }
+    }
+
+    // Where to read from again, given the newest event of a kind that has been seen
+    fn since(&self, newest: NaiveDateTime) -> NaiveDateTime {
+        cmp::max(self.start, newest - chrono::Duration::seconds(OVERLAP_SECS))
+    }
+
+    // Whether the event hasn't been seen yet, remembering it
+    fn first_sighting(&mut self, key: String, created_at: NaiveDateTime) -> bool {
+        self.seen.insert(key, created_at).is_none()
+    }
+
+    // Forgets the events that are too old to be read again
+    fn prune(&mut self) {
+        let horizon = cmp::min(self.since(self.uploads), self.since(self.changes));
+        self.seen.retain(|_, created_at| *created_at >= horizon);
+    }
+}
+
+struct Subscriber {
+    account_id: Option<i64>,
+    origin:     Option<String>,
+    channel:    Option<String>,
+    sender:     mpsc::Sender<Bytes>,
+}
+
+impl Subscriber {
+    fn wants(&self, event: &DepotEvent, members: &HashSet<(String, i64)>) -> bool {
+        if matches!(&self.origin, Some(origin) if *origin != event.origin)
+           || matches!(&self.channel, Some(channel) if *channel != event.channel)
+        {
+            return false;
+        }
+
+        if event.visibility == PackageVisibility::Public {
+            return true;
+        }
+
+        match self.account_id {
+            Some(account_id) => {
+                account_id == event.actor_id
+                || members.contains(&(event.package_origin.clone(), account_id))
+            }
+            None => false,
+        }
+    }
+}
+
+/// The streams being served by this instance.
+#[derive(Clone, Default)]
+pub struct EventStream {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl EventStream {
+    /// Opens a stream of the events visible to the account, or to anonymous readers when there
+    /// is none, optionally limited to an origin and channel.
+    pub fn subscribe(&self,
+                     account_id: Option<i64>,
+                     origin: Option<String>,
+                     channel: Option<String>)
+                     -> mpsc::Receiver<Bytes> {
+        let (mut sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        // Tells the client how long to wait before reconnecting, and starts the response.
+        let _ = sender.try_send(Bytes::from(format!("retry: {}\n\n", RETRY_MILLIS)));
+
+        self.subscribers.lock().unwrap().push(Subscriber { account_id,
+                                                           origin,
+                                                           channel,
+                                                           sender });
+        receiver
+    }
+
+    // The accounts that currently have a stream open, or None when nobody is listening.
+    fn readers(&self) -> Option<Vec<i64>> {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| !subscriber.sender.is_closed());
+        if subscribers.is_empty() {
+            return None;
+        }
+
+        let account_ids: HashSet<i64> =
+            subscribers.iter()
+                       .filter_map(|subscriber| subscriber.account_id)
+                       .collect();
+        Some(account_ids.into_iter().collect())
+    }
+
+    fn publish(&self, events: &[DepotEvent], members: &HashSet<(String, i64)>) {
+        let messages: Vec<Bytes> = events.iter().map(DepotEvent::message).collect();
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain_mut(|subscriber| {
+                       events.iter()
+                             .zip(messages.iter())
+                             .filter(|(event, _)| subscriber.wants(event, members))
+                             .all(|(_, message)| {
+                                 subscriber.sender.try_send(message.clone()).is_ok()
+                             })
+                   });
+    }
+
+    fn keep_alive(&self) {
+        let comment = Bytes::from_static(b": keep-alive\n\n");
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain_mut(|subscriber| subscriber.sender.try_send(comment.clone()).is_ok());
+    }
+}
+
+pub fn start(db: DbPool) -> EventStream {
+    let stream = EventStream::default();
+    let events = stream.clone();
+
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(Duration::from_millis(POLL_INTERVAL_MILLIS));
+        let mut cursor = Cursor::now();
+        let mut polls: u64 = 0;
+        loop {
+            interval.tick().await;
+            polls += 1;
+
+            let readers = match events.readers() {
+                Some(readers) => readers,
+                None => {
+                    // Nothing is sent for the time nobody was listening.
+                    cursor = Cursor::now();
+                    continue;
+                }
+            };
+
+            let db = db.clone();
+            let from = cursor.clone();
+            match web::block(move || poll(&db, from, &readers)).await {
+                Ok(Ok((found, members, next))) => {
+                    cursor = next;
+                    events.publish(&found, &members);
+                }
+                Ok(Err(err)) => warn!("Unable to poll for depot events, err={}", err),
+                Err(err) => warn!("Depot event poll did not complete, err={}", err),
+            }
+
+            if polls % KEEP_ALIVE_POLLS == 0 {
+                events.keep_alive();
+            }
+        }
+    });
+
+    stream
+}
+
+// The events recorded since the cursor that haven't been sent yet, oldest first, the memberships
+// of the readers in the origins of any private packages among them, and the cursor to poll from
+// next. Events are read a page at a time until there are no more.
+#[allow(clippy::type_complexity)]
+fn poll(db: &DbPool,
+        mut cursor: Cursor,
+        account_ids: &[i64])
+        -> Result<(Vec<DepotEvent>, HashSet<(String, i64)>, Cursor)> {
+    let mut conn = db.get_conn().map_err(Error::DbError)?;
+    let mut events = Vec::new();
+    let unstable = ChannelIdent::unstable().to_string();
+
+    let mut uploads = Vec::new();
+    let (mut after, mut after_id) = (cursor.since(cursor.uploads), 0);
+    loop {
+        let page = Package::list_uploaded_after(after, after_id, BATCH_SIZE, &mut conn)
+            .map_err(Error::DieselError)?;
+        let more = page.len() as i64 == BATCH_SIZE;
+        for upload in page {
+            after_id = upload.id;
+            if let Some(created_at) = upload.created_at {
+                after = created_at;
+                cursor.uploads = cmp::max(cursor.uploads, created_at);
+                if cursor.first_sighting(format!("upload:{}", upload.id), created_at) {
+                    uploads.push(upload);
+                }
+            }
+        }
+        if !more {
+            break;
+        }
+    }
+
+    for upload in uploads {
+        let data = serde_json::to_string(&UploadEvent { origin:        &upload.origin,
+                                                        channel:       &unstable,
+                                                        package_ident: &upload.ident,
+                                                        target:        &upload.target,
+                                                        created_at:    upload.created_at, })?;
+        events.push(DepotEvent { name: "upload",
+                                 origin: upload.origin.clone(),
+                                 channel: unstable.clone(),
+                                 package_origin: upload.origin,
+                                 visibility: upload.visibility,
+                                 actor_id: upload.owner_id,
+                                 created_at: upload.created_at,
+                                 data });
+    }
+
+    let mut changes = Vec::new();
+    let (mut after, mut after_ident) = (cursor.since(cursor.changes), String::new());
+    loop {
+        let page = AuditPackage::list_after(after, &after_ident, BATCH_SIZE, &mut conn)
+            .map_err(Error::DieselError)?;
+        let more = page.len() as i64 == BATCH_SIZE;
+        for change in page {
+            after_ident = change.0.package_ident.to_string();
+            if let Some(created_at) = change.0.created_at {
+                after = created_at;
+                cursor.changes = cmp::max(cursor.changes, created_at);
+                let key = format!("change:{}:{}", created_at, after_ident);
+                if cursor.first_sighting(key, created_at) {
+                    changes.push(change);
+                }
+            }
+        }
+        if !more {
+            break;
+        }
+    }
+
+    for (audit, package_origin, visibility) in changes {
+        let name = match audit.operation {
+            PackageChannelOperation::Promote => "promote",
+            PackageChannelOperation::Demote => "demote",
+        };
+        let origin = audit.origin.clone();
+        let channel = audit.channel.clone();
+        let actor_id = audit.requester_id;
+        let created_at = audit.created_at;
+        let event: AuditPackageEvent = audit.into();
+        events.push(DepotEvent { name,
+                                 origin,
+                                 channel,
+                                 package_origin,
+                                 visibility,
+                                 actor_id,
+                                 created_at,
+                                 data: serde_json::to_string(&event)? });
+    }
+    events.sort_by_key(|event| event.created_at);
+
+    let private_origins: HashSet<&String> =
+        events.iter()
+              .filter(|event| event.visibility != PackageVisibility::Public)
+              .map(|event| &event.package_origin)
+              .collect();
+    let members = if private_origins.is_empty() || account_ids.is_empty() {
+        HashSet::new()
+    } else {
+        let origins: Vec<String> = private_origins.into_iter().cloned().collect();
+        OriginMember::list_memberships(account_ids, &origins, &mut conn)
+            .map_err(Error::DieselError)?
+            .into_iter()
+            .collect()
+    };
+
+    cursor.prune();
+    Ok((events, members, cursor))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn event(visibility: PackageVisibility) -> DepotEvent {
+        DepotEvent { name: "promote",
+                     origin: "core".to_string(),
+                     channel: "stable".to_string(),
+                     package_origin: "core".to_string(),
+                     visibility,
+                     actor_id: 1,
+                     created_at: None,
+                     data: "{}".to_string() }
+    }
+
+    fn subscriber(account_id: Option<i64>,
+                  origin: Option<&str>,
+                  channel: Option<&str>)
+                  -> Subscriber {
+        let (sender, _) = mpsc::channel(1);
+        Subscriber { account_id,
+                     origin: origin.map(str::to_string),
+                     channel: channel.map(str::to_string),
+                     sender }
+    }
+
+    #[test]
+    fn subscribers_only_see_what_they_may() {
+        let members: HashSet<(String, i64)> = vec![("core".to_string(), 2)].into_iter().collect();
+        let public = event(PackageVisibility::Public);
+        let private = event(PackageVisibility::Private);
+
+        assert!(subscriber(None, None, None).wants(&public, &members));
+        assert!(!subscriber(None, None, None).wants(&private, &members));
+        assert!(subscriber(Some(1), None, None).wants(&private, &members));
+        assert!(subscriber(Some(2), None, None).wants(&private, &members));
+        assert!(!subscriber(Some(3), None, None).wants(&private, &members));
+    }
+
+    #[test]
+    fn subscribers_filter_by_origin_and_channel() {
+        let members = HashSet::new();
+        let public = event(PackageVisibility::Public);
+
+        assert!(subscriber(None, Some("core"), Some("stable")).wants(&public, &members));
+        assert!(!subscriber(None, Some("other"), None).wants(&public, &members));
+        assert!(!subscriber(None, None, Some("unstable")).wants(&public, &members));
+    }
+
+    #[test]
+    fn cursor_sends_events_once_within_the_overlap() {
+        let mut cursor = Cursor::now();
+        let seen_at = cursor.start + chrono::Duration::seconds(1);
+        cursor.uploads = seen_at;
+        cursor.changes = seen_at;
+
+        assert!(cursor.first_sighting("upload:1".to_string(), seen_at));
+        assert!(!cursor.first_sighting("upload:1".to_string(), seen_at));
+        assert_eq!(cursor.since(seen_at), cursor.start);
+
+        cursor.uploads = seen_at + chrono::Duration::seconds(OVERLAP_SECS + 1);
+        cursor.changes = cursor.uploads;
+        cursor.prune();
+        assert!(cursor.seen.is_empty());
+    }
+}
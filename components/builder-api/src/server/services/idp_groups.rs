@@ -0,0 +1,279 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Origin memberships granted by the groups of an identity provider.
+//!
+//! The `group_mappings` config grants the members of a group a role in an origin. As accounts
+//! sign in, the memberships their groups grant are added, their roles brought up to date, and
+//! those their groups no longer grant are removed. Memberships granted by invitation are never
+//! touched, and an account holding one keeps it whatever its groups say.
+use std::collections::HashMap;
+
+use diesel::pg::PgConnection;
+
+use crate::{config::GroupMappingCfg,
+            db::models::{account::Account,
+                         audit::AuditAction,
+                         origin::{OriginMember,
+                                  OriginMemberRole}},
+            server::{resources::origins::notify_member_change,
+                     services::audit,
+                     AppState}};
+
+#[derive(Debug, PartialEq)]
+enum MembershipChange {
+    Add(String, OriginMemberRole),
+    Update(String, OriginMemberRole, OriginMemberRole),
+    Remove(String, OriginMemberRole),
+}
+
+/// Brings the memberships an account's groups grant up to date. Failures are logged and never
+/// keep the account from signing in.
+pub fn sync_memberships(account: &Account,
+                        groups: &[String],
+                        state: &AppState,
+                        conn: &mut PgConnection) {
+    let granted = granted_roles(groups, &state.config.group_mappings);
+
+    let memberships = match OriginMember::list_for_account(account.id, conn) {
+        Ok(memberships) => memberships,
+        Err(err) => {
+            warn!("Unable to list the memberships of {}, err={}", account.name, err);
+            return;
+        }
+    };
+
+    for change in membership_changes(granted, memberships) {
+        match change {
+            MembershipChange::Add(origin, role) => {
+                add_membership(account, &origin, role, state, conn)
+            }
+            MembershipChange::Update(origin, role, granted_role) => {
+                update_membership(account, &origin, role, granted_role, state, conn)
+            }
+            MembershipChange::Remove(origin, role) => {
+                remove_membership(account, &origin, role, state, conn)
+            }
+        }
+    }
+}
+
+// Works out how an account's memberships, as (origin, role, idp_managed), have to change to
+// match what its groups grant
+fn membership_changes(mut granted: HashMap<&str, OriginMemberRole>,
+                      memberships: Vec<(String, OriginMemberRole, bool)>)
+                      -> Vec<MembershipChange> {
+    let mut changes = Vec::new();
+
+    for (origin, role, idp_managed) in memberships {
+        let granted_role = granted.remove(origin.as_str());
+        if !idp_managed {
+            continue;
+        }
+
+        match granted_role {
+            Some(granted_role) if granted_role == role => (),
+            Some(granted_role) => {
+                changes.push(MembershipChange::Update(origin, role, granted_role))
+            }
+            None => changes.push(MembershipChange::Remove(origin, role)),
+        }
+    }
+
+    let mut added: Vec<_> = granted.into_iter().collect();
+    added.sort_by_key(|(origin, _)| *origin);
+    changes.extend(added.into_iter()
+                        .map(|(origin, role)| MembershipChange::Add(origin.to_string(), role)));
+    changes
+}
+
+// The highest role any of the groups grants in each origin
+fn granted_roles<'a>(groups: &[String],
+                     mappings: &'a [GroupMappingCfg])
+                     -> HashMap<&'a str, OriginMemberRole> {
+    let mut granted = HashMap::new();
+
+    for mapping in mappings.iter().filter(|mapping| groups.contains(&mapping.group)) {
+        // Origins only change owners by transfer
+        if mapping.role == OriginMemberRole::Owner {
+            warn!("Ignoring the mapping of group {} to the owner of {}",
+                  mapping.group, mapping.origin);
+            continue;
+        }
+
+        let role = granted.entry(mapping.origin.as_str()).or_insert(mapping.role);
+        if mapping.role > *role {
+            *role = mapping.role;
+        }
+    }
+    granted
+}
+
+fn add_membership(account: &Account,
+                  origin: &str,
+                  role: OriginMemberRole,
+                  state: &AppState,
+                  conn: &mut PgConnection) {
+    if let Err(err) = OriginMember::add_idp_managed(origin, account.id, role, conn) {
+        warn!("Unable to add {} to origin {}, err={}", account.name, origin, err);
+        return;
+    }
+
+    state.memcache
+         .borrow_mut()
+         .clear_cache_for_member_role(origin, account.id as u64);
+    audit::record_for_account(account,
+                              Some(origin),
+                              AuditAction::MemberAdd,
+                              &account.name,
+                              None,
+                              Some(json!({ "role": role, "idp_managed": true })),
+                              conn);
+    notify_member_change(origin, "added", &account.name, Some(role), conn);
+}
+
+fn update_membership(account: &Account,
+                     origin: &str,
+                     role: OriginMemberRole,
+                     granted_role: OriginMemberRole,
+                     state: &AppState,
+                     conn: &mut PgConnection) {
+    if let Err(err) = OriginMember::update_member_role(origin, account.id, conn, granted_role) {
+        warn!("Unable to update the role of {} in origin {}, err={}",
+              account.name, origin, err);
+        return;
+    }
+
+    state.memcache
+         .borrow_mut()
+         .clear_cache_for_member_role(origin, account.id as u64);
+    audit::record_for_account(account,
+                              Some(origin),
+                              AuditAction::MemberRoleUpdate,
+                              &account.name,
+                              Some(json!({ "role": role })),
+                              Some(json!({ "role": granted_role })),
+                              conn);
+    notify_member_change(origin, "role_changed", &account.name, Some(granted_role), conn);
+}
+
+fn remove_membership(account: &Account,
+                     origin: &str,
+                     role: OriginMemberRole,
+                     state: &AppState,
+                     conn: &mut PgConnection) {
+    if let Err(err) = OriginMember::delete(origin, &account.name, conn) {
+        warn!("Unable to remove {} from origin {}, err={}",
+              account.name, origin, err);
+        return;
+    }
+
+    state.memcache
+         .borrow_mut()
+         .clear_cache_for_member_role(origin, account.id as u64);
+    audit::record_for_account(account,
+                              Some(origin),
+                              AuditAction::MemberRemove,
+                              &account.name,
+                              Some(json!({ "role": role })),
+                              None,
+                              conn);
+    notify_member_change(origin, "removed", &account.name, None, conn);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn mapping(group: &str, origin: &str, role: OriginMemberRole) -> GroupMappingCfg {
+        GroupMappingCfg { group: group.to_string(),
+                          origin: origin.to_string(),
+                          role }
+    }
+
+    fn groups(groups: &[&str]) -> Vec<String> { groups.iter().map(|g| g.to_string()).collect() }
+
+    fn mappings() -> Vec<GroupMappingCfg> {
+        vec![mapping("platform", "core", OriginMemberRole::Maintainer),
+             mapping("platform-leads", "core", OriginMemberRole::Administrator),
+             mapping("web", "frontend", OriginMemberRole::Member),
+             mapping("founders", "core", OriginMemberRole::Owner),]
+    }
+
+    #[test]
+    fn groups_grant_their_highest_role() {
+        let mappings = mappings();
+        let granted = granted_roles(&groups(&["platform", "platform-leads", "web"]), &mappings);
+        assert_eq!(granted.len(), 2);
+        assert_eq!(granted["core"], OriginMemberRole::Administrator);
+        assert_eq!(granted["frontend"], OriginMemberRole::Member);
+
+        assert!(granted_roles(&groups(&["unmapped"]), &mappings).is_empty());
+    }
+
+    #[test]
+    fn owner_mappings_are_ignored() {
+        let mappings = mappings();
+        assert!(granted_roles(&groups(&["founders"]), &mappings).is_empty());
+
+        let granted = granted_roles(&groups(&["founders", "platform"]), &mappings);
+        assert_eq!(granted["core"], OriginMemberRole::Maintainer);
+    }
+
+    #[test]
+    fn granted_memberships_are_added() {
+        let mappings = mappings();
+        let granted = granted_roles(&groups(&["platform", "web"]), &mappings);
+        assert_eq!(membership_changes(granted, Vec::new()),
+                   vec![MembershipChange::Add("core".to_string(), OriginMemberRole::Maintainer),
+                        MembershipChange::Add("frontend".to_string(), OriginMemberRole::Member),]);
+    }
+
+    #[test]
+    fn idp_managed_memberships_follow_the_groups() {
+        let mappings = mappings();
+        let granted = granted_roles(&groups(&["platform-leads"]), &mappings);
+        let memberships = vec![("core".to_string(), OriginMemberRole::Maintainer, true),
+                               ("frontend".to_string(), OriginMemberRole::Member, true),];
+        assert_eq!(membership_changes(granted, memberships),
+                   vec![MembershipChange::Update("core".to_string(),
+                                                 OriginMemberRole::Maintainer,
+                                                 OriginMemberRole::Administrator),
+                        MembershipChange::Remove("frontend".to_string(),
+                                                 OriginMemberRole::Member),]);
+    }
+
+    #[test]
+    fn unchanged_memberships_are_left_alone() {
+        let mappings = mappings();
+        let granted = granted_roles(&groups(&["platform"]), &mappings);
+        let memberships = vec![("core".to_string(), OriginMemberRole::Maintainer, true)];
+        assert_eq!(membership_changes(granted, memberships), Vec::new());
+    }
+
+    #[test]
+    fn manual_memberships_are_left_alone() {
+        let mappings = mappings();
+
+        // Granted by invitation, so the groups neither change its role nor add it again
+        let granted = granted_roles(&groups(&["platform-leads"]), &mappings);
+        let memberships = vec![("core".to_string(), OriginMemberRole::Member, false)];
+        assert_eq!(membership_changes(granted, memberships), Vec::new());
+
+        // Nor is it removed once the groups no longer grant it
+        let memberships = vec![("core".to_string(), OriginMemberRole::Owner, false),
+                               ("other".to_string(), OriginMemberRole::Member, false),];
+        assert_eq!(membership_changes(HashMap::new(), memberships), Vec::new());
+    }
+}
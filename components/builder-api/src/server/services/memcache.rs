@@ -127,7 +127,16 @@ impl MemcacheClient {
     }
 
     pub fn clear_cache_for_member_role(&mut self, origin: &str, account_id: u64) {
-        self.delete_role_key(&member_role_ns_key(origin, account_id));
+        let key = self.member_role_key(origin, account_id);
+        self.delete_role_key(&key);
+        let key = self.member_permissions_key(origin, account_id);
+        self.delete_role_key(&key);
+    }
+
+    // Team and custom role changes can change the access of any of the origin's members, so the
+    // cached roles and permissions of the whole origin are dropped at once
+    pub fn clear_cache_for_origin_roles(&mut self, origin: &str) {
+        self.reset_namespace(&roles_ns_key(origin));
     }
 
     pub fn clear_cache_for_channel(&mut self, origin: &str, channel: &ChannelIdent) {
@@ -169,10 +178,13 @@ impl MemcacheClient {
         };
     }
 
+    /// How long, in seconds, a session is cached for unless told otherwise.
+    pub fn session_ttl(&self) -> u32 { self.ttl * 60 }
+
     pub fn set_session(&mut self, token: &str, session: &Session, ttl: Option<u32>) {
         let computed_ttl = match ttl {
             Some(ttl) => ttl,
-            None => self.ttl * 60,
+            None => self.session_ttl(),
         };
 
         match self.cli.set(&hash_key(token),
@@ -184,6 +196,23 @@ impl MemcacheClient {
         };
     }
 
+    // Web sessions are only ever held in the cache, so rather than finding and dropping those of
+    // a disabled account, the account is marked for as long as any of them may be cached
+    pub fn set_account_disabled(&mut self, account_id: u64, ttl: u32) {
+        match self.cli.set(&account_disabled_key(account_id), true, ttl) {
+            Ok(_) => trace!("Saved disabled account {} to memcached!", account_id),
+            Err(e) => warn!("Failed to save disabled account to memcached: {}", e),
+        }
+    }
+
+    pub fn is_account_disabled(&mut self, account_id: u64) -> bool {
+        self.get_bool(&account_disabled_key(account_id)).unwrap_or(false)
+    }
+
+    pub fn clear_account_disabled(&mut self, account_id: u64) {
+        self.delete_role_key(&account_disabled_key(account_id));
+    }
+
     pub fn set_origin_member(&mut self, origin: &str, account_id: u64, val: bool) {
         let key = format!("member:{}/{}", origin, account_id);
 
@@ -218,7 +247,7 @@ impl MemcacheClient {
                origin,
                account_id);
 
-        let key = member_role_ns_key(origin, account_id);
+        let key = self.member_role_key(origin, account_id);
         let start_time = Instant::now();
         let ret = self.get_string(&key);
         let duration_millis = start_time.elapsed().as_millis();
@@ -230,7 +259,7 @@ impl MemcacheClient {
     }
 
     pub fn set_origin_member_role(&mut self, origin: &str, account_id: u64, role: &str) {
-        let key = member_role_ns_key(origin, account_id);
+        let key = self.member_role_key(origin, account_id);
         match self.cli.set(&key, role, self.ttl * 60) {
             Ok(_) => {
                 debug!("Saved origin role membership {}/{}/{} to memcached!",
@@ -240,6 +269,39 @@ impl MemcacheClient {
         }
     }
 
+    pub fn get_origin_member_permissions(&mut self,
+                                         origin: &str,
+                                         account_id: u64)
+                                         -> Option<String> {
+        trace!("Getting origin member permissions for {} {} from memcached",
+               origin,
+               account_id);
+
+        let key = self.member_permissions_key(origin, account_id);
+        let start_time = Instant::now();
+        let ret = self.get_string(&key);
+        let duration_millis = start_time.elapsed().as_millis();
+        trace!("Memcache get_origin_member_permissions time: {} ms",
+               duration_millis);
+        Histogram::MemcacheCallTime.set(duration_millis as f64);
+
+        ret
+    }
+
+    pub fn set_origin_member_permissions(&mut self,
+                                         origin: &str,
+                                         account_id: u64,
+                                         permissions: &str) {
+        let key = self.member_permissions_key(origin, account_id);
+        match self.cli.set(&key, permissions, self.ttl * 60) {
+            Ok(_) => {
+                debug!("Saved origin member permissions {}/{}/{} to memcached!",
+                       origin, account_id, permissions);
+            }
+            Err(e) => warn!("Failed to save origin member permissions to memcached: {}", e),
+        }
+    }
+
     fn package_namespace(&mut self, origin: &str, name: &str) -> String {
         self.get_namespace(&package_ns_key(origin, name))
     }
@@ -248,6 +310,18 @@ impl MemcacheClient {
         self.get_namespace(&channel_ns_key(origin, channel))
     }
 
+    fn member_role_key(&mut self, origin: &str, account_id: u64) -> String {
+        let roles_namespace = self.get_namespace(&roles_ns_key(origin));
+        format!("{}:{}", member_role_ns_key(origin, account_id), roles_namespace)
+    }
+
+    fn member_permissions_key(&mut self, origin: &str, account_id: u64) -> String {
+        let roles_namespace = self.get_namespace(&roles_ns_key(origin));
+        format!("{}:{}",
+                member_permissions_ns_key(origin, account_id),
+                roles_namespace)
+    }
+
     fn get_namespace(&mut self, namespace_key: &str) -> String {
         match self.get_string(namespace_key) {
             Some(value) => value,
@@ -310,6 +384,14 @@ fn member_role_ns_key(origin: &str, account_id: u64) -> String {
     format!("member_role:{}/{}", origin, account_id)
 }
 
+fn member_permissions_ns_key(origin: &str, account_id: u64) -> String {
+    format!("member_permissions:{}/{}", origin, account_id)
+}
+
+fn roles_ns_key(origin: &str) -> String { format!("roles:{}", origin) }
+
+fn account_disabled_key(account_id: u64) -> String { format!("account_disabled:{}", account_id) }
+
 fn hash_key(key: &str) -> String {
     let mut hasher = Sha512::new();
     hasher.update(key);
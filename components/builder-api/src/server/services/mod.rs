@@ -1,3 +1,11 @@
+pub mod audit;
+pub mod channel_check;
+pub mod channel_reaper;
+pub mod event_stream;
+pub mod idp_groups;
 pub mod memcache;
 pub mod metrics;
+pub mod promotion_scheduler;
 pub mod s3;
+pub mod webhooks;
+pub mod workload_identity;
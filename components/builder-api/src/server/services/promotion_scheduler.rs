@@ -0,0 +1,77 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runs scheduled channel promotions once they come due.
+//!
+//! Every api instance runs the scheduler. Due promotions are claimed in a single update before
+//! they are run, so each one is only picked up by one instance.
+use std::time::Duration;
+
+use actix_web::web;
+
+use crate::{config::MemcacheCfg,
+            db::{models::scheduled_promotion::ScheduledPromotion,
+                 DbPool},
+            server::{error::Error,
+                     resources::channels,
+                     services::memcache::MemcacheClient}};
+
+const SCHEDULER_INTERVAL_SECS: u64 = 60;
+
+pub fn start(memcache_cfg: MemcacheCfg, db: DbPool) {
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(Duration::from_secs(SCHEDULER_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+
+            let cfg = memcache_cfg.clone();
+            let db = db.clone();
+            if let Err(err) = web::block(move || run_due_promotions(&cfg, &db)).await {
+                warn!("Scheduled promotions did not complete, err={}", err);
+            }
+        }
+    });
+}
+
+fn run_due_promotions(memcache_cfg: &MemcacheCfg, db: &DbPool) {
+    let mut conn = match db.get_conn().map_err(Error::DbError) {
+        Ok(conn) => conn,
+        Err(err) => {
+            warn!("Unable to run scheduled promotions, err={}", err);
+            return;
+        }
+    };
+
+    let due = match ScheduledPromotion::claim_due(&mut conn) {
+        Ok(due) => due,
+        Err(err) => {
+            warn!("Unable to claim scheduled promotions, err={}", err);
+            return;
+        }
+    };
+
+    if due.is_empty() {
+        return;
+    }
+
+    let mut memcache = MemcacheClient::new(memcache_cfg);
+    for scheduled in due.iter() {
+        debug!("Running scheduled promotion {} of {} to {}/{}",
+               scheduled.id,
+               scheduled.ident.to_string(),
+               scheduled.origin,
+               scheduled.channel);
+        channels::run_scheduled_promotion(scheduled, &mut memcache, &mut conn);
+    }
+}
@@ -0,0 +1,363 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Origin webhooks.
+//!
+//! Events are recorded as pending deliveries, one per subscribed webhook, in the same request
+//! that caused them. Every api instance then sends due deliveries on an interval, signing each
+//! body with the webhook's secret, which is stored encrypted with the builder key. Failures are
+//! retried with exponential backoff until the configured number of attempts is used up.
+//! Deliveries are kept as the webhook's history.
+//!
+//! Webhook hosts have to resolve to public addresses only, both when a webhook is saved and
+//! each time a delivery is sent, and redirects are never followed, so a webhook can't be used
+//! to reach services on Builder's own network.
+use std::{cmp,
+          net::{IpAddr,
+                SocketAddr,
+                ToSocketAddrs},
+          time::Duration};
+
+use actix_web::web;
+use chrono::{DateTime,
+             Utc};
+use diesel::pg::PgConnection;
+use habitat_core::crypto::keys::KeyCache;
+use openssl::{hash::MessageDigest,
+              pkey::PKey,
+              sign::Signer};
+use reqwest::{blocking::Client,
+              header::CONTENT_TYPE,
+              redirect::Policy,
+              StatusCode,
+              Url};
+
+use crate::{bldr_core::crypto,
+            config::WebhookCfg,
+            db::{models::webhook::{NewWebhookDelivery,
+                                   Webhook,
+                                   WebhookDelivery,
+                                   WebhookDeliveryState,
+                                   WebhookEvent},
+                 DbPool},
+            server::error::{Error,
+                            Result}};
+
+pub const EVENT_HEADER: &str = "X-Habitat-Event";
+pub const DELIVERY_HEADER: &str = "X-Habitat-Delivery";
+pub const SIGNATURE_HEADER: &str = "X-Habitat-Signature-256";
+
+const BACKOFF_BASE_SECS: i64 = 30;
+const BACKOFF_MAX_SECS: i64 = 3600;
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    event:       WebhookEvent,
+    origin:      &'a str,
+    occurred_at: DateTime<Utc>,
+    data:        serde_json::Value,
+}
+
+/// Resolves the host of a webhook url, refusing it when any of the addresses it resolves to is
+/// loopback, private, link-local, unique local or otherwise not publicly routable.
+pub fn resolve_destination(url: &str) -> Result<(String, Vec<SocketAddr>)> {
+    let refused =
+        |reason: &str| Error::PolicyViolation(format!("Webhook url '{}' {}", url, reason));
+
+    let parsed = Url::parse(url).map_err(|_| refused("is not a valid url"))?;
+    let host = match parsed.host_str() {
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']').to_string(),
+        None => return Err(refused("has no host")),
+    };
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let addrs = match (host.as_str(), port).to_socket_addrs() {
+        Ok(addrs) => addrs.collect::<Vec<_>>(),
+        Err(err) => return Err(refused(&format!("can't be resolved: {}", err))),
+    };
+    if addrs.is_empty() {
+        return Err(refused("doesn't resolve to any address"));
+    }
+    if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+        return Err(refused(&format!("resolves to {}, which is not a public address", addr.ip())));
+    }
+    Ok((host, addrs))
+}
+
+/// Queues a delivery of the event to every active webhook of the origin subscribed to it.
+/// Webhooks are a side channel, so a failure here is logged and never fails the request that
+/// raised the event.
+pub fn notify(origin: &str,
+              event: WebhookEvent,
+              data: serde_json::Value,
+              conn: &mut PgConnection) {
+    if let Err(err) = enqueue(origin, event, data, conn) {
+        warn!("Unable to queue {} webhooks for {}, err={}", event, origin, err);
+    }
+}
+
+fn enqueue(origin: &str,
+           event: WebhookEvent,
+           data: serde_json::Value,
+           conn: &mut PgConnection)
+           -> Result<()> {
+    let webhooks = Webhook::list_for_event(origin, event, conn).map_err(Error::DieselError)?;
+    if webhooks.is_empty() {
+        return Ok(());
+    }
+
+    let payload = serde_json::to_string(&WebhookPayload { event,
+                                                          origin,
+                                                          occurred_at: Utc::now(),
+                                                          data })?;
+    let deliveries: Vec<NewWebhookDelivery> =
+        webhooks.iter()
+                .map(|webhook| {
+                    NewWebhookDelivery { webhook_id: webhook.id,
+                                         event:      event.as_str(),
+                                         payload:    &payload, }
+                })
+                .collect();
+
+    WebhookDelivery::create(&deliveries, conn).map_err(Error::DieselError)?;
+    Ok(())
+}
+
+/// The value of the signature header for a body: the hex encoded HMAC-SHA256 of the body keyed
+/// with the webhook's secret.
+pub fn sign(secret: &[u8], body: &[u8]) -> Result<String> {
+    let key = PKey::hmac(secret)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(body)?;
+    let digest = signer.sign_to_vec()?;
+    Ok(format!("sha256={}",
+               digest.iter()
+                     .map(|b| format!("{:02x}", b))
+                     .collect::<String>()))
+}
+
+pub fn start(config: WebhookCfg, key_cache: KeyCache, db: DbPool) {
+    if config.interval == 0 {
+        return;
+    }
+
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(Duration::from_secs(config.interval));
+        loop {
+            interval.tick().await;
+
+            let cfg = config.clone();
+            let key_cache = key_cache.clone();
+            let db = db.clone();
+            if let Err(err) =
+                web::block(move || send_due_deliveries(&cfg, &key_cache, &db)).await
+            {
+                warn!("Webhook deliveries did not complete, err={}", err);
+            }
+        }
+    });
+}
+
+fn send_due_deliveries(config: &WebhookCfg, key_cache: &KeyCache, db: &DbPool) {
+    let mut conn = match db.get_conn().map_err(Error::DbError) {
+        Ok(conn) => conn,
+        Err(err) => {
+            warn!("Unable to send webhook deliveries, err={}", err);
+            return;
+        }
+    };
+
+    // A run sends its deliveries one after another, so one still delivering after every
+    // delivery of a batch could have timed out was left behind by an instance that stopped
+    let stale_secs = (config.timeout as i64).saturating_mul(config.batch_size.max(1));
+    let stale_before = Utc::now().naive_utc() - chrono::Duration::seconds(stale_secs);
+
+    let due = match WebhookDelivery::claim_due(config.batch_size, stale_before, &mut conn) {
+        Ok(due) => due,
+        Err(err) => {
+            warn!("Unable to claim webhook deliveries, err={}", err);
+            return;
+        }
+    };
+
+    for delivery in due.iter() {
+        deliver(config, key_cache, delivery, &mut conn);
+    }
+}
+
+fn deliver(config: &WebhookCfg,
+           key_cache: &KeyCache,
+           delivery: &WebhookDelivery,
+           conn: &mut PgConnection) {
+    let webhook = match Webhook::get_by_id(delivery.webhook_id, conn) {
+        Ok(webhook) => webhook,
+        Err(err) => {
+            warn!("Unable to load webhook {} for delivery {}, err={}",
+                  delivery.webhook_id, delivery.id, err);
+            return;
+        }
+    };
+
+    let (response_code, error) = if webhook.active {
+        match send(config, key_cache, &webhook, delivery) {
+            Ok(status) if status.is_success() => {
+                debug!("Delivered {} to webhook {}", delivery.event, webhook.id);
+                record(WebhookDelivery::finish(delivery.id,
+                                               WebhookDeliveryState::Succeeded,
+                                               Some(i32::from(status.as_u16())),
+                                               None,
+                                               conn),
+                       delivery);
+                return;
+            }
+            Ok(status) => {
+                (Some(i32::from(status.as_u16())), format!("Endpoint responded with {}", status))
+            }
+            Err(err) => (None, err.to_string()),
+        }
+    } else {
+        (None, "Webhook is inactive".to_string())
+    };
+
+    debug!("Delivery {} to webhook {} failed on attempt {}: {}",
+           delivery.id, webhook.id, delivery.attempts, error);
+
+    if !webhook.active || i64::from(delivery.attempts) >= i64::from(config.max_attempts) {
+        record(WebhookDelivery::finish(delivery.id,
+                                       WebhookDeliveryState::Failed,
+                                       response_code,
+                                       Some(&error),
+                                       conn),
+               delivery);
+    } else {
+        let next_attempt_at = Utc::now().naive_utc() + backoff(delivery.attempts);
+        record(WebhookDelivery::retry_at(delivery.id,
+                                         next_attempt_at,
+                                         response_code,
+                                         Some(&error),
+                                         conn),
+               delivery);
+    }
+}
+
+fn send(config: &WebhookCfg,
+        key_cache: &KeyCache,
+        webhook: &Webhook,
+        delivery: &WebhookDelivery)
+        -> Result<StatusCode> {
+    // The client is pinned to the addresses just checked, so the host can't be re-resolved to
+    // a different one while the request is made.
+    let (host, addrs) = resolve_destination(&webhook.url)?;
+    let client = Client::builder().timeout(Duration::from_secs(config.timeout))
+                                  .redirect(Policy::none())
+                                  .resolve_to_addrs(&host, &addrs)
+                                  .build()
+                                  .map_err(Error::HttpClient)?;
+
+    let secret = crypto::decrypt(key_cache, &webhook.secret).map_err(Error::BuilderCore)?;
+    let signature = sign(&secret, delivery.payload.as_bytes())?;
+    let response = client.post(&webhook.url)
+                         .header(CONTENT_TYPE, "application/json")
+                         .header(EVENT_HEADER, delivery.event.as_str())
+                         .header(DELIVERY_HEADER, delivery.id.to_string())
+                         .header(SIGNATURE_HEADER, signature)
+                         .body(delivery.payload.clone())
+                         .send()
+                         .map_err(Error::HttpClient)?;
+    Ok(response.status())
+}
+
+fn record(result: diesel::QueryResult<usize>, delivery: &WebhookDelivery) {
+    if let Err(err) = result {
+        warn!("Unable to record webhook delivery {}, err={}", delivery.id, err);
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            // 100.64.0.0/10 is shared address space for carrier-grade NAT
+            let shared = octets[0] == 100 && (octets[1] & 0xc0) == 64;
+            !(ip.is_loopback()
+              || ip.is_private()
+              || ip.is_link_local()
+              || ip.is_unspecified()
+              || ip.is_broadcast()
+              || ip.is_multicast()
+              || ip.is_documentation()
+              || shared
+              || octets[0] == 0)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(mapped));
+            }
+            let segment = ip.segments()[0];
+            // fc00::/7 is unique local, fe80::/10 link-local
+            !(ip.is_loopback()
+              || ip.is_unspecified()
+              || ip.is_multicast()
+              || (segment & 0xfe00) == 0xfc00
+              || (segment & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+// Doubles the wait after each failed attempt, starting at 30 seconds and capped at an hour.
+fn backoff(attempts: i32) -> chrono::Duration {
+    let secs = BACKOFF_BASE_SECS << (attempts.clamp(1, 8) - 1);
+    chrono::Duration::seconds(cmp::min(secs, BACKOFF_MAX_SECS))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sign_produces_hmac_sha256() {
+        let signature = sign(b"key", b"The quick brown fox jumps over the lazy dog").unwrap();
+        assert_eq!(signature,
+                   "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8");
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_an_hour() {
+        assert_eq!(backoff(1), chrono::Duration::seconds(30));
+        assert_eq!(backoff(2), chrono::Duration::seconds(60));
+        assert_eq!(backoff(7), chrono::Duration::seconds(1920));
+        assert_eq!(backoff(8), chrono::Duration::seconds(3600));
+        assert_eq!(backoff(20), chrono::Duration::seconds(3600));
+    }
+
+    #[test]
+    fn only_public_addresses_are_allowed() {
+        for ip in &["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254",
+                    "100.64.0.1", "0.0.0.0", "::1", "::", "fd00::1", "fe80::1", "::ffff:127.0.0.1"]
+        {
+            assert!(!is_public(ip.parse().unwrap()), "{} should be refused", ip);
+        }
+        for ip in &["1.1.1.1", "140.82.112.3", "2606:4700:4700::1111"] {
+            assert!(is_public(ip.parse().unwrap()), "{} should be allowed", ip);
+        }
+    }
+
+    #[test]
+    fn destinations_on_the_local_network_are_refused() {
+        assert!(resolve_destination("http://127.0.0.1:9636/v1/status").is_err());
+        assert!(resolve_destination("https://[::1]/hook").is_err());
+        assert!(resolve_destination("http://169.254.169.254/latest/meta-data").is_err());
+        assert!(resolve_destination("https://1.1.1.1/hook").is_ok());
+    }
+}
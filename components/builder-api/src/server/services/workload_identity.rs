@@ -0,0 +1,108 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of the identity tokens that workloads, such as CI jobs, are issued by their
+//! platforms.
+//!
+//! Only the issuers in the `workload_identity` config are trusted. Their signing keys are
+//! cached for every worker, and reloaded periodically or as soon as a token is signed with a
+//! key the cache doesn't have yet. Those reloads happen at most once a minute per issuer, so
+//! tokens naming made up keys can't be used to flood the issuer with requests.
+use std::{collections::HashMap,
+          sync::Mutex};
+
+use chrono::{DateTime,
+             Duration,
+             Utc};
+
+use crate::{bldr_core::{error::Error as CoreError,
+                        jwt::{self,
+                              Claims,
+                              JwkSet,
+                              Validation}},
+            config::{OidcIssuerCfg,
+                     WorkloadIdentityCfg},
+            server::error::{Error,
+                            Result}};
+
+const JWKS_REFRESH_MINUTES: i64 = 10;
+const JWKS_MIN_REFETCH_SECS: i64 = 60;
+
+lazy_static! {
+    static ref JWKS_CACHE: Mutex<HashMap<String, (JwkSet, DateTime<Utc>)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Verifies a workload's identity token, giving its issuer and claims.
+pub async fn verify(token: &str, cfg: &WorkloadIdentityCfg) -> Result<(String, Claims)> {
+    let issuer = jwt::unverified_claims(token).ok()
+                                              .and_then(|claims| jwt::claim_string(&claims, "iss"))
+                                              .ok_or(Error::Authentication)?;
+
+    let issuer_cfg = match cfg.issuers.iter().find(|i| i.issuer == issuer) {
+        Some(issuer_cfg) => issuer_cfg,
+        None => {
+            debug!("Identity token issuer {} is not trusted", issuer);
+            return Err(Error::Authentication);
+        }
+    };
+
+    let keys = keys_for(issuer_cfg, token).await?;
+    let validation = Validation { issuer:   &issuer,
+                                  audience: &cfg.audience, };
+
+    match jwt::verify(token, &keys, &validation) {
+        Ok(claims) => Ok((issuer, claims)),
+        Err(err @ CoreError::TokenExpired) | Err(err @ CoreError::TokenInvalid) => {
+            debug!("Identity token from {} rejected, err={}", issuer, err);
+            Err(Error::Authentication)
+        }
+        Err(err) => Err(Error::BuilderCore(err)),
+    }
+}
+
+async fn keys_for(issuer_cfg: &OidcIssuerCfg, token: &str) -> Result<JwkSet> {
+    let cached = JWKS_CACHE.lock()
+                           .expect("JWKS cache lock poisoned")
+                           .get(&issuer_cfg.issuer)
+                           .cloned();
+    if let Some((keys, fetched_at)) = cached {
+        let age = Utc::now() - fetched_at;
+        if age < Duration::minutes(JWKS_REFRESH_MINUTES) {
+            if keys.has_key_for(token) {
+                return Ok(keys);
+            }
+            if age < Duration::seconds(JWKS_MIN_REFETCH_SECS) {
+                debug!("Identity token from {} is signed with an unknown key",
+                       issuer_cfg.issuer);
+                return Err(Error::Authentication);
+            }
+        }
+    }
+
+    let keys = match (&issuer_cfg.jwks_path, &issuer_cfg.jwks_url) {
+        (Some(path), _) => JwkSet::from_file(path)?,
+        (None, Some(url)) => JwkSet::fetch(url).await?,
+        (None, None) => {
+            warn!("No keys configured for identity token issuer {}",
+                  issuer_cfg.issuer);
+            return Err(Error::Authentication);
+        }
+    };
+
+    JWKS_CACHE.lock()
+              .expect("JWKS cache lock poisoned")
+              .insert(issuer_cfg.issuer.clone(), (keys.clone(), Utc::now()));
+    Ok(keys)
+}
@@ -10,8 +10,6 @@ use crate::{crypto,
 use chrono::{self,
              DateTime,
              Duration,
-             LocalResult::Single,
-             TimeZone,
              Utc};
 use habitat_core::crypto::keys::{KeyCache,
                                  SignedBox};
@@ -79,15 +77,55 @@ impl AccessToken {
         Self::generate_access_token(key_cache,
                                     BUILDER_ACCOUNT_ID,
                                     FeatureFlags::all().bits(),
-                                    Duration::hours(BUILDER_TOKEN_LIFETIME_HOURS))
+                                    Duration::hours(BUILDER_TOKEN_LIFETIME_HOURS),
+                                    None)
     }
 
-    /// Constructor used for creating never-expiring access tokens for "normal"
-    /// user accounts.
+    /// Constructor used for creating access tokens for "normal" user
+    /// accounts.
     ///
-    /// Currently , user tokens never expire, and can only be revoked.
-    pub fn user_token(key_cache: &KeyCache, account_id: u64, privileges: u32) -> Result<Self> {
-        Self::generate_access_token(key_cache, account_id, privileges, Duration::MAX)
+    /// User tokens expire at `expires_at` if one is given; otherwise they
+    /// never expire, and can only be revoked.
+    pub fn user_token(key_cache: &KeyCache,
+                      account_id: u64,
+                      privileges: u32,
+                      expires_at: Option<DateTime<Utc>>)
+                      -> Result<Self> {
+        Self::generate_access_token(key_cache,
+                                    account_id,
+                                    privileges,
+                                    Self::lifetime_until(expires_at),
+                                    None)
+    }
+
+    /// Constructor for user tokens that may only be used within the given
+    /// scope. The scope is encrypted along with the rest of the token, so it
+    /// can't be altered by whoever holds it.
+    pub fn scoped_user_token(key_cache: &KeyCache,
+                             account_id: u64,
+                             privileges: u32,
+                             expires_at: Option<DateTime<Utc>>,
+                             scope: originsrv::TokenScope)
+                             -> Result<Self> {
+        Self::generate_access_token(key_cache,
+                                    account_id,
+                                    privileges,
+                                    Self::lifetime_until(expires_at),
+                                    Some(scope))
+    }
+
+    /// Constructor for short-lived tokens exchanged for the identity token of
+    /// a workload, such as a CI job. Like Builder tokens they aren't backed by
+    /// a user account, so what they may do is entirely given by their scope.
+    pub fn workload_token(key_cache: &KeyCache,
+                          scope: originsrv::TokenScope,
+                          lifetime: Duration)
+                          -> Result<Self> {
+        Self::generate_access_token(key_cache,
+                                    BUILDER_ACCOUNT_ID,
+                                    FeatureFlags::empty().bits(),
+                                    lifetime,
+                                    Some(scope))
     }
 
     /// Given the string form of an `AccessToken`, fully process it to yield an
@@ -102,34 +140,13 @@ impl AccessToken {
         // protobuf inside.
         let payload = access_token.decrypt(key_cache)?;
 
-        // Ensure that the token has not expired yet.
-        //
-        // TODO (CM - 2020-09-15): This logic would be better as an `expired`
-        // method on the `originsrv::AccessToken` type itself.
-        match Utc.timestamp_opt(payload.expires(), 0 /* nanoseconds */) {
-            Single(expires) => {
-                if expires < Utc::now() {
-                    return Err(Error::TokenExpired);
-                }
-            }
-            _ => {
-                // mwrock - 2024-09-13
-                // Prior to the chronos update 4 months ago this is the max timestamp used for user
-                // tokens We have succesfully parsed this value via the call to
-                // Utc.timestamp_opt above for years Yesterday (2024-09-12), this
-                // value became out of bounds and yields a 401 trying to auth
-                // Right now we have no idea why and some day perhaps we will all laugh around the
-                // fire as we remember this bug and how trivial it truly was. Today
-                // no one is laughing. I hope there will be a better fix than this
-                // in the near future but this will allow the many keys currently
-                // out in the wild with this value to authenticate.
-                if payload.expires() != 8_210_298_326_400 {
-                    trace!("unable to parse timestamp from expires {} for token {}",
-                           payload.expires(),
-                           token);
-                    return Err(Error::TokenInvalid);
-                }
-            }
+        // Ensure that the token has not expired yet. Expiry times are compared
+        // as seconds past the epoch rather than as dates, as never-expiring
+        // tokens out in the wild carry times that newer versions of chrono
+        // can no longer represent.
+        if payload.expires() < Utc::now().timestamp() {
+            trace!("token {} expired at {}", token, payload.expires());
+            return Err(Error::TokenExpired);
         }
 
         // If all is OK, finally convert into an `originsrv::Session`.
@@ -138,15 +155,25 @@ impl AccessToken {
 
     ////////////////////////////////////////////////////////////////////////
 
+    /// The lifetime of a token that should expire at `expires_at`, or that
+    /// should never expire if there is none.
+    fn lifetime_until(expires_at: Option<DateTime<Utc>>) -> Duration {
+        match expires_at {
+            Some(expires_at) => expires_at - Utc::now(),
+            None => Duration::MAX,
+        }
+    }
+
     /// Helper function with common logic creating an `AccessToken` from all the
     /// necessary inputs.
     fn generate_access_token(key_cache: &KeyCache,
                              account_id: u64,
                              flags: u32,
-                             lifetime: Duration)
+                             lifetime: Duration,
+                             scope: Option<originsrv::TokenScope>)
                              -> Result<Self> {
         // Create originsrv::AccessToken protobuf struct
-        let token = AccessToken::new_proto(account_id, flags, lifetime);
+        let token = AccessToken::new_proto(account_id, flags, lifetime, scope);
 
         // Encrypt that protobuf struct to a String.
         let token = AccessToken::encrypt(&token, key_cache)?;
@@ -162,7 +189,11 @@ impl AccessToken {
     ///
     /// Would call this function `new`, but that's already taken by the
     /// protobuf-generated code :/
-    fn new_proto(account_id: u64, flags: u32, lifetime: Duration) -> originsrv::AccessToken {
+    fn new_proto(account_id: u64,
+                 flags: u32,
+                 lifetime: Duration,
+                 scope: Option<originsrv::TokenScope>)
+                 -> originsrv::AccessToken {
         let expires = Utc::now().checked_add_signed(lifetime)
                                 .unwrap_or(DateTime::<Utc>::MAX_UTC)
                                 .timestamp();
@@ -171,6 +202,7 @@ impl AccessToken {
         token.set_account_id(account_id);
         token.set_flags(flags);
         token.set_expires(expires);
+        token.scope = protobuf::MessageField::from_option(scope);
 
         token
     }
@@ -337,7 +369,7 @@ mod tests {
         let account_id = 2112;
         let privileges = FeatureFlags::default().bits();
 
-        let token = AccessToken::user_token(&cache, account_id, privileges).unwrap();
+        let token = AccessToken::user_token(&cache, account_id, privileges, None).unwrap();
 
         let inner = token.decrypt(&cache).unwrap();
 
@@ -350,9 +382,46 @@ mod tests {
         assert_eq!(inner.expires(), maximum_time);
     }
 
+    #[test]
+    fn creates_expiring_user_token() {
+        let (cache, _dir) = new_cache();
+        let expires_at = Utc::now() + Duration::days(30);
+
+        let token = AccessToken::user_token(&cache, 2112, 0, Some(expires_at)).unwrap();
+
+        let inner = token.decrypt(&cache).unwrap();
+
+        // As above, allow a second of wiggle room for slow machines
+        let acceptable_range = (expires_at.timestamp() - 1)..=expires_at.timestamp();
+        assert!(acceptable_range.contains(&inner.expires()),
+                "User tokens should expire when asked to (expected {}, got {})",
+                expires_at.timestamp(),
+                inner.expires());
+    }
+
     mod validate_access_token {
         use super::*;
 
+        #[test]
+        fn never_expiring_token_validates() {
+            let (cache, _dir) = new_cache();
+            let token = AccessToken::user_token(&cache, 2112, 0, None).unwrap();
+
+            assert!(AccessToken::validate_access_token(&token.to_string(), &cache).is_ok());
+        }
+
+        #[test]
+        fn token_with_old_maximum_expiry_validates() {
+            // Before a chrono update, never-expiring user tokens were given
+            // this expiry, which chrono can no longer turn into a date
+            let (cache, _dir) = new_cache();
+            let mut payload = AccessToken::new_proto(2112, 0, Duration::MAX, None);
+            payload.set_expires(8_210_298_326_400);
+            let token = AccessToken(AccessToken::encrypt(&payload, &cache).unwrap());
+
+            assert!(AccessToken::validate_access_token(&token.to_string(), &cache).is_ok());
+        }
+
         #[test]
         fn new_token_validates() {
             let (cache, _dir) = new_cache();
@@ -373,7 +442,8 @@ mod tests {
             // Using private `generate_access_token` function here to gain control
             // of the token duration; the public constructors hide this.
             let token =
-                AccessToken::generate_access_token(&cache, account_id, flags, lifetime).unwrap();
+                AccessToken::generate_access_token(&cache, account_id, flags, lifetime, None)
+                    .unwrap();
 
             // Sleep to ensure enough time has passed for the token to definitely be
             // marked as expired.
@@ -384,6 +454,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn scoped_user_token_carries_its_scope() {
+        use crate::privilege::TokenCapabilities;
+
+        let (cache, _dir) = new_cache();
+        let mut scope = originsrv::TokenScope::new();
+        scope.origins = vec!["core".to_string()];
+        scope.set_capabilities(TokenCapabilities::DOWNLOAD.bits());
+
+        let token = AccessToken::scoped_user_token(&cache, 2112, 0, None, scope.clone()).unwrap();
+        let session = AccessToken::validate_access_token(&token.to_string(), &cache).unwrap();
+
+        assert_eq!(session.scope.as_ref(), Some(&scope));
+    }
+
+    #[test]
+    fn workload_token_has_no_privileges_beyond_its_scope() {
+        let (cache, _dir) = new_cache();
+        let mut scope = originsrv::TokenScope::new();
+        scope.origins = vec!["core".to_string()];
+        scope.set_role("member".to_string());
+        scope.set_subject("repo:habitat-sh/core-plans:ref:refs/heads/main".to_string());
+
+        let token = AccessToken::workload_token(&cache, scope.clone(), Duration::minutes(15))
+            .unwrap();
+        let session = AccessToken::validate_access_token(&token.to_string(), &cache).unwrap();
+
+        assert_eq!(session.id(), BUILDER_ACCOUNT_ID);
+        assert_eq!(session.flags(), FeatureFlags::empty().bits());
+        assert_eq!(session.scope.as_ref(), Some(&scope));
+    }
+
     mod display {
         use super::*;
 
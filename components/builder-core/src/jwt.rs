@@ -0,0 +1,341 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of the JSON Web Tokens that OpenID Connect providers issue,
+//! such as the ID tokens CI systems hand to their jobs.
+
+use std::{fs,
+          path::Path};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD,
+             Engine};
+use chrono::Utc;
+use openssl::{bn::BigNum,
+              hash::MessageDigest,
+              pkey::{PKey,
+                     Public},
+              rsa::Rsa,
+              sign::Verifier};
+use serde::de::DeserializeOwned;
+use serde_json::{Map,
+                 Value};
+
+use crate::error::{Error,
+                   Result};
+
+/// Seconds of clock difference with an issuer that are tolerated when
+/// checking when a token expires or becomes valid.
+const CLOCK_SKEW_LEEWAY_SECS: i64 = 60;
+
+pub type Claims = Map<String, Value>;
+
+/// One of the public keys in a JSON Web Key Set. Only RSA keys are supported.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Jwk {
+    #[serde(default)]
+    pub kid: Option<String>,
+    pub kty: String,
+    #[serde(default)]
+    pub n:   Option<String>,
+    #[serde(default)]
+    pub e:   Option<String>,
+}
+
+/// The public keys an issuer signs its tokens with.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+impl JwkSet {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content =
+            fs::read_to_string(path).map_err(|e| Error::ConfigFileIO(path.to_path_buf(), e))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub async fn fetch(url: &str) -> Result<Self> {
+        let resp = reqwest::get(url).await?;
+        let status = resp.status();
+        let body = resp.text().await?;
+        if !status.is_success() {
+            return Err(Error::ApiError(status, body));
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Whether the set has the key a token was signed with. When it doesn't,
+    /// the issuer has likely rotated its keys and the set should be reloaded.
+    pub fn has_key_for(&self, token: &str) -> bool {
+        match split(token).and_then(|(header, ..)| decode_part::<Header>(header)) {
+            Ok(header) => self.find(header.kid.as_deref()).is_some(),
+            Err(_) => false,
+        }
+    }
+
+    fn find(&self, kid: Option<&str>) -> Option<&Jwk> {
+        match kid {
+            Some(kid) => self.keys.iter().find(|k| k.kid.as_deref() == Some(kid)),
+            // Without a key id, only a set with a single key is unambiguous
+            None if self.keys.len() == 1 => self.keys.first(),
+            None => None,
+        }
+    }
+}
+
+/// What a token has to have been issued for to be accepted.
+#[derive(Clone, Debug)]
+pub struct Validation<'a> {
+    pub issuer:   &'a str,
+    pub audience: &'a str,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+/// Reads the claims of a token without verifying it, for instance to find
+/// out which issuer's keys it should be verified with. Nothing read this way
+/// may be trusted.
+pub fn unverified_claims(token: &str) -> Result<Claims> {
+    let (_, payload, _) = split(token)?;
+    decode_part(payload)
+}
+
+/// Verifies the signature of a token against the issuer's keys and checks
+/// its issuer, audience and validity period, giving its claims.
+pub fn verify(token: &str, keys: &JwkSet, validation: &Validation) -> Result<Claims> {
+    let (header, payload, signature) = split(token)?;
+    let header: Header = decode_part(header)?;
+
+    let digest = match header.alg.as_str() {
+        "RS256" => MessageDigest::sha256(),
+        "RS384" => MessageDigest::sha384(),
+        "RS512" => MessageDigest::sha512(),
+        alg => {
+            debug!("Unsupported token signing algorithm {}", alg);
+            return Err(Error::TokenInvalid);
+        }
+    };
+
+    let jwk = keys.find(header.kid.as_deref()).ok_or_else(|| {
+                                                   debug!("No key found for token key id {:?}",
+                                                          header.kid);
+                                                   Error::TokenInvalid
+                                               })?;
+    let key = public_key(jwk)?;
+    let signature = URL_SAFE_NO_PAD.decode(signature)?;
+
+    // The signature covers the header and payload exactly as they were sent
+    let signed = &token[..token.len() - signature_len(token)];
+    let verified = Verifier::new(digest, &key).and_then(|mut verifier| {
+                                                  verifier.update(signed.as_bytes())?;
+                                                  verifier.verify(&signature)
+                                              });
+    match verified {
+        Ok(true) => (),
+        Ok(false) => {
+            debug!("Token signature does not match");
+            return Err(Error::TokenInvalid);
+        }
+        Err(err) => {
+            debug!("Unable to verify token signature, err={}", err);
+            return Err(Error::TokenInvalid);
+        }
+    }
+
+    let claims: Claims = decode_part(payload)?;
+    validate_claims(&claims, validation)?;
+    Ok(claims)
+}
+
+/// A claim as a string. Claims that aren't strings, such as booleans, are
+/// rendered as JSON so that they can still be matched against.
+pub fn claim_string(claims: &Claims, name: &str) -> Option<String> {
+    match claims.get(name)? {
+        Value::String(s) => Some(s.to_string()),
+        Value::Null => None,
+        value => Some(value.to_string()),
+    }
+}
+
+fn validate_claims(claims: &Claims, validation: &Validation) -> Result<()> {
+    if claims.get("iss").and_then(Value::as_str) != Some(validation.issuer) {
+        debug!("Token was not issued by {}", validation.issuer);
+        return Err(Error::TokenInvalid);
+    }
+
+    let audience_matches = match claims.get("aud") {
+        Some(Value::String(aud)) => aud == validation.audience,
+        Some(Value::Array(auds)) => auds.iter().any(|aud| aud == validation.audience),
+        _ => false,
+    };
+    if !audience_matches {
+        debug!("Token was not issued for {}", validation.audience);
+        return Err(Error::TokenInvalid);
+    }
+
+    let now = Utc::now().timestamp();
+    match claims.get("exp").and_then(Value::as_i64) {
+        Some(exp) if exp + CLOCK_SKEW_LEEWAY_SECS < now => return Err(Error::TokenExpired),
+        Some(_) => (),
+        None => {
+            debug!("Token has no expiry");
+            return Err(Error::TokenInvalid);
+        }
+    }
+    if let Some(nbf) = claims.get("nbf").and_then(Value::as_i64) {
+        if nbf - CLOCK_SKEW_LEEWAY_SECS > now {
+            debug!("Token is not valid before {}", nbf);
+            return Err(Error::TokenInvalid);
+        }
+    }
+    Ok(())
+}
+
+fn public_key(jwk: &Jwk) -> Result<PKey<Public>> {
+    let (n, e) = match (jwk.kty.as_str(), &jwk.n, &jwk.e) {
+        ("RSA", Some(n), Some(e)) => (URL_SAFE_NO_PAD.decode(n)?, URL_SAFE_NO_PAD.decode(e)?),
+        _ => {
+            debug!("Unsupported key {:?} of type {}", jwk.kid, jwk.kty);
+            return Err(Error::TokenInvalid);
+        }
+    };
+    BigNum::from_slice(&n).and_then(|n| Ok((n, BigNum::from_slice(&e)?)))
+                          .and_then(|(n, e)| Rsa::from_public_components(n, e))
+                          .and_then(PKey::from_rsa)
+                          .map_err(|err| {
+                              debug!("Invalid key {:?}, err={}", jwk.kid, err);
+                              Error::TokenInvalid
+                          })
+}
+
+fn split(token: &str) -> Result<(&str, &str, &str)> {
+    let mut parts = token.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(payload), Some(signature), None) => Ok((header, payload, signature)),
+        _ => Err(Error::TokenInvalid),
+    }
+}
+
+// Length of the signature, along with the dot that precedes it
+fn signature_len(token: &str) -> usize { token.rsplit('.').next().map_or(0, |s| s.len() + 1) }
+
+fn decode_part<T: DeserializeOwned>(part: &str) -> Result<T> {
+    let bytes = URL_SAFE_NO_PAD.decode(part.trim_end_matches('='))?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::{pkey::Private,
+                  sign::Signer};
+
+    const ISSUER: &str = "https://token.actions.githubusercontent.com";
+    const AUDIENCE: &str = "https://bldr.habitat.sh";
+
+    fn keypair() -> (PKey<Private>, JwkSet) {
+        let rsa = Rsa::generate(2048).unwrap();
+        let jwk = Jwk { kid: Some("test".to_string()),
+                        kty: "RSA".to_string(),
+                        n:   Some(URL_SAFE_NO_PAD.encode(rsa.n().to_vec())),
+                        e:   Some(URL_SAFE_NO_PAD.encode(rsa.e().to_vec())), };
+        (PKey::from_rsa(rsa).unwrap(), JwkSet { keys: vec![jwk] })
+    }
+
+    fn sign(key: &PKey<Private>, claims: &Value) -> String {
+        let header = json!({ "alg": "RS256", "kid": "test", "typ": "JWT" });
+        let signed = format!("{}.{}",
+                             URL_SAFE_NO_PAD.encode(header.to_string()),
+                             URL_SAFE_NO_PAD.encode(claims.to_string()));
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        signer.update(signed.as_bytes()).unwrap();
+        format!("{}.{}",
+                signed,
+                URL_SAFE_NO_PAD.encode(signer.sign_to_vec().unwrap()))
+    }
+
+    fn claims(exp: i64) -> Value {
+        json!({ "iss": ISSUER,
+                "aud": AUDIENCE,
+                "exp": exp,
+                "repository": "habitat-sh/core-plans",
+                "ref": "refs/heads/main" })
+    }
+
+    fn validation() -> Validation<'static> {
+        Validation { issuer:   ISSUER,
+                     audience: AUDIENCE, }
+    }
+
+    #[test]
+    fn verifies_signed_token() {
+        let (key, keys) = keypair();
+        let token = sign(&key, &claims(Utc::now().timestamp() + 300));
+
+        let claims = verify(&token, &keys, &validation()).unwrap();
+        assert_eq!(claim_string(&claims, "repository"),
+                   Some("habitat-sh/core-plans".to_string()));
+    }
+
+    #[test]
+    fn rejects_token_from_other_keys() {
+        let (key, _) = keypair();
+        let (_, other_keys) = keypair();
+        let token = sign(&key, &claims(Utc::now().timestamp() + 300));
+
+        assert!(verify(&token, &other_keys, &validation()).is_err());
+    }
+
+    #[test]
+    fn rejects_altered_token() {
+        let (key, keys) = keypair();
+        let token = sign(&key, &claims(Utc::now().timestamp() + 300));
+        let mut forged = claims(Utc::now().timestamp() + 300);
+        forged["repository"] = json!("mallory/core-plans");
+
+        let parts: Vec<&str> = token.split('.').collect();
+        let token = format!("{}.{}.{}",
+                            parts[0],
+                            URL_SAFE_NO_PAD.encode(forged.to_string()),
+                            parts[2]);
+        assert!(verify(&token, &keys, &validation()).is_err());
+    }
+
+    #[test]
+    fn rejects_token_for_other_audience() {
+        let (key, keys) = keypair();
+        let token = sign(&key, &claims(Utc::now().timestamp() + 300));
+        let validation = Validation { issuer:   ISSUER,
+                                      audience: "https://example.com", };
+
+        assert!(verify(&token, &keys, &validation).is_err());
+    }
+
+    #[test]
+    fn rejects_expired_token() {
+        let (key, keys) = keypair();
+        let token = sign(&key, &claims(Utc::now().timestamp() - 600));
+
+        match verify(&token, &keys, &validation()) {
+            Err(Error::TokenExpired) => (),
+            other => panic!("expected an expired token, got {:?}", other),
+        }
+    }
+}
@@ -34,6 +34,7 @@ pub mod config;
 pub mod crypto;
 pub mod error;
 pub mod http_client;
+pub mod jwt;
 pub mod keys;
 pub mod logger;
 pub mod metrics;
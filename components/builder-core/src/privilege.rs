@@ -20,3 +20,50 @@ bitflags! {
         const BUILD_WORKER = 0b0000_0100;
     }
 }
+
+bitflags! {
+    /// The operations a scoped access token may be used for.
+    #[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct TokenCapabilities: u32 {
+        const DOWNLOAD = 0b0000_0001;
+        const UPLOAD = 0b0000_0010;
+        const PROMOTE = 0b0000_0100;
+        const KEYS = 0b0000_1000;
+    }
+}
+
+impl TokenCapabilities {
+    const NAMES: [(&'static str, Self); 4] = [("download", Self::DOWNLOAD),
+                                              ("upload", Self::UPLOAD),
+                                              ("promote", Self::PROMOTE),
+                                              ("keys", Self::KEYS)];
+
+    /// Parses capability names, as given when creating a token.
+    pub fn from_names<S: AsRef<str>>(names: &[S]) -> Option<TokenCapabilities> {
+        names.iter().try_fold(TokenCapabilities::empty(), |caps, name| {
+                        Self::NAMES.iter()
+                                   .find(|(n, _)| *n == name.as_ref())
+                                   .map(|(_, cap)| caps | *cap)
+                    })
+    }
+
+    pub fn names(self) -> Vec<&'static str> {
+        Self::NAMES.iter()
+                   .filter(|(_, cap)| self.contains(*cap))
+                   .map(|(n, _)| *n)
+                   .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_capabilities_round_trip_through_names() {
+        let caps = TokenCapabilities::from_names(&["download", "promote"]).unwrap();
+        assert_eq!(caps, TokenCapabilities::DOWNLOAD | TokenCapabilities::PROMOTE);
+        assert_eq!(caps.names(), vec!["download", "promote"]);
+        assert!(TokenCapabilities::from_names(&["download", "delete"]).is_none());
+    }
+}
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS package_download_stats;
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS package_download_stats (
+    package_id bigint NOT NULL REFERENCES origin_packages(id) ON DELETE CASCADE,
+    day date NOT NULL,
+    origin text NOT NULL,
+    name text NOT NULL,
+    target text NOT NULL,
+    download_count bigint NOT NULL DEFAULT 0,
+    updated_at timestamp with time zone DEFAULT now(),
+    PRIMARY KEY (package_id, day)
+);
+
+CREATE INDEX IF NOT EXISTS package_download_stats_origin_name_day
+    ON package_download_stats(origin, name, day);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS origin_channel_policies (
+    channel_id bigint PRIMARY KEY REFERENCES origin_channels(id) ON DELETE CASCADE,
+    origin text NOT NULL,
+    channel text NOT NULL,
+    promote_role origin_member_role NOT NULL DEFAULT 'maintainer',
+    demote_role origin_member_role NOT NULL DEFAULT 'maintainer',
+    source_channel text,
+    require_tdeps boolean NOT NULL DEFAULT false,
+    block_demote boolean NOT NULL DEFAULT false,
+    created_at timestamp with time zone DEFAULT now(),
+    updated_at timestamp with time zone DEFAULT now(),
+    UNIQUE (origin, channel)
+);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS origin_promotion_requests;
+DROP SEQUENCE IF EXISTS origin_promotion_requests_id_seq;
+DROP TYPE IF EXISTS promotion_request_state;
+ALTER TABLE origin_channel_policies DROP COLUMN IF EXISTS require_approval;
@@ -0,0 +1,25 @@
+ALTER TYPE package_channel_trigger ADD VALUE IF NOT EXISTS 'approval';
+
+CREATE TYPE promotion_request_state AS ENUM ('pending', 'approved', 'rejected');
+
+ALTER TABLE origin_channel_policies ADD COLUMN IF NOT EXISTS require_approval boolean NOT NULL DEFAULT false;
+
+CREATE SEQUENCE IF NOT EXISTS origin_promotion_requests_id_seq;
+
+CREATE TABLE IF NOT EXISTS origin_promotion_requests (
+    id bigint DEFAULT next_id_v1('origin_promotion_requests_id_seq') PRIMARY KEY NOT NULL,
+    origin text NOT NULL,
+    channel text NOT NULL,
+    target text NOT NULL,
+    package_idents text[] NOT NULL,
+    state promotion_request_state NOT NULL DEFAULT 'pending',
+    requester_id bigint NOT NULL,
+    requester_name text NOT NULL,
+    reviewer_id bigint,
+    reviewer_name text,
+    review_comment text,
+    created_at timestamp with time zone DEFAULT now(),
+    reviewed_at timestamp with time zone
+);
+
+CREATE INDEX IF NOT EXISTS origin_promotion_requests_origin_channel_idx ON origin_promotion_requests(origin, channel, state);
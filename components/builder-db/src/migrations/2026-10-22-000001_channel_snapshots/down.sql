@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS origin_channel_snapshot_packages;
+DROP TABLE IF EXISTS origin_channel_snapshots;
+DROP SEQUENCE IF EXISTS origin_channel_snapshots_id_seq;
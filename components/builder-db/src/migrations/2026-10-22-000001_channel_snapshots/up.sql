@@ -0,0 +1,22 @@
+CREATE SEQUENCE IF NOT EXISTS origin_channel_snapshots_id_seq;
+
+CREATE TABLE IF NOT EXISTS origin_channel_snapshots (
+    id bigint DEFAULT next_id_v1('origin_channel_snapshots_id_seq') PRIMARY KEY NOT NULL,
+    channel_id bigint NOT NULL REFERENCES origin_channels(id) ON DELETE CASCADE,
+    origin text NOT NULL,
+    channel text NOT NULL,
+    name text NOT NULL,
+    owner_id bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now(),
+    UNIQUE (channel_id, name)
+);
+
+-- Package rows are copied rather than referenced so that a snapshot stays intact even if one of
+-- its packages is later deleted.
+CREATE TABLE IF NOT EXISTS origin_channel_snapshot_packages (
+    snapshot_id bigint NOT NULL REFERENCES origin_channel_snapshots(id) ON DELETE CASCADE,
+    package_id bigint NOT NULL,
+    ident text NOT NULL,
+    target text NOT NULL,
+    PRIMARY KEY (snapshot_id, package_id)
+);
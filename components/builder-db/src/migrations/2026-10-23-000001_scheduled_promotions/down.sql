@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS origin_scheduled_promotions;
+DROP SEQUENCE IF EXISTS origin_scheduled_promotions_id_seq;
+DROP TYPE IF EXISTS scheduled_promotion_state;
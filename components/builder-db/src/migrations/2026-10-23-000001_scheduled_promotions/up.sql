@@ -0,0 +1,25 @@
+ALTER TYPE package_channel_trigger ADD VALUE IF NOT EXISTS 'scheduled';
+
+CREATE TYPE scheduled_promotion_state AS ENUM ('pending', 'running', 'completed', 'skipped', 'failed', 'cancelled');
+
+CREATE SEQUENCE IF NOT EXISTS origin_scheduled_promotions_id_seq;
+
+CREATE TABLE IF NOT EXISTS origin_scheduled_promotions (
+    id bigint DEFAULT next_id_v1('origin_scheduled_promotions_id_seq') PRIMARY KEY NOT NULL,
+    origin text NOT NULL,
+    channel text NOT NULL,
+    ident text NOT NULL,
+    target text NOT NULL,
+    run_at timestamp with time zone NOT NULL,
+    source_channel text,
+    delay_secs bigint,
+    state scheduled_promotion_state NOT NULL DEFAULT 'pending',
+    result text,
+    requester_id bigint NOT NULL,
+    requester_name text NOT NULL,
+    created_at timestamp with time zone DEFAULT now(),
+    updated_at timestamp with time zone DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS origin_scheduled_promotions_origin_channel_idx ON origin_scheduled_promotions(origin, channel, state);
+CREATE INDEX IF NOT EXISTS origin_scheduled_promotions_due_idx ON origin_scheduled_promotions(run_at) WHERE state = 'pending';
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS origin_channels_expires_at_idx;
+ALTER TABLE origin_channels DROP COLUMN IF EXISTS expires_at;
@@ -0,0 +1,3 @@
+ALTER TABLE origin_channels ADD COLUMN IF NOT EXISTS expires_at timestamp with time zone;
+
+CREATE INDEX IF NOT EXISTS origin_channels_expires_at_idx ON origin_channels(expires_at) WHERE expires_at IS NOT NULL;
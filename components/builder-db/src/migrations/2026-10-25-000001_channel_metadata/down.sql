@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS origin_channel_labels;
+ALTER TABLE origin_channels DROP COLUMN IF EXISTS maintainers;
+ALTER TABLE origin_channels DROP COLUMN IF EXISTS frozen;
+ALTER TABLE origin_channels DROP COLUMN IF EXISTS description;
@@ -0,0 +1,12 @@
+ALTER TABLE origin_channels ADD COLUMN IF NOT EXISTS description text;
+ALTER TABLE origin_channels ADD COLUMN IF NOT EXISTS frozen boolean NOT NULL DEFAULT false;
+ALTER TABLE origin_channels ADD COLUMN IF NOT EXISTS maintainers text[] NOT NULL DEFAULT '{}';
+
+CREATE TABLE IF NOT EXISTS origin_channel_labels (
+    channel_id bigint REFERENCES origin_channels(id) ON DELETE CASCADE,
+    key text NOT NULL,
+    value text NOT NULL,
+    created_at timestamp with time zone DEFAULT now(),
+    updated_at timestamp with time zone DEFAULT now(),
+    PRIMARY KEY (channel_id, key)
+);
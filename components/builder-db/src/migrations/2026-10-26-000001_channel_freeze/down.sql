@@ -0,0 +1 @@
+ALTER TABLE audit_origin DROP COLUMN IF EXISTS reason;
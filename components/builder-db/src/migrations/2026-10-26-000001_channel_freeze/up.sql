@@ -0,0 +1,4 @@
+ALTER TYPE origin_operation ADD VALUE IF NOT EXISTS 'channel_freeze';
+ALTER TYPE origin_operation ADD VALUE IF NOT EXISTS 'channel_unfreeze';
+
+ALTER TABLE audit_origin ADD COLUMN IF NOT EXISTS reason text;
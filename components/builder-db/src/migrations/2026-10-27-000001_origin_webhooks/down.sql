@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS origin_webhook_deliveries;
+DROP SEQUENCE IF EXISTS origin_webhook_deliveries_id_seq;
+DROP TABLE IF EXISTS origin_webhooks;
+DROP SEQUENCE IF EXISTS origin_webhooks_id_seq;
+DROP TYPE IF EXISTS webhook_delivery_state;
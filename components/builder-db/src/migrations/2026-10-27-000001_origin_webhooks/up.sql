@@ -0,0 +1,36 @@
+CREATE TYPE webhook_delivery_state AS ENUM ('pending', 'delivering', 'succeeded', 'failed');
+
+CREATE SEQUENCE IF NOT EXISTS origin_webhooks_id_seq;
+
+CREATE TABLE IF NOT EXISTS origin_webhooks (
+    id bigint DEFAULT next_id_v1('origin_webhooks_id_seq') PRIMARY KEY NOT NULL,
+    origin text NOT NULL REFERENCES origins(name) ON DELETE CASCADE,
+    url text NOT NULL,
+    secret text NOT NULL,
+    events text[] NOT NULL DEFAULT '{}',
+    active boolean NOT NULL DEFAULT true,
+    owner_id bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now(),
+    updated_at timestamp with time zone DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS origin_webhooks_origin_idx ON origin_webhooks(origin);
+
+CREATE SEQUENCE IF NOT EXISTS origin_webhook_deliveries_id_seq;
+
+CREATE TABLE IF NOT EXISTS origin_webhook_deliveries (
+    id bigint DEFAULT next_id_v1('origin_webhook_deliveries_id_seq') PRIMARY KEY NOT NULL,
+    webhook_id bigint NOT NULL REFERENCES origin_webhooks(id) ON DELETE CASCADE,
+    event text NOT NULL,
+    payload text NOT NULL,
+    state webhook_delivery_state NOT NULL DEFAULT 'pending',
+    attempts integer NOT NULL DEFAULT 0,
+    next_attempt_at timestamp with time zone NOT NULL DEFAULT now(),
+    response_code integer,
+    error text,
+    created_at timestamp with time zone DEFAULT now(),
+    updated_at timestamp with time zone DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS origin_webhook_deliveries_webhook_idx ON origin_webhook_deliveries(webhook_id, created_at);
+CREATE INDEX IF NOT EXISTS origin_webhook_deliveries_due_idx ON origin_webhook_deliveries(next_attempt_at) WHERE state = 'pending';
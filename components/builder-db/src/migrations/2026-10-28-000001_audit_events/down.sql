@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS audit_events;
+DROP FUNCTION IF EXISTS audit_events_append_only();
+DROP SEQUENCE IF EXISTS audit_events_id_seq;
+DROP TYPE IF EXISTS audit_action;
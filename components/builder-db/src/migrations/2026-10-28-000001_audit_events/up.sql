@@ -0,0 +1,93 @@
+CREATE TYPE audit_action AS ENUM (
+    'origin_create',
+    'origin_update',
+    'origin_delete',
+    'owner_transfer',
+    'member_invite',
+    'invitation_accept',
+    'invitation_ignore',
+    'invitation_rescind',
+    'member_role_update',
+    'member_remove',
+    'member_depart',
+    'key_generate',
+    'public_key_upload',
+    'secret_key_upload',
+    'secret_create',
+    'secret_delete',
+    'integration_create',
+    'integration_delete',
+    'package_upload',
+    'package_delete',
+    'package_visibility_update',
+    'package_settings_create',
+    'package_settings_update',
+    'package_settings_delete',
+    'package_promote',
+    'package_demote',
+    'channel_create',
+    'channel_update',
+    'channel_delete',
+    'channel_expiry_extend',
+    'channel_freeze',
+    'channel_unfreeze',
+    'channel_policy_update',
+    'channel_policy_delete',
+    'channel_snapshot_create',
+    'channel_snapshot_restore',
+    'promotion_request_create',
+    'promotion_request_approve',
+    'promotion_request_reject',
+    'scheduled_promotion_create',
+    'scheduled_promotion_cancel',
+    'webhook_create',
+    'webhook_update',
+    'webhook_delete',
+    'webhook_redeliver',
+    'account_update',
+    'token_create',
+    'token_revoke',
+    'license_update'
+);
+
+CREATE SEQUENCE IF NOT EXISTS audit_events_id_seq;
+
+CREATE TABLE IF NOT EXISTS audit_events (
+    id bigint DEFAULT next_id_v1('audit_events_id_seq') PRIMARY KEY NOT NULL,
+    origin text,
+    actor_id bigint NOT NULL,
+    actor_name text NOT NULL,
+    action audit_action NOT NULL,
+    target text NOT NULL,
+    before jsonb,
+    after jsonb,
+    source_ip text,
+    trigger package_channel_trigger NOT NULL DEFAULT 'unknown',
+    created_at timestamp with time zone DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS audit_events_origin_idx ON audit_events(origin, created_at);
+CREATE INDEX IF NOT EXISTS audit_events_actor_idx ON audit_events(actor_id, created_at);
+
+-- The audit log is append-only. Like the other audit tables it has no foreign keys, so records
+-- outlive the origins and accounts they name.
+CREATE OR REPLACE FUNCTION audit_events_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_events is append-only';
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_append_only
+    BEFORE UPDATE OR DELETE OR TRUNCATE ON audit_events
+    FOR EACH STATEMENT EXECUTE PROCEDURE audit_events_append_only();
+
+INSERT INTO audit_events (origin, actor_id, actor_name, action, target, after, created_at)
+    SELECT origin,
+           COALESCE(requester_id, 0),
+           COALESCE(requester_name, ''),
+           operation::text::audit_action,
+           COALESCE(target_object, ''),
+           CASE WHEN reason IS NULL THEN NULL ELSE jsonb_build_object('reason', reason) END,
+           created_at
+    FROM audit_origin
+    WHERE operation IS NOT NULL;
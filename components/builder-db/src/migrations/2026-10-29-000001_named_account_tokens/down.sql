@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS account_tokens_account_id_idx;
+
+-- Only the newest token of each account survives going back to one token per account
+DELETE FROM account_tokens a USING account_tokens b
+    WHERE a.account_id = b.account_id AND a.created_at < b.created_at;
+
+ALTER TABLE account_tokens DROP COLUMN IF EXISTS expires_at;
+ALTER TABLE account_tokens DROP COLUMN IF EXISTS last_used_at;
+ALTER TABLE account_tokens DROP COLUMN IF EXISTS name;
+ALTER TABLE account_tokens ADD UNIQUE(account_id);
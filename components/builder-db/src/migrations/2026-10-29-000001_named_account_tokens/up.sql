@@ -0,0 +1,8 @@
+ALTER TABLE account_tokens DROP CONSTRAINT IF EXISTS account_tokens_account_id_key;
+
+ALTER TABLE account_tokens ADD COLUMN IF NOT EXISTS name text NOT NULL DEFAULT 'default';
+ALTER TABLE account_tokens ADD COLUMN IF NOT EXISTS last_used_at timestamp with time zone;
+ALTER TABLE account_tokens ADD COLUMN IF NOT EXISTS expires_at timestamp with time zone;
+ALTER TABLE account_tokens ALTER COLUMN name DROP DEFAULT;
+
+CREATE INDEX IF NOT EXISTS account_tokens_account_id_idx ON account_tokens(account_id);
@@ -0,0 +1 @@
+ALTER TABLE account_tokens ADD COLUMN IF NOT EXISTS scope jsonb;
@@ -0,0 +1,5 @@
+DELETE FROM account_tokens WHERE account_id IN (SELECT id FROM accounts WHERE robot_origin IS NOT NULL);
+DELETE FROM origin_members WHERE account_id IN (SELECT id FROM accounts WHERE robot_origin IS NOT NULL);
+DELETE FROM accounts WHERE robot_origin IS NOT NULL;
+
+ALTER TABLE accounts DROP COLUMN IF EXISTS robot_origin;
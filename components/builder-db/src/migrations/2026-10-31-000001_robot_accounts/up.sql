@@ -0,0 +1,8 @@
+-- Robot accounts belong to the origin they were created in, and can't be signed in to via OAuth
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS robot_origin text;
+
+CREATE INDEX IF NOT EXISTS accounts_robot_origin_idx ON accounts(robot_origin)
+    WHERE robot_origin IS NOT NULL;
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'robot_create';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'robot_delete';
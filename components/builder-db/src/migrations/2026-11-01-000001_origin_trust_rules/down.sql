@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS origin_trust_rules;
+DROP SEQUENCE IF EXISTS origin_trust_rules_id_seq;
@@ -0,0 +1,20 @@
+CREATE SEQUENCE IF NOT EXISTS origin_trust_rules_id_seq;
+
+CREATE TABLE IF NOT EXISTS origin_trust_rules (
+    id bigint DEFAULT next_id_v1('origin_trust_rules_id_seq') PRIMARY KEY NOT NULL,
+    origin text NOT NULL REFERENCES origins(name) ON DELETE CASCADE,
+    issuer text NOT NULL,
+    claims jsonb NOT NULL,
+    member_role origin_member_role NOT NULL,
+    capabilities text[] NOT NULL DEFAULT '{}',
+    channels text[] NOT NULL DEFAULT '{}',
+    owner_id bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now(),
+    updated_at timestamp with time zone DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS origin_trust_rules_origin_idx ON origin_trust_rules(origin, issuer);
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'trust_rule_create';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'trust_rule_delete';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'token_exchange';
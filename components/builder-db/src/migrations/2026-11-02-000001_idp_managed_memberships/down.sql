@@ -0,0 +1,3 @@
+DELETE FROM origin_members WHERE idp_managed;
+
+ALTER TABLE origin_members DROP COLUMN IF EXISTS idp_managed;
@@ -0,0 +1,5 @@
+-- Memberships granted by the groups of an identity provider are kept in step with them on
+-- every sign in, and never mixed up with memberships granted by invitation
+ALTER TABLE origin_members ADD COLUMN IF NOT EXISTS idp_managed boolean NOT NULL DEFAULT false;
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'member_add';
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS origin_team_members;
+DROP TABLE IF EXISTS origin_teams;
+DROP SEQUENCE IF EXISTS origin_teams_id_seq;
@@ -0,0 +1,31 @@
+CREATE SEQUENCE IF NOT EXISTS origin_teams_id_seq;
+
+CREATE TABLE IF NOT EXISTS origin_teams (
+    id bigint DEFAULT next_id_v1('origin_teams_id_seq') PRIMARY KEY NOT NULL,
+    origin text NOT NULL REFERENCES origins(name) ON DELETE CASCADE,
+    name text NOT NULL,
+    description text NOT NULL DEFAULT '',
+    member_role origin_member_role NOT NULL,
+    owner_id bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now(),
+    updated_at timestamp with time zone DEFAULT now(),
+    UNIQUE (origin, name)
+);
+
+-- Teams are made up of the origin's members, and whoever leaves the origin leaves its teams
+CREATE TABLE IF NOT EXISTS origin_team_members (
+    team_id bigint NOT NULL REFERENCES origin_teams(id) ON DELETE CASCADE,
+    origin text NOT NULL,
+    account_id bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now(),
+    PRIMARY KEY (team_id, account_id),
+    FOREIGN KEY (origin, account_id) REFERENCES origin_members(origin, account_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS origin_team_members_account_idx ON origin_team_members(origin, account_id);
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'team_create';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'team_update';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'team_delete';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'team_member_add';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'team_member_remove';
@@ -0,0 +1,4 @@
+ALTER TABLE origin_members DROP COLUMN IF EXISTS custom_role_id;
+
+DROP TABLE IF EXISTS origin_custom_roles;
+DROP SEQUENCE IF EXISTS origin_custom_roles_id_seq;
@@ -0,0 +1,25 @@
+CREATE SEQUENCE IF NOT EXISTS origin_custom_roles_id_seq;
+
+-- Roles an origin defines for itself, each bundling the permissions it names, such as
+-- 'secrets' or 'promote:staging'
+CREATE TABLE IF NOT EXISTS origin_custom_roles (
+    id bigint DEFAULT next_id_v1('origin_custom_roles_id_seq') PRIMARY KEY NOT NULL,
+    origin text NOT NULL REFERENCES origins(name) ON DELETE CASCADE,
+    name text NOT NULL,
+    description text NOT NULL DEFAULT '',
+    permissions text[] NOT NULL DEFAULT '{}',
+    owner_id bigint NOT NULL,
+    created_at timestamp with time zone DEFAULT now(),
+    updated_at timestamp with time zone DEFAULT now(),
+    UNIQUE (origin, name)
+);
+
+-- A member's custom role adds its permissions to those of their role
+ALTER TABLE origin_members ADD COLUMN IF NOT EXISTS custom_role_id bigint
+    REFERENCES origin_custom_roles(id) ON DELETE SET NULL;
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'role_create';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'role_update';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'role_delete';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'role_assign';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'role_unassign';
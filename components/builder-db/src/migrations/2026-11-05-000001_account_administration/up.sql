@@ -0,0 +1,7 @@
+-- Builder administrators may disable an account, which then can't sign in or use its tokens
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS disabled boolean NOT NULL DEFAULT false;
+
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'account_disable';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'account_enable';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'origin_hide';
+ALTER TYPE audit_action ADD VALUE IF NOT EXISTS 'origin_unhide';
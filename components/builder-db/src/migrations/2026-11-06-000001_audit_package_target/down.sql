@@ -0,0 +1 @@
+ALTER TABLE audit_package DROP COLUMN IF EXISTS target;
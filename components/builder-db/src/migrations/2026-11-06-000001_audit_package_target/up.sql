@@ -0,0 +1,3 @@
+-- The target a package was promoted or demoted for, so that channel history can be replayed per
+-- target. Changes recorded before this column existed have none.
+ALTER TABLE audit_package ADD COLUMN IF NOT EXISTS target text;
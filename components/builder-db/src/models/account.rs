@@ -1,36 +1,53 @@
 use super::db_id_format;
 use chrono::NaiveDateTime;
 use diesel::{self,
-             pg::PgConnection,
-             result::QueryResult,
+             pg::{Pg,
+                  PgConnection},
+             result::{Error,
+                      QueryResult},
+             Connection,
              ExpressionMethods,
+             PgTextExpressionMethods,
              QueryDsl,
              RunQueryDsl};
 
 use crate::{bldr_core::metrics::CounterMetric,
             metrics::Counter,
-            schema::account::{account_tokens,
-                              accounts}};
+            models::origin::{OriginMember,
+                             OriginMemberRole},
+            schema::{account::{account_tokens,
+                               accounts},
+                     member::origin_members}};
 
 #[derive(Debug, Identifiable, Serialize, Queryable)]
 pub struct Account {
     #[serde(with = "db_id_format")]
-    pub id:         i64,
-    pub email:      String,
-    pub name:       String,
-    pub created_at: Option<NaiveDateTime>,
-    pub updated_at: Option<NaiveDateTime>,
+    pub id:           i64,
+    pub email:        String,
+    pub name:         String,
+    pub created_at:   Option<NaiveDateTime>,
+    pub updated_at:   Option<NaiveDateTime>,
+    /// The origin a robot account belongs to. Robot accounts can't sign in through OAuth.
+    pub robot_origin: Option<String>,
+    /// Disabled accounts can neither sign in nor use their tokens
+    pub disabled:     bool,
 }
 
 #[derive(Identifiable, Debug, Serialize, Queryable)]
 #[diesel(table_name = account_tokens)]
 pub struct AccountToken {
     #[serde(with = "db_id_format")]
-    pub id:         i64,
+    pub id:           i64,
     #[serde(with = "db_id_format")]
-    pub account_id: i64,
-    pub token:      String,
-    pub created_at: Option<NaiveDateTime>,
+    pub account_id:   i64,
+    #[serde(skip_serializing)]
+    pub token:        String,
+    pub created_at:   Option<NaiveDateTime>,
+    pub name:         String,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub expires_at:   Option<NaiveDateTime>,
+    /// The origins, capabilities and channels the token is limited to, if any
+    pub scope:        Option<serde_json::Value>,
 }
 
 #[derive(Insertable)]
@@ -40,6 +57,20 @@ pub struct NewAccount<'a> {
     pub name:  &'a str,
 }
 
+#[derive(Insertable)]
+#[diesel(table_name = accounts)]
+pub struct NewRobotAccount<'a> {
+    pub email:        &'a str,
+    pub name:         &'a str,
+    pub robot_origin: &'a str,
+}
+
+pub struct SearchAccounts {
+    pub query: String,
+    pub page:  i64,
+    pub limit: i64,
+}
+
 impl Account {
     pub fn get(name: &str, conn: &mut PgConnection) -> QueryResult<Account> {
         Counter::DBCall.increment();
@@ -78,6 +109,85 @@ impl Account {
         diesel::update(accounts::table.find(id as i64)).set(accounts::email.eq(email))
                                                        .execute(conn)
     }
+
+    pub fn is_robot(&self) -> bool { self.robot_origin.is_some() }
+
+    /// A page of the accounts whose name or email contains the query, by name, and the number of
+    /// accounts that match.
+    pub fn search(sa: &SearchAccounts,
+                  conn: &mut PgConnection)
+                  -> QueryResult<(Vec<Account>, i64)> {
+        Counter::DBCall.increment();
+        let total_count = Self::matching(&sa.query).count().get_result(conn)?;
+        let accounts = Self::matching(&sa.query).order(accounts::name.asc())
+                                                .limit(sa.limit)
+                                                .offset((sa.page - 1) * sa.limit)
+                                                .get_results(conn)?;
+        Ok((accounts, total_count))
+    }
+
+    fn matching(query: &str) -> accounts::BoxedQuery<'_, Pg> {
+        if query.is_empty() {
+            return accounts::table.into_boxed();
+        }
+        // The query is matched literally, so LIKE's wildcards in it are escaped
+        let pattern = format!("%{}%",
+                              query.replace('\\', "\\\\")
+                                   .replace('%', "\\%")
+                                   .replace('_', "\\_"));
+        accounts::table.filter(accounts::name.ilike(pattern.clone())
+                                             .or(accounts::email.ilike(pattern)))
+                       .into_boxed()
+    }
+
+    pub fn set_disabled(id: i64, disabled: bool, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(accounts::table.find(id)).set((accounts::disabled.eq(disabled),
+                                                      accounts::updated_at.eq(diesel::dsl::now)))
+                                                .execute(conn)
+    }
+
+    /// Creates a robot account and makes it a member of its origin.
+    pub fn create_robot(account: &NewRobotAccount,
+                        member_role: OriginMemberRole,
+                        conn: &mut PgConnection)
+                        -> QueryResult<Account> {
+        Counter::DBCall.increment();
+        conn.transaction::<_, Error, _>(|txn_conn| {
+                let robot: Account = diesel::insert_into(accounts::table).values(account)
+                                                                         .get_result(txn_conn)?;
+                OriginMember::add(account.robot_origin, robot.id, txn_conn, member_role)?;
+                Ok(robot)
+            })
+    }
+
+    pub fn get_robot(origin: &str, name: &str, conn: &mut PgConnection) -> QueryResult<Account> {
+        Counter::DBCall.increment();
+        accounts::table.filter(accounts::robot_origin.eq(origin))
+                       .filter(accounts::name.eq(name))
+                       .get_result(conn)
+    }
+
+    pub fn list_robots(origin: &str, conn: &mut PgConnection) -> QueryResult<Vec<Account>> {
+        Counter::DBCall.increment();
+        accounts::table.filter(accounts::robot_origin.eq(origin))
+                       .order(accounts::name.asc())
+                       .get_results(conn)
+    }
+
+    /// Deletes a robot account along with its tokens and membership.
+    pub fn delete_robot(id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        conn.transaction::<_, Error, _>(|txn_conn| {
+                diesel::delete(account_tokens::table.filter(account_tokens::account_id.eq(id)))
+                    .execute(txn_conn)?;
+                diesel::delete(origin_members::table.filter(origin_members::account_id.eq(id)))
+                    .execute(txn_conn)?;
+                diesel::delete(accounts::table.filter(accounts::id.eq(id))
+                                              .filter(accounts::robot_origin.is_not_null()))
+                    .execute(txn_conn)
+            })
+    }
 }
 
 #[derive(Insertable)]
@@ -85,24 +195,33 @@ impl Account {
 pub struct NewAccountToken<'a> {
     pub account_id: i64,
     pub token:      &'a str,
+    pub name:       &'a str,
+    pub expires_at: Option<NaiveDateTime>,
+    pub scope:      Option<serde_json::Value>,
 }
 
 impl AccountToken {
     pub fn list(account_id: u64, conn: &mut PgConnection) -> QueryResult<Vec<AccountToken>> {
         Counter::DBCall.increment();
         account_tokens::table.filter(account_tokens::account_id.eq(account_id as i64))
+                             .order(account_tokens::created_at.asc())
                              .get_results(conn)
     }
 
     pub fn create(req: &NewAccountToken, conn: &mut PgConnection) -> QueryResult<AccountToken> {
         Counter::DBCall.increment();
         diesel::insert_into(account_tokens::table).values(req)
-                                                  .on_conflict(account_tokens::account_id)
-                                                  .do_update()
-                                                  .set(account_tokens::token.eq(req.token))
                                                   .get_result(conn)
     }
 
+    /// Records that the token was just used to authenticate.
+    pub fn touch(id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(account_tokens::table.find(id))
+            .set(account_tokens::last_used_at.eq(diesel::dsl::now))
+            .execute(conn)
+    }
+
     pub fn delete(id: u64, conn: &mut PgConnection) -> QueryResult<usize> {
         Counter::DBCall.increment();
         diesel::delete(account_tokens::table.find(id as i64)).execute(conn)
@@ -0,0 +1,182 @@
+use super::db_id_format;
+use chrono::NaiveDateTime;
+use diesel::{self,
+             expression::IntoSql,
+             pg::{Pg,
+                  PgConnection},
+             result::QueryResult,
+             sql_types::Timestamptz,
+             ExpressionMethods,
+             NullableExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+use diesel_derive_enum::DbEnum;
+
+use crate::{bldr_core::metrics::CounterMetric,
+            metrics::Counter,
+            models::channel::PackageChannelTrigger,
+            schema::audit::audit_events};
+
+/// Every change that is recorded in the audit log.
+#[derive(DbEnum, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[ExistingTypePath = "crate::schema::sql_types::AuditAction"]
+#[DbValueStyle = "snake_case"]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    OriginCreate,
+    OriginUpdate,
+    OriginDelete,
+    OwnerTransfer,
+    MemberInvite,
+    InvitationAccept,
+    InvitationIgnore,
+    InvitationRescind,
+    MemberRoleUpdate,
+    MemberRemove,
+    MemberDepart,
+    KeyGenerate,
+    PublicKeyUpload,
+    SecretKeyUpload,
+    SecretCreate,
+    SecretDelete,
+    IntegrationCreate,
+    IntegrationDelete,
+    PackageUpload,
+    PackageDelete,
+    PackageVisibilityUpdate,
+    PackageSettingsCreate,
+    PackageSettingsUpdate,
+    PackageSettingsDelete,
+    PackagePromote,
+    PackageDemote,
+    ChannelCreate,
+    ChannelUpdate,
+    ChannelDelete,
+    ChannelExpiryExtend,
+    ChannelFreeze,
+    ChannelUnfreeze,
+    ChannelPolicyUpdate,
+    ChannelPolicyDelete,
+    ChannelSnapshotCreate,
+    ChannelSnapshotRestore,
+    PromotionRequestCreate,
+    PromotionRequestApprove,
+    PromotionRequestReject,
+    ScheduledPromotionCreate,
+    ScheduledPromotionCancel,
+    WebhookCreate,
+    WebhookUpdate,
+    WebhookDelete,
+    WebhookRedeliver,
+    AccountUpdate,
+    TokenCreate,
+    TokenRevoke,
+    LicenseUpdate,
+    RobotCreate,
+    RobotDelete,
+    TrustRuleCreate,
+    TrustRuleDelete,
+    TokenExchange,
+    MemberAdd,
+    TeamCreate,
+    TeamUpdate,
+    TeamDelete,
+    TeamMemberAdd,
+    TeamMemberRemove,
+    RoleCreate,
+    RoleUpdate,
+    RoleDelete,
+    RoleAssign,
+    RoleUnassign,
+    AccountDisable,
+    AccountEnable,
+    OriginHide,
+    OriginUnhide,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct AuditEvent {
+    #[serde(with = "db_id_format")]
+    pub id:         i64,
+    pub origin:     Option<String>,
+    #[serde(with = "db_id_format")]
+    pub actor_id:   i64,
+    pub actor_name: String,
+    pub action:     AuditAction,
+    pub target:     String,
+    pub before:     Option<serde_json::Value>,
+    pub after:      Option<serde_json::Value>,
+    pub source_ip:  Option<String>,
+    pub trigger:    PackageChannelTrigger,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = audit_events)]
+pub struct NewAuditEvent<'a> {
+    pub origin:     Option<&'a str>,
+    pub actor_id:   i64,
+    pub actor_name: &'a str,
+    pub action:     AuditAction,
+    pub target:     &'a str,
+    pub before:     Option<serde_json::Value>,
+    pub after:      Option<serde_json::Value>,
+    pub source_ip:  Option<&'a str>,
+    pub trigger:    PackageChannelTrigger,
+}
+
+pub struct ListAuditEvents {
+    pub origin:    String,
+    pub action:    Option<AuditAction>,
+    pub actor:     Option<String>,
+    pub target:    Option<String>,
+    pub from_date: Option<NaiveDateTime>,
+    pub to_date:   Option<NaiveDateTime>,
+    pub page:      i64,
+    pub limit:     i64,
+}
+
+impl AuditEvent {
+    pub fn create(event: &NewAuditEvent, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::insert_into(audit_events::table).values(event)
+                                                .execute(conn)
+    }
+
+    /// A page of an origin's audit log, newest first, and the number of records that match.
+    pub fn list(le: &ListAuditEvents,
+                conn: &mut PgConnection)
+                -> QueryResult<(Vec<AuditEvent>, i64)> {
+        Counter::DBCall.increment();
+        let total_count = Self::filtered(le).count().get_result(conn)?;
+        let events = Self::filtered(le).order((audit_events::created_at.desc(),
+                                               audit_events::id.desc()))
+                                       .limit(le.limit)
+                                       .offset((le.page - 1) * le.limit)
+                                       .get_results(conn)?;
+        Ok((events, total_count))
+    }
+
+    fn filtered(le: &ListAuditEvents) -> audit_events::BoxedQuery<'_, Pg> {
+        let mut query = audit_events::table.filter(audit_events::origin.eq(&le.origin))
+                                           .into_boxed();
+        if let Some(action) = le.action {
+            query = query.filter(audit_events::action.eq(action));
+        }
+        if let Some(actor) = &le.actor {
+            query = query.filter(audit_events::actor_name.eq(actor));
+        }
+        if let Some(target) = &le.target {
+            query = query.filter(audit_events::target.eq(target));
+        }
+        if let Some(from_date) = le.from_date {
+            query = query.filter(audit_events::created_at.ge(from_date.into_sql::<Timestamptz>()
+                                                                      .nullable()));
+        }
+        if let Some(to_date) = le.to_date {
+            query = query.filter(audit_events::created_at.lt(to_date.into_sql::<Timestamptz>()
+                                                                    .nullable()));
+        }
+        query
+    }
+}
@@ -11,7 +11,8 @@ use crate::{bldr_core::metrics::{CounterMetric,
                               PackageWithVersionArray},
             schema::{audit::{audit_package,
                              audit_package_group},
-                     channel::{origin_channel_packages,
+                     channel::{origin_channel_labels,
+                               origin_channel_packages,
                                origin_channels},
                      member::origin_members,
                      origin::origins,
@@ -19,7 +20,9 @@ use crate::{bldr_core::metrics::{CounterMetric,
                                origin_packages_with_version_array}}};
 use chrono::NaiveDateTime;
 use diesel_derive_enum::DbEnum;
-use std::time::Instant;
+use std::{collections::{HashMap,
+                        HashSet},
+          time::Instant};
 
 use diesel::{self,
              dsl::{count,
@@ -31,6 +34,7 @@ use diesel::{self,
              result::QueryResult,
              sql_types::{Text,
                          Timestamptz},
+             upsert::excluded,
              ExpressionMethods,
              NullableExpressionMethods,
              PgArrayExpressionMethods,
@@ -43,13 +47,34 @@ use diesel_full_text_search::{to_tsquery,
 #[derive(Debug, Serialize, Deserialize, Queryable)]
 pub struct Channel {
     #[serde(with = "db_id_format")]
-    pub id:         i64,
+    pub id:          i64,
     #[serde(with = "db_id_format")]
-    pub owner_id:   i64,
-    pub name:       String,
-    pub created_at: Option<NaiveDateTime>,
-    pub updated_at: Option<NaiveDateTime>,
-    pub origin:     String,
+    pub owner_id:    i64,
+    pub name:        String,
+    pub created_at:  Option<NaiveDateTime>,
+    pub updated_at:  Option<NaiveDateTime>,
+    pub origin:      String,
+    pub expires_at:  Option<NaiveDateTime>,
+    pub description: Option<String>,
+    pub frozen:      bool,
+    pub maintainers: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct ChannelLabel {
+    #[serde(with = "db_id_format")]
+    pub channel_id: i64,
+    pub key:        String,
+    pub value:      String,
+}
+
+// Fields left as None are not changed. Setting the description to Some(None) clears it.
+#[derive(AsChangeset, Default)]
+#[diesel(table_name = origin_channels)]
+pub struct UpdateChannel<'a> {
+    pub description: Option<Option<&'a str>>,
+    pub frozen:      Option<bool>,
+    pub maintainers: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -57,32 +82,40 @@ pub struct ChannelWithPromotion {
     pub name:        String,
     pub created_at:  Option<NaiveDateTime>,
     pub promoted_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at:  Option<NaiveDateTime>,
 }
 
 #[derive(Insertable)]
 #[diesel(table_name = origin_channels)]
 pub struct CreateChannel<'a> {
     // This would be ChannelIdent, but Insertable requires implementing diesel::Expression
-    pub name:     &'a str,
-    pub owner_id: i64,
-    pub origin:   &'a str,
+    pub name:       &'a str,
+    pub owner_id:   i64,
+    pub origin:     &'a str,
+    pub expires_at: Option<NaiveDateTime>,
 }
 
+// Channels can hold public packages from other origins. `visibility` applies to packages of the
+// channel's own origin, and `hidden_origins` lists other origins whose packages must not be
+// returned to the requester.
 #[derive(Clone, Debug)]
 pub struct GetLatestPackage<'a> {
-    pub ident:      &'a BuilderPackageIdent,
-    pub visibility: &'a Vec<PackageVisibility>,
-    pub channel:    &'a ChannelIdent,
-    pub target:     &'a str,
+    pub ident:          &'a BuilderPackageIdent,
+    pub visibility:     &'a Vec<PackageVisibility>,
+    pub channel:        &'a ChannelIdent,
+    pub target:         &'a str,
+    pub hidden_origins: &'a [String],
 }
 
 pub struct ListChannelPackages {
-    pub ident:      BuilderPackageIdent,
-    pub visibility: Vec<PackageVisibility>,
-    pub channel:    ChannelIdent,
-    pub origin:     String,
-    pub page:       i64,
-    pub limit:      i64,
+    pub ident:          BuilderPackageIdent,
+    pub visibility:     Vec<PackageVisibility>,
+    pub channel:        ChannelIdent,
+    pub origin:         String,
+    pub page:           i64,
+    pub limit:          i64,
+    pub hidden_origins: Vec<String>,
 }
 
 pub struct ListAllChannelPackages<'a> {
@@ -92,10 +125,11 @@ pub struct ListAllChannelPackages<'a> {
 }
 
 pub struct ListAllChannelPackagesForTarget<'a> {
-    pub visibility: &'a Vec<PackageVisibility>,
-    pub channel:    &'a ChannelIdent,
-    pub origin:     &'a str,
-    pub target:     &'a str,
+    pub visibility:     &'a Vec<PackageVisibility>,
+    pub channel:        &'a ChannelIdent,
+    pub origin:         &'a str,
+    pub target:         &'a str,
+    pub hidden_origins: &'a [String],
 }
 
 impl Channel {
@@ -145,6 +179,131 @@ impl Channel {
         .execute(conn)
     }
 
+    pub fn set_expiry(origin: &str,
+                      channel: &ChannelIdent,
+                      expires_at: NaiveDateTime,
+                      conn: &mut PgConnection)
+                      -> QueryResult<Channel> {
+        Counter::DBCall.increment();
+        diesel::update(
+            origin_channels::table
+                .filter(origin_channels::origin.eq(origin))
+                .filter(origin_channels::name.eq(channel.as_str())),
+        )
+        .set((
+            origin_channels::expires_at.eq(expires_at),
+            origin_channels::updated_at.eq(diesel::dsl::now),
+        ))
+        .get_result(conn)
+    }
+
+    // Applies metadata changes to a channel. Labels in `set_labels` are added or overwritten and
+    // labels in `remove_labels` are dropped, all in the same transaction as the column updates.
+    pub fn update_metadata(channel_id: i64,
+                           changes: &UpdateChannel,
+                           set_labels: &[(String, String)],
+                           remove_labels: &[String],
+                           conn: &mut PgConnection)
+                           -> QueryResult<Channel> {
+        Counter::DBCall.increment();
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            if !set_labels.is_empty() {
+                let rows: Vec<_> = set_labels.iter()
+                                             .map(|(key, value)| {
+                                                 (origin_channel_labels::channel_id.eq(channel_id),
+                                                  origin_channel_labels::key.eq(key),
+                                                  origin_channel_labels::value.eq(value))
+                                             })
+                                             .collect();
+                diesel::insert_into(origin_channel_labels::table)
+                    .values(rows)
+                    .on_conflict((origin_channel_labels::channel_id, origin_channel_labels::key))
+                    .do_update()
+                    .set((
+                        origin_channel_labels::value.eq(excluded(origin_channel_labels::value)),
+                        origin_channel_labels::updated_at.eq(diesel::dsl::now),
+                    ))
+                    .execute(conn)?;
+            }
+
+            if !remove_labels.is_empty() {
+                diesel::delete(
+                    origin_channel_labels::table
+                        .filter(origin_channel_labels::channel_id.eq(channel_id))
+                        .filter(origin_channel_labels::key.eq_any(remove_labels)),
+                )
+                .execute(conn)?;
+            }
+
+            diesel::update(origin_channels::table.find(channel_id))
+                .set((changes, origin_channels::updated_at.eq(diesel::dsl::now)))
+                .get_result(conn)
+        })
+    }
+
+    pub fn list_labels(channel_ids: &[i64],
+                       conn: &mut PgConnection)
+                       -> QueryResult<Vec<ChannelLabel>> {
+        Counter::DBCall.increment();
+        origin_channel_labels::table
+            .filter(origin_channel_labels::channel_id.eq_any(channel_ids))
+            .select((origin_channel_labels::channel_id,
+                     origin_channel_labels::key,
+                     origin_channel_labels::value))
+            .order(origin_channel_labels::key.asc())
+            .get_results(conn)
+    }
+
+    // Channels that don't exist yet are not frozen, since promoting into them creates them
+    pub fn is_frozen(origin: &str,
+                     channel: &ChannelIdent,
+                     conn: &mut PgConnection)
+                     -> QueryResult<bool> {
+        Counter::DBCall.increment();
+        origin_channels::table.filter(origin_channels::origin.eq(origin))
+                              .filter(origin_channels::name.eq(channel.as_str()))
+                              .select(origin_channels::frozen)
+                              .first::<bool>(conn)
+                              .optional()
+                              .map(|frozen| frozen.unwrap_or(false))
+    }
+
+    // Frozen channels are kept past their expiry until they are unfrozen
+    pub fn list_expired(conn: &mut PgConnection) -> QueryResult<Vec<Channel>> {
+        Counter::DBCall.increment();
+        origin_channels::table.filter(origin_channels::expires_at.le(diesel::dsl::now))
+                              .filter(origin_channels::frozen.eq(false))
+                              .order(origin_channels::expires_at.asc())
+                              .get_results(conn)
+    }
+
+    // Deletes an expired channel along with its package memberships. The expiry is checked
+    // again under a row lock so that a channel extended or frozen since it was listed is left
+    // alone, in which case zero is returned.
+    pub fn delete_expired(channel_id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let expired =
+                origin_channels::table.filter(origin_channels::id.eq(channel_id))
+                                      .filter(origin_channels::expires_at.le(diesel::dsl::now))
+                                      .filter(origin_channels::frozen.eq(false))
+                                      .select(origin_channels::id)
+                                      .for_update()
+                                      .get_result::<i64>(conn)
+                                      .optional()?;
+            if expired.is_none() {
+                return Ok(0);
+            }
+
+            diesel::delete(
+                origin_channel_packages::table
+                    .filter(origin_channel_packages::channel_id.eq(channel_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(origin_channels::table.find(channel_id)).execute(conn)
+        })
+    }
+
     pub fn delete_channel_package(package_id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
         Counter::DBCall.increment();
         diesel::delete(
@@ -159,20 +318,33 @@ impl Channel {
                               -> QueryResult<PackageWithVersionArray> {
         Counter::DBCall.increment();
         let ident = req.ident;
+        let hidden_origins = req.hidden_origins;
         let start_time = Instant::now();
 
+        // The ident's origin is the channel's origin, and the package may come from any origin
+        // whose packages are in that channel. Packages of the channel's own origin win a tie.
         let result = PackageWithVersionArray::all()
             .inner_join(origin_channel_packages::table.inner_join(origin_channels::table))
-            .filter(origin_packages_with_version_array::origin.eq(&ident.origin))
+            .filter(origin_channels::origin.eq(&ident.origin))
             .filter(origin_packages_with_version_array::name.eq(&ident.name))
-            .filter(origin_packages_with_version_array::ident_array.contains(ident.clone().parts()))
+            .filter(origin_packages_with_version_array::ident_array.contains(ident_parts(ident)))
             .filter(origin_channels::name.eq(req.channel.as_str()))
             .filter(origin_packages_with_version_array::target.eq(req.target))
-            .filter(origin_packages_with_version_array::visibility.eq_any(req.visibility))
-            .order(sql::<Text>(
-                "string_to_array(version_array[1],'.')::\
-                 numeric[] desc, version_array[2] desc, \
-                 ident_array[4] desc",
+            .filter(
+                origin_packages_with_version_array::origin
+                    .eq(&ident.origin)
+                    .and(origin_packages_with_version_array::visibility.eq_any(req.visibility))
+                    .or(origin_packages_with_version_array::visibility
+                        .eq(PackageVisibility::Public)
+                        .and(origin_packages_with_version_array::origin.ne_all(hidden_origins))),
+            )
+            .order((
+                origin_packages_with_version_array::origin.eq(&ident.origin).desc(),
+                sql::<Text>(
+                    "string_to_array(version_array[1],'.')::\
+                     numeric[] desc, version_array[2] desc, \
+                     ident_array[4] desc",
+                ),
             ))
             .limit(1)
             .get_result(conn);
@@ -193,23 +365,34 @@ impl Channel {
         let start_time = Instant::now();
         let channel = String::from(req.channel.as_str());
         let target = String::from(req.target);
+        let hidden_origins = req.hidden_origins;
 
         let query = origin_packages_with_version_array::table
             .inner_join(origin_channel_packages::table.inner_join(origin_channels::table))
-            .filter(origin_packages_with_version_array::origin.eq(&req.origin))
+            .filter(origin_channels::origin.eq(&req.origin))
             .filter(origin_channels::name.eq(&channel))
             .filter(origin_packages_with_version_array::target.eq(&target))
-            .filter(origin_packages_with_version_array::visibility.eq_any(req.visibility))
-            .distinct_on(origin_packages_with_version_array::name)
+            .filter(
+                origin_packages_with_version_array::origin
+                    .eq(&req.origin)
+                    .and(origin_packages_with_version_array::visibility.eq_any(req.visibility))
+                    .or(origin_packages_with_version_array::visibility
+                        .eq(PackageVisibility::Public)
+                        .and(origin_packages_with_version_array::origin.ne_all(hidden_origins))),
+            )
+            .distinct_on((
+                origin_packages_with_version_array::origin,
+                origin_packages_with_version_array::name,
+            ))
             .select((
                 origin_packages_with_version_array::name,
                 origin_packages_with_version_array::ident,
             ))
             .order((
+                origin_packages_with_version_array::origin,
                 origin_packages_with_version_array::name,
                 sql::<Text>(
-                    "name,\
-                string_to_array(version_array[1],'.')::numeric[] desc,\
+                    "string_to_array(version_array[1],'.')::numeric[] desc,\
                 version_array[2] desc,\
                 ident_array[4] desc",
                 ),
@@ -239,11 +422,11 @@ impl Channel {
         Counter::DBCall.increment();
         let start_time = Instant::now();
 
-        let origin_str = lcp.ident.origin.clone();
         let name_str = lcp.ident.name.clone();
         let channel_name: String = lcp.channel.clone().to_string();
-        let ident_parts = lcp.ident.clone().parts();
+        let ident_parts = ident_parts(&lcp.ident);
         let visibility_list = lcp.visibility.clone();
+        let hidden_origins = lcp.hidden_origins.clone();
         let origin_name = lcp.origin.clone();
         let page_i64 = lcp.page;
         let limit_i64 = lcp.limit;
@@ -256,15 +439,21 @@ impl Channel {
             .into_boxed::<diesel::pg::Pg>();
         // We need the into_boxed above to be able to conditionally filter and not break the
         // typesystem.
-        count_query = count_query.filter(origin_packages::origin.eq(&origin_str));
         if !name_str.is_empty() {
             count_query = count_query.filter(origin_packages::name.eq(&name_str));
         }
-        count_query =
-            count_query.filter(origin_packages::ident_array.contains(ident_parts.clone()))
-                       .filter(origin_packages::visibility.eq_any(visibility_list.clone()))
-                       .filter(origins::name.eq(&origin_name))
-                       .filter(origin_channels::name.eq(&channel_name));
+        count_query = count_query
+            .filter(origin_packages::ident_array.contains(ident_parts.clone()))
+            .filter(
+                origin_packages::origin
+                    .eq(&origin_name)
+                    .and(origin_packages::visibility.eq_any(visibility_list.clone()))
+                    .or(origin_packages::visibility
+                        .eq(PackageVisibility::Public)
+                        .and(origin_packages::origin.ne_all(hidden_origins.clone()))),
+            )
+            .filter(origins::name.eq(&origin_name))
+            .filter(origin_channels::name.eq(&channel_name));
 
         let total_count: i64 = count_query.select(count_star()).first(conn)?;
 
@@ -275,16 +464,22 @@ impl Channel {
             )
             .into_boxed::<diesel::pg::Pg>();
 
-        page_base = page_base.filter(origin_packages::origin.eq(&origin_str));
-
         if !name_str.is_empty() {
             page_base = page_base.filter(origin_packages::name.eq(&name_str));
         }
 
-        page_base = page_base.filter(origin_packages::ident_array.contains(ident_parts.clone()))
-                             .filter(origin_packages::visibility.eq_any(visibility_list))
-                             .filter(origins::name.eq(&origin_name))
-                             .filter(origin_channels::name.eq(&channel_name));
+        page_base = page_base
+            .filter(origin_packages::ident_array.contains(ident_parts))
+            .filter(
+                origin_packages::origin
+                    .eq(&origin_name)
+                    .and(origin_packages::visibility.eq_any(visibility_list))
+                    .or(origin_packages::visibility
+                        .eq(PackageVisibility::Public)
+                        .and(origin_packages::origin.ne_all(hidden_origins))),
+            )
+            .filter(origins::name.eq(&origin_name))
+            .filter(origin_channels::name.eq(&channel_name));
 
         let idents: Vec<String> = page_base.select(origin_packages::ident)
                                            .order(origin_packages::ident.asc())
@@ -374,6 +569,23 @@ impl Channel {
         result
     }
 
+    // The origins, other than the channel's own, that have packages in the channel
+    pub fn list_foreign_origins(origin: &str,
+                                channel: &ChannelIdent,
+                                conn: &mut PgConnection)
+                                -> QueryResult<Vec<String>> {
+        Counter::DBCall.increment();
+        origin_packages::table
+            .inner_join(origin_channel_packages::table.inner_join(origin_channels::table))
+            .filter(origin_channels::origin.eq(origin))
+            .filter(origin_channels::name.eq(channel.as_str()))
+            .filter(origin_packages::origin.ne(origin))
+            .select(origin_packages::origin)
+            .distinct()
+            .order(origin_packages::origin.asc())
+            .get_results(conn)
+    }
+
     pub fn count_origin_channels(origin: &str, conn: &mut PgConnection) -> QueryResult<i64> {
         Counter::DBCall.increment();
         origin_channels::table.select(count(origin_channels::id))
@@ -428,6 +640,42 @@ impl Channel {
         }
         Ok(pkg_ids)
     }
+
+    // Brings a channel back to exactly the given package set, returning the ids that were
+    // promoted and demoted to get there.
+    pub fn restore_packages(channel_id: i64,
+                            package_ids: &[i64],
+                            conn: &mut PgConnection)
+                            -> QueryResult<(Vec<i64>, Vec<i64>)> {
+        let current: Vec<i64> =
+            Channel::list_all_packages_by_channel_id(channel_id, &PackageVisibility::all(), conn)?;
+
+        let wanted: HashSet<i64> = package_ids.iter().copied().collect();
+        let present: HashSet<i64> = current.iter().copied().collect();
+
+        let to_promote: Vec<i64> = package_ids.iter()
+                                              .filter(|id| !present.contains(id))
+                                              .copied()
+                                              .collect();
+        let to_demote: Vec<i64> = current.into_iter()
+                                         .filter(|id| !wanted.contains(id))
+                                         .collect();
+
+        if !to_demote.is_empty() {
+            debug!("Restore demoting Pkg IDs: {:?}", &to_demote);
+            Channel::demote_packages(channel_id, &to_demote, conn)?;
+        }
+        if !to_promote.is_empty() {
+            debug!("Restore promoting Pkg IDs: {:?}", &to_promote);
+            Channel::promote_packages(channel_id, &to_promote, conn)?;
+        }
+        Ok((to_promote, to_demote))
+    }
+}
+
+// Ident parts without the origin, for matching packages from any origin in a channel
+fn ident_parts(ident: &BuilderPackageIdent) -> Vec<String> {
+    ident.clone().parts().into_iter().skip(1).collect()
 }
 
 #[derive(DbEnum, Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -437,6 +685,8 @@ pub enum PackageChannelTrigger {
     Unknown,
     BuilderUi,
     HabClient,
+    Approval,
+    Scheduled,
 }
 
 /// Rust ↔ Postgres mapping for `package_channel_operation`
@@ -469,11 +719,20 @@ pub struct AuditPackage {
     pub requester_name: String,
     pub created_at:     Option<NaiveDateTime>,
     pub origin:         String,
+    pub target:         Option<String>,
+}
+
+#[derive(Debug, Queryable)]
+pub struct ChannelMembershipChange {
+    pub package_ident: BuilderPackageIdent,
+    pub operation:     PackageChannelOperation,
+    pub created_at:    Option<NaiveDateTime>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct AuditPackageEvent {
     pub operation:     PackageChannelOperation,
+    pub trigger:       PackageChannelTrigger,
     pub created_at:    Option<NaiveDateTime>,
     pub origin:        String,
     pub channel:       String,
@@ -588,11 +847,115 @@ impl AuditPackage {
 
         Ok((events, total_count))
     }
+
+    /// Promotes and demotes recorded after the change to `after_ident` at `after`, oldest first,
+    /// along with the origin and visibility of the package so that the caller can apply the same
+    /// visibility rules as `list` to each of its readers. Changes recorded at the same time are
+    /// ordered by package, so pages never skip or repeat any.
+    pub fn list_after(after: NaiveDateTime,
+                      after_ident: &str,
+                      limit: i64,
+                      conn: &mut PgConnection)
+                      -> QueryResult<Vec<(AuditPackage, String, PackageVisibility)>> {
+        Counter::DBCall.increment();
+        let after = after.into_sql::<Timestamptz>().nullable();
+        audit_package::table
+            .inner_join(
+                origin_packages::table.on(origin_packages::ident.eq(audit_package::package_ident)),
+            )
+            .select((audit_package::all_columns,
+                     origin_packages::origin,
+                     origin_packages::visibility))
+            .distinct_on((audit_package::created_at, audit_package::package_ident))
+            .filter(
+                audit_package::created_at
+                    .gt(after)
+                    .or(audit_package::created_at
+                        .eq(after)
+                        .and(audit_package::package_ident.gt(after_ident))),
+            )
+            .order((audit_package::created_at.asc(), audit_package::package_ident.asc()))
+            .limit(limit)
+            .get_results(conn)
+    }
+
+    /// Every recorded promote and demote of packages for the target in a channel up to and
+    /// including `until`, oldest first. Group operations are expanded into one change per
+    /// package; packages that have since been deleted can no longer be named and are skipped.
+    /// Changes recorded before targets were audited are included whatever their target.
+    pub fn list_channel_changes(origin: &str,
+                                channel: &ChannelIdent,
+                                target: PackageTarget,
+                                until: NaiveDateTime,
+                                conn: &mut PgConnection)
+                                -> QueryResult<Vec<ChannelMembershipChange>> {
+        Counter::DBCall.increment();
+        let start_time = Instant::now();
+
+        let single: Vec<ChannelMembershipChange> =
+            audit_package::table
+                .filter(audit_package::origin.eq(origin))
+                .filter(audit_package::channel.eq(channel.as_str()))
+                .filter(
+                    audit_package::target
+                        .eq(target.to_string())
+                        .or(audit_package::target.is_null()),
+                )
+                .filter(audit_package::created_at.le(until.into_sql::<Timestamptz>().nullable()))
+                .select((audit_package::package_ident,
+                         audit_package::operation,
+                         audit_package::created_at))
+                .order(audit_package::created_at.asc())
+                .get_results(conn)?;
+
+        let groups: Vec<(Vec<i64>, PackageChannelOperation, Option<NaiveDateTime>)> =
+            audit_package_group::table
+                .filter(audit_package_group::origin.eq(origin))
+                .filter(audit_package_group::channel.eq(channel.as_str()))
+                .filter(audit_package_group::created_at
+                            .le(until.into_sql::<Timestamptz>().nullable()))
+                .select((audit_package_group::package_ids,
+                         audit_package_group::operation,
+                         audit_package_group::created_at))
+                .order(audit_package_group::created_at.asc())
+                .get_results(conn)?;
+
+        let ids: Vec<i64> = groups.iter()
+                                  .flat_map(|(ids, ..)| ids.iter().copied())
+                                  .collect();
+        let idents: HashMap<i64, BuilderPackageIdent> =
+            origin_packages::table.filter(origin_packages::id.eq_any(ids))
+                                  .filter(origin_packages::target.eq(target.to_string()))
+                                  .select((origin_packages::id, origin_packages::ident))
+                                  .get_results(conn)?
+                                  .into_iter()
+                                  .collect();
+
+        let mut changes = single;
+        for (ids, operation, created_at) in groups {
+            for ident in ids.iter().filter_map(|id| idents.get(id)) {
+                changes.push(ChannelMembershipChange { package_ident: ident.clone(),
+                                                       operation,
+                                                       created_at });
+            }
+        }
+        // Both lists are already ordered, and the sort is stable, so changes recorded at the
+        // same instant keep their relative order.
+        changes.sort_by_key(|c| c.created_at);
+
+        let duration_millis = start_time.elapsed().as_millis();
+        trace!("DBCall channel::list_channel_changes time: {} ms",
+               duration_millis);
+        Histogram::DbCallTime.set(duration_millis as f64);
+
+        Ok(changes)
+    }
 }
 
 impl From<AuditPackage> for AuditPackageEvent {
     fn from(value: AuditPackage) -> AuditPackageEvent {
         AuditPackageEvent { operation:     value.operation,
+                            trigger:       value.trigger,
                             created_at:    value.created_at,
                             origin:        value.origin.clone(),
                             channel:       value.channel.clone(),
@@ -604,7 +967,8 @@ impl From<Channel> for ChannelWithPromotion {
     fn from(value: Channel) -> ChannelWithPromotion {
         ChannelWithPromotion { name:        value.name.clone(),
                                created_at:  value.created_at,
-                               promoted_at: value.updated_at, }
+                               promoted_at: value.updated_at,
+                               expires_at:  value.expires_at, }
     }
 }
 
@@ -619,6 +983,7 @@ pub struct PackageChannelAudit<'a> {
     pub requester_id:   i64,
     pub requester_name: &'a str,
     pub origin:         &'a str,
+    pub target:         &'a str,
 }
 
 impl PackageChannelAudit<'_> {
@@ -700,6 +1065,26 @@ impl OriginChannelPackage {
             .execute(conn)
     }
 
+    // When the package was added to the channel, or None if it is not in the channel
+    pub fn promoted_at(origin: &str,
+                       channel: &ChannelIdent,
+                       ident: &BuilderPackageIdent,
+                       target: PackageTarget,
+                       conn: &mut PgConnection)
+                       -> QueryResult<Option<NaiveDateTime>> {
+        Counter::DBCall.increment();
+        origin_channel_packages::table.inner_join(origin_channels::table)
+                                      .inner_join(origin_packages::table)
+                                      .filter(origin_channels::origin.eq(origin))
+                                      .filter(origin_channels::name.eq(channel.as_str()))
+                                      .filter(origin_packages::ident.eq(ident.to_string()))
+                                      .filter(origin_packages::target.eq(target.to_string()))
+                                      .select(origin_channel_packages::created_at)
+                                      .get_result::<Option<NaiveDateTime>>(conn)
+                                      .optional()
+                                      .map(Option::flatten)
+    }
+
     pub fn demote(package: OriginChannelDemote, conn: &mut PgConnection) -> QueryResult<usize> {
         Counter::DBCall.increment();
         diesel::delete(
@@ -726,3 +1111,19 @@ impl OriginChannelPackage {
         .execute(conn)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn ident_parts_leave_out_the_origin() {
+        let ident = BuilderPackageIdent(PackageIdent::from_str("core/redis/4.0.14/20190319155852")
+                                                       .unwrap());
+        assert_eq!(ident_parts(&ident), vec!["redis", "4.0.14", "20190319155852"]);
+
+        let ident = BuilderPackageIdent(PackageIdent::from_str("core/redis").unwrap());
+        assert_eq!(ident_parts(&ident), vec!["redis"]);
+    }
+}
@@ -0,0 +1,84 @@
+use super::db_id_format;
+use chrono::NaiveDateTime;
+use diesel::{self,
+             pg::PgConnection,
+             result::QueryResult,
+             ExpressionMethods,
+             OptionalExtension,
+             QueryDsl,
+             RunQueryDsl};
+
+use crate::{bldr_core::metrics::CounterMetric,
+            hab_core::ChannelIdent,
+            metrics::Counter,
+            models::origin::OriginMemberRole,
+            schema::channel::origin_channel_policies};
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct ChannelPolicy {
+    #[serde(with = "db_id_format")]
+    pub channel_id:       i64,
+    pub origin:           String,
+    pub channel:          String,
+    pub promote_role:     OriginMemberRole,
+    pub demote_role:      OriginMemberRole,
+    pub source_channel:   Option<String>,
+    pub require_tdeps:    bool,
+    pub block_demote:     bool,
+    pub require_approval: bool,
+    pub created_at:       Option<NaiveDateTime>,
+    pub updated_at:       Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = origin_channel_policies, treat_none_as_null = true)]
+pub struct NewChannelPolicy<'a> {
+    pub channel_id:       i64,
+    pub origin:           &'a str,
+    // This would be ChannelIdent, but Insertable requires implementing diesel::Expression
+    pub channel:          &'a str,
+    pub promote_role:     OriginMemberRole,
+    pub demote_role:      OriginMemberRole,
+    pub source_channel:   Option<&'a str>,
+    pub require_tdeps:    bool,
+    pub block_demote:     bool,
+    pub require_approval: bool,
+}
+
+impl ChannelPolicy {
+    pub fn get(origin: &str,
+               channel: &ChannelIdent,
+               conn: &mut PgConnection)
+               -> QueryResult<Option<ChannelPolicy>> {
+        Counter::DBCall.increment();
+        origin_channel_policies::table.filter(origin_channel_policies::origin.eq(origin))
+                                      .filter(origin_channel_policies::channel.eq(channel.as_str()))
+                                      .get_result(conn)
+                                      .optional()
+    }
+
+    pub fn create_or_update(policy: &NewChannelPolicy,
+                            conn: &mut PgConnection)
+                            -> QueryResult<ChannelPolicy> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_channel_policies::table)
+            .values(policy)
+            .on_conflict(origin_channel_policies::channel_id)
+            .do_update()
+            .set((policy, origin_channel_policies::updated_at.eq(diesel::dsl::now)))
+            .get_result(conn)
+    }
+
+    pub fn delete(origin: &str,
+                  channel: &ChannelIdent,
+                  conn: &mut PgConnection)
+                  -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::delete(
+            origin_channel_policies::table
+                .filter(origin_channel_policies::origin.eq(origin))
+                .filter(origin_channel_policies::channel.eq(channel.as_str())),
+        )
+        .execute(conn)
+    }
+}
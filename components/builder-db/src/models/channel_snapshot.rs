@@ -0,0 +1,163 @@
+use super::db_id_format;
+use chrono::NaiveDateTime;
+use diesel::{self,
+             dsl::{exists,
+                   not},
+             pg::PgConnection,
+             result::QueryResult,
+             Connection,
+             ExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+use std::time::Instant;
+
+use crate::{bldr_core::metrics::{CounterMetric,
+                                 HistogramMetric},
+            hab_core::ChannelIdent,
+            metrics::{Counter,
+                      Histogram},
+            models::package::BuilderPackageIdent,
+            schema::{channel::{origin_channel_packages,
+                               origin_channel_snapshot_packages,
+                               origin_channel_snapshots},
+                     package::origin_packages}};
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct ChannelSnapshot {
+    #[serde(with = "db_id_format")]
+    pub id:         i64,
+    #[serde(with = "db_id_format")]
+    pub channel_id: i64,
+    pub origin:     String,
+    pub channel:    String,
+    pub name:       String,
+    #[serde(with = "db_id_format")]
+    pub owner_id:   i64,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// A package as recorded in a snapshot, or as currently present in a live channel.
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone, PartialEq, Eq, Hash)]
+pub struct SnapshotPackage {
+    #[serde(with = "db_id_format")]
+    pub package_id: i64,
+    pub ident:      BuilderPackageIdent,
+    pub target:     String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = origin_channel_snapshots)]
+pub struct NewChannelSnapshot<'a> {
+    pub channel_id: i64,
+    pub origin:     &'a str,
+    // This would be ChannelIdent, but Insertable requires implementing diesel::Expression
+    pub channel:    &'a str,
+    pub name:       &'a str,
+    pub owner_id:   i64,
+}
+
+impl ChannelSnapshot {
+    /// Records the current package set of a channel under a new snapshot. The snapshot row and
+    /// its packages are written in one transaction so a snapshot is never partially populated.
+    pub fn create(snapshot: &NewChannelSnapshot,
+                  conn: &mut PgConnection)
+                  -> QueryResult<ChannelSnapshot> {
+        Counter::DBCall.increment();
+        let start_time = Instant::now();
+
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let created: ChannelSnapshot =
+                diesel::insert_into(origin_channel_snapshots::table).values(snapshot)
+                                                                    .get_result(conn)?;
+
+            let rows: Vec<_> =
+                ChannelSnapshot::live_packages(snapshot.channel_id, conn)?
+                    .into_iter()
+                    .map(|p| {
+                        (origin_channel_snapshot_packages::snapshot_id.eq(created.id),
+                         origin_channel_snapshot_packages::package_id.eq(p.package_id),
+                         origin_channel_snapshot_packages::ident.eq(p.ident.to_string()),
+                         origin_channel_snapshot_packages::target.eq(p.target))
+                    })
+                    .collect();
+
+            diesel::insert_into(origin_channel_snapshot_packages::table).values(rows)
+                                                                        .execute(conn)?;
+            Ok(created)
+        });
+
+        let duration_millis = start_time.elapsed().as_millis();
+        trace!("DBCall channel_snapshot::create time: {} ms", duration_millis);
+        Histogram::DbCallTime.set(duration_millis as f64);
+        result
+    }
+
+    pub fn get(origin: &str,
+               channel: &ChannelIdent,
+               name: &str,
+               conn: &mut PgConnection)
+               -> QueryResult<ChannelSnapshot> {
+        Counter::DBCall.increment();
+        origin_channel_snapshots::table.filter(origin_channel_snapshots::origin.eq(origin))
+                                       .filter(origin_channel_snapshots::channel
+                                                   .eq(channel.as_str()))
+                                       .filter(origin_channel_snapshots::name.eq(name))
+                                       .get_result(conn)
+    }
+
+    pub fn list(origin: &str,
+                channel: &ChannelIdent,
+                conn: &mut PgConnection)
+                -> QueryResult<Vec<ChannelSnapshot>> {
+        Counter::DBCall.increment();
+        origin_channel_snapshots::table.filter(origin_channel_snapshots::origin.eq(origin))
+                                       .filter(origin_channel_snapshots::channel
+                                                   .eq(channel.as_str()))
+                                       .order(origin_channel_snapshots::created_at.desc())
+                                       .get_results(conn)
+    }
+
+    pub fn list_packages(snapshot_id: i64,
+                         conn: &mut PgConnection)
+                         -> QueryResult<Vec<SnapshotPackage>> {
+        Counter::DBCall.increment();
+        origin_channel_snapshot_packages::table
+            .filter(origin_channel_snapshot_packages::snapshot_id.eq(snapshot_id))
+            .select((origin_channel_snapshot_packages::package_id,
+                     origin_channel_snapshot_packages::ident,
+                     origin_channel_snapshot_packages::target))
+            .order(origin_channel_snapshot_packages::ident.asc())
+            .get_results(conn)
+    }
+
+    /// Snapshot packages that have since been deleted and so can no longer be restored.
+    pub fn missing_packages(snapshot_id: i64,
+                            conn: &mut PgConnection)
+                            -> QueryResult<Vec<SnapshotPackage>> {
+        Counter::DBCall.increment();
+        origin_channel_snapshot_packages::table
+            .filter(origin_channel_snapshot_packages::snapshot_id.eq(snapshot_id))
+            .filter(not(exists(origin_packages::table.filter(
+                origin_packages::id.eq(origin_channel_snapshot_packages::package_id),
+            ))))
+            .select((origin_channel_snapshot_packages::package_id,
+                     origin_channel_snapshot_packages::ident,
+                     origin_channel_snapshot_packages::target))
+            .get_results(conn)
+    }
+
+    /// The packages currently in a channel, in the same shape as snapshot packages so the two
+    /// can be compared.
+    pub fn live_packages(channel_id: i64,
+                         conn: &mut PgConnection)
+                         -> QueryResult<Vec<SnapshotPackage>> {
+        Counter::DBCall.increment();
+        origin_packages::table.inner_join(origin_channel_packages::table)
+                              .filter(origin_channel_packages::channel_id.eq(channel_id))
+                              .select((origin_packages::id,
+                                       origin_packages::ident,
+                                       origin_packages::target))
+                              .order(origin_packages::ident.asc())
+                              .get_results(conn)
+    }
+}
@@ -0,0 +1,140 @@
+use chrono::{NaiveDate,
+             Utc};
+use diesel::{self,
+             dsl::sql,
+             pg::PgConnection,
+             result::QueryResult,
+             sql_types::BigInt,
+             ExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+use std::time::Instant;
+
+use crate::{bldr_core::metrics::{CounterMetric,
+                                 HistogramMetric},
+            metrics::{Counter,
+                      Histogram},
+            models::package::PackageVisibility,
+            schema::{download_stats::package_download_stats,
+                     package::origin_packages}};
+
+// Correlated subqueries used to order package searches by popularity. The first is for queries
+// against origin_packages alone, the second for queries grouped on the origins join.
+pub const PACKAGE_POPULARITY: &str = "(SELECT COALESCE(SUM(pds.download_count), 0)::bigint FROM \
+                                      package_download_stats pds WHERE pds.origin = \
+                                      origin_packages.origin AND pds.name = origin_packages.name)";
+pub const DISTINCT_PACKAGE_POPULARITY: &str =
+    "(SELECT COALESCE(SUM(pds.download_count), 0)::bigint FROM package_download_stats pds WHERE \
+     pds.origin = origins.name AND pds.name = origin_packages.name)";
+
+const DOWNLOAD_SUM: &str = "SUM(package_download_stats.download_count)::bigint";
+
+pub struct NewPackageDownload<'a> {
+    pub package_id: i64,
+    pub origin:     &'a str,
+    pub name:       &'a str,
+    pub target:     &'a str,
+}
+
+pub struct ListDownloadStats<'a> {
+    pub origin:     &'a str,
+    pub from_date:  NaiveDate,
+    pub to_date:    NaiveDate,
+    pub visibility: Vec<PackageVisibility>,
+}
+
+#[derive(Debug, Serialize, Queryable)]
+pub struct PackageDownloadCount {
+    pub day:       NaiveDate,
+    pub target:    String,
+    pub downloads: i64,
+}
+
+#[derive(Debug, Serialize, Queryable)]
+pub struct OriginPackageDownloadCount {
+    pub name:      String,
+    pub downloads: i64,
+}
+
+pub struct PackageDownloadStat;
+
+impl PackageDownloadStat {
+    /// Bumps today's download counter for a single package release and target.
+    pub fn record(req: &NewPackageDownload, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+
+        diesel::insert_into(package_download_stats::table)
+            .values((
+                package_download_stats::package_id.eq(req.package_id),
+                package_download_stats::day.eq(Utc::now().date_naive()),
+                package_download_stats::origin.eq(req.origin),
+                package_download_stats::name.eq(req.name),
+                package_download_stats::target.eq(req.target),
+                package_download_stats::download_count.eq(1),
+            ))
+            .on_conflict((package_download_stats::package_id, package_download_stats::day))
+            .do_update()
+            .set((
+                package_download_stats::download_count
+                    .eq(package_download_stats::download_count + 1),
+                package_download_stats::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)
+    }
+
+    /// Daily download totals for every release of a package, broken down by target.
+    pub fn list_for_package(name: &str,
+                            ls: &ListDownloadStats,
+                            conn: &mut PgConnection)
+                            -> QueryResult<Vec<PackageDownloadCount>> {
+        Counter::DBCall.increment();
+        let start_time = Instant::now();
+
+        let result = package_download_stats::table
+            .inner_join(origin_packages::table)
+            .filter(package_download_stats::origin.eq(ls.origin))
+            .filter(package_download_stats::name.eq(name))
+            .filter(package_download_stats::day.ge(ls.from_date))
+            .filter(package_download_stats::day.le(ls.to_date))
+            .filter(origin_packages::visibility.eq_any(ls.visibility.clone()))
+            .filter(origin_packages::hidden.eq(false))
+            .group_by((package_download_stats::day, package_download_stats::target))
+            .select((package_download_stats::day,
+                     package_download_stats::target,
+                     sql::<BigInt>(DOWNLOAD_SUM)))
+            .order((package_download_stats::day.asc(), package_download_stats::target.asc()))
+            .get_results(conn);
+
+        let duration_millis = start_time.elapsed().as_millis();
+        trace!("DBCall download_stats::list_for_package time: {} ms",
+               duration_millis);
+        Histogram::DbCallTime.set(duration_millis as f64);
+        result
+    }
+
+    /// Download totals per package within an origin, most downloaded first.
+    pub fn list_for_origin(ls: &ListDownloadStats,
+                           conn: &mut PgConnection)
+                           -> QueryResult<Vec<OriginPackageDownloadCount>> {
+        Counter::DBCall.increment();
+        let start_time = Instant::now();
+
+        let result = package_download_stats::table
+            .inner_join(origin_packages::table)
+            .filter(package_download_stats::origin.eq(ls.origin))
+            .filter(package_download_stats::day.ge(ls.from_date))
+            .filter(package_download_stats::day.le(ls.to_date))
+            .filter(origin_packages::visibility.eq_any(ls.visibility.clone()))
+            .filter(origin_packages::hidden.eq(false))
+            .group_by(package_download_stats::name)
+            .select((package_download_stats::name, sql::<BigInt>(DOWNLOAD_SUM)))
+            .order((sql::<BigInt>(DOWNLOAD_SUM).desc(), package_download_stats::name.asc()))
+            .get_results(conn);
+
+        let duration_millis = start_time.elapsed().as_millis();
+        trace!("DBCall download_stats::list_for_origin time: {} ms",
+               duration_millis);
+        Histogram::DbCallTime.set(duration_millis as f64);
+        result
+    }
+}
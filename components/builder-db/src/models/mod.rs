@@ -6,7 +6,11 @@
 mod migration_support;
 
 pub mod account;
+pub mod audit;
 pub mod channel;
+pub mod channel_policy;
+pub mod channel_snapshot;
+pub mod download_stats;
 pub mod integration;
 pub mod invitations;
 pub mod keys;
@@ -16,8 +20,15 @@ pub mod package;
 pub mod pagination;
 pub mod project_integration;
 pub mod projects;
+pub mod promotion_request;
+pub mod role;
+pub mod scheduled_promotion;
 pub mod secrets;
 pub mod settings;
+pub mod system_stats;
+pub mod team;
+pub mod trust_rule;
+pub mod webhook;
 
 mod db_id_format {
     use serde::{self,
@@ -1,4 +1,5 @@
-use super::db_id_format;
+use super::{db_id_format,
+            db_optional_id_format};
 use chrono::NaiveDateTime;
 use diesel::{self,
              dsl::count,
@@ -13,10 +14,10 @@ use diesel::{self,
 
 use crate::models::{channel::{Channel,
                               CreateChannel},
-                    package::PackageVisibility};
+                    package::PackageVisibility,
+                    team::Team};
 
-use crate::schema::{audit::audit_origin,
-                    channel::origin_channels,
+use crate::schema::{channel::origin_channels,
                     integration::origin_integrations,
                     invitation::origin_invitations,
                     key::{origin_private_encryption_keys,
@@ -52,6 +53,8 @@ pub struct Origin {
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
     pub default_package_visibility: PackageVisibility,
+    /// Hidden origins, along with their packages, are left out of listings and searches
+    pub hidden: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Queryable)]
@@ -139,11 +142,17 @@ impl FromStr for OriginMemberRole {
 #[diesel(table_name = origin_members)]
 pub struct OriginMember {
     #[serde(with = "db_id_format")]
-    pub account_id:  i64,
-    pub origin:      String,
-    pub member_role: OriginMemberRole,
-    pub created_at:  Option<NaiveDateTime>,
-    pub updated_at:  Option<NaiveDateTime>,
+    pub account_id:     i64,
+    pub origin:         String,
+    pub member_role:    OriginMemberRole,
+    pub created_at:     Option<NaiveDateTime>,
+    pub updated_at:     Option<NaiveDateTime>,
+    /// Whether the membership is granted by the groups of the account's identity provider,
+    /// rather than by an invitation
+    pub idp_managed:    bool,
+    /// The origin's custom role the member holds, whose permissions add to those of their role
+    #[serde(with = "db_optional_id_format")]
+    pub custom_role_id: Option<i64>,
 }
 
 #[derive(Insertable)]
@@ -154,51 +163,6 @@ pub struct NewOrigin<'a> {
     pub default_package_visibility: &'a PackageVisibility,
 }
 
-#[derive(Clone, Copy, DbEnum, Debug, Serialize, Deserialize)]
-#[ExistingTypePath = "crate::schema::sql_types::OriginOperation"]
-#[DbValueStyle = "snake_case"]
-pub enum OriginOperation {
-    OriginCreate,
-    OriginDelete,
-    OwnerTransfer,
-}
-
-#[derive(Debug, Serialize, Deserialize, Insertable)]
-#[diesel(table_name = audit_origin)]
-struct OriginAudit<'a> {
-    operation:      OriginOperation,
-    origin:         &'a str,
-    requester_id:   i64,
-    requester_name: &'a str,
-    target_object:  &'a str,
-}
-
-impl OriginAudit<'_> {
-    fn audit(origin_audit_record: &OriginAudit, conn: &mut PgConnection) -> QueryResult<usize> {
-        Counter::DBCall.increment();
-        diesel::insert_into(audit_origin::table).values(origin_audit_record)
-                                                .execute(conn)
-    }
-}
-
-pub fn origin_audit(origin: &str,
-                    op: OriginOperation,
-                    target: &str,
-                    id: i64,
-                    name: &str,
-                    conn: &mut PgConnection) {
-    if let Err(err) = OriginAudit::audit(&OriginAudit { operation: op,
-                                                        origin,
-                                                        target_object: target,
-                                                        requester_id: id,
-                                                        requester_name: name },
-                                         conn)
-    {
-        debug!("Failed to save origin [{}] {:?} operation to audit log: {}",
-               origin, op, err);
-    }
-}
-
 impl Origin {
     pub fn get(origin: &str, conn: &mut PgConnection) -> QueryResult<OriginWithSecretKey> {
         Counter::DBCall.increment();
@@ -221,13 +185,15 @@ impl Origin {
                                                             .get_result(conn)?;
 
         OriginMember::add(req.name, req.owner_id, conn, OriginMemberRole::Owner)?;
-        Channel::create(&CreateChannel { name:     ChannelIdent::unstable().as_str(),
-                                         owner_id: req.owner_id,
-                                         origin:   req.name, },
+        Channel::create(&CreateChannel { name:       ChannelIdent::unstable().as_str(),
+                                         owner_id:   req.owner_id,
+                                         origin:     req.name,
+                                         expires_at: None, },
                         conn)?;
-        Channel::create(&CreateChannel { name:     ChannelIdent::stable().as_str(),
-                                         owner_id: req.owner_id,
-                                         origin:   req.name, },
+        Channel::create(&CreateChannel { name:       ChannelIdent::stable().as_str(),
+                                         owner_id:   req.owner_id,
+                                         origin:     req.name,
+                                         expires_at: None, },
                         conn)?;
 
         Ok(new_origin)
@@ -316,6 +282,44 @@ impl Origin {
             })
     }
 
+    /// Transfers the origin to any account, making it a member first if it isn't one.
+    pub fn force_transfer(origin: &str,
+                          account_id: i64,
+                          conn: &mut PgConnection)
+                          -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        conn.transaction::<_, Error, _>(|txn_conn| {
+                diesel::insert_into(origin_members::table)
+                .values((
+                    origin_members::origin.eq(origin),
+                    origin_members::account_id.eq(account_id),
+                    origin_members::member_role.eq(OriginMemberRole::Maintainer),
+                ))
+                .on_conflict((origin_members::origin, origin_members::account_id))
+                .do_nothing()
+                .execute(txn_conn)?;
+
+                Self::transfer(origin, account_id, txn_conn)
+            })
+    }
+
+    /// Hides or unhides the origin along with all of its packages.
+    pub fn set_hidden(origin: &str, hidden: bool, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        conn.transaction::<_, Error, _>(|txn_conn| {
+            diesel::update(origin_packages::table.filter(origin_packages::origin.eq(origin)))
+                .set(origin_packages::hidden.eq(hidden))
+                .execute(txn_conn)?;
+            diesel::update(
+                origin_package_settings::table.filter(origin_package_settings::origin.eq(origin)),
+            )
+            .set(origin_package_settings::hidden.eq(hidden))
+            .execute(txn_conn)?;
+            diesel::update(origins::table.find(origin)).set(origins::hidden.eq(hidden))
+                                                       .execute(txn_conn)
+        })
+    }
+
     pub fn depart(origin: &str, account_id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
         Counter::DBCall.increment();
         diesel::delete(
@@ -350,6 +354,32 @@ impl OriginMember {
                              .get_results(conn)
     }
 
+    /// Names of the members whose membership the groups of their identity provider grant.
+    pub fn list_idp_managed(origin: &str, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+        use crate::schema::account::accounts;
+
+        Counter::DBCall.increment();
+        origin_members::table.inner_join(accounts::table)
+                             .select(accounts::name)
+                             .filter(origin_members::origin.eq(origin))
+                             .filter(origin_members::idp_managed.eq(true))
+                             .order(accounts::name.asc())
+                             .get_results(conn)
+    }
+
+    /// The (origin, account id) pairs for which any of the accounts is a member of any of the
+    /// origins.
+    pub fn list_memberships(account_ids: &[i64],
+                            origins: &[String],
+                            conn: &mut PgConnection)
+                            -> QueryResult<Vec<(String, i64)>> {
+        Counter::DBCall.increment();
+        origin_members::table.select((origin_members::origin, origin_members::account_id))
+                             .filter(origin_members::account_id.eq_any(account_ids))
+                             .filter(origin_members::origin.eq_any(origins))
+                             .get_results(conn)
+    }
+
     pub fn delete(origin: &str, account_name: &str, conn: &mut PgConnection) -> QueryResult<usize> {
         use crate::schema::account::accounts;
 
@@ -381,6 +411,47 @@ impl OriginMember {
             .execute(conn)
     }
 
+    /// Grants a membership on behalf of the account's identity provider.
+    pub fn add_idp_managed(origin: &str,
+                           account_id: i64,
+                           member_role: OriginMemberRole,
+                           conn: &mut PgConnection)
+                           -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_members::table)
+            .values((
+                origin_members::origin.eq(origin),
+                origin_members::account_id.eq(account_id),
+                origin_members::member_role.eq(member_role),
+                origin_members::idp_managed.eq(true),
+            ))
+            .execute(conn)
+    }
+
+    /// Every membership of an account, along with its role and whether its identity provider
+    /// manages it.
+    pub fn list_for_account(account_id: i64,
+                            conn: &mut PgConnection)
+                            -> QueryResult<Vec<(String, OriginMemberRole, bool)>> {
+        Counter::DBCall.increment();
+        origin_members::table.select((origin_members::origin,
+                                      origin_members::member_role,
+                                      origin_members::idp_managed))
+                             .filter(origin_members::account_id.eq(account_id))
+                             .get_results(conn)
+    }
+
+    pub fn is_idp_managed(origin: &str,
+                          account_id: i64,
+                          conn: &mut PgConnection)
+                          -> QueryResult<bool> {
+        Counter::DBCall.increment();
+        origin_members::table.select(origin_members::idp_managed)
+                             .filter(origin_members::origin.eq(origin))
+                             .filter(origin_members::account_id.eq(account_id))
+                             .get_result(conn)
+    }
+
     pub fn count_origin_members(origin: &str, conn: &mut PgConnection) -> QueryResult<i64> {
         Counter::DBCall.increment();
         origin_members::table.select(count(origin_members::account_id))
@@ -399,6 +470,16 @@ impl OriginMember {
                              .get_result(conn)
     }
 
+    /// The role an account holds in an origin: the higher of its own role and the roles of
+    /// the origin's teams it is on.
+    pub fn effective_role(origin: &str,
+                          account_id: i64,
+                          conn: &mut PgConnection)
+                          -> QueryResult<OriginMemberRole> {
+        let role = Self::member_role(origin, account_id, conn)?;
+        Ok(higher_role(role, Team::max_role(origin, account_id, conn)?))
+    }
+
     pub fn update_member_role(origin: &str,
                               account_id: i64,
                               conn: &mut PgConnection,
@@ -412,6 +493,14 @@ impl OriginMember {
     }
 }
 
+// The role a member ends up with, given their own role and the highest role of their teams
+fn higher_role(role: OriginMemberRole, team_role: Option<OriginMemberRole>) -> OriginMemberRole {
+    match team_role {
+        Some(team_role) if team_role > role => team_role,
+        _ => role,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -428,4 +517,20 @@ mod tests {
         assert!(maintainer > member);
         assert!(member > readonly_member);
     }
+
+    #[test]
+    fn effective_role_is_the_higher_of_own_and_team_role() {
+        assert_eq!(higher_role(OriginMemberRole::Member, Some(OriginMemberRole::Maintainer)),
+                   OriginMemberRole::Maintainer);
+        assert_eq!(higher_role(OriginMemberRole::Administrator, Some(OriginMemberRole::Member)),
+                   OriginMemberRole::Administrator);
+        assert_eq!(higher_role(OriginMemberRole::Maintainer, Some(OriginMemberRole::Maintainer)),
+                   OriginMemberRole::Maintainer);
+    }
+
+    #[test]
+    fn effective_role_without_teams_is_own_role() {
+        assert_eq!(higher_role(OriginMemberRole::ReadonlyMember, None),
+                   OriginMemberRole::ReadonlyMember);
+    }
 }
@@ -20,6 +20,8 @@ use crate::{hab_core::{self,
             models::{channel::{Channel,
                                OriginChannelPackage,
                                OriginChannelPromote},
+                     download_stats::{DISTINCT_PACKAGE_POPULARITY,
+                                      PACKAGE_POPULARITY},
                      settings::OriginPackageSettings},
             schema::{channel::{origin_channel_packages,
                                origin_channels},
@@ -48,7 +50,9 @@ use diesel::{self,
                          IsNull,
                          Output,
                          ToSql},
-             sql_types::Text,
+             sql_types::{BigInt,
+                         Text,
+                         Timestamptz},
              PgArrayExpressionMethods,
              RunQueryDsl};
 use diesel_full_text_search::{to_tsquery,
@@ -353,6 +357,26 @@ pub struct SearchPackages {
     pub account_id: Option<i64>,
     pub page:       i64,
     pub limit:      i64,
+    pub order:      SearchPackagesOrder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchPackagesOrder {
+    #[default]
+    Name,
+    Popularity,
+}
+
+impl FromStr for SearchPackagesOrder {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<SearchPackagesOrder, ()> {
+        match s {
+            "name" => Ok(SearchPackagesOrder::Name),
+            "popularity" => Ok(SearchPackagesOrder::Popularity),
+            _ => Err(()),
+        }
+    }
 }
 #[derive(Debug, Serialize, Deserialize, Queryable)]
 pub struct OriginPackageVersions {
@@ -366,6 +390,17 @@ pub struct OriginPackageVersions {
     pub visibility:    PackageVisibility,
 }
 
+#[derive(Debug, Queryable)]
+pub struct PackageUpload {
+    pub id:         i64,
+    pub ident:      BuilderPackageIdent,
+    pub target:     BuilderPackageTarget,
+    pub origin:     String,
+    pub visibility: PackageVisibility,
+    pub owner_id:   i64,
+    pub created_at: Option<NaiveDateTime>,
+}
+
 #[derive(DbEnum, Debug, Eq, Hash, Serialize, Deserialize, PartialEq, Clone)]
 #[ExistingTypePath = "crate::schema::sql_types::OriginPackageVisibility"]
 #[DbValueStyle = "snake_case"]
@@ -480,6 +515,55 @@ impl Package {
         result
     }
 
+    // Returns the hidden flag of every matching package, whatever its visibility. Idents that
+    // are absent from the result no longer exist.
+    pub fn list_hidden_states(idents: &[BuilderPackageIdent],
+                              conn: &mut PgConnection)
+                              -> QueryResult<Vec<(BuilderPackageIdent, bool)>> {
+        Counter::DBCall.increment();
+        origin_packages::table.filter(origin_packages::ident.eq_any(idents))
+                              .select((origin_packages::ident, origin_packages::hidden))
+                              .get_results(conn)
+    }
+
+    pub fn list_idents_by_id(ids: &[i64],
+                             conn: &mut PgConnection)
+                             -> QueryResult<Vec<(BuilderPackageIdent, BuilderPackageTarget)>> {
+        Counter::DBCall.increment();
+        origin_packages::table.filter(origin_packages::id.eq_any(ids))
+                              .select((origin_packages::ident, origin_packages::target))
+                              .get_results(conn)
+    }
+
+    /// Packages uploaded after the one with id `after_id` uploaded at `after`, oldest first.
+    /// Packages uploaded at the same time are ordered by id, so pages never skip or repeat any.
+    pub fn list_uploaded_after(after: NaiveDateTime,
+                               after_id: i64,
+                               limit: i64,
+                               conn: &mut PgConnection)
+                               -> QueryResult<Vec<PackageUpload>> {
+        Counter::DBCall.increment();
+        let after = after.into_sql::<Timestamptz>().nullable();
+        origin_packages::table
+            .select((origin_packages::id,
+                     origin_packages::ident,
+                     origin_packages::target,
+                     origin_packages::origin,
+                     origin_packages::visibility,
+                     origin_packages::owner_id,
+                     origin_packages::created_at))
+            .filter(
+                origin_packages::created_at
+                    .gt(after)
+                    .or(origin_packages::created_at
+                        .eq(after)
+                        .and(origin_packages::id.gt(after_id))),
+            )
+            .order((origin_packages::created_at.asc(), origin_packages::id.asc()))
+            .limit(limit)
+            .get_results(conn)
+    }
+
     pub fn get_all(req_ident: &BuilderPackageIdent,
                    conn: &mut PgConnection)
                    -> QueryResult<Vec<Package>> {
@@ -836,11 +920,17 @@ impl Package {
                 origin_channel_packages::created_at,
                 origin_channel_packages::updated_at,
                 origin_channels::origin,
+                origin_channels::expires_at,
+                origin_channels::description,
+                origin_channels::frozen,
+                origin_channels::maintainers,
             ))
             .filter(origin_packages::ident.eq(ident))
             .filter(origin_packages::target.eq(target.to_string()))
             .filter(origin_packages::visibility.eq_any(visibility))
             .filter(origin_packages::hidden.eq(false))
+            // Channels of other origins can hold this package too, but only its own are listed
+            .filter(origin_channels::origin.eq(&ident.origin))
             .order(origin_channels::name.desc())
             .get_results(conn);
 
@@ -946,8 +1036,15 @@ impl Package {
         let limit = sp.limit;
         let offset = (sp.page.saturating_sub(1)) * sp.limit;
 
+        page_query = match sp.order {
+            SearchPackagesOrder::Name => page_query.order(origin_packages::ident.asc()),
+            SearchPackagesOrder::Popularity => {
+                page_query.order((sql::<BigInt>(PACKAGE_POPULARITY).desc(),
+                                  origin_packages::ident.asc()))
+            }
+        };
+
         let packages: Vec<BuilderPackageIdent> = page_query.select(origin_packages::ident)
-                                                           .order(origin_packages::ident.asc())
                                                            .limit(limit)
                                                            .offset(offset)
                                                            .load(conn)?;
@@ -991,34 +1088,73 @@ impl Package {
                                                                  origin_packages.name))"))
                        .first(conn)?;
 
-        let mut page_query = origin_packages::table
-            .inner_join(origins::table)
-            .select(sql::<diesel::sql_types::Text>(
-                "concat_ws('/', origins.name, origin_packages.name)",
-            ))
-            .distinct_on((origin_packages::name, origins::name))
-            .order((origin_packages::name.asc(), origins::name.asc()))
-            .filter(to_tsquery(format!("{}:*", sp.query)).matches(origin_packages::ident_vector))
-            .filter(origin_packages::hidden.eq(false))
-            .into_boxed();
-
-        if let Some(session_id) = sp.account_id {
-            page_query = page_query.filter(
-                origin_packages::visibility
-                    .eq_any(PackageVisibility::private())
-                    .and(origins::owner_id.eq(session_id))
-                    .or(origin_packages::visibility.eq(PackageVisibility::Public)),
-            );
-        } else {
-            page_query =
-                page_query.filter(origin_packages::visibility.eq(PackageVisibility::Public));
-        }
-
         let limit = sp.limit;
         let offset = (sp.page.saturating_sub(1)) * sp.limit;
 
-        let packages: Vec<BuilderPackageIdent> =
-            page_query.limit(limit).offset(offset).load(conn)?;
+        // DISTINCT ON forces the ordering to lead with the distinct columns, so popularity
+        // ordering groups on origin and name instead.
+        let packages: Vec<BuilderPackageIdent> = match sp.order {
+            SearchPackagesOrder::Name => {
+                let mut page_query = origin_packages::table
+                    .inner_join(origins::table)
+                    .select(sql::<diesel::sql_types::Text>(
+                        "concat_ws('/', origins.name, origin_packages.name)",
+                    ))
+                    .distinct_on((origin_packages::name, origins::name))
+                    .order((origin_packages::name.asc(), origins::name.asc()))
+                    .filter(
+                        to_tsquery(format!("{}:*", sp.query))
+                            .matches(origin_packages::ident_vector),
+                    )
+                    .filter(origin_packages::hidden.eq(false))
+                    .into_boxed();
+
+                if let Some(session_id) = sp.account_id {
+                    page_query = page_query.filter(
+                        origin_packages::visibility
+                            .eq_any(PackageVisibility::private())
+                            .and(origins::owner_id.eq(session_id))
+                            .or(origin_packages::visibility.eq(PackageVisibility::Public)),
+                    );
+                } else {
+                    page_query = page_query
+                        .filter(origin_packages::visibility.eq(PackageVisibility::Public));
+                }
+
+                page_query.limit(limit).offset(offset).load(conn)?
+            }
+            SearchPackagesOrder::Popularity => {
+                let mut page_query = origin_packages::table
+                    .inner_join(origins::table)
+                    .group_by((origin_packages::name, origins::name))
+                    .select(sql::<diesel::sql_types::Text>(
+                        "concat_ws('/', origins.name, origin_packages.name)",
+                    ))
+                    .order((sql::<BigInt>(DISTINCT_PACKAGE_POPULARITY).desc(),
+                            origin_packages::name.asc(),
+                            origins::name.asc()))
+                    .filter(
+                        to_tsquery(format!("{}:*", sp.query))
+                            .matches(origin_packages::ident_vector),
+                    )
+                    .filter(origin_packages::hidden.eq(false))
+                    .into_boxed();
+
+                if let Some(session_id) = sp.account_id {
+                    page_query = page_query.filter(
+                        origin_packages::visibility
+                            .eq_any(PackageVisibility::private())
+                            .and(origins::owner_id.eq(session_id))
+                            .or(origin_packages::visibility.eq(PackageVisibility::Public)),
+                    );
+                } else {
+                    page_query = page_query
+                        .filter(origin_packages::visibility.eq(PackageVisibility::Public));
+                }
+
+                page_query.limit(limit).offset(offset).load(conn)?
+            }
+        };
 
         let duration_millis = start_time.elapsed().as_millis();
         trace!("DBCall package::search time: {} ms", duration_millis);
@@ -1408,3 +1544,18 @@ impl From<BuilderPackageIdent> for PackageIdentWithChannelPlatform {
                                           platforms: Vec::new(), }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_packages_order_from_str() {
+        assert_eq!(SearchPackagesOrder::from_str("name"), Ok(SearchPackagesOrder::Name));
+        assert_eq!(SearchPackagesOrder::from_str("popularity"),
+                   Ok(SearchPackagesOrder::Popularity));
+        assert_eq!(SearchPackagesOrder::from_str("downloads"), Err(()));
+        assert_eq!(SearchPackagesOrder::from_str("Popularity"), Err(()));
+        assert_eq!(SearchPackagesOrder::default(), SearchPackagesOrder::Name);
+    }
+}
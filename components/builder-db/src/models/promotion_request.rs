@@ -0,0 +1,126 @@
+use super::db_id_format;
+use chrono::NaiveDateTime;
+use diesel::{self,
+             pg::PgConnection,
+             result::QueryResult,
+             ExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+use diesel_derive_enum::DbEnum;
+
+use crate::{bldr_core::metrics::CounterMetric,
+            hab_core::ChannelIdent,
+            metrics::Counter,
+            models::package::BuilderPackageIdent,
+            schema::channel::origin_promotion_requests};
+
+#[derive(DbEnum, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[ExistingTypePath = "crate::schema::sql_types::PromotionRequestState"]
+#[DbValueStyle = "snake_case"]
+#[serde(rename_all = "snake_case")]
+pub enum PromotionRequestState {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct PromotionRequest {
+    #[serde(with = "db_id_format")]
+    pub id:             i64,
+    pub origin:         String,
+    pub channel:        String,
+    pub target:         String,
+    pub package_idents: Vec<BuilderPackageIdent>,
+    pub state:          PromotionRequestState,
+    #[serde(with = "db_id_format")]
+    pub requester_id:   i64,
+    pub requester_name: String,
+    pub reviewer_id:    Option<i64>,
+    pub reviewer_name:  Option<String>,
+    pub review_comment: Option<String>,
+    pub created_at:     Option<NaiveDateTime>,
+    pub reviewed_at:    Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = origin_promotion_requests)]
+pub struct NewPromotionRequest<'a> {
+    pub origin:         &'a str,
+    // This would be ChannelIdent, but Insertable requires implementing diesel::Expression
+    pub channel:        &'a str,
+    pub target:         &'a str,
+    pub package_idents: Vec<BuilderPackageIdent>,
+    pub requester_id:   i64,
+    pub requester_name: &'a str,
+}
+
+pub struct ReviewPromotionRequest<'a> {
+    pub id:             i64,
+    pub state:          PromotionRequestState,
+    pub reviewer_id:    i64,
+    pub reviewer_name:  &'a str,
+    pub review_comment: Option<&'a str>,
+}
+
+impl PromotionRequest {
+    pub fn create(req: &NewPromotionRequest,
+                  conn: &mut PgConnection)
+                  -> QueryResult<PromotionRequest> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_promotion_requests::table).values(req)
+                                                             .get_result(conn)
+    }
+
+    pub fn get(origin: &str,
+               channel: &ChannelIdent,
+               id: i64,
+               conn: &mut PgConnection)
+               -> QueryResult<PromotionRequest> {
+        Counter::DBCall.increment();
+        origin_promotion_requests::table.filter(origin_promotion_requests::id.eq(id))
+                                        .filter(origin_promotion_requests::origin.eq(origin))
+                                        .filter(origin_promotion_requests::channel
+                                                    .eq(channel.as_str()))
+                                        .get_result(conn)
+    }
+
+    pub fn list(origin: &str,
+                channel: &ChannelIdent,
+                state: Option<PromotionRequestState>,
+                conn: &mut PgConnection)
+                -> QueryResult<Vec<PromotionRequest>> {
+        Counter::DBCall.increment();
+        let mut query =
+            origin_promotion_requests::table.filter(origin_promotion_requests::origin.eq(origin))
+                                            .filter(origin_promotion_requests::channel
+                                                        .eq(channel.as_str()))
+                                            .into_boxed();
+        if let Some(state) = state {
+            query = query.filter(origin_promotion_requests::state.eq(state));
+        }
+        query.order(origin_promotion_requests::created_at.desc())
+             .get_results(conn)
+    }
+
+    // Only pending requests can be reviewed, so a request that was decided concurrently is left
+    // untouched and zero rows are returned.
+    pub fn review(review: &ReviewPromotionRequest,
+                  conn: &mut PgConnection)
+                  -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(
+            origin_promotion_requests::table
+                .filter(origin_promotion_requests::id.eq(review.id))
+                .filter(origin_promotion_requests::state.eq(PromotionRequestState::Pending)),
+        )
+        .set((
+            origin_promotion_requests::state.eq(review.state),
+            origin_promotion_requests::reviewer_id.eq(review.reviewer_id),
+            origin_promotion_requests::reviewer_name.eq(review.reviewer_name),
+            origin_promotion_requests::review_comment.eq(review.review_comment),
+            origin_promotion_requests::reviewed_at.eq(diesel::dsl::now),
+        ))
+        .execute(conn)
+    }
+}
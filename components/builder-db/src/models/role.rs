@@ -0,0 +1,316 @@
+use super::db_id_format;
+use chrono::NaiveDateTime;
+use diesel::{self,
+             pg::PgConnection,
+             result::QueryResult,
+             ExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+
+use crate::{bldr_core::{metrics::CounterMetric,
+                        Error as BuilderError},
+            metrics::Counter,
+            models::origin::{OriginMember,
+                             OriginMemberRole},
+            schema::{account::accounts,
+                     member::origin_members,
+                     role::origin_custom_roles}};
+
+use std::{fmt,
+          str::FromStr};
+
+/// Something a member may be allowed to do in an origin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Permission {
+    /// Upload packages
+    Upload,
+    /// Promote packages to, and demote them from, a channel. `*` stands for every channel.
+    Promote(String),
+    /// Delete packages
+    Delete,
+    /// Create, update and delete the origin's channels, and snapshot and restore them
+    Channels,
+    /// Change whether the origin's packages are public or private, and their package settings
+    Visibility,
+    /// Invite accounts to join the origin
+    Invite,
+    /// Generate and upload the origin's keys
+    Keys,
+    /// Manage the origin's secrets
+    Secrets,
+    /// Manage the origin's members, their roles, teams and robot accounts, and invite accounts
+    Members,
+    /// Manage the origin's settings, webhooks, trust rules and channel policies, and read its
+    /// audit log
+    Settings,
+}
+
+impl Permission {
+    /// Whether holding this permission allows what the required one does.
+    pub fn covers(&self, required: &Permission) -> bool {
+        match (self, required) {
+            (Permission::Promote(held), Permission::Promote(channel)) => {
+                held == "*" || held == channel
+            }
+            (Permission::Members, Permission::Invite) => true,
+            _ => self == required,
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Permission::Upload => write!(f, "upload"),
+            Permission::Promote(ref channel) => write!(f, "promote:{}", channel),
+            Permission::Delete => write!(f, "delete"),
+            Permission::Channels => write!(f, "channels"),
+            Permission::Visibility => write!(f, "visibility"),
+            Permission::Invite => write!(f, "invite"),
+            Permission::Keys => write!(f, "keys"),
+            Permission::Secrets => write!(f, "secrets"),
+            Permission::Members => write!(f, "members"),
+            Permission::Settings => write!(f, "settings"),
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = BuilderError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_ref() {
+            "upload" => Ok(Permission::Upload),
+            "delete" => Ok(Permission::Delete),
+            "channels" => Ok(Permission::Channels),
+            "visibility" => Ok(Permission::Visibility),
+            "invite" => Ok(Permission::Invite),
+            "keys" => Ok(Permission::Keys),
+            "secrets" => Ok(Permission::Secrets),
+            "members" => Ok(Permission::Members),
+            "settings" => Ok(Permission::Settings),
+            other => {
+                match other.strip_prefix("promote:") {
+                    Some(channel) if !channel.trim().is_empty() => {
+                        Ok(Permission::Promote(channel.to_string()))
+                    }
+                    _ => {
+                        Err(BuilderError::OriginMemberRoleError(format!(
+                            "Invalid permission \"{}\", must be one of: [\"upload\", \
+                             \"promote:<channel>\",\"delete\",\"channels\",\"visibility\",\
+                             \"invite\",\"keys\",\"secrets\",\"members\",\"settings\"].",
+                            value
+                        )))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl OriginMemberRole {
+    /// Whether the role carries a permission. Each role carries the permissions of the roles
+    /// below it.
+    pub fn grants(self, permission: &Permission) -> bool {
+        let min_role = match *permission {
+            Permission::Upload | Permission::Delete => OriginMemberRole::Member,
+            Permission::Promote(_)
+            | Permission::Channels
+            | Permission::Visibility
+            | Permission::Invite => OriginMemberRole::Maintainer,
+            Permission::Keys
+            | Permission::Secrets
+            | Permission::Members
+            | Permission::Settings => OriginMemberRole::Administrator,
+        };
+        self >= min_role
+    }
+}
+
+impl OriginMember {
+    /// Whether an account holds a permission in the origin, through its role or its custom
+    /// role.
+    pub fn has_permission(origin: &str,
+                          account_id: i64,
+                          permission: &Permission,
+                          conn: &mut PgConnection)
+                          -> QueryResult<bool> {
+        if Self::effective_role(origin, account_id, conn)?.grants(permission) {
+            return Ok(true);
+        }
+        let custom_permissions = CustomRole::member_permissions(origin, account_id, conn)?;
+        Ok(custom_permissions.iter().any(|held| held.covers(permission)))
+    }
+}
+
+/// A role an origin defines for itself, bundling the permissions it names.
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct CustomRole {
+    #[serde(with = "db_id_format")]
+    pub id:          i64,
+    pub origin:      String,
+    pub name:        String,
+    pub description: String,
+    pub permissions: Vec<String>,
+    #[serde(with = "db_id_format")]
+    pub owner_id:    i64,
+    pub created_at:  Option<NaiveDateTime>,
+    pub updated_at:  Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = origin_custom_roles)]
+pub struct NewCustomRole<'a> {
+    pub origin:      &'a str,
+    pub name:        &'a str,
+    pub description: &'a str,
+    pub permissions: Vec<String>,
+    pub owner_id:    i64,
+}
+
+#[derive(AsChangeset, Default)]
+#[diesel(table_name = origin_custom_roles)]
+pub struct UpdateCustomRole<'a> {
+    pub description: Option<&'a str>,
+    pub permissions: Option<Vec<String>>,
+}
+
+impl CustomRole {
+    pub fn create(role: &NewCustomRole, conn: &mut PgConnection) -> QueryResult<CustomRole> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_custom_roles::table).values(role)
+                                                       .get_result(conn)
+    }
+
+    pub fn get(origin: &str, name: &str, conn: &mut PgConnection) -> QueryResult<CustomRole> {
+        Counter::DBCall.increment();
+        origin_custom_roles::table.filter(origin_custom_roles::origin.eq(origin))
+                                  .filter(origin_custom_roles::name.eq(name))
+                                  .get_result(conn)
+    }
+
+    pub fn list(origin: &str, conn: &mut PgConnection) -> QueryResult<Vec<CustomRole>> {
+        Counter::DBCall.increment();
+        origin_custom_roles::table.filter(origin_custom_roles::origin.eq(origin))
+                                  .order(origin_custom_roles::name.asc())
+                                  .get_results(conn)
+    }
+
+    pub fn update(id: i64,
+                  changes: &UpdateCustomRole,
+                  conn: &mut PgConnection)
+                  -> QueryResult<CustomRole> {
+        Counter::DBCall.increment();
+        diesel::update(origin_custom_roles::table.find(id))
+            .set((changes, origin_custom_roles::updated_at.eq(diesel::dsl::now)))
+            .get_result(conn)
+    }
+
+    /// Deletes the role. Its members keep their memberships, without it.
+    pub fn delete(id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::delete(origin_custom_roles::table.find(id)).execute(conn)
+    }
+
+    /// Names of the members holding the role.
+    pub fn list_members(id: i64, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+        Counter::DBCall.increment();
+        origin_members::table.inner_join(accounts::table)
+                             .select(accounts::name)
+                             .filter(origin_members::custom_role_id.eq(id))
+                             .order(accounts::name.asc())
+                             .get_results(conn)
+    }
+
+    /// Gives the role to one of the origin's members, in place of any custom role they held.
+    pub fn assign(&self, account_id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(origin_members::table.filter(origin_members::origin.eq(&self.origin)))
+            .filter(origin_members::account_id.eq(account_id))
+            .set(origin_members::custom_role_id.eq(self.id))
+            .execute(conn)
+    }
+
+    pub fn unassign(&self, account_id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(origin_members::table.filter(origin_members::origin.eq(&self.origin)))
+            .filter(origin_members::account_id.eq(account_id))
+            .filter(origin_members::custom_role_id.eq(self.id))
+            .set(origin_members::custom_role_id.eq(None::<i64>))
+            .execute(conn)
+    }
+
+    /// The permissions of the custom role a member holds in the origin, if any.
+    pub fn member_permissions(origin: &str,
+                              account_id: i64,
+                              conn: &mut PgConnection)
+                              -> QueryResult<Vec<Permission>> {
+        Counter::DBCall.increment();
+        let names: Vec<Vec<String>> =
+            origin_members::table.inner_join(origin_custom_roles::table)
+                                 .select(origin_custom_roles::permissions)
+                                 .filter(origin_members::origin.eq(origin))
+                                 .filter(origin_members::account_id.eq(account_id))
+                                 .get_results(conn)?;
+
+        // Permissions are checked as roles are saved, so none should fail to parse
+        Ok(names.iter()
+                .flatten()
+                .filter_map(|name| name.parse::<Permission>().ok())
+                .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permission_round_trip() {
+        for name in &["upload",
+                      "promote:stable",
+                      "promote:*",
+                      "delete",
+                      "channels",
+                      "visibility",
+                      "invite",
+                      "keys",
+                      "secrets",
+                      "members",
+                      "settings"]
+        {
+            let permission = name.parse::<Permission>().unwrap();
+            assert_eq!(permission.to_string(), *name);
+        }
+        assert!("promote:".parse::<Permission>().is_err());
+        assert!("publish".parse::<Permission>().is_err());
+    }
+
+    #[test]
+    fn promote_covers_its_channel() {
+        let staging = Permission::Promote("staging".to_string());
+        let stable = Permission::Promote("stable".to_string());
+        let any = Permission::Promote("*".to_string());
+
+        assert!(staging.covers(&staging));
+        assert!(!staging.covers(&stable));
+        assert!(any.covers(&stable));
+        assert!(!Permission::Secrets.covers(&Permission::Keys));
+        assert!(Permission::Members.covers(&Permission::Invite));
+        assert!(!Permission::Invite.covers(&Permission::Members));
+    }
+
+    #[test]
+    fn roles_grant_permissions_of_lower_roles() {
+        let stable = Permission::Promote("stable".to_string());
+
+        assert!(!OriginMemberRole::ReadonlyMember.grants(&Permission::Upload));
+        assert!(OriginMemberRole::Member.grants(&Permission::Upload));
+        assert!(!OriginMemberRole::Member.grants(&stable));
+        assert!(OriginMemberRole::Maintainer.grants(&stable));
+        assert!(OriginMemberRole::Maintainer.grants(&Permission::Channels));
+        assert!(!OriginMemberRole::Member.grants(&Permission::Visibility));
+        assert!(!OriginMemberRole::Maintainer.grants(&Permission::Secrets));
+        assert!(OriginMemberRole::Owner.grants(&Permission::Settings));
+    }
+}
@@ -0,0 +1,167 @@
+use super::db_id_format;
+use chrono::NaiveDateTime;
+use diesel::{self,
+             pg::PgConnection,
+             result::QueryResult,
+             ExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+use diesel_derive_enum::DbEnum;
+
+use crate::{bldr_core::metrics::CounterMetric,
+            hab_core::ChannelIdent,
+            metrics::Counter,
+            models::package::BuilderPackageIdent,
+            schema::channel::origin_scheduled_promotions};
+
+#[derive(DbEnum, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[ExistingTypePath = "crate::schema::sql_types::ScheduledPromotionState"]
+#[DbValueStyle = "snake_case"]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledPromotionState {
+    Pending,
+    Running,
+    Completed,
+    Skipped,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct ScheduledPromotion {
+    #[serde(with = "db_id_format")]
+    pub id:             i64,
+    pub origin:         String,
+    pub channel:        String,
+    pub ident:          BuilderPackageIdent,
+    pub target:         String,
+    pub run_at:         NaiveDateTime,
+    pub source_channel: Option<String>,
+    pub delay_secs:     Option<i64>,
+    pub state:          ScheduledPromotionState,
+    pub result:         Option<String>,
+    #[serde(with = "db_id_format")]
+    pub requester_id:   i64,
+    pub requester_name: String,
+    pub created_at:     Option<NaiveDateTime>,
+    pub updated_at:     Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = origin_scheduled_promotions)]
+pub struct NewScheduledPromotion<'a> {
+    pub origin:         &'a str,
+    // This would be ChannelIdent, but Insertable requires implementing diesel::Expression
+    pub channel:        &'a str,
+    pub ident:          BuilderPackageIdent,
+    pub target:         &'a str,
+    pub run_at:         NaiveDateTime,
+    pub source_channel: Option<&'a str>,
+    pub delay_secs:     Option<i64>,
+    pub requester_id:   i64,
+    pub requester_name: &'a str,
+}
+
+impl ScheduledPromotion {
+    pub fn create(promotions: &[NewScheduledPromotion],
+                  conn: &mut PgConnection)
+                  -> QueryResult<Vec<ScheduledPromotion>> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_scheduled_promotions::table).values(promotions)
+                                                               .get_results(conn)
+    }
+
+    pub fn get(origin: &str,
+               channel: &ChannelIdent,
+               id: i64,
+               conn: &mut PgConnection)
+               -> QueryResult<ScheduledPromotion> {
+        Counter::DBCall.increment();
+        origin_scheduled_promotions::table.filter(origin_scheduled_promotions::id.eq(id))
+                                          .filter(origin_scheduled_promotions::origin.eq(origin))
+                                          .filter(origin_scheduled_promotions::channel
+                                                      .eq(channel.as_str()))
+                                          .get_result(conn)
+    }
+
+    pub fn list(origin: &str,
+                channel: &ChannelIdent,
+                state: Option<ScheduledPromotionState>,
+                conn: &mut PgConnection)
+                -> QueryResult<Vec<ScheduledPromotion>> {
+        Counter::DBCall.increment();
+        let mut query =
+            origin_scheduled_promotions::table.filter(origin_scheduled_promotions::origin
+                                                          .eq(origin))
+                                              .filter(origin_scheduled_promotions::channel
+                                                          .eq(channel.as_str()))
+                                              .into_boxed();
+        if let Some(state) = state {
+            query = query.filter(origin_scheduled_promotions::state.eq(state));
+        }
+        query.order(origin_scheduled_promotions::run_at.asc())
+             .get_results(conn)
+    }
+
+    // Only pending promotions can be cancelled, so one that has already been picked up by the
+    // scheduler is left untouched and zero rows are returned.
+    pub fn cancel(id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(
+            origin_scheduled_promotions::table
+                .filter(origin_scheduled_promotions::id.eq(id))
+                .filter(origin_scheduled_promotions::state.eq(ScheduledPromotionState::Pending)),
+        )
+        .set((
+            origin_scheduled_promotions::state.eq(ScheduledPromotionState::Cancelled),
+            origin_scheduled_promotions::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(conn)
+    }
+
+    // Marks every pending promotion that has come due as running and returns them. Doing this in
+    // a single update means that when several api instances run the scheduler, each promotion is
+    // only picked up by one of them.
+    pub fn claim_due(conn: &mut PgConnection) -> QueryResult<Vec<ScheduledPromotion>> {
+        Counter::DBCall.increment();
+        diesel::update(
+            origin_scheduled_promotions::table
+                .filter(origin_scheduled_promotions::state.eq(ScheduledPromotionState::Pending))
+                .filter(origin_scheduled_promotions::run_at.le(diesel::dsl::now)),
+        )
+        .set((
+            origin_scheduled_promotions::state.eq(ScheduledPromotionState::Running),
+            origin_scheduled_promotions::updated_at.eq(diesel::dsl::now),
+        ))
+        .get_results(conn)
+    }
+
+    pub fn finish(id: i64,
+                  state: ScheduledPromotionState,
+                  result: Option<&str>,
+                  conn: &mut PgConnection)
+                  -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(origin_scheduled_promotions::table.find(id))
+            .set((
+                origin_scheduled_promotions::state.eq(state),
+                origin_scheduled_promotions::result.eq(result),
+                origin_scheduled_promotions::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)
+    }
+
+    pub fn reschedule(id: i64,
+                      run_at: NaiveDateTime,
+                      conn: &mut PgConnection)
+                      -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(origin_scheduled_promotions::table.find(id))
+            .set((
+                origin_scheduled_promotions::state.eq(ScheduledPromotionState::Pending),
+                origin_scheduled_promotions::run_at.eq(run_at),
+                origin_scheduled_promotions::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)
+    }
+}
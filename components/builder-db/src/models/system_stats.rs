@@ -0,0 +1,48 @@
+use diesel::{self,
+             pg::PgConnection,
+             result::QueryResult,
+             ExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+
+use crate::{bldr_core::metrics::CounterMetric,
+            metrics::Counter,
+            schema::{account::{account_tokens,
+                               accounts},
+                     origin::origins,
+                     package::origin_packages}};
+
+/// Counts of what the whole of Builder holds, for its administrators.
+#[derive(Debug, Serialize)]
+pub struct SystemStats {
+    pub accounts:          i64,
+    pub robot_accounts:    i64,
+    pub disabled_accounts: i64,
+    pub access_tokens:     i64,
+    pub origins:           i64,
+    pub hidden_origins:    i64,
+    pub packages:          i64,
+}
+
+impl SystemStats {
+    pub fn get(conn: &mut PgConnection) -> QueryResult<SystemStats> {
+        Counter::DBCall.increment();
+        let robot_accounts = accounts::table.filter(accounts::robot_origin.is_not_null())
+                                            .count()
+                                            .get_result(conn)?;
+        let disabled_accounts = accounts::table.filter(accounts::disabled.eq(true))
+                                               .count()
+                                               .get_result(conn)?;
+        let hidden_origins = origins::table.filter(origins::hidden.eq(true))
+                                           .count()
+                                           .get_result(conn)?;
+
+        Ok(SystemStats { accounts: accounts::table.count().get_result(conn)?,
+                         robot_accounts,
+                         disabled_accounts,
+                         access_tokens: account_tokens::table.count().get_result(conn)?,
+                         origins: origins::table.count().get_result(conn)?,
+                         hidden_origins,
+                         packages: origin_packages::table.count().get_result(conn)? })
+    }
+}
@@ -0,0 +1,135 @@
+use super::db_id_format;
+use chrono::NaiveDateTime;
+use diesel::{self,
+             pg::PgConnection,
+             result::QueryResult,
+             ExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+
+use crate::{bldr_core::metrics::CounterMetric,
+            metrics::Counter,
+            models::origin::OriginMemberRole,
+            schema::{account::accounts,
+                     team::{origin_team_members,
+                            origin_teams}}};
+
+/// A named group of an origin's members, who all hold at least the team's role in the origin.
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct Team {
+    #[serde(with = "db_id_format")]
+    pub id:          i64,
+    pub origin:      String,
+    pub name:        String,
+    pub description: String,
+    pub member_role: OriginMemberRole,
+    #[serde(with = "db_id_format")]
+    pub owner_id:    i64,
+    pub created_at:  Option<NaiveDateTime>,
+    pub updated_at:  Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = origin_teams)]
+pub struct NewTeam<'a> {
+    pub origin:      &'a str,
+    pub name:        &'a str,
+    pub description: &'a str,
+    pub member_role: OriginMemberRole,
+    pub owner_id:    i64,
+}
+
+#[derive(AsChangeset, Default)]
+#[diesel(table_name = origin_teams)]
+pub struct UpdateTeam<'a> {
+    pub description: Option<&'a str>,
+    pub member_role: Option<OriginMemberRole>,
+}
+
+impl Team {
+    pub fn create(team: &NewTeam, conn: &mut PgConnection) -> QueryResult<Team> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_teams::table).values(team)
+                                                .get_result(conn)
+    }
+
+    pub fn get(origin: &str, name: &str, conn: &mut PgConnection) -> QueryResult<Team> {
+        Counter::DBCall.increment();
+        origin_teams::table.filter(origin_teams::origin.eq(origin))
+                           .filter(origin_teams::name.eq(name))
+                           .get_result(conn)
+    }
+
+    pub fn list(origin: &str, conn: &mut PgConnection) -> QueryResult<Vec<Team>> {
+        Counter::DBCall.increment();
+        origin_teams::table.filter(origin_teams::origin.eq(origin))
+                           .order(origin_teams::name.asc())
+                           .get_results(conn)
+    }
+
+    pub fn update(id: i64, changes: &UpdateTeam, conn: &mut PgConnection) -> QueryResult<Team> {
+        Counter::DBCall.increment();
+        diesel::update(origin_teams::table.find(id))
+            .set((changes, origin_teams::updated_at.eq(diesel::dsl::now)))
+            .get_result(conn)
+    }
+
+    pub fn delete(id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::delete(origin_teams::table.find(id)).execute(conn)
+    }
+
+    /// Names of the team's members.
+    pub fn list_members(id: i64, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+        Counter::DBCall.increment();
+        origin_team_members::table.inner_join(accounts::table)
+                                  .select(accounts::name)
+                                  .filter(origin_team_members::team_id.eq(id))
+                                  .order(accounts::name.asc())
+                                  .get_results(conn)
+    }
+
+    /// Adds one of the origin's members to the team. Accounts that aren't members of the
+    /// origin can't be added.
+    pub fn add_member(&self, account_id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_team_members::table)
+            .values((
+                origin_team_members::team_id.eq(self.id),
+                origin_team_members::origin.eq(&self.origin),
+                origin_team_members::account_id.eq(account_id),
+            ))
+            .execute(conn)
+    }
+
+    pub fn remove_member(&self, account_id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::delete(
+            origin_team_members::table
+                .filter(origin_team_members::team_id.eq(self.id))
+                .filter(origin_team_members::account_id.eq(account_id)),
+        )
+        .execute(conn)
+    }
+
+    /// The highest role any of an account's teams in the origin grants, if it is on any.
+    pub fn max_role(origin: &str,
+                    account_id: i64,
+                    conn: &mut PgConnection)
+                    -> QueryResult<Option<OriginMemberRole>> {
+        Counter::DBCall.increment();
+        let roles: Vec<OriginMemberRole> =
+            origin_team_members::table.inner_join(origin_teams::table)
+                                      .select(origin_teams::member_role)
+                                      .filter(origin_team_members::origin.eq(origin))
+                                      .filter(origin_team_members::account_id.eq(account_id))
+                                      .get_results(conn)?;
+
+        Ok(roles.into_iter().fold(None, |max, role| {
+                                match max {
+                                    Some(max) if max >= role => Some(max),
+                                    _ => Some(role),
+                                }
+                            }))
+    }
+}
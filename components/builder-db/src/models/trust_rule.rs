@@ -0,0 +1,109 @@
+use super::db_id_format;
+use chrono::NaiveDateTime;
+use diesel::{self,
+             pg::PgConnection,
+             result::QueryResult,
+             ExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+
+use crate::{bldr_core::{jwt::{self,
+                              Claims},
+                        metrics::CounterMetric},
+            metrics::Counter,
+            models::origin::OriginMemberRole,
+            schema::trust_rule::origin_trust_rules};
+
+/// Lets workloads, such as CI jobs, act in an origin with the identity tokens
+/// their platform issues them. A token is trusted when it comes from the
+/// rule's issuer and carries every one of the rule's claims.
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct TrustRule {
+    #[serde(with = "db_id_format")]
+    pub id:           i64,
+    pub origin:       String,
+    pub issuer:       String,
+    /// Required claim values. A value ending in `*` matches any claim that
+    /// starts with the rest of it.
+    pub claims:       serde_json::Value,
+    pub member_role:  OriginMemberRole,
+    pub capabilities: Vec<String>,
+    pub channels:     Vec<String>,
+    #[serde(with = "db_id_format")]
+    pub owner_id:     i64,
+    pub created_at:   Option<NaiveDateTime>,
+    pub updated_at:   Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = origin_trust_rules)]
+pub struct NewTrustRule<'a> {
+    pub origin:       &'a str,
+    pub issuer:       &'a str,
+    pub claims:       serde_json::Value,
+    pub member_role:  OriginMemberRole,
+    pub capabilities: Vec<String>,
+    pub channels:     Vec<String>,
+    pub owner_id:     i64,
+}
+
+impl TrustRule {
+    pub fn create(rule: &NewTrustRule, conn: &mut PgConnection) -> QueryResult<TrustRule> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_trust_rules::table).values(rule)
+                                                      .get_result(conn)
+    }
+
+    pub fn get(origin: &str, id: i64, conn: &mut PgConnection) -> QueryResult<TrustRule> {
+        Counter::DBCall.increment();
+        origin_trust_rules::table.filter(origin_trust_rules::id.eq(id))
+                                 .filter(origin_trust_rules::origin.eq(origin))
+                                 .get_result(conn)
+    }
+
+    pub fn list(origin: &str, conn: &mut PgConnection) -> QueryResult<Vec<TrustRule>> {
+        Counter::DBCall.increment();
+        origin_trust_rules::table.filter(origin_trust_rules::origin.eq(origin))
+                                 .order(origin_trust_rules::created_at.asc())
+                                 .get_results(conn)
+    }
+
+    pub fn list_for_issuer(origin: &str,
+                           issuer: &str,
+                           conn: &mut PgConnection)
+                           -> QueryResult<Vec<TrustRule>> {
+        Counter::DBCall.increment();
+        origin_trust_rules::table.filter(origin_trust_rules::origin.eq(origin))
+                                 .filter(origin_trust_rules::issuer.eq(issuer))
+                                 .order(origin_trust_rules::created_at.asc())
+                                 .get_results(conn)
+    }
+
+    pub fn delete(origin: &str, id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::delete(origin_trust_rules::table.filter(origin_trust_rules::id.eq(id))
+                                                .filter(origin_trust_rules::origin.eq(origin)))
+            .execute(conn)
+    }
+
+    /// Whether a token's verified claims satisfy every claim the rule requires.
+    pub fn matches(&self, claims: &Claims) -> bool {
+        let required = match self.claims.as_object() {
+            Some(required) if !required.is_empty() => required,
+            // A rule that requires nothing would trust every token of the issuer
+            _ => return false,
+        };
+
+        required.iter().all(|(name, expected)| {
+                           match (expected.as_str(), jwt::claim_string(claims, name)) {
+                               (Some(expected), Some(actual)) => {
+                                   match expected.strip_suffix('*') {
+                                       Some(prefix) => actual.starts_with(prefix),
+                                       None => actual == expected,
+                                   }
+                               }
+                               _ => false,
+                           }
+                       })
+    }
+}
@@ -0,0 +1,317 @@
+use super::db_id_format;
+use chrono::NaiveDateTime;
+use diesel::{self,
+             pg::PgConnection,
+             result::QueryResult,
+             BoolExpressionMethods,
+             ExpressionMethods,
+             PgArrayExpressionMethods,
+             QueryDsl,
+             RunQueryDsl};
+use diesel_derive_enum::DbEnum;
+use std::{fmt,
+          str::FromStr};
+
+use crate::{bldr_core::metrics::CounterMetric,
+            metrics::Counter,
+            schema::webhook::{origin_webhook_deliveries,
+                              origin_webhooks}};
+
+/// Events an origin webhook can subscribe to. Subscriptions are stored as the snake case names
+/// so that new events can be added without a migration.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    PackageUpload,
+    PackagePromote,
+    PackageDemote,
+    PackageDelete,
+    MemberChange,
+    KeyUpload,
+}
+
+impl WebhookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookEvent::PackageUpload => "package_upload",
+            WebhookEvent::PackagePromote => "package_promote",
+            WebhookEvent::PackageDemote => "package_demote",
+            WebhookEvent::PackageDelete => "package_delete",
+            WebhookEvent::MemberChange => "member_change",
+            WebhookEvent::KeyUpload => "key_upload",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.as_str()) }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "package_upload" => Ok(WebhookEvent::PackageUpload),
+            "package_promote" => Ok(WebhookEvent::PackagePromote),
+            "package_demote" => Ok(WebhookEvent::PackageDemote),
+            "package_delete" => Ok(WebhookEvent::PackageDelete),
+            "member_change" => Ok(WebhookEvent::MemberChange),
+            "key_upload" => Ok(WebhookEvent::KeyUpload),
+            _ => Err(format!("Unknown webhook event: {}", value)),
+        }
+    }
+}
+
+#[derive(DbEnum, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[ExistingTypePath = "crate::schema::sql_types::WebhookDeliveryState"]
+#[DbValueStyle = "snake_case"]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryState {
+    Pending,
+    Delivering,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct Webhook {
+    #[serde(with = "db_id_format")]
+    pub id:         i64,
+    pub origin:     String,
+    pub url:        String,
+    #[serde(skip_serializing)]
+    pub secret:     String,
+    pub events:     Vec<String>,
+    pub active:     bool,
+    #[serde(with = "db_id_format")]
+    pub owner_id:   i64,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = origin_webhooks)]
+pub struct NewWebhook<'a> {
+    pub origin:   &'a str,
+    pub url:      &'a str,
+    pub secret:   &'a str,
+    pub events:   Vec<String>,
+    pub owner_id: i64,
+}
+
+#[derive(AsChangeset, Default)]
+#[diesel(table_name = origin_webhooks)]
+pub struct UpdateWebhook<'a> {
+    pub url:    Option<&'a str>,
+    pub secret: Option<&'a str>,
+    pub events: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Clone)]
+pub struct WebhookDelivery {
+    #[serde(with = "db_id_format")]
+    pub id:              i64,
+    #[serde(with = "db_id_format")]
+    pub webhook_id:      i64,
+    pub event:           String,
+    pub payload:         String,
+    pub state:           WebhookDeliveryState,
+    pub attempts:        i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub response_code:   Option<i32>,
+    pub error:           Option<String>,
+    pub created_at:      Option<NaiveDateTime>,
+    pub updated_at:      Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = origin_webhook_deliveries)]
+pub struct NewWebhookDelivery<'a> {
+    pub webhook_id: i64,
+    pub event:      &'a str,
+    // The payload is kept as the exact text that is sent and signed, so that a retry carries the
+    // same body and signature as the first attempt.
+    pub payload:    &'a str,
+}
+
+impl Webhook {
+    pub fn create(webhook: &NewWebhook, conn: &mut PgConnection) -> QueryResult<Webhook> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_webhooks::table).values(webhook)
+                                                   .get_result(conn)
+    }
+
+    pub fn get(origin: &str, id: i64, conn: &mut PgConnection) -> QueryResult<Webhook> {
+        Counter::DBCall.increment();
+        origin_webhooks::table.filter(origin_webhooks::id.eq(id))
+                              .filter(origin_webhooks::origin.eq(origin))
+                              .get_result(conn)
+    }
+
+    pub fn get_by_id(id: i64, conn: &mut PgConnection) -> QueryResult<Webhook> {
+        Counter::DBCall.increment();
+        origin_webhooks::table.find(id).get_result(conn)
+    }
+
+    pub fn list(origin: &str, conn: &mut PgConnection) -> QueryResult<Vec<Webhook>> {
+        Counter::DBCall.increment();
+        origin_webhooks::table.filter(origin_webhooks::origin.eq(origin))
+                              .order(origin_webhooks::created_at.asc())
+                              .get_results(conn)
+    }
+
+    /// The active webhooks of an origin that are subscribed to the given event.
+    pub fn list_for_event(origin: &str,
+                          event: WebhookEvent,
+                          conn: &mut PgConnection)
+                          -> QueryResult<Vec<Webhook>> {
+        Counter::DBCall.increment();
+        origin_webhooks::table.filter(origin_webhooks::origin.eq(origin))
+                              .filter(origin_webhooks::active.eq(true))
+                              .filter(origin_webhooks::events.contains(vec![event.to_string()]))
+                              .get_results(conn)
+    }
+
+    pub fn update(id: i64,
+                  changes: &UpdateWebhook,
+                  conn: &mut PgConnection)
+                  -> QueryResult<Webhook> {
+        Counter::DBCall.increment();
+        diesel::update(origin_webhooks::table.find(id))
+            .set((changes, origin_webhooks::updated_at.eq(diesel::dsl::now)))
+            .get_result(conn)
+    }
+
+    pub fn delete(origin: &str, id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::delete(origin_webhooks::table.filter(origin_webhooks::id.eq(id))
+                                             .filter(origin_webhooks::origin.eq(origin)))
+            .execute(conn)
+    }
+}
+
+impl WebhookDelivery {
+    pub fn create(deliveries: &[NewWebhookDelivery],
+                  conn: &mut PgConnection)
+                  -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::insert_into(origin_webhook_deliveries::table).values(deliveries)
+                                                             .execute(conn)
+    }
+
+    pub fn get(webhook_id: i64, id: i64, conn: &mut PgConnection) -> QueryResult<WebhookDelivery> {
+        Counter::DBCall.increment();
+        origin_webhook_deliveries::table.filter(origin_webhook_deliveries::id.eq(id))
+                                        .filter(origin_webhook_deliveries::webhook_id
+                                                    .eq(webhook_id))
+                                        .get_result(conn)
+    }
+
+    /// The most recent deliveries of a webhook, newest first.
+    pub fn list(webhook_id: i64,
+                limit: i64,
+                conn: &mut PgConnection)
+                -> QueryResult<Vec<WebhookDelivery>> {
+        Counter::DBCall.increment();
+        origin_webhook_deliveries::table
+            .filter(origin_webhook_deliveries::webhook_id.eq(webhook_id))
+            .order(origin_webhook_deliveries::created_at.desc())
+            .limit(limit)
+            .get_results(conn)
+    }
+
+    // Marks up to `limit` pending deliveries that have come due as delivering and returns them,
+    // counting the attempt. Deliveries still marked as delivering since before `stale_before`
+    // were claimed by an instance that stopped before finishing them, and are claimed again. As
+    // with scheduled promotions, doing this in a single update means each delivery is only picked
+    // up by one api instance; the state is checked again on the outer update so a row claimed
+    // concurrently by another instance is skipped.
+    pub fn claim_due(limit: i64,
+                     stale_before: NaiveDateTime,
+                     conn: &mut PgConnection)
+                     -> QueryResult<Vec<WebhookDelivery>> {
+        Counter::DBCall.increment();
+        let due = origin_webhook_deliveries::table
+            .select(origin_webhook_deliveries::id)
+            .filter(
+                origin_webhook_deliveries::state
+                    .eq(WebhookDeliveryState::Pending)
+                    .and(origin_webhook_deliveries::next_attempt_at.le(diesel::dsl::now))
+                    .or(origin_webhook_deliveries::state
+                        .eq(WebhookDeliveryState::Delivering)
+                        .and(origin_webhook_deliveries::updated_at.lt(stale_before))),
+            )
+            .order(origin_webhook_deliveries::next_attempt_at.asc())
+            .limit(limit);
+
+        diesel::update(
+            origin_webhook_deliveries::table
+                .filter(origin_webhook_deliveries::id.eq_any(due))
+                .filter(
+                    origin_webhook_deliveries::state
+                        .eq(WebhookDeliveryState::Pending)
+                        .or(origin_webhook_deliveries::updated_at.lt(stale_before)),
+                ),
+        )
+        .set((
+            origin_webhook_deliveries::state.eq(WebhookDeliveryState::Delivering),
+            origin_webhook_deliveries::attempts.eq(origin_webhook_deliveries::attempts + 1),
+            origin_webhook_deliveries::updated_at.eq(diesel::dsl::now),
+        ))
+        .get_results(conn)
+    }
+
+    pub fn finish(id: i64,
+                  state: WebhookDeliveryState,
+                  response_code: Option<i32>,
+                  error: Option<&str>,
+                  conn: &mut PgConnection)
+                  -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(origin_webhook_deliveries::table.find(id))
+            .set((
+                origin_webhook_deliveries::state.eq(state),
+                origin_webhook_deliveries::response_code.eq(response_code),
+                origin_webhook_deliveries::error.eq(error),
+                origin_webhook_deliveries::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)
+    }
+
+    pub fn retry_at(id: i64,
+                    next_attempt_at: NaiveDateTime,
+                    response_code: Option<i32>,
+                    error: Option<&str>,
+                    conn: &mut PgConnection)
+                    -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(origin_webhook_deliveries::table.find(id))
+            .set((
+                origin_webhook_deliveries::state.eq(WebhookDeliveryState::Pending),
+                origin_webhook_deliveries::next_attempt_at.eq(next_attempt_at),
+                origin_webhook_deliveries::response_code.eq(response_code),
+                origin_webhook_deliveries::error.eq(error),
+                origin_webhook_deliveries::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)
+    }
+
+    /// Queues a finished delivery to be sent again straight away, with a fresh set of attempts.
+    pub fn redeliver(id: i64, conn: &mut PgConnection) -> QueryResult<usize> {
+        Counter::DBCall.increment();
+        diesel::update(
+            origin_webhook_deliveries::table
+                .filter(origin_webhook_deliveries::id.eq(id))
+                .filter(origin_webhook_deliveries::state.ne(WebhookDeliveryState::Delivering)),
+        )
+        .set((
+            origin_webhook_deliveries::state.eq(WebhookDeliveryState::Pending),
+            origin_webhook_deliveries::attempts.eq(0),
+            origin_webhook_deliveries::next_attempt_at.eq(diesel::dsl::now),
+            origin_webhook_deliveries::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(conn)
+    }
+}
@@ -5,6 +5,8 @@ table! {
         name -> Text,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
+        robot_origin -> Nullable<Text>,
+        disabled -> Bool,
     }
 }
 
@@ -14,5 +16,9 @@ table! {
         account_id -> BigInt,
         token -> Text,
         created_at -> Nullable<Timestamptz>,
+        name -> Text,
+        last_used_at -> Nullable<Timestamptz>,
+        expires_at -> Nullable<Timestamptz>,
+        scope -> Nullable<Jsonb>,
     }
 }
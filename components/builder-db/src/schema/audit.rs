@@ -11,6 +11,7 @@ table! {
         requester_name  -> Text,
         created_at      -> Nullable<Timestamptz>,
         origin          -> Text,
+        target          -> Nullable<Text>,
     }
 }
 
@@ -43,6 +44,26 @@ table! {
         requester_name  -> Text,
         target_object   -> Text,
         created_at      -> Nullable<Timestamptz>,
+        reason          -> Nullable<Text>,
+    }
+}
+
+table! {
+    use crate::schema::sql_types::{AuditAction, PackageChannelTrigger};
+    use diesel::sql_types::{BigInt, Jsonb, Text, Nullable, Timestamptz};
+
+    audit_events (id) {
+        id              -> BigInt,
+        origin          -> Nullable<Text>,
+        actor_id        -> BigInt,
+        actor_name      -> Text,
+        action          -> AuditAction,
+        target          -> Text,
+        before          -> Nullable<Jsonb>,
+        after           -> Nullable<Jsonb>,
+        source_ip       -> Nullable<Text>,
+        trigger         -> PackageChannelTrigger,
+        created_at      -> Nullable<Timestamptz>,
     }
 }
 
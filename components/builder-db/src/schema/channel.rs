@@ -6,6 +6,20 @@ table! {
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
         origin -> Text,
+        expires_at -> Nullable<Timestamptz>,
+        description -> Nullable<Text>,
+        frozen -> Bool,
+        maintainers -> Array<Text>,
+    }
+}
+
+table! {
+    origin_channel_labels (channel_id, key) {
+        channel_id -> BigInt,
+        key -> Text,
+        value -> Text,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -18,6 +32,89 @@ table! {
     }
 }
 
+table! {
+    use crate::schema::sql_types::OriginMemberRole;
+    use diesel::sql_types::{BigInt, Bool, Nullable, Text, Timestamptz};
+
+    origin_channel_policies (channel_id) {
+        channel_id -> BigInt,
+        origin -> Text,
+        channel -> Text,
+        promote_role -> OriginMemberRole,
+        demote_role -> OriginMemberRole,
+        source_channel -> Nullable<Text>,
+        require_tdeps -> Bool,
+        block_demote -> Bool,
+        require_approval -> Bool,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    use crate::schema::sql_types::PromotionRequestState;
+    use diesel::sql_types::{Array, BigInt, Nullable, Text, Timestamptz};
+
+    origin_promotion_requests (id) {
+        id -> BigInt,
+        origin -> Text,
+        channel -> Text,
+        target -> Text,
+        package_idents -> Array<Text>,
+        state -> PromotionRequestState,
+        requester_id -> BigInt,
+        requester_name -> Text,
+        reviewer_id -> Nullable<BigInt>,
+        reviewer_name -> Nullable<Text>,
+        review_comment -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        reviewed_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    use crate::schema::sql_types::ScheduledPromotionState;
+    use diesel::sql_types::{BigInt, Nullable, Text, Timestamptz};
+
+    origin_scheduled_promotions (id) {
+        id -> BigInt,
+        origin -> Text,
+        channel -> Text,
+        ident -> Text,
+        target -> Text,
+        run_at -> Timestamptz,
+        source_channel -> Nullable<Text>,
+        delay_secs -> Nullable<BigInt>,
+        state -> ScheduledPromotionState,
+        result -> Nullable<Text>,
+        requester_id -> BigInt,
+        requester_name -> Text,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    origin_channel_snapshots (id) {
+        id -> BigInt,
+        channel_id -> BigInt,
+        origin -> Text,
+        channel -> Text,
+        name -> Text,
+        owner_id -> BigInt,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    origin_channel_snapshot_packages (snapshot_id, package_id) {
+        snapshot_id -> BigInt,
+        package_id -> BigInt,
+        ident -> Text,
+        target -> Text,
+    }
+}
+
 use super::{origin::origins,
             package::{origin_packages,
                       origin_packages_with_version_array}};
@@ -26,9 +123,17 @@ joinable!(origin_channel_packages -> origin_packages (package_id));
 joinable!(origin_channel_packages -> origin_packages_with_version_array (package_id));
 joinable!(origin_channel_packages -> origin_channels (channel_id));
 joinable!(origin_channels -> origins (origin));
+joinable!(origin_channel_policies -> origin_channels (channel_id));
+joinable!(origin_channel_labels -> origin_channels (channel_id));
+joinable!(origin_channel_snapshots -> origin_channels (channel_id));
+joinable!(origin_channel_snapshot_packages -> origin_channel_snapshots (snapshot_id));
 
 allow_tables_to_appear_in_same_query!(origin_channels,
                                       origin_channel_packages,
+                                      origin_channel_policies,
+                                      origin_channel_labels,
+                                      origin_channel_snapshots,
+                                      origin_channel_snapshot_packages,
                                       origin_packages,
                                       origin_packages_with_version_array,
                                       origins);
@@ -0,0 +1,16 @@
+table! {
+    package_download_stats (package_id, day) {
+        package_id -> BigInt,
+        day -> Date,
+        origin -> Text,
+        name -> Text,
+        target -> Text,
+        download_count -> BigInt,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+use super::package::origin_packages;
+
+joinable!(package_download_stats -> origin_packages (package_id));
+allow_tables_to_appear_in_same_query!(package_download_stats, origin_packages);
@@ -1,12 +1,14 @@
 table! {
     use crate::schema::sql_types::OriginMemberRole;
-    use diesel::sql_types::{BigInt, Text, Nullable, Timestamptz};
+    use diesel::sql_types::{BigInt, Bool, Text, Nullable, Timestamptz};
     origin_members (origin, account_id) {
         account_id -> BigInt,
         origin -> Text,
         member_role -> OriginMemberRole,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
+        idp_managed -> Bool,
+        custom_role_id -> Nullable<BigInt>,
     }
 }
 
@@ -5,6 +5,7 @@
 pub mod account;
 pub mod audit;
 pub mod channel;
+pub mod download_stats;
 pub mod integration;
 pub mod invitation;
 pub mod key;
@@ -14,6 +15,10 @@ pub mod origin;
 pub mod package;
 pub mod project;
 pub mod project_integration;
+pub mod role;
 pub mod secrets;
 pub mod settings;
 pub mod sql_types;
+pub mod team;
+pub mod trust_rule;
+pub mod webhook;
@@ -1,12 +1,13 @@
 table! {
     use crate::schema::sql_types::OriginPackageVisibility;
-    use diesel::sql_types::{BigInt, Text, Nullable, Timestamptz};
+    use diesel::sql_types::{BigInt, Bool, Text, Nullable, Timestamptz};
     origins (name) {
         owner_id                     -> BigInt,
         name                         -> Text,
         created_at                   -> Nullable<Timestamptz>,
         updated_at                   -> Nullable<Timestamptz>,
         default_package_visibility   -> OriginPackageVisibility,
+        hidden                       -> Bool,
     }
 }
 
@@ -0,0 +1,21 @@
+table! {
+    use diesel::sql_types::{Array, BigInt, Nullable, Text, Timestamptz};
+
+    origin_custom_roles (id) {
+        id -> BigInt,
+        origin -> Text,
+        name -> Text,
+        description -> Text,
+        permissions -> Array<Text>,
+        owner_id -> BigInt,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+use super::{account::accounts,
+            member::origin_members};
+
+joinable!(origin_members -> origin_custom_roles (custom_role_id));
+allow_tables_to_appear_in_same_query!(origin_custom_roles, origin_members);
+allow_tables_to_appear_in_same_query!(origin_custom_roles, accounts);
@@ -25,3 +25,23 @@ pub struct OriginOperation;
 #[derive(SqlType, QueryId)]
 #[diesel(postgres_type(name = "origin_member_role"))]
 pub struct OriginMemberRole;
+
+/// Backing Postgres enum for origin_promotion_requests.state
+#[derive(SqlType, QueryId)]
+#[diesel(postgres_type(name = "promotion_request_state"))]
+pub struct PromotionRequestState;
+
+/// Backing Postgres enum for origin_scheduled_promotions.state
+#[derive(SqlType, QueryId)]
+#[diesel(postgres_type(name = "scheduled_promotion_state"))]
+pub struct ScheduledPromotionState;
+
+/// Backing Postgres enum for origin_webhook_deliveries.state
+#[derive(SqlType, QueryId)]
+#[diesel(postgres_type(name = "webhook_delivery_state"))]
+pub struct WebhookDeliveryState;
+
+/// Backing Postgres enum for audit_events.action
+#[derive(SqlType, QueryId)]
+#[diesel(postgres_type(name = "audit_action"))]
+pub struct AuditAction;
@@ -0,0 +1,30 @@
+table! {
+    use crate::schema::sql_types::OriginMemberRole;
+    use diesel::sql_types::{BigInt, Nullable, Text, Timestamptz};
+
+    origin_teams (id) {
+        id -> BigInt,
+        origin -> Text,
+        name -> Text,
+        description -> Text,
+        member_role -> OriginMemberRole,
+        owner_id -> BigInt,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    origin_team_members (team_id, account_id) {
+        team_id -> BigInt,
+        origin -> Text,
+        account_id -> BigInt,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+use super::account::accounts;
+
+joinable!(origin_team_members -> origin_teams (team_id));
+joinable!(origin_team_members -> accounts (account_id));
+allow_tables_to_appear_in_same_query!(origin_teams, origin_team_members, accounts);
@@ -0,0 +1,17 @@
+table! {
+    use crate::schema::sql_types::OriginMemberRole;
+    use diesel::sql_types::{Array, BigInt, Jsonb, Nullable, Text, Timestamptz};
+
+    origin_trust_rules (id) {
+        id -> BigInt,
+        origin -> Text,
+        issuer -> Text,
+        claims -> Jsonb,
+        member_role -> OriginMemberRole,
+        capabilities -> Array<Text>,
+        channels -> Array<Text>,
+        owner_id -> BigInt,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
@@ -0,0 +1,36 @@
+table! {
+    origin_webhooks (id) {
+        id -> BigInt,
+        origin -> Text,
+        url -> Text,
+        secret -> Text,
+        events -> Array<Text>,
+        active -> Bool,
+        owner_id -> BigInt,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    use crate::schema::sql_types::WebhookDeliveryState;
+    use diesel::sql_types::{BigInt, Integer, Nullable, Text, Timestamptz};
+
+    origin_webhook_deliveries (id) {
+        id -> BigInt,
+        webhook_id -> BigInt,
+        event -> Text,
+        payload -> Text,
+        state -> WebhookDeliveryState,
+        attempts -> Integer,
+        next_attempt_at -> Timestamptz,
+        response_code -> Nullable<Integer>,
+        error -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+joinable!(origin_webhook_deliveries -> origin_webhooks (webhook_id));
+
+allow_tables_to_appear_in_same_query!(origin_webhooks, origin_webhook_deliveries);
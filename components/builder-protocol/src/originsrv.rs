@@ -159,6 +159,7 @@ impl FromStr for OAuthProvider {
             "bitbucket" => Ok(OAuthProvider::Bitbucket),
             "okta" => Ok(OAuthProvider::Okta),
             "chef-automate" => Ok(OAuthProvider::ChefAutomate),
+            "oidc" => Ok(OAuthProvider::Oidc),
             "none" => Ok(OAuthProvider::None),
             "" => Ok(OAuthProvider::None),
             _ => Err(Error::BadOAuthProvider),
@@ -171,6 +172,8 @@ impl From<AccessToken> for Session {
         let mut session = Session::new();
         session.set_id(value.account_id());
         session.set_flags(value.flags());
+        session.set_expires(value.expires());
+        session.scope = value.scope;
         session
     }
 }
@@ -65,7 +65,13 @@ export function exchangeOAuthCode(code: string, state: string) {
 
     dispatch(signingIn(true));
 
-    fetch(`${authenticateEndpoint}/${code}`).then(response => {
+    // OpenID Connect providers are sent a PKCE code challenge and a nonce, which the API
+    // checks the code and ID token against
+    const challenge = getState().oauth.provider.type === 'oidc' ?
+      `?code_verifier=${encodeURIComponent(Browser.getCookie('oauthCodeVerifier'))}` +
+      `&nonce=${encodeURIComponent(Browser.getCookie('oauthNonce'))}` : '';
+
+    fetch(`${authenticateEndpoint}/${code}${challenge}`).then(response => {
       return response.json();
     })
       .then(data => {
@@ -109,6 +115,8 @@ export function loadOAuthState() {
 export function removeSession() {
   return dispatch => {
     Browser.removeCookie('oauthState');
+    Browser.removeCookie('oauthCodeVerifier');
+    Browser.removeCookie('oauthNonce');
     Browser.removeCookie('oauthToken');
     Browser.removeCookie('bldrSessionToken');
   };
@@ -127,19 +135,59 @@ export function setOAuthState() {
 export function loadOAuthProvider() {
   return (dispatch, getState) => {
     dispatch(setOAuthState());
-    dispatch(setOAuthProvider(
-      OAuthProvider.fromConfig(
-        config.oauth_provider,
-        config.oauth_client_id,
-        config.oauth_authorize_url,
-        config.oauth_redirect_url,
-        config.oauth_signup_url,
-        getState().oauth.state
-      )
-    ));
+
+    const fromConfig = (nonce?: string, codeChallenge?: string) => OAuthProvider.fromConfig(
+      config.oauth_provider,
+      config.oauth_client_id,
+      config.oauth_authorize_url,
+      config.oauth_redirect_url,
+      config.oauth_signup_url,
+      getState().oauth.state,
+      nonce,
+      codeChallenge
+    );
+
+    if (config.oauth_provider === 'oidc') {
+      const nonce = oidcNonce();
+      pkceChallenge(pkceVerifier()).then(challenge => dispatch(setOAuthProvider(fromConfig(nonce, challenge))));
+    } else {
+      dispatch(setOAuthProvider(fromConfig()));
+    }
   };
 }
 
+function pkceVerifier(): string {
+  let verifier = Browser.getCookie('oauthCodeVerifier');
+
+  if (!verifier) {
+    verifier = `${uuid()}-${uuid()}`;
+    Browser.setCookie('oauthCodeVerifier', verifier);
+  }
+
+  return verifier;
+}
+
+// The API refuses OpenID Connect sign ins whose ID token doesn't carry the nonce it was sent
+function oidcNonce(): string {
+  let nonce = Browser.getCookie('oauthNonce');
+
+  if (!nonce) {
+    nonce = uuid();
+    Browser.setCookie('oauthNonce', nonce);
+  }
+
+  return nonce;
+}
+
+function pkceChallenge(verifier: string): Promise<string> {
+  return window.crypto.subtle.digest('SHA-256', new TextEncoder().encode(verifier)).then(digest => {
+    return btoa(String.fromCharCode(...Array.from(new Uint8Array(digest))))
+      .replace(/\+/g, '-')
+      .replace(/\//g, '_')
+      .replace(/=+$/, '');
+  });
+}
+
 function setOAuthProvider(payload) {
   return {
     type: SET_OAUTH_PROVIDER,
@@ -495,7 +495,7 @@ export class BuilderApiClient {
         .then(response => {
           if (response.ok) {
             response.json().then(data => {
-              resolve(data['members']);
+              resolve(data['members'].map(member => member.name));
             });
           } else {
             reject(new Error(response.statusText));
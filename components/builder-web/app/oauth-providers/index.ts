@@ -6,6 +6,7 @@ enum OAuthProviderType {
   GitLab = 'gitlab',
   Bitbucket = 'bitbucket',
   Okta = 'okta',
+  Oidc = 'oidc',
 }
 
 export abstract class OAuthProvider {
@@ -43,7 +44,8 @@ export abstract class OAuthProvider {
     }
   }
 
-  static fromConfig(type: string, clientID: string, authorizeUrl: string, redirectUrl: string, signupUrl: string, state: string): OAuthProvider {
+  static fromConfig(type: string, clientID: string, authorizeUrl: string, redirectUrl: string, signupUrl: string, state: string,
+    nonce?: string, codeChallenge?: string): OAuthProvider {
     switch (type) {
       case OAuthProviderType.ActiveDirectory:
         return new ActiveDirectoryProvider(clientID, authorizeUrl, redirectUrl, signupUrl, state);
@@ -59,6 +61,8 @@ export abstract class OAuthProvider {
         return new BitbucketProvider(clientID, authorizeUrl, redirectUrl, signupUrl);
       case OAuthProviderType.Okta:
         return new OktaProvider(clientID, authorizeUrl, redirectUrl, signupUrl, state);
+      case OAuthProviderType.Oidc:
+        return new OidcProvider(clientID, authorizeUrl, redirectUrl, signupUrl, state, nonce, codeChallenge);
       case undefined:
       case '':
         console.error(`Please configure Builder with an OAuth provider. Supported providers are ${OAuthProvider.providers}.`);
@@ -222,3 +226,29 @@ class OktaProvider extends OAuthProvider {
     );
   }
 }
+
+class OidcProvider extends OAuthProvider {
+  name: string = 'OpenID Connect';
+
+  constructor(clientID: string, authorizeUrl: string, redirectUrl: string, signupUrl: string, state: string,
+    nonce: string, codeChallenge: string) {
+    super(
+      OAuthProviderType.Oidc,
+      clientID,
+      authorizeUrl,
+      redirectUrl,
+      signupUrl,
+      true,
+      {
+        client_id: clientID,
+        redirect_uri: redirectUrl,
+        response_type: 'code',
+        state: state,
+        scope: 'openid profile email',
+        nonce: nonce,
+        code_challenge: codeChallenge,
+        code_challenge_method: 'S256'
+      }
+    );
+  }
+}
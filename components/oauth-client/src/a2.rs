@@ -36,6 +36,8 @@ struct User {
     pub sub:                String,
     pub preferred_username: String,
     pub email:              Option<String>,
+    #[serde(default)]
+    pub groups:             Vec<String>,
 }
 
 impl A2 {
@@ -66,7 +68,8 @@ impl A2 {
 
             Ok(OAuth2User { id:       user.sub,
                             username: user.preferred_username,
-                            email:    user.email, })
+                            email:    user.email,
+                            groups:   user.groups, })
         } else {
             Err(Error::HttpResponse(status, body))
         }
@@ -65,7 +65,8 @@ impl ActiveDirectory {
 
             Ok(OAuth2User { id:       user.sub.to_string(),
                             username: user.sub,
-                            email:    None, })
+                            email:    None,
+                            groups:   Vec::new(), })
         } else {
             Err(Error::HttpResponse(status, body))
         }
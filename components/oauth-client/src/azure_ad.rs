@@ -65,7 +65,8 @@ impl AzureAD {
 
             Ok(OAuth2User { id:       user.sub,
                             username: user.upn,
-                            email:    None, })
+                            email:    None,
+                            groups:   Vec::new(), })
         } else {
             Err(Error::HttpResponse(status, body))
         }
@@ -77,7 +77,8 @@ impl Bitbucket {
 
             Ok(OAuth2User { id:       actual_uname.clone(),
                             username: actual_uname,
-                            email:    None, })
+                            email:    None,
+                            groups:   Vec::new(), })
         } else {
             Err(Error::HttpResponse(status, body))
         }
@@ -21,6 +21,7 @@ use crate::{a2::A2,
             github::GitHub,
             gitlab::GitLab,
             metrics::Counter,
+            oidc::Oidc,
             okta::Okta,
             types::*};
 use builder_core::{http_client::{HttpClient,
@@ -39,7 +40,12 @@ impl OAuth2Client {
         let header_values = vec![USER_AGENT_BLDR.clone()];
         let headers = header_values.into_iter().collect::<HeaderMap<_>>();
 
-        let client = HttpClient::new(&config.token_url, headers)?;
+        // OpenID Connect providers name their token endpoint in their discovery document
+        let url = match &config.provider[..] {
+            "oidc" => &config.issuer_url,
+            _ => &config.token_url,
+        };
+        let client = HttpClient::new(url, headers)?;
 
         let provider: Box<dyn OAuth2Provider> = match &config.provider[..] {
             "active-directory" => Box::new(ActiveDirectory),
@@ -49,6 +55,7 @@ impl OAuth2Client {
             "bitbucket" => Box::new(Bitbucket),
             "okta" => Box::new(Okta),
             "chef-automate" => Box::new(A2),
+            "oidc" => Box::new(Oidc::default()),
             _ => panic!("Unknown OAuth provider: {}", config.provider),
         };
 
@@ -64,4 +71,15 @@ impl OAuth2Client {
             .authenticate(&self.config, &self.inner, code)
             .await
     }
+
+    pub async fn authenticate_with_challenge(&self,
+                                             code: &str,
+                                             challenge: &AuthChallenge)
+                                             -> Result<(String, OAuth2User)> {
+        Counter::Authenticate(self.config.provider.clone()).increment();
+        debug!("Authenticate called, config: {:?}", self.config);
+        self.provider
+            .authenticate_with_challenge(&self.config, &self.inner, code, challenge)
+            .await
+    }
 }
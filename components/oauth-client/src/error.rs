@@ -19,6 +19,7 @@ pub enum Error {
     BuilderCore(builder_core::Error),
     HttpClient(reqwest::Error),
     HttpResponse(reqwest::StatusCode, String),
+    MissingClaim(String),
     Serialization(serde_json::Error),
 }
 
@@ -33,6 +34,9 @@ impl fmt::Display for Error {
                 format!("Received a non-200 response, status={}, response={}",
                         code, response)
             }
+            Error::MissingClaim(ref claim) => {
+                format!("The identity provider did not supply the {} claim", claim)
+            }
             Error::Serialization(ref e) => format!("{}", e),
         };
         write!(f, "{}", msg)
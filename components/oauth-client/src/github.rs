@@ -66,7 +66,8 @@ impl GitHub {
 
             Ok(OAuth2User { id:       user.id.to_string(),
                             username: user.login,
-                            email:    user.email, })
+                            email:    user.email,
+                            groups:   Vec::new(), })
         } else {
             Err(Error::HttpResponse(status, body))
         }
@@ -35,6 +35,8 @@ struct User {
     pub sub:      String,
     pub nickname: String,
     pub email:    Option<String>,
+    #[serde(default)]
+    pub groups:   Vec<String>,
 }
 
 impl GitLab {
@@ -65,7 +67,8 @@ impl GitLab {
 
             Ok(OAuth2User { id:       user.sub,
                             username: user.nickname,
-                            email:    user.email, })
+                            email:    user.email,
+                            groups:   user.groups, })
         } else {
             Err(Error::HttpResponse(status, body))
         }
@@ -28,5 +28,6 @@ pub mod error;
 pub mod github;
 pub mod gitlab;
 pub mod metrics;
+pub mod oidc;
 pub mod okta;
 pub mod types;
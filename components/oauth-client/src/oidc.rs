@@ -0,0 +1,417 @@
+// Copyright (c) 2026 Chef Software Inc. and/or applicable contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Any OpenID Connect provider, such as Keycloak, Dex or Authentik.
+//!
+//! The provider's endpoints and signing keys are read from its discovery document, and users
+//! are identified by the claims of the ID token it issues, as named in the config.
+
+use std::{sync::Mutex,
+          time::{Duration,
+                 Instant}};
+
+use reqwest::{header::HeaderMap,
+              Body};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use url::form_urlencoded;
+
+use builder_core::{error::Error as CoreError,
+                   http_client::{HttpClient,
+                                 ACCEPT_APPLICATION_JSON,
+                                 CONTENT_TYPE_FORM_URL_ENCODED},
+                   jwt::{self,
+                         Claims,
+                         JwkSet,
+                         Validation}};
+
+use crate::{config::OAuth2Cfg,
+            error::{Error,
+                    Result},
+            types::*};
+use async_trait::async_trait;
+
+const DISCOVERY_PATH: &str = ".well-known/openid-configuration";
+
+/// How long the discovery document and signing keys are kept before being read again. Keys
+/// are also read again as soon as a token is signed with one that isn't known yet, though at
+/// most once a minute, so tokens naming made up keys can't be used to flood the provider.
+const METADATA_TTL: Duration = Duration::from_secs(60 * 60);
+const METADATA_MIN_REFETCH: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+pub struct Oidc {
+    metadata: Mutex<Option<Metadata>>,
+}
+
+#[derive(Clone, Deserialize)]
+struct Discovery {
+    pub issuer:            String,
+    pub token_endpoint:    String,
+    pub jwks_uri:          String,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+}
+
+#[derive(Clone)]
+struct Metadata {
+    discovery:  Discovery,
+    keys:       JwkSet,
+    fetched_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct AuthOk {
+    pub access_token: String,
+    pub id_token:     String,
+}
+
+impl Oidc {
+    async fn metadata(&self,
+                      config: &OAuth2Cfg,
+                      client: &HttpClient,
+                      id_token: Option<&str>)
+                      -> Result<Metadata> {
+        let cached = self.metadata
+                         .lock()
+                         .expect("OIDC metadata lock poisoned")
+                         .clone();
+        if let Some(metadata) = cached {
+            let has_key = id_token.map_or(true, |token| metadata.keys.has_key_for(token));
+            if reuse_metadata(metadata.fetched_at.elapsed(), has_key)? {
+                return Ok(metadata);
+            }
+        }
+
+        let url = format!("{}/{}",
+                          config.issuer_url.trim_end_matches('/'),
+                          DISCOVERY_PATH);
+        let discovery: Discovery = get_json(client, &url, None).await?;
+        check_issuer(&discovery, config)?;
+
+        let keys: JwkSet = get_json(client, &discovery.jwks_uri, None).await?;
+
+        let metadata = Metadata { discovery,
+                                  keys,
+                                  fetched_at: Instant::now() };
+        *self.metadata.lock().expect("OIDC metadata lock poisoned") = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn user(&self,
+                  config: &OAuth2Cfg,
+                  client: &HttpClient,
+                  metadata: &Metadata,
+                  token: &str,
+                  mut claims: Claims)
+                  -> Result<OAuth2User> {
+        let claim_names = [&config.id_claim,
+                           &config.username_claim,
+                           &config.email_claim,
+                           &config.groups_claim];
+
+        // Providers may leave some claims out of the ID token and only hand them out from
+        // their userinfo endpoint
+        if claim_names.iter().any(|name| !claims.contains_key(name.as_str())) {
+            if let Some(ref url) = metadata.discovery.userinfo_endpoint {
+                let userinfo: Claims = get_json(client, url, Some(token)).await?;
+                merge_userinfo(&mut claims, userinfo)?;
+            }
+        }
+
+        // Groups are a list, though some providers give a single group as a string
+        let groups = match claims.get(&config.groups_claim) {
+            Some(Value::Array(groups)) => {
+                groups.iter()
+                      .filter_map(|group| group.as_str().map(str::to_string))
+                      .collect()
+            }
+            Some(Value::String(group)) => vec![group.to_string()],
+            _ => Vec::new(),
+        };
+
+        Ok(OAuth2User { id: required_claim(&claims, &config.id_claim)?,
+                        username: required_claim(&claims, &config.username_claim)?,
+                        email: jwt::claim_string(&claims, &config.email_claim),
+                        groups })
+    }
+}
+
+#[async_trait]
+impl OAuth2Provider for Oidc {
+    async fn authenticate(&self,
+                          config: &OAuth2Cfg,
+                          client: &HttpClient,
+                          code: &str)
+                          -> Result<(String, OAuth2User)> {
+        self.authenticate_with_challenge(config, client, code, &AuthChallenge::default())
+            .await
+    }
+
+    async fn authenticate_with_challenge(&self,
+                                         config: &OAuth2Cfg,
+                                         client: &HttpClient,
+                                         code: &str,
+                                         challenge: &AuthChallenge)
+                                         -> Result<(String, OAuth2User)> {
+        // Every authorization request carries a nonce, so an ID token without one was not
+        // issued for this sign in
+        let nonce = match challenge.nonce {
+            Some(ref nonce) if !nonce.is_empty() => nonce,
+            _ => {
+                warn!("OIDC sign in attempted without a nonce");
+                return Err(Error::BuilderCore(CoreError::TokenInvalid));
+            }
+        };
+
+        let metadata = self.metadata(config, client, None).await?;
+        let body = token_request_body(config, code, challenge);
+
+        let header_values = vec![ACCEPT_APPLICATION_JSON.clone(),
+                                 CONTENT_TYPE_FORM_URL_ENCODED.clone(),];
+        let headers = header_values.into_iter().collect::<HeaderMap<_>>();
+
+        let body: Body = body.into();
+
+        let resp = client.post(&metadata.discovery.token_endpoint)
+                         .headers(headers)
+                         .body(body)
+                         .send()
+                         .await
+                         .map_err(Error::HttpClient)?;
+
+        let status = resp.status();
+        let body = resp.text().await.map_err(Error::HttpClient)?;
+        debug!("OIDC response status: {}", status);
+
+        let auth = if status.is_success() {
+            match serde_json::from_str::<AuthOk>(&body) {
+                Ok(msg) => msg,
+                Err(e) => return Err(Error::Serialization(e)),
+            }
+        } else {
+            return Err(Error::HttpResponse(status, body));
+        };
+
+        // Reloads the keys should the provider have rotated them since they were read
+        let metadata = self.metadata(config, client, Some(&auth.id_token)).await?;
+        let validation = Validation { issuer:   &metadata.discovery.issuer,
+                                      audience: &config.client_id, };
+        let claims = jwt::verify(&auth.id_token, &metadata.keys, &validation)?;
+
+        check_nonce(&claims, nonce)?;
+
+        let user = self.user(config, client, &metadata, &auth.access_token, claims)
+                       .await?;
+        Ok((auth.access_token, user))
+    }
+}
+
+// Whether the cached discovery document and keys can be used for a token. Keys are read again
+// for a token signed with an unknown key, unless they were read less than a minute ago.
+fn reuse_metadata(age: Duration, has_key: bool) -> Result<bool> {
+    if age >= METADATA_TTL {
+        return Ok(false);
+    }
+    if has_key {
+        return Ok(true);
+    }
+    if age < METADATA_MIN_REFETCH {
+        debug!("OIDC ID token is signed with an unknown key");
+        return Err(Error::BuilderCore(CoreError::TokenInvalid));
+    }
+    Ok(false)
+}
+
+// The discovery document has to be for the issuer it was read from, or anyone able to serve it
+// could name themselves the issuer of the tokens
+fn check_issuer(discovery: &Discovery, config: &OAuth2Cfg) -> Result<()> {
+    if discovery.issuer.trim_end_matches('/') != config.issuer_url.trim_end_matches('/') {
+        warn!("OIDC discovery document is for issuer {}, not {}",
+              discovery.issuer, config.issuer_url);
+        return Err(Error::BuilderCore(CoreError::TokenInvalid));
+    }
+    Ok(())
+}
+
+fn check_nonce(claims: &Claims, nonce: &str) -> Result<()> {
+    if jwt::claim_string(claims, "nonce").as_deref() != Some(nonce) {
+        warn!("OIDC ID token nonce does not match the authorization request's");
+        return Err(Error::BuilderCore(CoreError::TokenInvalid));
+    }
+    Ok(())
+}
+
+// Adds the claims of a userinfo response that the ID token left out. The response is only
+// about the user the ID token is for.
+fn merge_userinfo(claims: &mut Claims, userinfo: Claims) -> Result<()> {
+    if userinfo.get("sub").is_none() || userinfo.get("sub") != claims.get("sub") {
+        warn!("OIDC userinfo response is for another subject than the ID token");
+        return Err(Error::BuilderCore(CoreError::TokenInvalid));
+    }
+    for (name, value) in userinfo {
+        claims.entry(name).or_insert(value);
+    }
+    Ok(())
+}
+
+fn token_request_body(config: &OAuth2Cfg, code: &str, challenge: &AuthChallenge) -> String {
+    let mut body = form_urlencoded::Serializer::new(String::new());
+    body.append_pair("client_id", &config.client_id)
+        .append_pair("client_secret", &config.client_secret)
+        .append_pair("grant_type", "authorization_code")
+        .append_pair("code", code)
+        .append_pair("redirect_uri", &config.redirect_url);
+    if let Some(ref code_verifier) = challenge.code_verifier {
+        body.append_pair("code_verifier", code_verifier);
+    }
+    body.finish()
+}
+
+fn required_claim(claims: &Claims, name: &str) -> Result<String> {
+    jwt::claim_string(claims, name).ok_or_else(|| Error::MissingClaim(name.to_string()))
+}
+
+async fn get_json<T: DeserializeOwned>(client: &HttpClient,
+                                       url: &str,
+                                       token: Option<&str>)
+                                       -> Result<T> {
+    let header_values = vec![ACCEPT_APPLICATION_JSON.clone()];
+    let headers = header_values.into_iter().collect::<HeaderMap<_>>();
+
+    let mut req = client.get(url).headers(headers);
+    if let Some(token) = token {
+        req = req.bearer_auth(token);
+    }
+    let resp = req.send().await.map_err(Error::HttpClient)?;
+
+    let status = resp.status();
+    let body = resp.text().await.map_err(Error::HttpClient)?;
+    debug!("OIDC response from {}: {}", url, body);
+
+    if status.is_success() {
+        serde_json::from_str::<T>(&body).map_err(Error::Serialization)
+    } else {
+        Err(Error::HttpResponse(status, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> OAuth2Cfg {
+        OAuth2Cfg { provider: "oidc".to_string(),
+                    issuer_url: "https://id.example.com/realms/builder".to_string(),
+                    client_id: "builder".to_string(),
+                    client_secret: "s3cr&t=1".to_string(),
+                    redirect_url: "https://bldr.example.com/?signin=1".to_string(),
+                    ..OAuth2Cfg::default() }
+    }
+
+    fn discovery(issuer: &str) -> Discovery {
+        Discovery { issuer:            issuer.to_string(),
+                    token_endpoint:    format!("{}/token", issuer),
+                    jwks_uri:          format!("{}/certs", issuer),
+                    userinfo_endpoint: None, }
+    }
+
+    fn claims(value: Value) -> Claims { value.as_object().unwrap().clone() }
+
+    fn challenge(code_verifier: Option<&str>) -> AuthChallenge {
+        AuthChallenge { code_verifier: code_verifier.map(str::to_string),
+                        nonce:         Some("n-0S6_WzA2Mj".to_string()), }
+    }
+
+    #[test]
+    fn discovery_must_be_for_the_configured_issuer() {
+        for issuer in &["https://id.example.com/realms/builder",
+                        "https://id.example.com/realms/builder/"]
+        {
+            assert!(check_issuer(&discovery(issuer), &config()).is_ok());
+        }
+
+        for issuer in &["https://evil.example.com/realms/builder",
+                        "https://id.example.com/realms/other",
+                        "https://id.example.com/realms"]
+        {
+            assert!(check_issuer(&discovery(issuer), &config()).is_err(),
+                    "{} should be refused",
+                    issuer);
+        }
+    }
+
+    #[test]
+    fn id_token_must_carry_the_nonce() {
+        assert!(check_nonce(&claims(json!({ "sub": "1", "nonce": "n-0S6_WzA2Mj" })),
+                            "n-0S6_WzA2Mj").is_ok());
+        assert!(check_nonce(&claims(json!({ "sub": "1", "nonce": "other" })), "n-0S6_WzA2Mj")
+                .is_err());
+        assert!(check_nonce(&claims(json!({ "sub": "1" })), "n-0S6_WzA2Mj").is_err());
+        assert!(check_nonce(&claims(json!({ "sub": "1", "nonce": null })), "n-0S6_WzA2Mj")
+                .is_err());
+    }
+
+    #[test]
+    fn token_request_passes_the_pkce_verifier() {
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        let body = token_request_body(&config(), "abc", &challenge(Some(verifier)));
+        let pairs: Vec<(String, String)> =
+            form_urlencoded::parse(body.as_bytes()).into_owned().collect();
+        assert!(pairs.contains(&("code_verifier".to_string(), verifier.to_string())));
+
+        let body = token_request_body(&config(), "abc", &challenge(None));
+        assert!(!body.contains("code_verifier"));
+    }
+
+    #[test]
+    fn token_request_is_form_encoded() {
+        let body = token_request_body(&config(), "a+b/c=d&e", &challenge(Some("v&w")));
+        assert_eq!(body,
+                   "client_id=builder&client_secret=s3cr%26t%3D1&grant_type=authorization_code&\
+                    code=a%2Bb%2Fc%3Dd%26e&redirect_uri=https%3A%2F%2Fbldr.example.com%2F%3F\
+                    signin%3D1&code_verifier=v%26w");
+    }
+
+    #[test]
+    fn userinfo_must_be_for_the_same_subject() {
+        let mut id_claims = claims(json!({ "sub": "1", "preferred_username": "bobo" }));
+        let userinfo = claims(json!({ "sub": "2", "email": "mallory@example.com" }));
+        assert!(merge_userinfo(&mut id_claims, userinfo).is_err());
+        assert!(!id_claims.contains_key("email"));
+
+        let userinfo = claims(json!({ "email": "mallory@example.com" }));
+        assert!(merge_userinfo(&mut id_claims, userinfo).is_err());
+    }
+
+    #[test]
+    fn userinfo_fills_in_missing_claims() {
+        let mut id_claims = claims(json!({ "sub": "1", "preferred_username": "bobo" }));
+        let userinfo = claims(json!({ "sub": "1",
+                                      "preferred_username": "someone-else",
+                                      "email": "bobo@example.com" }));
+        assert!(merge_userinfo(&mut id_claims, userinfo).is_ok());
+        assert_eq!(id_claims["preferred_username"], "bobo");
+        assert_eq!(id_claims["email"], "bobo@example.com");
+    }
+
+    #[test]
+    fn unknown_keys_are_refetched_at_most_once_a_minute() {
+        assert!(reuse_metadata(Duration::from_secs(30), true).unwrap());
+        assert!(reuse_metadata(Duration::from_secs(30), false).is_err());
+        assert!(!reuse_metadata(Duration::from_secs(90), false).unwrap());
+        assert!(!reuse_metadata(METADATA_TTL, true).unwrap());
+    }
+}
@@ -37,6 +37,8 @@ struct User {
     pub sub:                String,
     pub preferred_username: String,
     pub email:              Option<String>,
+    #[serde(default)]
+    pub groups:             Vec<String>,
 }
 
 impl Okta {
@@ -67,7 +69,8 @@ impl Okta {
 
             Ok(OAuth2User { id:       user.sub,
                             username: user.preferred_username,
-                            email:    user.email, })
+                            email:    user.email,
+                            groups:   user.groups, })
         } else {
             Err(Error::HttpResponse(status, body))
         }
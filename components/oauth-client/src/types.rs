@@ -21,6 +21,20 @@ pub struct OAuth2User {
     pub id:       String,
     pub username: String,
     pub email:    Option<String>,
+    /// Groups the provider says the user belongs to, for providers that say
+    pub groups:   Vec<String>,
+}
+
+/// Values a client generated when it sent the user to the provider, which the provider's
+/// response is checked against.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AuthChallenge {
+    /// PKCE code verifier the authorization request's code challenge was derived from
+    #[serde(default)]
+    pub code_verifier: Option<String>,
+    /// Nonce the ID token has to carry
+    #[serde(default)]
+    pub nonce:         Option<String>,
 }
 
 #[async_trait]
@@ -30,4 +44,15 @@ pub trait OAuth2Provider: Sync + Send {
                           client: &HttpClient,
                           code: &str)
                           -> Result<(String, OAuth2User)>;
+
+    /// Authenticates a user whose client sent a PKCE code challenge or an ID token nonce along
+    /// with the authorization request. Providers that support neither ignore the challenge.
+    async fn authenticate_with_challenge(&self,
+                                         config: &OAuth2Cfg,
+                                         client: &HttpClient,
+                                         code: &str,
+                                         _challenge: &AuthChallenge)
+                                         -> Result<(String, OAuth2User)> {
+        self.authenticate(config, client, code).await
+    }
 }
@@ -32,6 +32,7 @@ log_level = "debug,tokio_core=error,tokio_reactor=error,zmq=error,hyper=error"
 
 [api]
 allowed_users_for_origin_create = ['bobo', 'mystique', 'wesker', 'lkennedy']
+admin_users = ['bobo']
 features_enabled = ""
 
 [http]
@@ -0,0 +1,315 @@
+const expect = require("chai").expect;
+const supertest = require("supertest");
+const request = supertest("http://localhost:9636/v1");
+
+describe("Admin API", function () {
+  describe("Accounts", function () {
+    it("requires a Builder administrator", function (done) {
+      request
+        .get("/admin/accounts")
+        .set("Authorization", global.mystiqueBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("searches accounts by name", function (done) {
+      request
+        .get("/admin/accounts?query=mystique")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.total_count).to.equal(1);
+          expect(res.body.data[0].name).to.equal("mystique");
+          expect(res.body.data[0].disabled).to.equal(false);
+          done(err);
+        });
+    });
+    it("does not disable the requester's own account", function (done) {
+      request
+        .post("/admin/accounts/bobo/disable")
+        .set("Authorization", global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("requires a Builder administrator to disable accounts", function (done) {
+      request
+        .post("/admin/accounts/wesker/disable")
+        .set("Authorization", global.mystiqueBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("returns 404 when disabling accounts that do not exist", function (done) {
+      request
+        .post("/admin/accounts/nobody/disable")
+        .set("Authorization", global.boboBearer)
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("disables an account", function (done) {
+      request
+        .post("/admin/accounts/wesker/disable")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("shows the account as disabled", function (done) {
+      request
+        .get("/admin/accounts/wesker")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.account.name).to.equal("wesker");
+          expect(res.body.account.disabled).to.equal(true);
+          done(err);
+        });
+    });
+    it("disables an account only once", function (done) {
+      request
+        .post("/admin/accounts/wesker/disable")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("does not transfer origins to disabled accounts", function (done) {
+      request
+        .post("/admin/origins/xmen/transfer/wesker")
+        .set("Authorization", global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal("Origins can only be transferred to enabled user accounts");
+          done(err);
+        });
+    });
+    it("refuses disabled accounts", function (done) {
+      request
+        .get("/profile")
+        .set("Authorization", global.weskerBearer)
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("enables the account again", function (done) {
+      request
+        .post("/admin/accounts/wesker/enable")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("lets enabled accounts back in", function (done) {
+      request
+        .get("/profile")
+        .set("Authorization", global.weskerBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.name).to.equal("wesker");
+          done(err);
+        });
+    });
+    it("shows the account as enabled", function (done) {
+      request
+        .get("/admin/accounts/wesker")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.account.disabled).to.equal(false);
+          done(err);
+        });
+    });
+  });
+
+  describe("Origin transfers", function () {
+    before(function (done) {
+      request
+        .post("/depot/origins")
+        .set("Authorization", global.mystiqueBearer)
+        .send({ name: "brotherhood" })
+        .expect(201)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("requires a Builder administrator", function (done) {
+      request
+        .post("/admin/origins/brotherhood/transfer/wesker")
+        .set("Authorization", global.mystiqueBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("returns 404 for origins that do not exist", function (done) {
+      request
+        .post("/admin/origins/nope/transfer/wesker")
+        .set("Authorization", global.boboBearer)
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("returns 404 for accounts that do not exist", function (done) {
+      request
+        .post("/admin/origins/brotherhood/transfer/nobody")
+        .set("Authorization", global.boboBearer)
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("does not transfer an origin to its owner", function (done) {
+      request
+        .post("/admin/origins/brotherhood/transfer/mystique")
+        .set("Authorization", global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal("The account already owns the origin");
+          done(err);
+        });
+    });
+    it("transfers an origin to an account that is not a member", function (done) {
+      request
+        .post("/admin/origins/brotherhood/transfer/wesker")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("makes the recipient the owner", function (done) {
+      request
+        .get("/depot/origins/brotherhood")
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.owner_account).to.equal("wesker");
+          done(err);
+        });
+    });
+    it("gives the recipient the owner role at once", function (done) {
+      request
+        .get("/depot/origins/brotherhood/users/wesker/role")
+        .set("Authorization", global.weskerBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.role).to.equal("owner");
+          done(err);
+        });
+    });
+    it("keeps the previous owner as a maintainer", function (done) {
+      request
+        .get("/depot/origins/brotherhood/users/mystique/role")
+        .set("Authorization", global.weskerBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.role).to.equal("maintainer");
+          done(err);
+        });
+    });
+    it("records the transfer in the audit log", function (done) {
+      request
+        .get("/depot/origins/brotherhood/audit")
+        .query({ action: "owner_transfer" })
+        .set("Authorization", global.weskerBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.data[0].actor_name).to.equal("bobo");
+          expect(res.body.data[0].before).to.deep.equal({ owner: "mystique" });
+          expect(res.body.data[0].after).to.deep.equal({ owner: "wesker" });
+          done(err);
+        });
+    });
+  });
+
+  describe("Origins", function () {
+    it("requires a Builder administrator to hide origins", function (done) {
+      request
+        .post("/admin/origins/xmen/hide")
+        .set("Authorization", global.mystiqueBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("hides an origin", function (done) {
+      request
+        .post("/admin/origins/xmen/hide")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("marks the origin as hidden", function (done) {
+      request
+        .get("/depot/origins/xmen")
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.hidden).to.equal(true);
+          done(err);
+        });
+    });
+    it("leaves the hidden origin's packages out of searches", function (done) {
+      request
+        .get("/depot/pkgs/search/xmen%2Ftestapp")
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.total_count).to.equal(0);
+          done(err);
+        });
+    });
+    it("unhides the origin", function (done) {
+      request
+        .post("/admin/origins/xmen/unhide")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("returns the unhidden origin's packages in searches again", function (done) {
+      request
+        .get("/depot/pkgs/search/xmen%2Ftestapp")
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.total_count).to.be.above(0);
+          expect(res.body.data[0].origin).to.equal("xmen");
+          done(err);
+        });
+    });
+    it("returns 404 for origins that do not exist", function (done) {
+      request
+        .post("/admin/origins/nope/hide")
+        .set("Authorization", global.boboBearer)
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+
+  describe("Stats", function () {
+    it("counts what Builder holds", function (done) {
+      request
+        .get("/admin/stats")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.accounts).to.be.above(0);
+          expect(res.body.origins).to.be.above(0);
+          done(err);
+        });
+    });
+  });
+});
@@ -10,10 +10,12 @@ require('./invitations.js');
 require('./settings.js');
 require('./packages.js');
 require('./channels.js');
+require('./audit.js');
 require('./keys.js');
 require('./integrations.js');
 require('./profile.js');
 require('./ext.js');
 require('./misc.js');
 require('./roles.js');
+require('./admin.js');
 require('./etc.js');
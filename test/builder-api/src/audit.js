@@ -0,0 +1,104 @@
+const expect = require('chai').expect;
+const supertest = require('supertest');
+const request = supertest('http://localhost:9636/v1');
+const fs = require('fs');
+const { execSync } = require('child_process');
+
+const migration = __dirname + '/../../../components/builder-db/src/migrations/2026-10-28-000001_audit_events/up.sql';
+
+// The audit log can't be changed through the API, so the table itself is checked through psql,
+// the same way test.sh cleans up after the run
+function psql(sql) {
+  return execSync('psql builder -q -t -A -v ON_ERROR_STOP=1', { input: sql, stdio: 'pipe' })
+    .toString()
+    .trim();
+}
+
+function psqlError(sql) {
+  try {
+    psql(sql);
+  } catch (err) {
+    return err.stderr.toString();
+  }
+  return '';
+}
+
+describe('Audit log', function () {
+  it('requires the settings permission to list audit events', function (done) {
+    request.get('/depot/origins/neurosis/audit')
+      .set('Authorization', global.weskerBearer)
+      .expect(403)
+      .end(function (err, res) {
+        done(err);
+      });
+  });
+
+  it('lists the origin audit events', function (done) {
+    request.get('/depot/origins/neurosis/audit')
+      .query({ action: 'channel_freeze' })
+      .set('Authorization', global.boboBearer)
+      .expect(200)
+      .end(function (err, res) {
+        // Audit events outlive test runs, so only the most recent one is looked at
+        expect(res.body.total_count).to.be.at.least(1);
+        expect(res.body.data[0].origin).to.equal('neurosis');
+        expect(res.body.data[0].actor_name).to.equal('bobo');
+        expect(res.body.data[0].action).to.equal('channel_freeze');
+        expect(res.body.data[0].target).to.equal('bar');
+        expect(res.body.data[0].after).to.deep.equal({ 'reason': 'Release candidate under test' });
+        done(err);
+      });
+  });
+
+  it('filters audit events by actor', function (done) {
+    request.get('/depot/origins/neurosis/audit')
+      .query({ actor: 'mystique' })
+      .set('Authorization', global.boboBearer)
+      .expect(200)
+      .end(function (err, res) {
+        expect(res.body.total_count).to.equal(0);
+        expect(res.body.data).to.deep.equal([]);
+        done(err);
+      });
+  });
+
+  it('rejects updates to audit events', function () {
+    const error = psqlError("UPDATE audit_events SET actor_name = 'mallory' WHERE origin = 'neurosis';");
+    expect(error).to.include('audit_events is append-only');
+    expect(psql("SELECT count(*) FROM audit_events WHERE actor_name = 'mallory';")).to.equal('0');
+  });
+
+  it('rejects deletes of audit events', function () {
+    const before = psql("SELECT count(*) FROM audit_events WHERE origin = 'neurosis';");
+    const error = psqlError("DELETE FROM audit_events WHERE origin = 'neurosis';");
+    expect(error).to.include('audit_events is append-only');
+    expect(psql("SELECT count(*) FROM audit_events WHERE origin = 'neurosis';")).to.equal(before);
+  });
+
+  it('rejects truncating the audit log', function () {
+    expect(psqlError('TRUNCATE audit_events;')).to.include('audit_events is append-only');
+  });
+
+  it('moves the origin audit records over to the audit log', function () {
+    // Replays the migration's copy of audit_origin in a transaction that is rolled back
+    const sql = fs.readFileSync(migration).toString();
+    const backfill = sql.substring(sql.indexOf('INSERT INTO audit_events'));
+
+    const moved = psql(`
+      BEGIN;
+      INSERT INTO audit_origin (operation, origin, requester_id, requester_name, target_object, reason)
+        VALUES ('channel_freeze', 'audit-backfill', 42, 'bobo', 'stable', 'Release week'),
+               ('origin_create', 'audit-backfill', 42, 'bobo', 'audit-backfill', NULL),
+               (NULL, 'audit-backfill', 42, 'bobo', 'unknown', NULL);
+      ${backfill}
+      SELECT actor_id, actor_name, action, target, COALESCE(after->>'reason', '')
+        FROM audit_events WHERE origin = 'audit-backfill' ORDER BY action;
+      ROLLBACK;
+    `);
+
+    expect(moved.split('\n')).to.deep.equal([
+      '42|bobo|origin_create|audit-backfill|',
+      '42|bobo|channel_freeze|stable|Release week'
+    ]);
+  });
+});
@@ -992,4 +992,1304 @@ describe('Channels API', function () {
         });
     });
   });
+
+  describe('Channel policies', function () {
+    it('requires origin membership to view a channel policy', function (done) {
+      request.get('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.mystiqueBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('returns not found when the channel has no policy', function (done) {
+      request.get('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('requires origin membership to set a channel policy', function (done) {
+      request.put('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.mystiqueBearer)
+        .send({ 'block_demote': true })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('rejects policy roles lower than maintainer', function (done) {
+      request.put('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .send({ 'promote_role': 'member' })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Channel policy roles cannot be lower than maintainer');
+          done(err);
+        });
+    });
+
+    it('rejects a channel as its own source channel', function (done) {
+      request.put('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .send({ 'source_channel': 'bar' })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('A channel cannot be its own required source channel');
+          done(err);
+        });
+    });
+
+    it('sets the channel policy', function (done) {
+      request.put('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .send({ 'source_channel': 'stable', 'block_demote': true })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.channel).to.equal('bar');
+          expect(res.body.promote_role).to.equal('maintainer');
+          expect(res.body.demote_role).to.equal('maintainer');
+          expect(res.body.source_channel).to.equal('stable');
+          expect(res.body.block_demote).to.equal(true);
+          expect(res.body.require_approval).to.equal(false);
+          done(err);
+        });
+    });
+
+    it('returns the channel policy', function (done) {
+      request.get('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.source_channel).to.equal('stable');
+          expect(res.body.block_demote).to.equal(true);
+          done(err);
+        });
+    });
+
+    it('rejects promotion of packages that are not in the source channel', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.4/20171206004139/promote')
+        .set('Authorization', global.boboBearer)
+        .expect(403)
+        .end(function (err, res) {
+          expect(res.text).to.contain('only allows promotion from stable');
+          done(err);
+        });
+    });
+
+    it('allows promotion of packages that are in the source channel', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213/promote')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('rejects demotion when the policy blocks it', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213/demote')
+        .set('Authorization', global.boboBearer)
+        .expect(403)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Channel policy for bar does not allow packages to be demoted');
+          done(err);
+        });
+    });
+
+    it('replaces the channel policy', function (done) {
+      request.put('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .send({ 'require_approval': true })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.source_channel).to.be.null;
+          expect(res.body.block_demote).to.equal(false);
+          expect(res.body.require_approval).to.equal(true);
+          done(err);
+        });
+    });
+
+    it('rejects direct promotion when the policy requires approval', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213/promote')
+        .set('Authorization', global.boboBearer)
+        .expect(403)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Channel policy for bar requires an approved promotion request');
+          done(err);
+        });
+    });
+
+    it('deletes the channel policy', function (done) {
+      request.delete('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('returns not found when deleting a policy that does not exist', function (done) {
+      request.delete('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('allows demotion once the policy is deleted', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213/demote')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+
+  describe('Promotion requests', function () {
+    it('makes wesker a member of neurosis', function (done) {
+      request.put('/depot/origins/neurosis/users/wesker/role')
+        .query({ role: 'member' })
+        .set('Authorization', global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('requires approval for promotions to bar', function (done) {
+      request.put('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .send({ 'require_approval': true })
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('requires origin membership to request a promotion', function (done) {
+      request.post('/depot/channels/neurosis/bar/promotion_requests')
+        .set('Authorization', global.mystiqueBearer)
+        .send({ 'idents': ['neurosis/testapp/0.1.3/20171205003213'] })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('rejects a promotion request without packages', function (done) {
+      request.post('/depot/channels/neurosis/bar/promotion_requests')
+        .set('Authorization', global.weskerBearer)
+        .send({ 'idents': [] })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('A promotion request requires at least one package');
+          done(err);
+        });
+    });
+
+    it('rejects a promotion request for packages of another origin', function (done) {
+      request.post('/depot/channels/neurosis/bar/promotion_requests')
+        .set('Authorization', global.weskerBearer)
+        .send({ 'idents': ['core/testapp/0.1.3/20171205003213'] })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal("Invalid package identifier 'core/testapp/0.1.3/20171205003213'");
+          done(err);
+        });
+    });
+
+    it('creates a pending promotion request', function (done) {
+      request.post('/depot/channels/neurosis/bar/promotion_requests')
+        .set('Authorization', global.weskerBearer)
+        .send({ 'idents': ['neurosis/testapp/0.1.3/20171205003213'] })
+        .expect(201)
+        .end(function (err, res) {
+          expect(res.body.state).to.equal('pending');
+          expect(res.body.requester_name).to.equal('wesker');
+          expect(res.body.package_idents.length).to.equal(1);
+          expect(res.body.package_idents[0].name).to.equal('testapp');
+          expect(res.body.package_idents[0].release).to.equal('20171205003213');
+          global.promotionRequestWesker = res.body;
+          done(err);
+        });
+    });
+
+    it('creates a promotion request for bobo', function (done) {
+      request.post('/depot/channels/neurosis/bar/promotion_requests')
+        .set('Authorization', global.boboBearer)
+        .send({ 'idents': ['neurosis/testapp/0.1.3/20171205003213'] })
+        .expect(201)
+        .end(function (err, res) {
+          global.promotionRequestBobo = res.body;
+          done(err);
+        });
+    });
+
+    it('lists pending promotion requests', function (done) {
+      request.get('/depot/channels/neurosis/bar/promotion_requests')
+        .query({ state: 'pending' })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.length).to.equal(2);
+          done(err);
+        });
+    });
+
+    it('requires the promote role to review a promotion request', function (done) {
+      request.put(`/depot/channels/neurosis/bar/promotion_requests/${global.promotionRequestBobo.id}/approve`)
+        .set('Authorization', global.weskerBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('does not let the requester review their own promotion request', function (done) {
+      request.put(`/depot/channels/neurosis/bar/promotion_requests/${global.promotionRequestBobo.id}/approve`)
+        .set('Authorization', global.boboBearer)
+        .expect(403)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Promotion requests must be reviewed by someone other than the requester');
+          done(err);
+        });
+    });
+
+    it('promotes the packages when the request is approved', function (done) {
+      request.put(`/depot/channels/neurosis/bar/promotion_requests/${global.promotionRequestWesker.id}/approve`)
+        .set('Authorization', global.boboBearer)
+        .send({ 'comment': 'ship it' })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.state).to.equal('approved');
+          expect(res.body.reviewer_name).to.equal('bobo');
+          expect(res.body.review_comment).to.equal('ship it');
+          done(err);
+        });
+    });
+
+    it('finds the approved package in the channel', function (done) {
+      request.get('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213')
+        .type('application/json')
+        .accept('application/json')
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.ident.release).to.equal('20171205003213');
+          done(err);
+        });
+    });
+
+    it('does not review a promotion request twice', function (done) {
+      request.put(`/depot/channels/neurosis/bar/promotion_requests/${global.promotionRequestWesker.id}/reject`)
+        .set('Authorization', global.boboBearer)
+        .expect(409)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('creates another promotion request', function (done) {
+      request.post('/depot/channels/neurosis/bar/promotion_requests')
+        .set('Authorization', global.weskerBearer)
+        .send({ 'idents': ['neurosis/testapp/0.1.4/20171206004139'] })
+        .expect(201)
+        .end(function (err, res) {
+          global.promotionRequestRejected = res.body;
+          done(err);
+        });
+    });
+
+    it('rejects a promotion request', function (done) {
+      request.put(`/depot/channels/neurosis/bar/promotion_requests/${global.promotionRequestRejected.id}/reject`)
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.state).to.equal('rejected');
+          done(err);
+        });
+    });
+
+    it('does not promote the packages of a rejected request', function (done) {
+      request.get('/depot/channels/neurosis/bar/pkgs/testapp/0.1.4/20171206004139')
+        .type('application/json')
+        .accept('application/json')
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('returns a promotion request', function (done) {
+      request.get(`/depot/channels/neurosis/bar/promotion_requests/${global.promotionRequestWesker.id}`)
+        .set('Authorization', global.weskerBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.state).to.equal('approved');
+          expect(res.body.reviewer_name).to.equal('bobo');
+          done(err);
+        });
+    });
+
+    it('removes the channel policy', function (done) {
+      request.delete('/depot/channels/neurosis/bar/policy')
+        .set('Authorization', global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('demotes the approved package', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213/demote')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+
+  describe('Channel snapshots', function () {
+    it('requires the channels permission to take a snapshot', function (done) {
+      request.post('/depot/channels/neurosis/bar/snapshots/before')
+        .set('Authorization', global.weskerBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('rejects a snapshot without a name', function (done) {
+      request.post('/depot/channels/neurosis/bar/snapshots/%20')
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Snapshot name cannot be empty');
+          done(err);
+        });
+    });
+
+    it('takes a snapshot of the channel', function (done) {
+      request.post('/depot/channels/neurosis/bar/snapshots/before')
+        .set('Authorization', global.boboBearer)
+        .expect(201)
+        .end(function (err, res) {
+          expect(res.body.name).to.equal('before');
+          expect(res.body.channel).to.equal('bar');
+          expect(res.body.owner_id).to.equal(global.sessionBobo.id);
+          done(err);
+        });
+    });
+
+    it('refuses to reuse a snapshot name', function (done) {
+      request.post('/depot/channels/neurosis/bar/snapshots/before')
+        .set('Authorization', global.boboBearer)
+        .expect(409)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('records the packages in the snapshot', function (done) {
+      request.get('/depot/channels/neurosis/bar/snapshots/before')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.name).to.equal('before');
+          expect(res.body.packages.length).to.equal(1);
+          expect(res.body.packages[0].ident.release).to.equal('20171206004121');
+          done(err);
+        });
+    });
+
+    it('promotes a package after the snapshot', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213/promote')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('diffs the snapshot against the live channel', function (done) {
+      request.get('/depot/channels/neurosis/bar/snapshots/before/diff')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.from).to.equal('before');
+          expect(res.body.to).to.be.null;
+          expect(res.body.added.length).to.equal(1);
+          expect(res.body.added[0].ident.release).to.equal('20171205003213');
+          expect(res.body.removed.length).to.equal(0);
+          done(err);
+        });
+    });
+
+    it('takes a second snapshot', function (done) {
+      request.post('/depot/channels/neurosis/bar/snapshots/after')
+        .set('Authorization', global.boboBearer)
+        .expect(201)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('lists the snapshots of the channel', function (done) {
+      request.get('/depot/channels/neurosis/bar/snapshots')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.map(snapshot => snapshot.name)).to.have.members(['before', 'after']);
+          done(err);
+        });
+    });
+
+    it('diffs two snapshots', function (done) {
+      request.get('/depot/channels/neurosis/bar/snapshots/after/diff')
+        .query({ against: 'before' })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.from).to.equal('after');
+          expect(res.body.to).to.equal('before');
+          expect(res.body.added.length).to.equal(0);
+          expect(res.body.removed.length).to.equal(1);
+          expect(res.body.removed[0].ident.release).to.equal('20171205003213');
+          done(err);
+        });
+    });
+
+    it('requires origin membership to restore a snapshot', function (done) {
+      request.put('/depot/channels/neurosis/bar/snapshots/before/restore')
+        .set('Authorization', global.mystiqueBearer)
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('restores the channel to the snapshot', function (done) {
+      request.put('/depot/channels/neurosis/bar/snapshots/before/restore')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.to).to.equal('before');
+          expect(res.body.added.length).to.equal(0);
+          expect(res.body.removed.length).to.equal(1);
+          expect(res.body.removed[0].ident.release).to.equal('20171205003213');
+          done(err);
+        });
+    });
+
+    it('no longer finds packages promoted after the snapshot', function (done) {
+      request.get('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213')
+        .type('application/json')
+        .accept('application/json')
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('finds nothing to change when restoring the same snapshot again', function (done) {
+      request.put('/depot/channels/neurosis/bar/snapshots/before/restore')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.added.length).to.equal(0);
+          expect(res.body.removed.length).to.equal(0);
+          done(err);
+        });
+    });
+  });
+
+  describe('Channel history', function () {
+    let beforePromotion;
+    let afterPromotion;
+
+    it('creates the history channel', function (done) {
+      request.post('/depot/channels/neurosis/history')
+        .set('Authorization', global.boboBearer)
+        .expect(201)
+        .end(function (err, res) {
+          beforePromotion = new Date().toISOString();
+          done(err);
+        });
+    });
+
+    it('promotes a linux package', function (done) {
+      request.put('/depot/channels/neurosis/history/pkgs/testapp/0.1.3/20171205003213/promote')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('promotes a windows package', function (done) {
+      request.put('/depot/channels/neurosis/history/pkgs/testapp/0.1.4/20181115124506/promote')
+        .query({ target: 'x86_64-windows' })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          afterPromotion = new Date().toISOString();
+          done(err);
+        });
+    });
+
+    it('demotes the linux package', function (done) {
+      request.put('/depot/channels/neurosis/history/pkgs/testapp/0.1.3/20171205003213/demote')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('requires origin membership to view channel history', function (done) {
+      request.get('/depot/channels/neurosis/history/history')
+        .set('Authorization', global.mystiqueBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('returns the current packages for the target', function (done) {
+      request.get('/depot/channels/neurosis/history/history')
+        .query({ target: 'x86_64-linux' })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.channel).to.equal('history');
+          expect(res.body.target).to.equal('x86_64-linux');
+          expect(res.body.packages).to.deep.equal([]);
+          done(err);
+        });
+    });
+
+    it('returns the packages the channel held at a point in time', function (done) {
+      request.get('/depot/channels/neurosis/history/history')
+        .query({ target: 'x86_64-linux', at: afterPromotion })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.packages).to.deep.equal(['neurosis/testapp/0.1.3/20171205003213']);
+          done(err);
+        });
+    });
+
+    it('replays the history of each target separately', function (done) {
+      request.get('/depot/channels/neurosis/history/history')
+        .query({ target: 'x86_64-windows' })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.target).to.equal('x86_64-windows');
+          expect(res.body.packages).to.deep.equal(['neurosis/testapp/0.1.4/20181115124506']);
+          done(err);
+        });
+    });
+
+    it('returns no packages before the first promotion', function (done) {
+      request.get('/depot/channels/neurosis/history/history')
+        .query({ target: 'x86_64-windows', at: beforePromotion })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.packages).to.deep.equal([]);
+          done(err);
+        });
+    });
+
+    it('diffs the channel between two points in time', function (done) {
+      request.get('/depot/channels/neurosis/history/history/diff')
+        .query({ target: 'x86_64-linux', from: beforePromotion, to: afterPromotion })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.added).to.deep.equal(['neurosis/testapp/0.1.3/20171205003213']);
+          expect(res.body.removed).to.deep.equal([]);
+          done(err);
+        });
+    });
+
+    it('diffs the channel up to now', function (done) {
+      request.get('/depot/channels/neurosis/history/history/diff')
+        .query({ target: 'x86_64-linux', from: afterPromotion })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.added).to.deep.equal([]);
+          expect(res.body.removed).to.deep.equal(['neurosis/testapp/0.1.3/20171205003213']);
+          done(err);
+        });
+    });
+
+    it('rejects a diff that ends before it starts', function (done) {
+      request.get('/depot/channels/neurosis/history/history/diff')
+        .query({ from: afterPromotion, to: beforePromotion })
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('The from timestamp must not be after the to timestamp');
+          done(err);
+        });
+    });
+
+    it('rejects an invalid target', function (done) {
+      request.get('/depot/channels/neurosis/history/history')
+        .query({ target: 'x86_64-amiga' })
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal("Invalid package target 'x86_64-amiga'");
+          done(err);
+        });
+    });
+
+    it('has no history for the unstable channel', function (done) {
+      request.get('/depot/channels/neurosis/unstable/history')
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Channel history is not available for the unstable channel');
+          done(err);
+        });
+    });
+
+    it('deletes the history channel', function (done) {
+      request.delete('/depot/channels/neurosis/history')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+
+  describe('Planned promotion', function () {
+    it('plans a promotion without making it', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213/promote')
+        .query({ dry_run: true })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.channel).to.equal('bar');
+          expect(res.body.target).to.equal('x86_64-linux');
+          expect(res.body.dry_run).to.equal(true);
+          expect(res.body.packages.length).to.equal(1);
+          expect(res.body.packages[0].release).to.equal('20171205003213');
+          done(err);
+        });
+    });
+
+    it('does not promote anything on a dry run', function (done) {
+      request.get('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213')
+        .type('application/json')
+        .accept('application/json')
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('plans nothing for a package already in the channel', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171206004121/promote')
+        .query({ dry_run: true })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.packages).to.deep.equal([]);
+          done(err);
+        });
+    });
+
+    it('rejects planned promotion of packages from other origins', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/xmen/testapp/0.1.4/20171206005217/promote')
+        .query({ with_deps: true })
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('with_deps and dry_run are not supported for packages from other origins');
+          done(err);
+        });
+    });
+
+    it('requires origin membership to plan a promotion', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213/promote')
+        .query({ dry_run: true })
+        .set('Authorization', global.mystiqueBearer)
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+
+  describe('Channel consistency check', function () {
+    it('requires origin membership to check a channel', function (done) {
+      request.get('/depot/channels/neurosis/bar/check')
+        .set('Authorization', global.mystiqueBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('returns not found for a channel that does not exist', function (done) {
+      request.get('/depot/channels/neurosis/nosuchchannel/check')
+        .set('Authorization', global.boboBearer)
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('reports on every package in the channel', function (done) {
+      request.get('/depot/channels/neurosis/bar/check')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.origin).to.equal('neurosis');
+          expect(res.body.channel).to.equal('bar');
+          expect(res.body.checked).to.equal(1);
+          expect(res.body.fallbacks).to.be.an('array');
+          expect(res.body.problems).to.be.an('array');
+          res.body.problems.forEach(function (problem) {
+            expect(problem.package.release).to.equal('20171206004121');
+            expect(['missing', 'deleted', 'yanked']).to.include(problem.reason);
+          });
+          done(err);
+        });
+    });
+  });
+
+  describe('Scheduled promotions', function () {
+    it('requires origin membership to schedule a promotion', function (done) {
+      request.post('/depot/channels/neurosis/bar/scheduled_promotions')
+        .set('Authorization', global.mystiqueBearer)
+        .send({ 'idents': ['neurosis/testapp/0.1.3/20171205003213'], 'run_at': '2099-01-01T00:00:00Z' })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('rejects a scheduled promotion without packages', function (done) {
+      request.post('/depot/channels/neurosis/bar/scheduled_promotions')
+        .set('Authorization', global.boboBearer)
+        .send({ 'idents': [], 'run_at': '2099-01-01T00:00:00Z' })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('A scheduled promotion requires at least one package');
+          done(err);
+        });
+    });
+
+    it('rejects a scheduled promotion without a time or a delay', function (done) {
+      request.post('/depot/channels/neurosis/bar/scheduled_promotions')
+        .set('Authorization', global.boboBearer)
+        .send({ 'idents': ['neurosis/testapp/0.1.3/20171205003213'] })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Either run_at, or both source_channel and delay_hours, must be given');
+          done(err);
+        });
+    });
+
+    it('rejects a delayed promotion of a package that is not in the source channel', function (done) {
+      request.post('/depot/channels/neurosis/bar/scheduled_promotions')
+        .set('Authorization', global.boboBearer)
+        .send({ 'idents': ['neurosis/testapp/0.1.4/20171206004139'], 'source_channel': 'stable', 'delay_hours': 24 })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('neurosis/testapp/0.1.4/20171206004139 is not in the source channel');
+          done(err);
+        });
+    });
+
+    it('schedules a promotion at a fixed time', function (done) {
+      request.post('/depot/channels/neurosis/bar/scheduled_promotions')
+        .set('Authorization', global.boboBearer)
+        .send({ 'idents': ['neurosis/testapp/0.1.3/20171205003213'], 'run_at': '2099-01-01T00:00:00Z' })
+        .expect(201)
+        .end(function (err, res) {
+          expect(res.body.length).to.equal(1);
+          expect(res.body[0].state).to.equal('pending');
+          expect(res.body[0].run_at).to.equal('2099-01-01T00:00:00');
+          expect(res.body[0].requester_name).to.equal('bobo');
+          global.scheduledPromotionFixed = res.body[0];
+          done(err);
+        });
+    });
+
+    it('schedules a promotion a delay after the package landed in the source channel', function (done) {
+      request.post('/depot/channels/neurosis/bar/scheduled_promotions')
+        .set('Authorization', global.boboBearer)
+        .send({ 'idents': ['neurosis/testapp/0.1.3/20171205003213'], 'source_channel': 'stable', 'delay_hours': 24 })
+        .expect(201)
+        .end(function (err, res) {
+          expect(res.body.length).to.equal(1);
+          expect(res.body[0].source_channel).to.equal('stable');
+          expect(res.body[0].delay_secs).to.equal(86400);
+          global.scheduledPromotionDelayed = res.body[0];
+          done(err);
+        });
+    });
+
+    it('lists pending scheduled promotions', function (done) {
+      request.get('/depot/channels/neurosis/bar/scheduled_promotions')
+        .query({ state: 'pending' })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.map(promotion => promotion.id)).to.have.members([
+            global.scheduledPromotionFixed.id,
+            global.scheduledPromotionDelayed.id
+          ]);
+          done(err);
+        });
+    });
+
+    it('returns a scheduled promotion', function (done) {
+      request.get(`/depot/channels/neurosis/bar/scheduled_promotions/${global.scheduledPromotionFixed.id}`)
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.ident.release).to.equal('20171205003213');
+          done(err);
+        });
+    });
+
+    it('does not let other members without the promote permission cancel it', function (done) {
+      request.delete(`/depot/channels/neurosis/bar/scheduled_promotions/${global.scheduledPromotionFixed.id}`)
+        .set('Authorization', global.weskerBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('rejects an invalid scheduled promotion id', function (done) {
+      request.delete('/depot/channels/neurosis/bar/scheduled_promotions/soon')
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal("Invalid scheduled promotion id 'soon'");
+          done(err);
+        });
+    });
+
+    it('cancels a scheduled promotion', function (done) {
+      request.delete(`/depot/channels/neurosis/bar/scheduled_promotions/${global.scheduledPromotionFixed.id}`)
+        .set('Authorization', global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('cancels a delayed promotion', function (done) {
+      request.delete(`/depot/channels/neurosis/bar/scheduled_promotions/${global.scheduledPromotionDelayed.id}`)
+        .set('Authorization', global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('does not cancel a scheduled promotion twice', function (done) {
+      request.delete(`/depot/channels/neurosis/bar/scheduled_promotions/${global.scheduledPromotionFixed.id}`)
+        .set('Authorization', global.boboBearer)
+        .expect(409)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('shows the scheduled promotion as cancelled', function (done) {
+      request.get(`/depot/channels/neurosis/bar/scheduled_promotions/${global.scheduledPromotionFixed.id}`)
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.state).to.equal('cancelled');
+          done(err);
+        });
+    });
+  });
+
+  describe('Ephemeral channels', function () {
+    it('rejects an expiry in the past', function (done) {
+      request.post('/depot/channels/neurosis/preview')
+        .query({ expires_at: '2000-01-01T00:00:00Z' })
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Channel expiry must be in the future');
+          done(err);
+        });
+    });
+
+    it('rejects both an expiry and a ttl', function (done) {
+      request.post('/depot/channels/neurosis/preview')
+        .query({ expires_at: '2099-01-01T00:00:00Z', ttl_hours: 1 })
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Only one of expires_at and ttl_hours may be given');
+          done(err);
+        });
+    });
+
+    it('creates a channel that expires', function (done) {
+      request.post('/depot/channels/neurosis/preview')
+        .query({ ttl_hours: 1 })
+        .set('Authorization', global.boboBearer)
+        .expect(201)
+        .end(function (err, res) {
+          expect(res.body.name).to.equal('preview');
+          expect(res.body.expires_at).to.not.be.null;
+          global.channelPreview = res.body;
+          done(err);
+        });
+    });
+
+    it('requires an expiry to extend a channel', function (done) {
+      request.put('/depot/channels/neurosis/preview/expiry')
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Either expires_at or ttl_hours must be given');
+          done(err);
+        });
+    });
+
+    it('only lets the channel owner or an origin owner extend a channel', function (done) {
+      request.put('/depot/channels/neurosis/preview/expiry')
+        .query({ ttl_hours: 2 })
+        .set('Authorization', global.weskerBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('extends the channel expiry', function (done) {
+      request.put('/depot/channels/neurosis/preview/expiry')
+        .query({ expires_at: '2099-01-01T00:00:00Z' })
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.expires_at).to.equal('2099-01-01T00:00:00');
+          done(err);
+        });
+    });
+
+    it('does not give permanent channels an expiry', function (done) {
+      request.put('/depot/channels/neurosis/bar/expiry')
+        .query({ ttl_hours: 1 })
+        .set('Authorization', global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Channel bar does not expire');
+          done(err);
+        });
+    });
+
+    it('deletes the preview channel', function (done) {
+      request.delete('/depot/channels/neurosis/preview')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+
+  describe('Channel metadata', function () {
+    it('requires the channels permission to update a channel', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.weskerBearer)
+        .send({ 'description': 'Nightly builds' })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('rejects invalid label keys', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .send({ 'labels': { 'has space': 'yes' } })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Invalid label key has space');
+          done(err);
+        });
+    });
+
+    it('rejects maintainers who are not origin members', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .send({ 'maintainers': ['bobo', 'mystique'] })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('mystique is not a member of origin neurosis');
+          done(err);
+        });
+    });
+
+    it('updates the description, labels and maintainers', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .send({
+          'description': 'Nightly builds',
+          'labels': { 'team': 'release', 'ci/pipeline': 'nightly' },
+          'maintainers': ['bobo', 'wesker']
+        })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.name).to.equal('bar');
+          expect(res.body.description).to.equal('Nightly builds');
+          expect(res.body.labels).to.deep.equal({ 'ci/pipeline': 'nightly', 'team': 'release' });
+          expect(res.body.maintainers).to.deep.equal(['bobo', 'wesker']);
+          done(err);
+        });
+    });
+
+    it('lists the channel metadata', function (done) {
+      request.get('/depot/channels/neurosis')
+        .type('application/json')
+        .accept('application/json')
+        .expect(200)
+        .end(function (err, res) {
+          const bar = res.body.find(channel => channel.name === 'bar');
+          expect(bar.description).to.equal('Nightly builds');
+          expect(bar.labels).to.deep.equal({ 'ci/pipeline': 'nightly', 'team': 'release' });
+          expect(bar.maintainers).to.deep.equal(['bobo', 'wesker']);
+          done(err);
+        });
+    });
+
+    it('removes labels set to null and clears an empty description', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .send({ 'description': '', 'labels': { 'ci/pipeline': null } })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.description).to.be.null;
+          expect(res.body.labels).to.deep.equal({ 'team': 'release' });
+          expect(res.body.maintainers).to.deep.equal(['bobo', 'wesker']);
+          done(err);
+        });
+    });
+
+    it('clears the remaining metadata', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .send({ 'labels': { 'team': null }, 'maintainers': [] })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.labels).to.deep.equal({});
+          expect(res.body.maintainers).to.deep.equal([]);
+          done(err);
+        });
+    });
+  });
+
+  describe('Frozen channels', function () {
+    it('requires a reason to freeze a channel', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .send({ 'frozen': true })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('A reason is required to freeze or unfreeze a channel');
+          done(err);
+        });
+    });
+
+    it('freezes a channel', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .send({ 'frozen': true, 'reason': 'Release candidate under test' })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.frozen).to.equal(true);
+          done(err);
+        });
+    });
+
+    it('shows the channel as frozen', function (done) {
+      request.get('/depot/channels/neurosis')
+        .type('application/json')
+        .accept('application/json')
+        .expect(200)
+        .end(function (err, res) {
+          const bar = res.body.find(channel => channel.name === 'bar');
+          expect(bar.frozen).to.equal(true);
+          done(err);
+        });
+    });
+
+    it('refuses promotions into a frozen channel', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171205003213/promote')
+        .set('Authorization', global.boboBearer)
+        .expect(403)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Channel bar is frozen');
+          done(err);
+        });
+    });
+
+    it('refuses demotions from a frozen channel', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171206004121/demote')
+        .set('Authorization', global.boboBearer)
+        .expect(403)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Channel bar is frozen');
+          done(err);
+        });
+    });
+
+    it('refuses to delete a frozen channel', function (done) {
+      request.delete('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .expect(403)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Channel bar is frozen');
+          done(err);
+        });
+    });
+
+    it('requires a reason to unfreeze a channel', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .send({ 'frozen': false })
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('lets the origin owner unfreeze a channel', function (done) {
+      request.patch('/depot/channels/neurosis/bar')
+        .set('Authorization', global.boboBearer)
+        .send({ 'frozen': false, 'reason': 'Release shipped' })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.frozen).to.equal(false);
+          done(err);
+        });
+    });
+
+    it('accepts promotions once the channel is unfrozen', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/testapp/0.1.3/20171206004121/promote')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+
+  describe('Packages from other origins', function () {
+    it('requires origin membership to add a package from another origin', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/xmen/testapp/0.1.4/20171206005217/promote')
+        .set('Authorization', global.mystiqueBearer)
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('adds a public package from another origin', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/xmen/testapp/0.1.4/20171206005217/promote')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('lists the package with its own origin', function (done) {
+      request.get('/depot/channels/neurosis/bar/pkgs')
+        .type('application/json')
+        .accept('application/json')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          const foreign = res.body.data.filter(ident => ident.origin === 'xmen');
+          expect(foreign.length).to.equal(1);
+          expect(foreign[0].name).to.equal('testapp');
+          expect(foreign[0].version).to.equal('0.1.4');
+          expect(foreign[0].release).to.equal('20171206005217');
+          done(err);
+        });
+    });
+
+    it('prefers packages of the channel origin for latest', function (done) {
+      request.get('/depot/channels/neurosis/bar/pkgs/testapp/latest')
+        .type('application/json')
+        .accept('application/json')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.ident.origin).to.equal('neurosis');
+          expect(res.body.ident.name).to.equal('testapp');
+          done(err);
+        });
+    });
+
+    it('returns packages from other origins for latest by version', function (done) {
+      request.get('/depot/channels/neurosis/bar/pkgs/testapp/0.1.4/latest')
+        .type('application/json')
+        .accept('application/json')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.ident.origin).to.equal('xmen');
+          expect(res.body.ident.release).to.equal('20171206005217');
+          done(err);
+        });
+    });
+
+    it('refuses to add a private package to a channel of another origin', function (done) {
+      request.put('/depot/channels/xmen/stable/pkgs/neurosis/testapp/0.1.3/20171206004121/promote')
+        .set('Authorization', global.mystiqueBearer)
+        .expect(403)
+        .end(function (err, res) {
+          expect(res.text).to.equal('neurosis/testapp/0.1.3/20171206004121 is not public, so it can\'t be added to channels of other origins');
+          done(err);
+        });
+    });
+
+    it('removes the package from the channel', function (done) {
+      request.put('/depot/channels/neurosis/bar/pkgs/xmen/testapp/0.1.4/20171206005217/demote')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('no longer lists the package', function (done) {
+      request.get('/depot/channels/neurosis/bar/pkgs')
+        .type('application/json')
+        .accept('application/json')
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.data.filter(ident => ident.origin === 'xmen')).to.deep.equal([]);
+          done(err);
+        });
+    });
+  });
 });
@@ -229,7 +229,7 @@ describe('Related Origin API functions', function () {
         .expect(200)
         .end(function (err, res) {
           expect(res.body.origin).to.equal(global.originXmen.name);
-          expect(res.body.members).to.deep.equal(['bobo', 'mystique']);
+          expect(res.body.members.map(member => member.name)).to.deep.equal(['bobo', 'mystique']);
           done(err);
         });
     });
@@ -279,7 +279,7 @@ describe('Related Origin API functions', function () {
         .expect(200)
         .end(function (err, res) {
           expect(res.body.origin).to.equal(global.originXmen.name);
-          expect(res.body.members).to.deep.equal(['mystique']);
+          expect(res.body.members.map(member => member.name)).to.deep.equal(['mystique']);
           done(err);
         });
     });
@@ -391,7 +391,9 @@ describe("Related Origin API functions", function () {
         .expect(200)
         .end(function (err, res) {
           expect(res.body.origin).to.equal(global.originUmbrella.name);
-          expect(res.body.members).to.deep.equal(["bobo"]);
+          expect(res.body.members).to.deep.equal([
+            { name: "bobo", robot: false, idp_managed: false }
+          ]);
           done(err);
         });
     });
@@ -338,6 +338,131 @@ describe('Working with packages', function () {
     });
   });
 
+  describe('Download statistics', function () {
+    // Downloads are recorded off the request path, so wait for the count to show up
+    function waitForDownloads(path, done, attempts = 20) {
+      request.get(path)
+        .expect(200)
+        .end(function (err, res) {
+          if (err) return done(err);
+          if (res.body.total > 0 || attempts <= 1) return done();
+          setTimeout(() => waitForDownloads(path, done, attempts - 1), 100);
+        });
+    }
+
+    before(function (done) {
+      request.get(`/depot/pkgs/neurosis/testapp/0.1.3/${release1}/download`)
+        .set('Authorization', global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          if (err) return done(err);
+          waitForDownloads('/depot/pkgs/neurosis/testapp/stats', done);
+        });
+    });
+
+    it('counts downloads of a package per day and target', function (done) {
+      request.get('/depot/pkgs/neurosis/testapp/stats')
+        .type('application/json')
+        .accept('application/json')
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.origin).to.equal('neurosis');
+          expect(res.body.name).to.equal('testapp');
+          expect(res.body.total).to.equal(1);
+          expect(res.body.downloads.length).to.equal(1);
+          expect(res.body.downloads[0].target).to.equal('x86_64-linux');
+          expect(res.body.downloads[0].downloads).to.equal(1);
+          expect(res.body.downloads[0].day).to.equal(res.body.to_date);
+          done(err);
+        });
+    });
+
+    it('leaves out downloads before the date range', function (done) {
+      request.get('/depot/pkgs/neurosis/testapp/stats')
+        .query({ from_date: '2017-01-01', to_date: '2017-12-31' })
+        .type('application/json')
+        .accept('application/json')
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.from_date).to.equal('2017-01-01');
+          expect(res.body.to_date).to.equal('2017-12-31');
+          expect(res.body.total).to.equal(0);
+          expect(res.body.downloads).to.deep.equal([]);
+          done(err);
+        });
+    });
+
+    it('rejects a date range that ends before it starts', function (done) {
+      request.get('/depot/pkgs/neurosis/testapp/stats')
+        .query({ from_date: '2017-12-31', to_date: '2017-01-01' })
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('totals downloads for each package of an origin', function (done) {
+      request.get('/depot/origins/neurosis/stats')
+        .type('application/json')
+        .accept('application/json')
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.origin).to.equal('neurosis');
+          expect(res.body.total).to.equal(1);
+          expect(res.body.packages).to.deep.equal([{ 'name': 'testapp', 'downloads': 1 }]);
+          done(err);
+        });
+    });
+
+    it('rejects a date range for an origin that ends before it starts', function (done) {
+      request.get('/depot/origins/neurosis/stats')
+        .query({ from_date: '2017-12-31', to_date: '2017-01-01' })
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('sorts search results by popularity', function (done) {
+      request.get('/depot/pkgs/search/neurosis%2Ftestapp')
+        .query({ distinct: true, sort: 'popularity' })
+        .type('application/json')
+        .accept('application/json')
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.total_count).to.equal(3);
+          expect(res.body.data[0].origin).to.equal('neurosis');
+          expect(res.body.data[0].name).to.equal('testapp');
+          expect(res.body.data[1].name).to.equal('native-testapp');
+          expect(res.body.data[2].name).to.equal('testapp2');
+          done(err);
+        });
+    });
+
+    it('sorts search results by name by default', function (done) {
+      request.get('/depot/pkgs/search/neurosis%2Ftestapp')
+        .query({ distinct: true })
+        .type('application/json')
+        .accept('application/json')
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.data[0].name).to.equal('native-testapp');
+          expect(res.body.data[1].name).to.equal('testapp');
+          done(err);
+        });
+    });
+
+    it('rejects unknown sort orders', function (done) {
+      request.get('/depot/pkgs/search/testapp')
+        .query({ sort: 'downloads' })
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.equal('Invalid sort order \'downloads\'');
+          done(err);
+        });
+    });
+  });
+
   describe('Deleting origin after package exists', function () {
     it('is not allowed', function (done) {
       request.delete('/depot/origins/neurosis')
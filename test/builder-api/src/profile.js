@@ -83,6 +83,57 @@ describe('Profile API', function () {
       });
   });
 
+  it('accepts a name and an expiry', function (done) {
+    request.post('/profile/access-tokens')
+      .set('Authorization', global.boboBearer)
+      .type('application/json')
+      .accept('application/json')
+      .send({
+        name: 'ci',
+        expires_at: '2099-01-01T00:00:00Z'
+      })
+      .expect(200)
+      .end(function (err, res) {
+        expect(res.body.token).to.not.be.empty;
+        expect(res.body.name).to.equal('ci');
+        expect(res.body.expires_at).to.equal('2099-01-01T00:00:00');
+        done(err);
+      });
+  });
+
+  it('rejects an expiry in the past', function (done) {
+    request.post('/profile/access-tokens')
+      .set('Authorization', global.boboBearer)
+      .type('application/json')
+      .accept('application/json')
+      .send({
+        name: 'expired',
+        expires_at: '2000-01-01T00:00:00Z'
+      })
+      .expect(422)
+      .end(function (err, res) {
+        done(err);
+      });
+  });
+
+  it('rejects an unknown scope capability', function (done) {
+    request.post('/profile/access-tokens')
+      .set('Authorization', global.boboBearer)
+      .type('application/json')
+      .accept('application/json')
+      .send({
+        name: 'scoped',
+        scope: {
+          origins: ['neurosis'],
+          capabilities: ['download', 'teleport']
+        }
+      })
+      .expect(422)
+      .end(function (err, res) {
+        done(err);
+      });
+  });
+
   describe('Getting a list of access tokens', function () {
     it('requires authentication', function (done) {
       request.get('/profile/access-tokens')
@@ -104,7 +155,8 @@ describe('Profile API', function () {
       .expect(200)
       .end(function (err, res) {
         expect(res.body.tokens).to.not.be.empty;
-        expect(res.body.tokens[0].id).to.equal(global.boboTokenId);
+        expect(res.body.tokens.map(t => t.id)).to.include(global.boboTokenId);
+        res.body.tokens.forEach(t => expect(t.token).to.be.undefined);
         done(err);
       });
   });
@@ -192,4 +244,166 @@ describe('Profile API', function () {
         });
     });
   });
+
+  describe('Holding several named tokens', function () {
+    let laptopTokenId = null;
+    let ciTokenId = null;
+
+    function createToken(name, done) {
+      request.post('/profile/access-tokens')
+        .set('Authorization', global.mystiqueBearer)
+        .type('application/json')
+        .accept('application/json')
+        .send({ name: name })
+        .expect(200)
+        .end(function (err, res) {
+          if (err) return done(err);
+          expect(res.body.name).to.equal(name);
+          done(null, res.body.id);
+        });
+    }
+
+    before(function (done) {
+      createToken('laptop', function (err, id) {
+        if (err) return done(err);
+        laptopTokenId = id;
+        createToken('ci', function (err, id) {
+          ciTokenId = id;
+          done(err);
+        });
+      });
+    });
+
+    it('lists every token by name', function (done) {
+      request.get('/profile/access-tokens')
+        .set('Authorization', global.mystiqueBearer)
+        .expect(200)
+        .end(function (err, res) {
+          const names = res.body.tokens.map(t => t.name);
+          expect(names).to.include('laptop');
+          expect(names).to.include('ci');
+          done(err);
+        });
+    });
+
+    it('rejects a token id that is not a number', function (done) {
+      request.delete('/profile/access-tokens/laptop')
+        .set('Authorization', global.mystiqueBearer)
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('revokes a token by its id', function (done) {
+      request.delete('/profile/access-tokens/' + laptopTokenId)
+        .set('Authorization', global.mystiqueBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    it('keeps the other tokens of the account', function (done) {
+      request.get('/profile/access-tokens')
+        .set('Authorization', global.mystiqueBearer)
+        .expect(200)
+        .end(function (err, res) {
+          const ids = res.body.tokens.map(t => t.id);
+          expect(ids).to.not.include(laptopTokenId);
+          expect(ids).to.include(ciTokenId);
+          done(err);
+        });
+    });
+
+    it('does not revoke a token twice', function (done) {
+      request.delete('/profile/access-tokens/' + laptopTokenId)
+        .set('Authorization', global.mystiqueBearer)
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+
+    after(function (done) {
+      request.delete('/profile/access-tokens/' + ciTokenId)
+        .set('Authorization', global.mystiqueBearer)
+        .expect(200)
+        .end(done);
+    });
+  });
+
+  describe('Limiting the number of tokens', function () {
+    // Matches MAX_ACCESS_TOKENS in the profile resource
+    const maxAccessTokens = 50;
+    const created = [];
+
+    function createToken(name) {
+      return request.post('/profile/access-tokens')
+        .set('Authorization', global.hankBearer)
+        .type('application/json')
+        .accept('application/json')
+        .send({ name: name });
+    }
+
+    function revokeTokens(ids, done) {
+      if (ids.length === 0) return done();
+      request.delete('/profile/access-tokens/' + ids[0])
+        .set('Authorization', global.hankBearer)
+        .expect(200)
+        .end(function (err, res) {
+          if (err) return done(err);
+          revokeTokens(ids.slice(1), done);
+        });
+    }
+
+    before(function (done) {
+      this.timeout(30000);
+
+      function fill(held) {
+        if (held >= maxAccessTokens) return done();
+        createToken('limit-' + held)
+          .expect(200)
+          .end(function (err, res) {
+            if (err) return done(err);
+            created.push(res.body.id);
+            fill(held + 1);
+          });
+      }
+
+      request.get('/profile/access-tokens')
+        .set('Authorization', global.hankBearer)
+        .expect(200)
+        .end(function (err, res) {
+          if (err) return done(err);
+          fill(res.body.tokens.length);
+        });
+    });
+
+    it('rejects tokens beyond the limit', function (done) {
+      createToken('one-too-many')
+        .expect(422)
+        .end(function (err, res) {
+          expect(res.text).to.include('at most ' + maxAccessTokens + ' access tokens');
+          done(err);
+        });
+    });
+
+    it('accepts a new token once one is revoked', function (done) {
+      revokeTokens([created.pop()], function (err) {
+        if (err) return done(err);
+        createToken('replacement')
+          .expect(200)
+          .end(function (err, res) {
+            created.push(res.body.id);
+            done(err);
+          });
+      });
+    });
+
+    after(function (done) {
+      this.timeout(30000);
+      revokeTokens(created, done);
+    });
+  });
 });
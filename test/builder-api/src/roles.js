@@ -922,4 +922,590 @@ describe("Origin Roles API", function () {
         });
     });
   });
+
+  describe("Robot accounts", function () {
+    it("requires origin administrators to create them", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots")
+        .set("Authorization", global.hankBearer)
+        .send({ name: "release" })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("does not create owner robots", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "release", role: "owner" })
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("creates a robot account with the given role", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "release", role: "maintainer" })
+        .expect(201)
+        .end(function (err, res) {
+          expect(res.body.name).to.equal("release");
+          expect(res.body.full_name).to.equal("rcpd.release");
+          expect(res.body.role).to.equal("maintainer");
+          done(err);
+        });
+    });
+    it("does not create a robot twice", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "release" })
+        .expect(409)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("rejects invalid robot names", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "Release.Bot" })
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("requires the members permission to list robots", function (done) {
+      request
+        .get("/depot/origins/rcpd/robots")
+        .set("Authorization", global.hankBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("lists the robots by the name they were created with", function (done) {
+      request
+        .get("/depot/origins/rcpd/robots")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.origin).to.equal("rcpd");
+          expect(res.body.robots.length).to.equal(1);
+          expect(res.body.robots[0].name).to.equal("release");
+          expect(res.body.robots[0].full_name).to.equal("rcpd.release");
+          expect(res.body.robots[0].role).to.equal("maintainer");
+          done(err);
+        });
+    });
+    it("marks the robot among the origin members", function (done) {
+      request
+        .get("/depot/origins/rcpd/users")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.robots).to.be.undefined;
+          expect(res.body.members).to.deep.include({
+            name: "rcpd.release", robot: true, idp_managed: false
+          });
+          expect(res.body.members.filter((member) => member.robot).length).to.equal(1);
+          done(err);
+        });
+    });
+    it("generates tokens for the robot", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots/release/tokens")
+        .set("Authorization", global.boboBearer)
+        .type("application/json")
+        .send({ name: "ci" })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.name).to.equal("ci");
+          expect(res.body.token).to.not.be.empty;
+          global.rcpdRobotTokenId = res.body.id;
+          done(err);
+        });
+    });
+    it("requires the members permission to generate robot tokens", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots/release/tokens")
+        .set("Authorization", global.hankBearer)
+        .type("application/json")
+        .send({ name: "stolen" })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("does not generate tokens for unknown robots", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots/nightly/tokens")
+        .set("Authorization", global.boboBearer)
+        .type("application/json")
+        .send({ name: "ci" })
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("does not address robots by their full name", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots/rcpd.release/tokens")
+        .set("Authorization", global.boboBearer)
+        .type("application/json")
+        .send({ name: "ci" })
+        .expect(404)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("lists the robot's tokens without their values", function (done) {
+      request
+        .get("/depot/origins/rcpd/robots/release/tokens")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.tokens.length).to.equal(1);
+          expect(res.body.tokens[0].id).to.equal(global.rcpdRobotTokenId);
+          expect(res.body.tokens[0].name).to.equal("ci");
+          expect(res.body.tokens[0].token).to.be.undefined;
+          done(err);
+        });
+    });
+    it("does not list robot tokens among the requester's own", function (done) {
+      request
+        .get("/profile/access-tokens")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.tokens.map((t) => t.id)).to.not.include(global.rcpdRobotTokenId);
+          done(err);
+        });
+    });
+    it("does not revoke robot tokens as the requester's own", function (done) {
+      request
+        .delete("/profile/access-tokens/" + global.rcpdRobotTokenId)
+        .set("Authorization", global.boboBearer)
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("revokes a robot token by its id", function (done) {
+      request
+        .delete("/depot/origins/rcpd/robots/release/tokens/" + global.rcpdRobotTokenId)
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("no longer lists the revoked robot token", function (done) {
+      request
+        .get("/depot/origins/rcpd/robots/release/tokens")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.tokens).to.deep.equal([]);
+          done(err);
+        });
+    });
+    it("does not invite robots to other origins", function (done) {
+      request
+        .post("/depot/origins/umbrella/users/rcpd.release/invitations")
+        .set("Authorization", global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("deletes the robot account", function (done) {
+      request
+        .delete("/depot/origins/rcpd/robots/release")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+
+  describe("Teams", function () {
+    it("requires origin administrators to create them", function (done) {
+      request
+        .post("/depot/origins/rcpd/teams")
+        .set("Authorization", global.lkennedyBearer)
+        .send({ name: "release", role: "maintainer" })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("does not create owner teams", function (done) {
+      request
+        .post("/depot/origins/rcpd/teams")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "release", role: "owner" })
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("creates a team with the given role", function (done) {
+      request
+        .post("/depot/origins/rcpd/teams")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "release", role: "maintainer" })
+        .expect(201)
+        .end(function (err, res) {
+          expect(res.body.name).to.equal("release");
+          expect(res.body.member_role).to.equal("maintainer");
+          done(err);
+        });
+    });
+    it("does not add users who are not origin members", function (done) {
+      request
+        .put("/depot/origins/rcpd/teams/release/members/hank")
+        .set("Authorization", global.boboBearer)
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("caches the member's own role before they join a team", function (done) {
+      request
+        .post("/depot/channels/rcpd/team-check")
+        .set("Authorization", global.lkennedyBearer)
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("adds an origin member to the team", function (done) {
+      request
+        .put("/depot/origins/rcpd/teams/release/members/lkennedy")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("lists the team's members", function (done) {
+      request
+        .get("/depot/origins/rcpd/teams/release")
+        .set("Authorization", global.lkennedyBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.members).to.deep.equal(["lkennedy"]);
+          done(err);
+        });
+    });
+    it("gives the member the team's role at once", function (done) {
+      request
+        .post("/depot/channels/rcpd/team-check")
+        .set("Authorization", global.lkennedyBearer)
+        .expect(201)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("lowers the team's role", function (done) {
+      request
+        .patch("/depot/origins/rcpd/teams/release")
+        .set("Authorization", global.boboBearer)
+        .send({ role: "member" })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.member_role).to.equal("member");
+          done(err);
+        });
+    });
+    it("takes the lowered role away from the team's members at once", function (done) {
+      request
+        .post("/depot/channels/rcpd/team-check-lowered")
+        .set("Authorization", global.lkennedyBearer)
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("raises the team's role again", function (done) {
+      request
+        .patch("/depot/origins/rcpd/teams/release")
+        .set("Authorization", global.boboBearer)
+        .send({ role: "maintainer" })
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.member_role).to.equal("maintainer");
+          done(err);
+        });
+    });
+    it("keeps the member's own role", function (done) {
+      request
+        .get("/depot/origins/rcpd/users/lkennedy/role")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.text).to.equal('{"role":"member"}');
+          done(err);
+        });
+    });
+    it("removes the member from the team", function (done) {
+      request
+        .delete("/depot/origins/rcpd/teams/release/members/lkennedy")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("takes the team's role away once the member leaves", function (done) {
+      request
+        .post("/depot/channels/rcpd/team-check-removed")
+        .set("Authorization", global.lkennedyBearer)
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("deletes the channel the team member created", function (done) {
+      request
+        .delete("/depot/channels/rcpd/team-check")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("deletes the team", function (done) {
+      request
+        .delete("/depot/origins/rcpd/teams/release")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+  describe("Custom roles", function () {
+    it("requires origin administrators to create them", function (done) {
+      request
+        .post("/depot/origins/rcpd/roles")
+        .set("Authorization", global.lkennedyBearer)
+        .send({ name: "secrets-manager", permissions: ["secrets"] })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("rejects unknown permissions", function (done) {
+      request
+        .post("/depot/origins/rcpd/roles")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "secrets-manager", permissions: ["publish"] })
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("creates a role with the given permissions", function (done) {
+      request
+        .post("/depot/origins/rcpd/roles")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "secrets-manager", permissions: ["secrets"] })
+        .expect(201)
+        .end(function (err, res) {
+          expect(res.body.name).to.equal("secrets-manager");
+          expect(res.body.permissions).to.deep.equal(["secrets"]);
+          done(err);
+        });
+    });
+    it("members without the role cannot list secrets", function (done) {
+      request
+        .get("/depot/origins/rcpd/secret")
+        .set("Authorization", global.lkennedyBearer)
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("gives the role to an origin member", function (done) {
+      request
+        .put("/depot/origins/rcpd/roles/secrets-manager/members/lkennedy")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("members with the role can list secrets", function (done) {
+      request
+        .get("/depot/origins/rcpd/secret")
+        .set("Authorization", global.lkennedyBearer)
+        .expect(200)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("lists the role's members", function (done) {
+      request
+        .get("/depot/origins/rcpd/roles/secrets-manager")
+        .set("Authorization", global.lkennedyBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.members).to.deep.equal(["lkennedy"]);
+          done(err);
+        });
+    });
+    it("takes the role from the member", function (done) {
+      request
+        .delete("/depot/origins/rcpd/roles/secrets-manager/members/lkennedy")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("deletes the role", function (done) {
+      request
+        .delete("/depot/origins/rcpd/roles/secrets-manager")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("creates a role with the members permission", function (done) {
+      request
+        .post("/depot/origins/rcpd/roles")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "member-manager", permissions: ["members"] })
+        .expect(201)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("gives the members permission to a member", function (done) {
+      request
+        .put("/depot/origins/rcpd/roles/member-manager/members/lkennedy")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("does not let the members permission hand out administrator", function (done) {
+      request
+        .post("/depot/origins/rcpd/robots")
+        .set("Authorization", global.lkennedyBearer)
+        .send({ name: "escalate", role: "administrator" })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("deletes the members role", function (done) {
+      request
+        .delete("/depot/origins/rcpd/roles/member-manager")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
+  describe("Trust rules", function () {
+    it("requires origin administrators to create them", function (done) {
+      request
+        .post("/depot/origins/rcpd/trust-rules")
+        .set("Authorization", global.hankBearer)
+        .send({
+          issuer: "https://token.actions.githubusercontent.com",
+          claims: { repository: "rcpd/plans" },
+          capabilities: ["upload"]
+        })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("does not trust issuers the Builder is not configured for", function (done) {
+      request
+        .post("/depot/origins/rcpd/trust-rules")
+        .set("Authorization", global.boboBearer)
+        .send({
+          issuer: "https://ci.example.com",
+          claims: { repository: "rcpd/plans" },
+          capabilities: ["upload"]
+        })
+        .expect(422)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("creates a role with the settings permission", function (done) {
+      request
+        .post("/depot/origins/rcpd/roles")
+        .set("Authorization", global.boboBearer)
+        .send({ name: "settings-manager", permissions: ["settings", "members"] })
+        .expect(201)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("gives the settings permission to a member", function (done) {
+      request
+        .put("/depot/origins/rcpd/roles/settings-manager/members/lkennedy")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("does not let the settings permission create administrator trust rules", function (done) {
+      request
+        .post("/depot/origins/rcpd/trust-rules")
+        .set("Authorization", global.lkennedyBearer)
+        .send({
+          issuer: "https://token.actions.githubusercontent.com",
+          claims: { repository: "rcpd/plans" },
+          role: "administrator",
+          capabilities: ["keys"]
+        })
+        .expect(403)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("deletes the settings role", function (done) {
+      request
+        .delete("/depot/origins/rcpd/roles/settings-manager")
+        .set("Authorization", global.boboBearer)
+        .expect(204)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+    it("lists no trust rules", function (done) {
+      request
+        .get("/depot/origins/rcpd/trust-rules")
+        .set("Authorization", global.boboBearer)
+        .expect(200)
+        .end(function (err, res) {
+          expect(res.body.trust_rules).to.deep.equal([]);
+          done(err);
+        });
+    });
+    it("does not exchange invalid identity tokens", function (done) {
+      request
+        .post("/depot/origins/rcpd/token-exchange")
+        .send({ token: "not.a.token" })
+        .expect(401)
+        .end(function (err, res) {
+          done(err);
+        });
+    });
+  });
 });